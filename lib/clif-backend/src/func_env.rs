@@ -1,12 +1,12 @@
 use crate::{module::Converter, module_env::ModuleEnv, relocation::call_names};
 use cranelift_codegen::{
-    cursor::FuncCursor,
+    cursor::{Cursor, FuncCursor},
     ir::{self, InstBuilder},
     isa,
 };
 use cranelift_entity::EntityRef;
 use cranelift_wasm::{self, FuncEnvironment, ModuleEnvironment};
-use std::mem;
+use std::{collections::HashMap, mem};
 use wasmer_runtime_core::{
     memory::MemoryType,
     structures::TypedIndex,
@@ -42,6 +42,122 @@ impl<'env, 'module, 'isa> FuncEnv<'env, 'module, 'isa> {
     }
 }
 
+/// Calls the vmcall `call_name`, which never returns, if `condition` is nonzero.
+pub fn emit_exit_call(
+    pos: &mut FuncCursor,
+    call_conv: isa::CallConv,
+    pointer_type: ir::Type,
+    condition: ir::Value,
+    call_name: u32,
+) {
+    let vmctx = pos
+        .func
+        .special_param(ir::ArgumentPurpose::VMContext)
+        .expect("missing vmctx parameter");
+
+    let exit_ebb = pos.func.dfg.make_ebb();
+    pos.ins().brnz(condition, exit_ebb, &[]);
+
+    let signature = pos.func.import_signature(ir::Signature {
+        call_conv,
+        params: vec![ir::AbiParam::special(
+            pointer_type,
+            ir::ArgumentPurpose::VMContext,
+        )],
+        returns: vec![],
+    });
+
+    let exit_func = pos.func.import_function(ir::ExtFuncData {
+        name: ir::ExternalName::user(call_names::LOCAL_NAMESPACE, call_name),
+        signature,
+        colocated: false,
+    });
+
+    // The call never returns, so the slow path is placed out of line.
+    let position = pos.position();
+    pos.func.layout.append_ebb(exit_ebb);
+    pos.goto_bottom(exit_ebb);
+    pos.ins().call(exit_func, &[vmctx]);
+    pos.ins().trap(ir::TrapCode::UnreachableCodeReached);
+    pos.set_position(position);
+}
+
+/// Stops the function if the interrupt flag of the instance is set.
+pub fn emit_interrupt_check(
+    pos: &mut FuncCursor,
+    call_conv: isa::CallConv,
+    pointer_type: ir::Type,
+) {
+    let vmctx = pos
+        .func
+        .special_param(ir::ArgumentPurpose::VMContext)
+        .expect("missing vmctx parameter");
+
+    let flag_ptr = pos.ins().load(
+        pointer_type,
+        ir::MemFlags::trusted(),
+        vmctx,
+        vm::Ctx::offset_interrupted() as i32,
+    );
+    let interrupted = pos
+        .ins()
+        .load(ir::types::I8, ir::MemFlags::trusted(), flag_ptr, 0);
+
+    emit_exit_call(
+        pos,
+        call_conv,
+        pointer_type,
+        interrupted,
+        call_names::INTERRUPTED,
+    );
+}
+
+/// Stops the function if the interrupt flag of the instance is set, every time
+/// the header of a loop is reached.
+///
+/// `cranelift-wasm` has no hook for the start of a loop, so this runs once the
+/// function has been translated: an ebb that is branched to from itself or from
+/// an ebb placed after it is the header of a loop.
+pub fn emit_loop_interrupt_checks(
+    func: &mut ir::Function,
+    call_conv: isa::CallConv,
+    pointer_type: ir::Type,
+) {
+    let positions: HashMap<ir::Ebb, usize> = func
+        .layout
+        .ebbs()
+        .enumerate()
+        .map(|(position, ebb)| (ebb, position))
+        .collect();
+
+    let mut headers = vec![];
+    for ebb in func.layout.ebbs() {
+        for inst in func.layout.ebb_insts(ebb) {
+            let mut destinations = vec![];
+            match func.dfg[inst].analyze_branch(&func.dfg.value_lists) {
+                ir::instructions::BranchInfo::SingleDest(destination, _) => {
+                    destinations.push(destination)
+                }
+                ir::instructions::BranchInfo::Table(table, default) => {
+                    destinations.extend(func.jump_tables[table].iter().cloned());
+                    destinations.extend(default);
+                }
+                ir::instructions::BranchInfo::NotABranch => {}
+            }
+            for destination in destinations {
+                if positions[&destination] <= positions[&ebb] && !headers.contains(&destination) {
+                    headers.push(destination);
+                }
+            }
+        }
+    }
+
+    for header in headers {
+        let mut pos = FuncCursor::new(func).at_first_insertion_point(header);
+        emit_interrupt_check(&mut pos, call_conv, pointer_type);
+    }
+}
+
 impl<'env, 'module, 'isa> FuncEnvironment for FuncEnv<'env, 'module, 'isa> {
     /// Gets configuration information needed for compiling functions
     fn target_config(&self) -> isa::TargetFrontendConfig {
@@ -1,6 +1,7 @@
 mod cache;
 mod func_env;
 mod libcalls;
+mod metering;
 mod module;
 mod module_env;
 mod relocation;
@@ -16,9 +17,10 @@ use target_lexicon::Triple;
 
 use wasmer_runtime_core::cache::{Artifact, Error as CacheError};
 use wasmer_runtime_core::{
-    backend::{Compiler, CompilerConfig, Token},
+    backend::{Backend, Compiler, CompilerConfig, Token},
     error::{CompileError, CompileResult},
     module::ModuleInner,
+    proposals,
 };
 
 #[macro_use]
@@ -49,8 +51,13 @@ impl Compiler for CraneliftCompiler {
 
         let isa = get_isa();
 
+        let fuel_costs = match compiler_config.metering {
+            Some(ref costs) => Some(costs.module_costs(wasm)?),
+            None => None,
+        };
+
         let mut module = module::Module::new(&compiler_config);
-        let module_env = module_env::ModuleEnv::new(&mut module, &*isa);
+        let module_env = module_env::ModuleEnv::new(&mut module, &*isa, fuel_costs);
 
         let func_bodies = module_env.translate(wasm)?;
 
@@ -63,6 +70,10 @@ impl Compiler for CraneliftCompiler {
         module::Module::from_cache(cache)
     }
 
+    fn backend(&self) -> Backend {
+        Backend::Cranelift
+    }
+
     //
     // fn compile_to_backend_cache_data(
     //     &self,
@@ -112,6 +123,9 @@ fn get_isa() -> Box<isa::TargetIsa> {
 }
 
 fn validate(bytes: &[u8]) -> CompileResult<()> {
+    // The parser of cranelift predates these proposals.
+    proposals::check_proposals(bytes, &[], "cranelift")?;
+
     let mut parser = wasmparser::ValidatingParser::new(bytes, None);
     loop {
         let state = parser.read();
@@ -120,6 +134,14 @@ fn validate(bytes: &[u8]) -> CompileResult<()> {
             wasmparser::ParserState::Error(err) => Err(CompileError::ValidationError {
                 msg: err.message.to_string(),
             })?,
+            wasmparser::ParserState::TypeSectionEntry(ref func_type)
+                if func_type.returns.len() > 1 =>
+            {
+                Err(CompileError::ValidationError {
+                    msg: "multi-value returns are not supported by the cranelift backend"
+                        .to_string(),
+                })?
+            }
             _ => {}
         }
     }
@@ -0,0 +1,97 @@
+//! Fuel metering for the Cranelift backend.
+//!
+//! Cranelift doesn't let us hook into the translation of each operator, so the
+//! fuel is charged once a function has been translated. Every instruction
+//! carries the source location of the operator it was generated for, which
+//! maps it back to the cost of that operator.
+use crate::{func_env, relocation::call_names};
+use cranelift_codegen::{
+    cursor::{Cursor, FuncCursor},
+    ir::{self, condcodes::IntCC, InstBuilder},
+    isa,
+};
+use std::collections::BTreeMap;
+use wasmer_runtime_core::{metering::OperatorCost, vm};
+
+/// Charges the fuel consumed by each basic block of `func` when the block is entered.
+///
+/// An operator is charged in the first block that contains one of its instructions.
+/// Operators that don't generate instructions of their own, such as `get_local`,
+/// are charged along with the next operator that does.
+pub fn charge_fuel(
+    func: &mut ir::Function,
+    costs: &[OperatorCost],
+    call_conv: isa::CallConv,
+    pointer_type: ir::Type,
+) {
+    // The fuel charged for each source location that has instructions.
+    let mut charges: BTreeMap<u32, u64> = BTreeMap::new();
+    for ebb in func.layout.ebbs() {
+        for inst in func.layout.ebb_insts(ebb) {
+            let srcloc = func.srclocs[inst];
+            if !srcloc.is_default() {
+                charges.insert(srcloc.bits(), 0);
+            }
+        }
+    }
+    for op in costs {
+        if let Some((_, charge)) = charges.range_mut(op.offset as u32..).next() {
+            *charge += op.cost;
+        }
+    }
+
+    // An ebb can be left by a branch in its middle, so it can hold several basic blocks.
+    let mut blocks: Vec<(ir::Inst, u64)> = vec![];
+    for ebb in func.layout.ebbs() {
+        let mut block = None;
+        for inst in func.layout.ebb_insts(ebb) {
+            let (_, cost) = block.get_or_insert((inst, 0));
+            if let Some(charge) = charges.remove(&func.srclocs[inst].bits()) {
+                *cost += charge;
+            }
+            if func.dfg[inst].opcode().is_branch() {
+                blocks.extend(block.take());
+            }
+        }
+        blocks.extend(block);
+    }
+
+    for (inst, cost) in blocks {
+        if cost > 0 {
+            let mut pos = FuncCursor::new(func).at_inst(inst);
+            emit_fuel_check(&mut pos, call_conv, pointer_type, cost);
+        }
+    }
+}
+
+/// Adds `cost` to the fuel consumed by the instance, and calls into
+/// the runtime if that exceeds its fuel limit.
+fn emit_fuel_check(
+    pos: &mut FuncCursor,
+    call_conv: isa::CallConv,
+    pointer_type: ir::Type,
+    cost: u64,
+) {
+    let vmctx = pos
+        .func
+        .special_param(ir::ArgumentPurpose::VMContext)
+        .expect("missing vmctx parameter");
+
+    let flags = ir::MemFlags::new();
+    let used_offset = vm::Ctx::offset_fuel_used() as i32;
+    let limit_offset = vm::Ctx::offset_fuel_limit() as i32;
+
+    let used = pos.ins().load(ir::types::I64, flags, vmctx, used_offset);
+    let used = pos.ins().iadd_imm(used, cost as i64);
+    pos.ins().store(flags, used, vmctx, used_offset);
+    let limit = pos.ins().load(ir::types::I64, flags, vmctx, limit_offset);
+    let exhausted = pos.ins().icmp(IntCC::UnsignedGreaterThan, used, limit);
+
+    func_env::emit_exit_call(
+        pos,
+        call_conv,
+        pointer_type,
+        exhausted,
+        call_names::FUEL_EXHAUSTED,
+    );
+}
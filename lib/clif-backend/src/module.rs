@@ -12,7 +12,7 @@ use wasmer_runtime_core::cache::{Artifact, Error as CacheError};
 use wasmer_runtime_core::{
     backend::{Backend, CompilerConfig},
     error::CompileResult,
-    module::{ModuleInfo, ModuleInner, StringTable},
+    module::{ModuleInfo, ModuleInner, NameSection, StringTable},
     structures::{Map, TypedIndex},
     types::{
         FuncIndex, FuncSig, GlobalIndex, LocalFuncIndex, MemoryIndex, SigIndex, TableIndex, Type,
@@ -42,6 +42,9 @@ impl Module {
                 data_initializers: Vec::new(),
                 elem_initializers: Vec::new(),
 
+                passive_data: HashMap::new(),
+                passive_elements: HashMap::new(),
+
                 start_func: None,
 
                 func_assoc: Map::new(),
@@ -53,6 +56,8 @@ impl Module {
                 em_symbol_map: compiler_config.symbol_map.clone(),
 
                 custom_sections: HashMap::new(),
+
+                name_section: NameSection::default(),
             },
         }
     }
@@ -175,6 +180,7 @@ impl From<Converter<ir::Type>> for Type {
             ir::types::I64 => Type::I64,
             ir::types::F32 => Type::F32,
             ir::types::F64 => Type::F64,
+            ir::types::I8X16 => Type::V128,
             _ => panic!("unsupported wasm type"),
         }
     }
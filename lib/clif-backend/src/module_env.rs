@@ -1,11 +1,13 @@
 use crate::{
-    func_env::FuncEnv,
+    func_env::{self, FuncEnv},
+    metering,
     module::{Converter, Module},
 };
 use cranelift_codegen::{ir, isa};
-use cranelift_wasm::{self, translate_module, FuncTranslator, ModuleEnvironment};
+use cranelift_wasm::{self, translate_module, FuncEnvironment, FuncTranslator, ModuleEnvironment};
 use wasmer_runtime_core::{
     error::{CompileError, CompileResult},
+    metering::OperatorCost,
     module::{
         DataInitializer, ExportIndex, ImportName, NameIndex, NamespaceIndex, StringTableBuilder,
         TableInitializer,
@@ -26,10 +28,16 @@ pub struct ModuleEnv<'module, 'isa> {
     func_bodies: Map<LocalFuncIndex, ir::Function>,
     namespace_table_builder: StringTableBuilder<NamespaceIndex>,
     name_table_builder: StringTableBuilder<NameIndex>,
+    /// The cost of each operator of each local function, when metering.
+    fuel_costs: Option<Vec<Vec<OperatorCost>>>,
 }
 
 impl<'module, 'isa> ModuleEnv<'module, 'isa> {
-    pub fn new(module: &'module mut Module, isa: &'isa isa::TargetIsa) -> Self {
+    pub fn new(
+        module: &'module mut Module,
+        isa: &'isa isa::TargetIsa,
+        fuel_costs: Option<Vec<Vec<OperatorCost>>>,
+    ) -> Self {
         Self {
             module,
             isa,
@@ -38,6 +46,7 @@ impl<'module, 'isa> ModuleEnv<'module, 'isa> {
             func_bodies: Map::new(),
             namespace_table_builder: StringTableBuilder::new(),
             name_table_builder: StringTableBuilder::new(),
+            fuel_costs,
         }
     }
 
@@ -386,6 +395,26 @@ impl<'module, 'isa, 'data> ModuleEnvironment<'data> for ModuleEnv<'module, 'isa>
 
             func_translator.translate(body_bytes, body_offset, &mut func, &mut func_env)?;
 
+            {
+                use cranelift_codegen::cursor::{Cursor, FuncCursor};
+                let call_conv = func_env.target_config().default_call_conv;
+                let pointer_type = func_env.pointer_type();
+
+                if let Some(ref fuel_costs) = self.fuel_costs {
+                    metering::charge_fuel(
+                        &mut func,
+                        &fuel_costs[func_index.index()],
+                        call_conv,
+                        pointer_type,
+                    );
+                }
+
+                func_env::emit_loop_interrupt_checks(&mut func, call_conv, pointer_type);
+                let entry_ebb = func.layout.entry_block().unwrap();
+                let mut pos = FuncCursor::new(&mut func).at_first_insertion_point(entry_ebb);
+                func_env::emit_interrupt_check(&mut pos, call_conv, pointer_type);
+            }
+
             #[cfg(feature = "debug")]
             {
                 use cranelift_codegen::cursor::{Cursor, FuncCursor};
@@ -257,8 +257,12 @@ impl FuncResolverBuilder {
                             VmCallKind::StaticMemoryGrow => vmcalls::local_static_memory_grow as _,
                             VmCallKind::StaticMemorySize => vmcalls::local_static_memory_size as _,
 
-                            VmCallKind::SharedStaticMemoryGrow => unimplemented!(),
-                            VmCallKind::SharedStaticMemorySize => unimplemented!(),
+                            VmCallKind::SharedStaticMemoryGrow => {
+                                vmcalls::local_shared_static_memory_grow as _
+                            }
+                            VmCallKind::SharedStaticMemorySize => {
+                                vmcalls::local_shared_static_memory_size as _
+                            }
 
                             VmCallKind::DynamicMemoryGrow => {
                                 vmcalls::local_dynamic_memory_grow as _
@@ -266,6 +270,9 @@ impl FuncResolverBuilder {
                             VmCallKind::DynamicMemorySize => {
                                 vmcalls::local_dynamic_memory_size as _
                             }
+
+                            VmCallKind::FuelExhausted => vmcalls::fuel_exhausted as _,
+                            VmCallKind::Interrupted => vmcalls::interrupted as _,
                         },
                         VmCall::Import(kind) => match kind {
                             VmCallKind::StaticMemoryGrow => {
@@ -275,8 +282,12 @@ impl FuncResolverBuilder {
                                 vmcalls::imported_static_memory_size as _
                             }
 
-                            VmCallKind::SharedStaticMemoryGrow => unimplemented!(),
-                            VmCallKind::SharedStaticMemorySize => unimplemented!(),
+                            VmCallKind::SharedStaticMemoryGrow => {
+                                vmcalls::imported_shared_static_memory_grow as _
+                            }
+                            VmCallKind::SharedStaticMemorySize => {
+                                vmcalls::imported_shared_static_memory_size as _
+                            }
 
                             VmCallKind::DynamicMemoryGrow => {
                                 vmcalls::imported_dynamic_memory_grow as _
@@ -284,6 +295,9 @@ impl FuncResolverBuilder {
                             VmCallKind::DynamicMemorySize => {
                                 vmcalls::imported_dynamic_memory_size as _
                             }
+
+                            VmCallKind::FuelExhausted => vmcalls::fuel_exhausted as _,
+                            VmCallKind::Interrupted => vmcalls::interrupted as _,
                         },
                     },
                     RelocationType::Signature(sig_index) => {
@@ -360,6 +374,20 @@ impl FuncResolver {
     pub fn lookup(&self, index: LocalFuncIndex) -> Option<NonNull<vm::Func>> {
         lookup_func(&self.map, &self.memory, index)
     }
+
+    /// Finds the function whose code contains `address`.
+    pub fn lookup_address(&self, address: usize) -> Option<LocalFuncIndex> {
+        let start = self.memory.as_ptr() as usize;
+        if address < start || address >= start + self.memory.size() {
+            return None;
+        }
+        let offset = address - start;
+        self.map
+            .iter()
+            .filter(|&(_, &func_offset)| func_offset <= offset)
+            .max_by_key(|&(_, &func_offset)| func_offset)
+            .map(|(index, _)| index)
+    }
 }
 
 #[inline]
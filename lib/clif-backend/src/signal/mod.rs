@@ -110,6 +110,10 @@ impl RunnableModule for Caller {
         TRAP_EARLY_DATA.with(|cell| cell.set(Some(data)));
         trigger_trap()
     }
+
+    fn get_local_func_index_at(&self, _: &ModuleInfo, address: usize) -> Option<LocalFuncIndex> {
+        self.resolver.lookup_address(address)
+    }
 }
 
 unsafe impl Send for HandlerData {}
@@ -18,7 +18,10 @@ use nix::sys::signal::{
 use std::cell::{Cell, UnsafeCell};
 use std::ptr;
 use std::sync::Once;
-use wasmer_runtime_core::typed_func::WasmTrapInfo;
+use wasmer_runtime_core::{
+    backend::{record_trap_frames, walk_trap_frames, MAX_TRAP_FRAMES},
+    typed_func::WasmTrapInfo,
+};
 
 extern "C" fn signal_trap_handler(
     signum: ::nix::libc::c_int,
@@ -53,6 +56,9 @@ pub static SIGHANDLER_INIT: Once = Once::new();
 thread_local! {
     pub static SETJMP_BUFFER: UnsafeCell<[c_int; SETJMP_BUFFER_LEN]> = UnsafeCell::new([0; SETJMP_BUFFER_LEN]);
     pub static CAUGHT_ADDRESSES: Cell<(*const c_void, *const c_void)> = Cell::new((ptr::null(), ptr::null()));
+    pub static CAUGHT_FRAMES: UnsafeCell<(usize, [usize; MAX_TRAP_FRAMES])> = UnsafeCell::new((0, [0; MAX_TRAP_FRAMES]));
+    /// The frame of the innermost `call_protected`, which trap backtraces stop at.
+    pub static STACK_END: Cell<usize> = Cell::new(0);
     pub static CURRENT_EXECUTABLE_BUFFER: Cell<*const c_void> = Cell::new(ptr::null());
 }
 
@@ -69,6 +75,8 @@ pub fn call_protected<T>(
     unsafe {
         let jmp_buf = SETJMP_BUFFER.with(|buf| buf.get());
         let prev_jmp_buf = *jmp_buf;
+        let prev_stack_end =
+            STACK_END.with(|cell| cell.replace(&prev_jmp_buf as *const _ as usize));
 
         SIGHANDLER_INIT.call_once(|| {
             install_sighandler();
@@ -77,6 +85,7 @@ pub fn call_protected<T>(
         let signum = setjmp(jmp_buf as *mut _);
         if signum != 0 {
             *jmp_buf = prev_jmp_buf;
+            STACK_END.with(|cell| cell.set(prev_stack_end));
 
             if let Some(data) = super::TRAP_EARLY_DATA.with(|cell| cell.replace(None)) {
                 Err(CallProtError::Error(data))
@@ -88,12 +97,20 @@ pub fn call_protected<T>(
                     srcloc: _,
                 }) = handler_data.lookup(inst_ptr)
                 {
+                    CAUGHT_FRAMES.with(|cell| {
+                        let (len, frames) = &*cell.get();
+                        record_trap_frames(&frames[..*len]);
+                    });
                     Err(CallProtError::Trap(match Signal::from_c_int(signum) {
                         Ok(SIGILL) => match trapcode {
                             TrapCode::BadSignature => WasmTrapInfo::IncorrectCallIndirectSignature,
                             TrapCode::IndirectCallToNull => WasmTrapInfo::CallIndirectOOB,
                             TrapCode::HeapOutOfBounds => WasmTrapInfo::MemoryOutOfBounds,
                             TrapCode::TableOutOfBounds => WasmTrapInfo::CallIndirectOOB,
+                            TrapCode::UnreachableCodeReached => WasmTrapInfo::Unreachable,
+                            TrapCode::IntegerOverflow
+                            | TrapCode::IntegerDivisionByZero
+                            | TrapCode::BadConversionToInteger => WasmTrapInfo::IllegalArithmetic,
                             _ => WasmTrapInfo::Unknown,
                         },
                         Ok(SIGSEGV) | Ok(SIGBUS) => WasmTrapInfo::MemoryOutOfBounds,
@@ -117,6 +134,7 @@ pub fn call_protected<T>(
         } else {
             let ret = f(); // TODO: Switch stack?
             *jmp_buf = prev_jmp_buf;
+            STACK_END.with(|cell| cell.set(prev_stack_end));
             Ok(ret)
         }
     }
@@ -133,7 +151,15 @@ pub unsafe fn do_unwind(signum: i32, siginfo: *const c_void, ucontext: *const c_
         ::std::process::abort();
     }
 
-    CAUGHT_ADDRESSES.with(|cell| cell.set(get_faulting_addr_and_ip(siginfo, ucontext)));
+    let (faulting_addr, inst_ptr) = get_faulting_addr_and_ip(siginfo, ucontext);
+    CAUGHT_ADDRESSES.with(|cell| cell.set((faulting_addr, inst_ptr)));
+
+    let (fp, sp) = get_frame_and_stack_pointers(ucontext);
+    let stack_end = STACK_END.with(|cell| cell.get());
+    CAUGHT_FRAMES.with(|cell| {
+        let (len, frames) = &mut *cell.get();
+        *len = walk_trap_frames(inst_ptr as usize, fp, sp, stack_end, frames);
+    });
 
     longjmp(jmp_buf as *mut ::nix::libc::c_void, signum)
 }
@@ -227,6 +253,41 @@ unsafe fn get_faulting_addr_and_ip(
     (si_addr, rip as _)
 }
 
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+unsafe fn get_frame_and_stack_pointers(ucontext: *const c_void) -> (usize, usize) {
+    use libc::{ucontext_t, REG_RBP, REG_RSP};
+
+    let ucontext = ucontext as *const ucontext_t;
+    let gregs = &(*ucontext).uc_mcontext.gregs;
+    (gregs[REG_RBP as usize] as _, gregs[REG_RSP as usize] as _)
+}
+
+#[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+unsafe fn get_frame_and_stack_pointers(ucontext: *const c_void) -> (usize, usize) {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct ucontext_t {
+        uc_onstack: u32,
+        uc_sigmask: u32,
+        uc_stack: libc::stack_t,
+        uc_link: *const ucontext_t,
+        uc_mcsize: u64,
+        uc_mcontext: *const mcontext_t,
+    }
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct mcontext_t {
+        exception_state: [u64; 2],
+        // rax, rbx, rcx, rdx, rdi, rsi, rbp, rsp, ...
+        gprs: [u64; 8],
+        // ...
+    }
+
+    let ucontext = ucontext as *const ucontext_t;
+    let gprs = &(*(*ucontext).uc_mcontext).gprs;
+    (gprs[6] as _, gprs[7] as _)
+}
+
 #[cfg(not(any(
     all(target_os = "macos", target_arch = "x86_64"),
     all(target_os = "linux", target_arch = "x86_64"),
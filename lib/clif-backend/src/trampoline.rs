@@ -166,31 +166,31 @@ fn generate_func(func_sig: &FuncSig) -> ir::Function {
 
     let mut args_vec = Vec::with_capacity(func_sig.params().len() + 1);
     args_vec.push(vmctx_ptr);
-    for (index, wasm_ty) in func_sig.params().iter().enumerate() {
+    let mut offset = 0;
+    for wasm_ty in func_sig.params() {
         let mem_flags = ir::MemFlags::trusted();
 
         let val = pos.ins().load(
             wasm_ty_to_clif(*wasm_ty),
             mem_flags,
             args_ptr,
-            (index * mem::size_of::<u64>()) as i32,
+            offset as i32,
         );
         args_vec.push(val);
+        offset += slot_size(*wasm_ty);
     }
 
     let call_inst = pos.ins().call_indirect(export_sig_ref, func_ptr, &args_vec);
 
     let return_values = pos.func.dfg.inst_results(call_inst).to_vec();
 
-    for (index, return_val) in return_values.iter().enumerate() {
+    let mut offset = 0;
+    for (return_val, wasm_ty) in return_values.iter().zip(func_sig.returns()) {
         let mem_flags = ir::MemFlags::trusted();
 
-        pos.ins().store(
-            mem_flags,
-            *return_val,
-            returns_ptr,
-            (index * mem::size_of::<u64>()) as i32,
-        );
+        pos.ins()
+            .store(mem_flags, *return_val, returns_ptr, offset as i32);
+        offset += slot_size(*wasm_ty);
     }
 
     pos.ins().return_(&[]);
@@ -204,6 +204,18 @@ fn wasm_ty_to_clif(ty: Type) -> ir::types::Type {
         Type::I64 => ir::types::I64,
         Type::F32 => ir::types::F32,
         Type::F64 => ir::types::F64,
+        // Modules that use reference types are rejected by `validate`, but
+        // references are passed as their raw word everywhere else.
+        Type::AnyRef | Type::FuncRef => ir::types::I64,
+        Type::V128 => ir::types::I8X16,
+    }
+}
+
+/// The size of a value of this type in the argument and result arrays.
+fn slot_size(ty: Type) -> usize {
+    match ty {
+        Type::V128 => 2 * mem::size_of::<u64>(),
+        _ => mem::size_of::<u64>(),
     }
 }
 
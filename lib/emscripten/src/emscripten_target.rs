@@ -6,127 +6,127 @@ use libc::getdtablesize;
 use wasmer_runtime_core::vm::Ctx;
 
 pub fn setTempRet0(ctx: &mut Ctx, val: i32) {
-    debug!("emscripten::setTempRet0: {}", val);
+    debug!(target: "emscripten::setTempRet0", "{}", val);
     get_emscripten_data(ctx).temp_ret_0 = val;
 }
 
 pub fn getTempRet0(ctx: &mut Ctx) -> i32 {
-    debug!("emscripten::getTempRet0");
+    debug!(target: "emscripten::getTempRet0", "called");
     get_emscripten_data(ctx).temp_ret_0
 }
 
 pub fn __Unwind_Backtrace(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::__Unwind_Backtrace");
+    debug!(target: "emscripten::__Unwind_Backtrace", "called");
     0
 }
 pub fn __Unwind_FindEnclosingFunction(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::__Unwind_FindEnclosingFunction");
+    debug!(target: "emscripten::__Unwind_FindEnclosingFunction", "called");
     0
 }
 pub fn __Unwind_GetIPInfo(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::__Unwind_GetIPInfo");
+    debug!(target: "emscripten::__Unwind_GetIPInfo", "called");
     0
 }
 pub fn ___cxa_find_matching_catch_2(_ctx: &mut Ctx) -> i32 {
-    debug!("emscripten::___cxa_find_matching_catch_2");
+    debug!(target: "emscripten::___cxa_find_matching_catch_2", "called");
     0
 }
 pub fn ___cxa_find_matching_catch_3(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::___cxa_find_matching_catch_3");
+    debug!(target: "emscripten::___cxa_find_matching_catch_3", "called");
     0
 }
 pub fn ___cxa_free_exception(_ctx: &mut Ctx, _a: i32) {
-    debug!("emscripten::___cxa_free_exception");
+    debug!(target: "emscripten::___cxa_free_exception", "called");
 }
 pub fn ___resumeException(_ctx: &mut Ctx, _a: i32) {
-    debug!("emscripten::___resumeException");
+    debug!(target: "emscripten::___resumeException", "called");
 }
 pub fn _dladdr(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::_dladdr");
+    debug!(target: "emscripten::_dladdr", "called");
     0
 }
 pub fn _pthread_cond_destroy(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::_pthread_cond_destroy");
+    debug!(target: "emscripten::_pthread_cond_destroy", "called");
     0
 }
 pub fn _pthread_getspecific(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::_pthread_getspecific");
+    debug!(target: "emscripten::_pthread_getspecific", "called");
     0
 }
 pub fn _pthread_setspecific(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::_pthread_setspecific");
+    debug!(target: "emscripten::_pthread_setspecific", "called");
     0
 }
 pub fn _pthread_once(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::_pthread_once");
+    debug!(target: "emscripten::_pthread_once", "called");
     0
 }
 pub fn _pthread_key_create(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::_pthread_key_create");
+    debug!(target: "emscripten::_pthread_key_create", "called");
     0
 }
 pub fn _pthread_create(_ctx: &mut Ctx, _a: i32, _b: i32, _c: i32, _d: i32) -> i32 {
-    debug!("emscripten::_pthread_create");
+    debug!(target: "emscripten::_pthread_create", "called");
     0
 }
 pub fn _pthread_join(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::_pthread_join");
+    debug!(target: "emscripten::_pthread_join", "called");
     0
 }
 pub fn _pthread_cond_init(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::_pthread_cond_init");
+    debug!(target: "emscripten::_pthread_cond_init", "called");
     0
 }
 pub fn _pthread_cond_signal(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::_pthread_cond_signal");
+    debug!(target: "emscripten::_pthread_cond_signal", "called");
     0
 }
 pub fn _pthread_cond_wait(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::_pthread_cond_wait");
+    debug!(target: "emscripten::_pthread_cond_wait", "called");
     0
 }
 pub fn _pthread_condattr_destroy(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::_pthread_condattr_destroy");
+    debug!(target: "emscripten::_pthread_condattr_destroy", "called");
     0
 }
 pub fn _pthread_condattr_init(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::_pthread_condattr_init");
+    debug!(target: "emscripten::_pthread_condattr_init", "called");
     0
 }
 pub fn _pthread_condattr_setclock(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::_pthread_condattr_setclock");
+    debug!(target: "emscripten::_pthread_condattr_setclock", "called");
     0
 }
 pub fn _pthread_mutex_destroy(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::_pthread_mutex_destroy");
+    debug!(target: "emscripten::_pthread_mutex_destroy", "called");
     0
 }
 pub fn _pthread_mutex_init(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::_pthread_mutex_init");
+    debug!(target: "emscripten::_pthread_mutex_init", "called");
     0
 }
 pub fn _pthread_mutexattr_destroy(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::_pthread_mutexattr_destroy");
+    debug!(target: "emscripten::_pthread_mutexattr_destroy", "called");
     0
 }
 pub fn _pthread_mutexattr_init(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::_pthread_mutexattr_init");
+    debug!(target: "emscripten::_pthread_mutexattr_init", "called");
     0
 }
 pub fn _pthread_mutexattr_settype(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::_pthread_mutexattr_settype");
+    debug!(target: "emscripten::_pthread_mutexattr_settype", "called");
     0
 }
 pub fn _pthread_rwlock_rdlock(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::_pthread_rwlock_rdlock");
+    debug!(target: "emscripten::_pthread_rwlock_rdlock", "called");
     0
 }
 pub fn _pthread_rwlock_unlock(_ctx: &mut Ctx, _a: i32) -> i32 {
-    debug!("emscripten::_pthread_rwlock_unlock");
+    debug!(target: "emscripten::_pthread_rwlock_unlock", "called");
     0
 }
 pub fn _pthread_setcancelstate(_ctx: &mut Ctx, _a: i32, _b: i32) -> i32 {
-    debug!("emscripten::_pthread_setcancelstate");
+    debug!(target: "emscripten::_pthread_setcancelstate", "called");
     0
 }
 pub fn ___gxx_personality_v0(
@@ -138,21 +138,21 @@ pub fn ___gxx_personality_v0(
     _e: i32,
     _f: i32,
 ) -> i32 {
-    debug!("emscripten::___gxx_personality_v0");
+    debug!(target: "emscripten::___gxx_personality_v0", "called");
     0
 }
 #[cfg(target_os = "linux")]
 pub fn _getdtablesize(_ctx: &mut Ctx) -> i32 {
-    debug!("emscripten::getdtablesize");
+    debug!(target: "emscripten::_getdtablesize", "emscripten::getdtablesize");
     unsafe { getdtablesize() }
 }
 #[cfg(not(target_os = "linux"))]
 pub fn _getdtablesize(_ctx: &mut Ctx) -> i32 {
-    debug!("emscripten::getdtablesize");
+    debug!(target: "emscripten::_getdtablesize", "emscripten::getdtablesize");
     -1
 }
 pub fn _gethostbyaddr(_ctx: &mut Ctx, _addr: i32, _addrlen: i32, _atype: i32) -> i32 {
-    debug!("emscripten::gethostbyaddr");
+    debug!(target: "emscripten::_gethostbyaddr", "emscripten::gethostbyaddr");
     0
 }
 pub fn _gethostbyname_r(
@@ -164,12 +164,12 @@ pub fn _gethostbyname_r(
     _out: i32,
     _err: i32,
 ) -> i32 {
-    debug!("emscripten::gethostbyname_r");
+    debug!(target: "emscripten::_gethostbyname_r", "emscripten::gethostbyname_r");
     0
 }
 // NOTE: php.js has proper impl; libc has proper impl for linux
 pub fn _getloadavg(_ctx: &mut Ctx, _loadavg: i32, _nelem: i32) -> i32 {
-    debug!("emscripten::getloadavg");
+    debug!(target: "emscripten::_getloadavg", "emscripten::getloadavg");
     0
 }
 
@@ -211,56 +211,56 @@ macro_rules! invoke_no_return {
 
 // Invoke functions
 pub fn invoke_i(ctx: &mut Ctx, index: i32) -> i32 {
-    debug!("emscripten::invoke_i");
+    debug!(target: "emscripten::invoke_i", "called");
     invoke!(ctx, dyn_call_i, index)
 }
 pub fn invoke_ii(ctx: &mut Ctx, index: i32, a1: i32) -> i32 {
-    debug!("emscripten::invoke_ii");
+    debug!(target: "emscripten::invoke_ii", "called");
     invoke!(ctx, dyn_call_ii, index, a1)
 }
 pub fn invoke_iii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32) -> i32 {
-    debug!("emscripten::invoke_iii");
+    debug!(target: "emscripten::invoke_iii", "called");
     invoke!(ctx, dyn_call_iii, index, a1, a2)
 }
 pub fn invoke_iiii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32) -> i32 {
-    debug!("emscripten::invoke_iiii");
+    debug!(target: "emscripten::invoke_iiii", "called");
     invoke!(ctx, dyn_call_iiii, index, a1, a2, a3)
 }
 pub fn invoke_iifi(ctx: &mut Ctx, index: i32, a1: i32, a2: f64, a3: i32) -> i32 {
-    debug!("emscripten::invoke_iifi");
+    debug!(target: "emscripten::invoke_iifi", "called");
     invoke!(ctx, dyn_call_iifi, index, a1, a2, a3)
 }
 pub fn invoke_v(ctx: &mut Ctx, index: i32) {
-    debug!("emscripten::invoke_v");
+    debug!(target: "emscripten::invoke_v", "called");
     invoke_no_return!(ctx, dyn_call_v, index);
 }
 pub fn invoke_vi(ctx: &mut Ctx, index: i32, a1: i32) {
-    debug!("emscripten::invoke_vi");
+    debug!(target: "emscripten::invoke_vi", "called");
     invoke_no_return!(ctx, dyn_call_vi, index, a1);
 }
 pub fn invoke_vii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32) {
-    debug!("emscripten::invoke_vii");
+    debug!(target: "emscripten::invoke_vii", "called");
     invoke_no_return!(ctx, dyn_call_vii, index, a1, a2);
 }
 
 pub fn invoke_viii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32) {
-    debug!("emscripten::invoke_viii");
+    debug!(target: "emscripten::invoke_viii", "called");
     invoke_no_return!(ctx, dyn_call_viii, index, a1, a2, a3);
 }
 pub fn invoke_viiii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32) {
-    debug!("emscripten::invoke_viiii");
+    debug!(target: "emscripten::invoke_viiii", "called");
     invoke_no_return!(ctx, dyn_call_viiii, index, a1, a2, a3, a4);
 }
 pub fn invoke_dii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32) -> f64 {
-    debug!("emscripten::invoke_dii");
+    debug!(target: "emscripten::invoke_dii", "called");
     invoke!(ctx, dyn_call_dii, index, a1, a2)
 }
 pub fn invoke_diiii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32) -> f64 {
-    debug!("emscripten::invoke_diiii");
+    debug!(target: "emscripten::invoke_diiii", "called");
     invoke!(ctx, dyn_call_diiii, index, a1, a2, a3, a4)
 }
 pub fn invoke_iiiii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32) -> i32 {
-    debug!("emscripten::invoke_iiiii");
+    debug!(target: "emscripten::invoke_iiiii", "called");
     invoke!(ctx, dyn_call_iiiii, index, a1, a2, a3, a4)
 }
 pub fn invoke_iiiiii(
@@ -272,7 +272,7 @@ pub fn invoke_iiiiii(
     a4: i32,
     a5: i32,
 ) -> i32 {
-    debug!("emscripten::invoke_iiiiii");
+    debug!(target: "emscripten::invoke_iiiiii", "called");
     invoke!(ctx, dyn_call_iiiiii, index, a1, a2, a3, a4, a5)
 }
 pub fn invoke_iiiiiii(
@@ -285,7 +285,7 @@ pub fn invoke_iiiiiii(
     a5: i32,
     a6: i32,
 ) -> i32 {
-    debug!("emscripten::invoke_iiiiiii");
+    debug!(target: "emscripten::invoke_iiiiiii", "called");
     invoke!(ctx, dyn_call_iiiiiii, index, a1, a2, a3, a4, a5, a6)
 }
 pub fn invoke_iiiiiiii(
@@ -299,7 +299,7 @@ pub fn invoke_iiiiiiii(
     a6: i32,
     a7: i32,
 ) -> i32 {
-    debug!("emscripten::invoke_iiiiiiii");
+    debug!(target: "emscripten::invoke_iiiiiiii", "called");
     invoke!(ctx, dyn_call_iiiiiiii, index, a1, a2, a3, a4, a5, a6, a7)
 }
 pub fn invoke_iiiiiiiii(
@@ -314,7 +314,7 @@ pub fn invoke_iiiiiiiii(
     a7: i32,
     a8: i32,
 ) -> i32 {
-    debug!("emscripten::invoke_iiiiiiiii");
+    debug!(target: "emscripten::invoke_iiiiiiiii", "called");
     invoke!(
         ctx,
         dyn_call_iiiiiiiii,
@@ -342,7 +342,7 @@ pub fn invoke_iiiiiiiiii(
     a8: i32,
     a9: i32,
 ) -> i32 {
-    debug!("emscripten::invoke_iiiiiiiiii");
+    debug!(target: "emscripten::invoke_iiiiiiiiii", "called");
     invoke!(
         ctx,
         dyn_call_iiiiiiiiii,
@@ -372,7 +372,7 @@ pub fn invoke_iiiiiiiiiii(
     a9: i32,
     a10: i32,
 ) -> i32 {
-    debug!("emscripten::invoke_iiiiiiiiiii");
+    debug!(target: "emscripten::invoke_iiiiiiiiiii", "called");
     invoke!(
         ctx,
         dyn_call_iiiiiiiiiii,
@@ -390,11 +390,11 @@ pub fn invoke_iiiiiiiiiii(
     )
 }
 pub fn invoke_vd(ctx: &mut Ctx, index: i32, a1: f64) {
-    debug!("emscripten::invoke_vd");
+    debug!(target: "emscripten::invoke_vd", "called");
     invoke_no_return!(ctx, dyn_call_vd, index, a1)
 }
 pub fn invoke_viiiii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32, a5: i32) {
-    debug!("emscripten::invoke_viiiii");
+    debug!(target: "emscripten::invoke_viiiii", "called");
     invoke_no_return!(ctx, dyn_call_viiiii, index, a1, a2, a3, a4, a5)
 }
 pub fn invoke_viiiiii(
@@ -407,7 +407,7 @@ pub fn invoke_viiiiii(
     a5: i32,
     a6: i32,
 ) {
-    debug!("emscripten::invoke_viiiiii");
+    debug!(target: "emscripten::invoke_viiiiii", "called");
     invoke_no_return!(ctx, dyn_call_viiiiii, index, a1, a2, a3, a4, a5, a6)
 }
 pub fn invoke_viiiiiii(
@@ -421,7 +421,7 @@ pub fn invoke_viiiiiii(
     a6: i32,
     a7: i32,
 ) {
-    debug!("emscripten::invoke_viiiiiii");
+    debug!(target: "emscripten::invoke_viiiiiii", "called");
     invoke_no_return!(ctx, dyn_call_viiiiiii, index, a1, a2, a3, a4, a5, a6, a7)
 }
 pub fn invoke_viiiiiiii(
@@ -436,7 +436,7 @@ pub fn invoke_viiiiiiii(
     a7: i32,
     a8: i32,
 ) {
-    debug!("emscripten::invoke_viiiiiiii");
+    debug!(target: "emscripten::invoke_viiiiiiii", "called");
     invoke_no_return!(
         ctx,
         dyn_call_viiiiiiii,
@@ -464,7 +464,7 @@ pub fn invoke_viiiiiiiii(
     a8: i32,
     a9: i32,
 ) {
-    debug!("emscripten::invoke_viiiiiiiii");
+    debug!(target: "emscripten::invoke_viiiiiiiii", "called");
     invoke_no_return!(
         ctx,
         dyn_call_viiiiiiiii,
@@ -494,7 +494,7 @@ pub fn invoke_viiiiiiiiii(
     a9: i32,
     a10: i32,
 ) {
-    debug!("emscripten::invoke_viiiiiiiiii");
+    debug!(target: "emscripten::invoke_viiiiiiiiii", "called");
     invoke_no_return!(
         ctx,
         dyn_call_viiiiiiiiii,
@@ -513,12 +513,12 @@ pub fn invoke_viiiiiiiiii(
 }
 
 pub fn invoke_iij(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32) -> i32 {
-    debug!("emscripten::invoke_iij");
+    debug!(target: "emscripten::invoke_iij", "called");
     invoke!(ctx, dyn_call_iij, index, a1, a2, a3)
 }
 
 pub fn invoke_iiji(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32) -> i32 {
-    debug!("emscripten::invoke_iiji");
+    debug!(target: "emscripten::invoke_iiji", "called");
     invoke!(ctx, dyn_call_iiji, index, a1, a2, a3, a4)
 }
 
@@ -532,11 +532,11 @@ pub fn invoke_iiijj(
     a5: i32,
     a6: i32,
 ) -> i32 {
-    debug!("emscripten::invoke_iiijj");
+    debug!(target: "emscripten::invoke_iiijj", "called");
     invoke!(ctx, dyn_call_iiijj, index, a1, a2, a3, a4, a5, a6)
 }
 pub fn invoke_j(ctx: &mut Ctx, index: i32) -> i32 {
-    debug!("emscripten::invoke_j");
+    debug!(target: "emscripten::invoke_j", "called");
     if let Some(dyn_call_j) = &get_emscripten_data(ctx).dyn_call_j {
         dyn_call_j.call(index).unwrap()
     } else {
@@ -544,7 +544,7 @@ pub fn invoke_j(ctx: &mut Ctx, index: i32) -> i32 {
     }
 }
 pub fn invoke_ji(ctx: &mut Ctx, index: i32, a1: i32) -> i32 {
-    debug!("emscripten::invoke_ji");
+    debug!(target: "emscripten::invoke_ji", "called");
     if let Some(dyn_call_ji) = &get_emscripten_data(ctx).dyn_call_ji {
         dyn_call_ji.call(index, a1).unwrap()
     } else {
@@ -552,7 +552,7 @@ pub fn invoke_ji(ctx: &mut Ctx, index: i32, a1: i32) -> i32 {
     }
 }
 pub fn invoke_jii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32) -> i32 {
-    debug!("emscripten::invoke_jii");
+    debug!(target: "emscripten::invoke_jii", "called");
     if let Some(dyn_call_jii) = &get_emscripten_data(ctx).dyn_call_jii {
         dyn_call_jii.call(index, a1, a2).unwrap()
     } else {
@@ -561,7 +561,7 @@ pub fn invoke_jii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32) -> i32 {
 }
 
 pub fn invoke_jij(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32) -> i32 {
-    debug!("emscripten::invoke_jij");
+    debug!(target: "emscripten::invoke_jij", "called");
     if let Some(dyn_call_jij) = &get_emscripten_data(ctx).dyn_call_jij {
         dyn_call_jij.call(index, a1, a2, a3).unwrap()
     } else {
@@ -569,7 +569,7 @@ pub fn invoke_jij(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32) -> i32 {
     }
 }
 pub fn invoke_jjj(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32) -> i32 {
-    debug!("emscripten::invoke_jjj");
+    debug!(target: "emscripten::invoke_jjj", "called");
     if let Some(dyn_call_jjj) = &get_emscripten_data(ctx).dyn_call_jjj {
         dyn_call_jjj.call(index, a1, a2, a3, a4).unwrap()
     } else {
@@ -577,7 +577,7 @@ pub fn invoke_jjj(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32)
     }
 }
 pub fn invoke_viiij(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32, a5: i32) {
-    debug!("emscripten::invoke_viiij");
+    debug!(target: "emscripten::invoke_viiij", "called");
     if let Some(dyn_call_viiij) = &get_emscripten_data(ctx).dyn_call_viiij {
         dyn_call_viiij.call(index, a1, a2, a3, a4, a5).unwrap();
     } else {
@@ -597,7 +597,7 @@ pub fn invoke_viiijiiii(
     a8: i32,
     a9: i32,
 ) {
-    debug!("emscripten::invoke_viiijiiii");
+    debug!(target: "emscripten::invoke_viiijiiii", "called");
     if let Some(dyn_call_viiijiiii) = &get_emscripten_data(ctx).dyn_call_viiijiiii {
         dyn_call_viiijiiii
             .call(index, a1, a2, a3, a4, a5, a6, a7, a8, a9)
@@ -621,7 +621,7 @@ pub fn invoke_viiijiiiiii(
     a10: i32,
     a11: i32,
 ) {
-    debug!("emscripten::invoke_viiijiiiiii");
+    debug!(target: "emscripten::invoke_viiijiiiiii", "called");
     if let Some(dyn_call_viiijiiiiii) = &get_emscripten_data(ctx).dyn_call_viiijiiiiii {
         dyn_call_viiijiiiiii
             .call(index, a1, a2, a3, a4, a5, a6, a7, a8, a9, a10, a11)
@@ -631,7 +631,7 @@ pub fn invoke_viiijiiiiii(
     }
 }
 pub fn invoke_viij(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32) {
-    debug!("emscripten::invoke_viij");
+    debug!(target: "emscripten::invoke_viij", "called");
     if let Some(dyn_call_viij) = &get_emscripten_data(ctx).dyn_call_viij {
         dyn_call_viij.call(index, a1, a2, a3, a4).unwrap();
     } else {
@@ -639,7 +639,7 @@ pub fn invoke_viij(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32
     }
 }
 pub fn invoke_viiji(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32, a5: i32) {
-    debug!("emscripten::invoke_viiji");
+    debug!(target: "emscripten::invoke_viiji", "called");
     if let Some(dyn_call_viiji) = &get_emscripten_data(ctx).dyn_call_viiji {
         dyn_call_viiji.call(index, a1, a2, a3, a4, a5).unwrap();
     } else {
@@ -657,7 +657,7 @@ pub fn invoke_viijiii(
     a6: i32,
     a7: i32,
 ) {
-    debug!("emscripten::invoke_viijiii");
+    debug!(target: "emscripten::invoke_viijiii", "called");
     if let Some(dyn_call_viijiii) = &get_emscripten_data(ctx).dyn_call_viijiii {
         dyn_call_viijiii
             .call(index, a1, a2, a3, a4, a5, a6, a7)
@@ -676,7 +676,7 @@ pub fn invoke_viijj(
     a5: i32,
     a6: i32,
 ) {
-    debug!("emscripten::invoke_viijj");
+    debug!(target: "emscripten::invoke_viijj", "called");
     if let Some(dyn_call_viijj) = &get_emscripten_data(ctx).dyn_call_viijj {
         dyn_call_viijj.call(index, a1, a2, a3, a4, a5, a6).unwrap();
     } else {
@@ -684,7 +684,7 @@ pub fn invoke_viijj(
     }
 }
 pub fn invoke_vj(ctx: &mut Ctx, index: i32, a1: i32, a2: i32) {
-    debug!("emscripten::invoke_vj");
+    debug!(target: "emscripten::invoke_vj", "called");
     if let Some(dyn_call_vj) = &get_emscripten_data(ctx).dyn_call_vj {
         dyn_call_vj.call(index, a1, a2).unwrap();
     } else {
@@ -692,7 +692,7 @@ pub fn invoke_vj(ctx: &mut Ctx, index: i32, a1: i32, a2: i32) {
     }
 }
 pub fn invoke_vij(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32) {
-    debug!("emscripten::invoke_vij");
+    debug!(target: "emscripten::invoke_vij", "called");
     if let Some(dyn_call_vij) = &get_emscripten_data(ctx).dyn_call_vij {
         dyn_call_vij.call(index, a1, a2, a3).unwrap();
     } else {
@@ -700,7 +700,7 @@ pub fn invoke_vij(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32) {
     }
 }
 pub fn invoke_viji(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32) {
-    debug!("emscripten::invoke_viji");
+    debug!(target: "emscripten::invoke_viji", "called");
     if let Some(dyn_call_viji) = &get_emscripten_data(ctx).dyn_call_viji {
         dyn_call_viji.call(index, a1, a2, a3, a4).unwrap()
     } else {
@@ -717,7 +717,7 @@ pub fn invoke_vijiii(
     a5: i32,
     a6: i32,
 ) {
-    debug!("emscripten::invoke_vijiii");
+    debug!(target: "emscripten::invoke_vijiii", "called");
     if let Some(dyn_call_vijiii) = &get_emscripten_data(ctx).dyn_call_vijiii {
         dyn_call_vijiii.call(index, a1, a2, a3, a4, a5, a6).unwrap()
     } else {
@@ -725,7 +725,7 @@ pub fn invoke_vijiii(
     }
 }
 pub fn invoke_vijj(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32, a5: i32) {
-    debug!("emscripten::invoke_vijj");
+    debug!(target: "emscripten::invoke_vijj", "called");
     if let Some(dyn_call_vijj) = &get_emscripten_data(ctx).dyn_call_vijj {
         dyn_call_vijj.call(index, a1, a2, a3, a4, a5).unwrap()
     } else {
@@ -733,11 +733,11 @@ pub fn invoke_vijj(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: i32, a4: i32
     }
 }
 pub fn invoke_viid(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: f64) {
-    debug!("emscripten::invoke_viid");
+    debug!(target: "emscripten::invoke_viid", "called");
     invoke_no_return!(ctx, dyn_call_viid, index, a1, a2, a3);
 }
 pub fn invoke_viidii(ctx: &mut Ctx, index: i32, a1: i32, a2: i32, a3: f64, a4: i32, a5: i32) {
-    debug!("emscripten::invoke_viidii");
+    debug!(target: "emscripten::invoke_viidii", "called");
     invoke_no_return!(ctx, dyn_call_viidii, index, a1, a2, a3, a4, a5);
 }
 pub fn invoke_viidddddddd(
@@ -754,7 +754,7 @@ pub fn invoke_viidddddddd(
     a9: f64,
     a10: f64,
 ) {
-    debug!("emscripten::invoke_viidddddddd");
+    debug!(target: "emscripten::invoke_viidddddddd", "called");
     invoke_no_return!(
         ctx,
         dyn_call_viidddddddd,
@@ -0,0 +1,587 @@
+//! A sandboxed filesystem for Emscripten modules.
+//!
+//! When a module is run with [`run_emscripten_instance_with_vfs`], its file
+//! syscalls are served from a [`Vfs`] instead of the host filesystem. Only the
+//! standard streams are shared with the host.
+//!
+//! [`run_emscripten_instance_with_vfs`]: ../fn.run_emscripten_instance_with_vfs.html
+//! [`Vfs`]: ../../wasmer_runtime_abi/vfs/vfs/struct.Vfs.html
+use byteorder::{ByteOrder, LittleEndian};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use wasmer_runtime_abi::vfs::{file_like::FileLike, vfs::Vfs};
+
+// The flags and error codes of musl, the libc used by Emscripten. They
+// don't always match the ones of the host.
+const O_ACCMODE: i32 = 0o3;
+const O_RDONLY: i32 = 0o0;
+const O_WRONLY: i32 = 0o1;
+const O_RDWR: i32 = 0o2;
+const O_CREAT: i32 = 0o100;
+const O_EXCL: i32 = 0o200;
+const O_TRUNC: i32 = 0o1000;
+const O_APPEND: i32 = 0o2000;
+const O_DIRECTORY: i32 = 0o200_000;
+
+const F_DUPFD: i32 = 0;
+const F_GETFD: i32 = 1;
+const F_SETFD: i32 = 2;
+const F_GETFL: i32 = 3;
+const F_SETFL: i32 = 4;
+const F_SETLK: i32 = 13;
+const F_SETLKW: i32 = 14;
+
+pub(crate) const ENOENT: i32 = 2;
+pub(crate) const EIO: i32 = 5;
+pub(crate) const EBADF: i32 = 9;
+pub(crate) const ENOMEM: i32 = 12;
+pub(crate) const EFAULT: i32 = 14;
+pub(crate) const EBUSY: i32 = 16;
+pub(crate) const EEXIST: i32 = 17;
+pub(crate) const ENOTDIR: i32 = 20;
+pub(crate) const EISDIR: i32 = 21;
+pub(crate) const EINVAL: i32 = 22;
+pub(crate) const ESPIPE: i32 = 29;
+pub(crate) const ERANGE: i32 = 34;
+pub(crate) const ENOSYS: i32 = 38;
+pub(crate) const ENOTEMPTY: i32 = 39;
+
+const S_IFCHR: u32 = 0o020_000;
+const S_IFDIR: u32 = 0o040_000;
+const S_IFREG: u32 = 0o100_000;
+
+const DT_DIR: u8 = 4;
+const DT_REG: u8 = 8;
+
+/// The size of the `dirent` records written by `getdents`.
+const DIRENT_SIZE: usize = 280;
+/// The offset of `d_name` in a `dirent` record.
+const DIRENT_NAME_OFFSET: usize = 19;
+
+#[derive(Clone)]
+enum Handle {
+    /// A standard stream, which is shared with the host.
+    Stdio(i32),
+    File {
+        file: Rc<RefCell<dyn FileLike>>,
+        readable: bool,
+        writable: bool,
+        append: bool,
+    },
+    Dir {
+        path: PathBuf,
+        /// The index of the next entry returned by `getdents`.
+        position: usize,
+    },
+}
+
+impl Handle {
+    /// The flags returned by `fcntl(F_GETFL)`.
+    fn flags(&self) -> i32 {
+        match self {
+            Handle::Stdio(0) => O_RDONLY,
+            Handle::Stdio(_) => O_WRONLY,
+            Handle::File {
+                readable,
+                writable,
+                append,
+                ..
+            } => {
+                let access_mode = match (readable, writable) {
+                    (true, true) => O_RDWR,
+                    (false, true) => O_WRONLY,
+                    _ => O_RDONLY,
+                };
+                if *append {
+                    access_mode | O_APPEND
+                } else {
+                    access_mode
+                }
+            }
+            Handle::Dir { .. } => O_RDONLY | O_DIRECTORY,
+        }
+    }
+}
+
+/// The file descriptors and working directory of an Emscripten module
+/// that runs in a [`Vfs`].
+///
+/// The methods mirror the syscalls they implement: they return the result
+/// of the syscall, or a negated errno on failure.
+///
+/// [`Vfs`]: ../../wasmer_runtime_abi/vfs/vfs/struct.Vfs.html
+pub struct EmscriptenVfs {
+    vfs: Vfs,
+    handles: BTreeMap<i32, Handle>,
+    cwd: PathBuf,
+}
+
+impl EmscriptenVfs {
+    /// Gives the module access to the files in `vfs`, starting in the root directory.
+    pub fn new(vfs: Vfs) -> Self {
+        Self {
+            vfs,
+            handles: (0..3).map(|fd| (fd, Handle::Stdio(fd))).collect(),
+            cwd: PathBuf::from("/"),
+        }
+    }
+
+    fn resolve(&self, path: &str) -> PathBuf {
+        resolve(&self.cwd, path)
+    }
+
+    fn is_dir(&self, path: &Path) -> Option<bool> {
+        self.vfs.metadata(path).map(|metadata| !metadata.is_file)
+    }
+
+    /// Checks that the parent of `path` is an existing directory.
+    fn check_parent(&self, path: &Path) -> Result<(), i32> {
+        match path.parent().map(|parent| self.is_dir(parent)) {
+            Some(Some(true)) => Ok(()),
+            Some(Some(false)) => Err(-ENOTDIR),
+            Some(None) => Err(-ENOENT),
+            // `path` is the root directory, which always exists.
+            None => Err(-EEXIST),
+        }
+    }
+
+    /// Stores `handle` under the lowest free file descriptor.
+    fn insert(&mut self, handle: Handle) -> i32 {
+        self.insert_from(0, handle)
+    }
+
+    /// Stores `handle` under the lowest free file descriptor that is at least `min_fd`.
+    fn insert_from(&mut self, min_fd: i32, handle: Handle) -> i32 {
+        let fd = (min_fd..)
+            .find(|fd| !self.handles.contains_key(fd))
+            .expect("no free file descriptor");
+        self.handles.insert(fd, handle);
+        fd
+    }
+
+    pub(crate) fn open(&mut self, path: &str, flags: i32) -> i32 {
+        let path = self.resolve(path);
+        let exclusive = flags & O_CREAT != 0 && flags & O_EXCL != 0;
+        let file = match self.is_dir(&path) {
+            Some(true) => {
+                if exclusive {
+                    return -EEXIST;
+                }
+                if flags & O_ACCMODE != O_RDONLY {
+                    return -EISDIR;
+                }
+                return self.insert(Handle::Dir { path, position: 0 });
+            }
+            Some(false) if exclusive => return -EEXIST,
+            Some(false) if flags & O_DIRECTORY != 0 => return -ENOTDIR,
+            Some(false) => self.vfs.open_file(&path),
+            None if flags & O_CREAT == 0 => return -ENOENT,
+            None => {
+                if let Err(errno) = self.check_parent(&path) {
+                    return errno;
+                }
+                self.vfs.create_file(&path)
+            }
+        };
+        let file = match file {
+            Some(file) => file,
+            None => return -EIO,
+        };
+
+        let access_mode = flags & O_ACCMODE;
+        let writable = access_mode != O_RDONLY;
+        if flags & O_TRUNC != 0 && writable && file.borrow_mut().set_file_len(0).is_err() {
+            return -EIO;
+        }
+        self.insert(Handle::File {
+            file,
+            readable: access_mode != O_WRONLY,
+            writable,
+            append: flags & O_APPEND != 0,
+        })
+    }
+
+    pub(crate) fn close(&mut self, fd: i32) -> i32 {
+        match self.handles.remove(&fd) {
+            Some(_) => 0,
+            None => -EBADF,
+        }
+    }
+
+    pub(crate) fn dup(&mut self, fd: i32) -> i32 {
+        match self.handles.get(&fd).cloned() {
+            Some(handle) => self.insert(handle),
+            None => -EBADF,
+        }
+    }
+
+    pub(crate) fn dup2(&mut self, src: i32, dst: i32) -> i32 {
+        if dst < 0 {
+            return -EBADF;
+        }
+        match self.handles.get(&src).cloned() {
+            Some(handle) => {
+                self.handles.insert(dst, handle);
+                dst
+            }
+            None => -EBADF,
+        }
+    }
+
+    pub(crate) fn fcntl(&mut self, fd: i32, cmd: i32, arg: i32) -> i32 {
+        let handle = match self.handles.get_mut(&fd) {
+            Some(handle) => handle,
+            None => return -EBADF,
+        };
+        match cmd {
+            F_DUPFD if arg < 0 => -EINVAL,
+            F_DUPFD => {
+                let handle = handle.clone();
+                self.insert_from(arg, handle)
+            }
+            // There is no `exec`, so the close-on-exec flag doesn't matter.
+            F_GETFD | F_SETFD => 0,
+            F_GETFL => handle.flags(),
+            F_SETFL => {
+                if let Handle::File { append, .. } = handle {
+                    *append = arg & O_APPEND != 0;
+                }
+                0
+            }
+            // Nothing else can open the files of the vfs, so locks always succeed.
+            F_SETLK | F_SETLKW => 0,
+            _ => -EINVAL,
+        }
+    }
+
+    pub(crate) fn read(&mut self, fd: i32, buf: &mut [u8]) -> i32 {
+        match self.handles.get(&fd) {
+            Some(Handle::Stdio(fd)) => {
+                let ret = unsafe { libc::read(*fd, buf.as_mut_ptr() as *mut _, buf.len()) };
+                if ret < 0 {
+                    -EIO
+                } else {
+                    ret as i32
+                }
+            }
+            Some(Handle::File {
+                file,
+                readable: true,
+                ..
+            }) => match file.borrow_mut().read(buf) {
+                Ok(read) => read as i32,
+                Err(_) => -EIO,
+            },
+            Some(Handle::Dir { .. }) => -EISDIR,
+            _ => -EBADF,
+        }
+    }
+
+    pub(crate) fn write(&mut self, fd: i32, buf: &[u8]) -> i32 {
+        match self.handles.get(&fd) {
+            Some(Handle::Stdio(fd)) => {
+                let ret = unsafe { libc::write(*fd, buf.as_ptr() as *const _, buf.len()) };
+                if ret < 0 {
+                    -EIO
+                } else {
+                    ret as i32
+                }
+            }
+            Some(Handle::File {
+                file,
+                writable: true,
+                append,
+                ..
+            }) => {
+                let mut file = file.borrow_mut();
+                if *append && file.seek(SeekFrom::End(0)).is_err() {
+                    return -EIO;
+                }
+                match file.write(buf) {
+                    Ok(written) => written as i32,
+                    Err(_) => -EIO,
+                }
+            }
+            _ => -EBADF,
+        }
+    }
+
+    /// Returns the new position in the file, or a negated errno.
+    pub(crate) fn seek(&mut self, fd: i32, offset: i64, whence: i32) -> i64 {
+        let pos = match whence {
+            0 if offset >= 0 => SeekFrom::Start(offset as u64),
+            1 => SeekFrom::Current(offset),
+            2 => SeekFrom::End(offset),
+            _ => return -i64::from(EINVAL),
+        };
+        match self.handles.get_mut(&fd) {
+            Some(Handle::File { file, .. }) => match file.borrow_mut().seek(pos) {
+                Ok(position) => position as i64,
+                Err(_) => -i64::from(EINVAL),
+            },
+            // Only rewinding is supported, which is what `rewinddir` does.
+            Some(Handle::Dir { position, .. }) if offset == 0 && whence == 0 => {
+                *position = 0;
+                0
+            }
+            Some(Handle::Dir { .. }) => -i64::from(EINVAL),
+            Some(Handle::Stdio(_)) => -i64::from(ESPIPE),
+            None => -i64::from(EBADF),
+        }
+    }
+
+    pub(crate) fn pread(&mut self, fd: i32, buf: &mut [u8], offset: i64) -> i32 {
+        self.at_offset(fd, offset, |vfs| vfs.read(fd, buf))
+    }
+
+    pub(crate) fn pwrite(&mut self, fd: i32, buf: &[u8], offset: i64) -> i32 {
+        self.at_offset(fd, offset, |vfs| vfs.write(fd, buf))
+    }
+
+    /// Runs `f` with the file positioned at `offset`, then restores the position.
+    fn at_offset<F: FnOnce(&mut Self) -> i32>(&mut self, fd: i32, offset: i64, f: F) -> i32 {
+        let position = self.seek(fd, 0, 1);
+        if position < 0 {
+            return position as i32;
+        }
+        let ret = self.seek(fd, offset, 0);
+        if ret < 0 {
+            return ret as i32;
+        }
+        let ret = f(self);
+        self.seek(fd, position, 0);
+        ret
+    }
+
+    pub(crate) fn ftruncate(&mut self, fd: i32, length: i64) -> i32 {
+        match self.handles.get(&fd) {
+            Some(Handle::File { .. }) if length < 0 => -EINVAL,
+            Some(Handle::File {
+                file,
+                writable: true,
+                ..
+            }) => match file.borrow_mut().set_file_len(length as usize) {
+                Ok(()) => 0,
+                Err(_) => -EIO,
+            },
+            Some(Handle::Dir { .. }) => -EISDIR,
+            _ => -EBADF,
+        }
+    }
+
+    pub(crate) fn stat(&mut self, path: &str) -> Result<libc::stat, i32> {
+        let path = self.resolve(path);
+        match self.vfs.metadata(&path) {
+            Some(ref metadata) if metadata.is_file => Ok(make_stat(S_IFREG | 0o644, metadata.len)),
+            Some(_) => Ok(make_stat(S_IFDIR | 0o755, 0)),
+            None => Err(-ENOENT),
+        }
+    }
+
+    pub(crate) fn fstat(&mut self, fd: i32) -> Result<libc::stat, i32> {
+        match self.handles.get(&fd) {
+            Some(Handle::Stdio(_)) => Ok(make_stat(S_IFCHR | 0o620, 0)),
+            Some(Handle::File { file, .. }) => match file.borrow().metadata() {
+                Ok(metadata) => Ok(make_stat(S_IFREG | 0o644, metadata.len)),
+                Err(_) => Err(-EIO),
+            },
+            Some(Handle::Dir { .. }) => Ok(make_stat(S_IFDIR | 0o755, 0)),
+            None => Err(-EBADF),
+        }
+    }
+
+    pub(crate) fn access(&mut self, path: &str) -> i32 {
+        match self.vfs.metadata(self.resolve(path)) {
+            Some(_) => 0,
+            None => -ENOENT,
+        }
+    }
+
+    pub(crate) fn mkdir(&mut self, path: &str) -> i32 {
+        let path = self.resolve(path);
+        if self.is_dir(&path).is_some() {
+            return -EEXIST;
+        }
+        if let Err(errno) = self.check_parent(&path) {
+            return errno;
+        }
+        self.vfs.make_dir(&path);
+        0
+    }
+
+    pub(crate) fn rmdir(&mut self, path: &str) -> i32 {
+        let path = self.resolve(path);
+        match self.is_dir(&path) {
+            Some(true) => {}
+            Some(false) => return -ENOTDIR,
+            None => return -ENOENT,
+        }
+        if path.parent().is_none() {
+            return -EBUSY;
+        }
+        match self.vfs.read_dir(&path) {
+            Some(ref entries) if !entries.is_empty() => -ENOTEMPTY,
+            _ => match self.vfs.remove_dir(&path) {
+                Ok(()) => 0,
+                Err(_) => -EIO,
+            },
+        }
+    }
+
+    pub(crate) fn unlink(&mut self, path: &str) -> i32 {
+        let path = self.resolve(path);
+        match self.is_dir(&path) {
+            Some(false) => match self.vfs.remove_file(&path) {
+                Ok(()) => 0,
+                Err(_) => -EIO,
+            },
+            Some(true) => -EISDIR,
+            None => -ENOENT,
+        }
+    }
+
+    pub(crate) fn chdir(&mut self, path: &str) -> i32 {
+        let path = self.resolve(path);
+        match self.is_dir(&path) {
+            Some(true) => {
+                self.cwd = path;
+                0
+            }
+            Some(false) => -ENOTDIR,
+            None => -ENOENT,
+        }
+    }
+
+    pub(crate) fn getcwd(&self) -> String {
+        self.cwd.to_string_lossy().into_owned()
+    }
+
+    /// Fills `buf` with as many `dirent` records as fit, and returns the number of bytes written.
+    pub(crate) fn getdents(&mut self, fd: i32, buf: &mut [u8]) -> i32 {
+        let (path, position) = match self.handles.get_mut(&fd) {
+            Some(Handle::Dir { path, position }) => (path, position),
+            Some(_) => return -ENOTDIR,
+            None => return -EBADF,
+        };
+        let children = match self.vfs.read_dir(&*path) {
+            Some(children) => children,
+            None => return -ENOENT,
+        };
+
+        let mut entries = vec![(".".to_string(), DT_DIR), ("..".to_string(), DT_DIR)];
+        entries.extend(
+            children
+                .into_iter()
+                .map(|(name, metadata)| (name, if metadata.is_file { DT_REG } else { DT_DIR })),
+        );
+
+        let mut written = 0;
+        for (index, (name, d_type)) in entries.iter().enumerate().skip(*position) {
+            if written + DIRENT_SIZE > buf.len() {
+                break;
+            }
+            let record = &mut buf[written..written + DIRENT_SIZE];
+            LittleEndian::write_u64(&mut record[0..], index as u64 + 1);
+            LittleEndian::write_i64(&mut record[8..], ((index + 1) * DIRENT_SIZE) as i64);
+            LittleEndian::write_u16(&mut record[16..], DIRENT_SIZE as u16);
+            record[18] = *d_type;
+            let name = name.as_bytes();
+            let len = name.len().min(DIRENT_SIZE - DIRENT_NAME_OFFSET - 1);
+            record[DIRENT_NAME_OFFSET..DIRENT_NAME_OFFSET + len].copy_from_slice(&name[..len]);
+            record[DIRENT_NAME_OFFSET + len] = 0;
+
+            written += DIRENT_SIZE;
+            *position = index + 1;
+        }
+
+        if written == 0 && *position < entries.len() {
+            return -EINVAL;
+        }
+        written as i32
+    }
+}
+
+/// Resolves `path` against the working directory `cwd`.
+///
+/// `..` never leaves the root, like on a regular filesystem.
+fn resolve(cwd: &Path, path: &str) -> PathBuf {
+    let mut resolved = cwd.to_path_buf();
+    for component in Path::new(path).components() {
+        match component {
+            Component::RootDir => resolved = PathBuf::from("/"),
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(name) => resolved.push(name),
+            Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    resolved
+}
+
+fn make_stat(mode: u32, len: usize) -> libc::stat {
+    let mut stat: libc::stat = unsafe { mem::zeroed() };
+    stat.st_mode = mode as _;
+    stat.st_nlink = 1;
+    stat.st_size = len as _;
+    stat.st_blocks = ((len + 511) / 512) as _;
+    stat
+}
+
+#[cfg(test)]
+mod emscripten_vfs_tests {
+    use super::{
+        resolve, EmscriptenVfs, EBADF, ENOENT, F_DUPFD, F_GETFL, F_SETFL, O_APPEND, O_CREAT,
+        O_WRONLY,
+    };
+    use std::path::{Path, PathBuf};
+    use wasmer_runtime_abi::vfs::vfs::Vfs;
+
+    #[test]
+    fn test_resolve_stays_in_root() {
+        let cwd = Path::new("/data");
+
+        assert_eq!(resolve(cwd, "a/./b"), PathBuf::from("/data/a/b"));
+        assert_eq!(
+            resolve(cwd, "../../../etc/passwd"),
+            PathBuf::from("/etc/passwd")
+        );
+        assert_eq!(resolve(cwd, "/tmp"), PathBuf::from("/tmp"));
+    }
+
+    #[test]
+    fn test_write_then_read() {
+        let (vfs, _) = Vfs::new().unwrap();
+        let mut vfs = EmscriptenVfs::new(vfs);
+        assert_eq!(vfs.open("missing.txt", 0), -ENOENT);
+
+        let fd = vfs.open("hello.txt", O_CREAT | O_WRONLY);
+        assert_eq!(fd, 3);
+        assert_eq!(vfs.write(fd, b"hello"), 5);
+        assert_eq!(vfs.read(fd, &mut [0; 5]), -EBADF);
+        assert_eq!(vfs.close(fd), 0);
+
+        let fd = vfs.open("/hello.txt", 0);
+        let mut buf = [0; 8];
+        assert_eq!(vfs.read(fd, &mut buf), 5);
+        assert_eq!(&buf[..5], b"hello");
+        assert_eq!(vfs.stat("hello.txt").unwrap().st_size, 5);
+    }
+
+    #[test]
+    fn test_fcntl() {
+        let (vfs, _) = Vfs::new().unwrap();
+        let mut vfs = EmscriptenVfs::new(vfs);
+        let fd = vfs.open("log.txt", O_CREAT | O_WRONLY);
+
+        assert_eq!(vfs.fcntl(fd, F_GETFL, 0), O_WRONLY);
+        assert_eq!(vfs.fcntl(fd, F_SETFL, O_APPEND), 0);
+        assert_eq!(vfs.fcntl(fd, F_GETFL, 0), O_WRONLY | O_APPEND);
+        assert_eq!(vfs.fcntl(fd, F_DUPFD, 10), 10);
+        assert_eq!(vfs.fcntl(42, F_GETFL, 0), -EBADF);
+    }
+}
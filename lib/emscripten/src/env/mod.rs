@@ -15,7 +15,7 @@ use std::os::raw::c_int;
 use wasmer_runtime_core::vm::Ctx;
 
 pub fn _getaddrinfo(_ctx: &mut Ctx, _one: i32, _two: i32, _three: i32, _four: i32) -> i32 {
-    debug!("emscripten::_getaddrinfo");
+    debug!(target: "emscripten::_getaddrinfo", "called");
     -1
 }
 
@@ -43,7 +43,7 @@ pub(crate) fn get_emscripten_data(ctx: &mut Ctx) -> &mut EmscriptenData {
 }
 
 pub fn _getpagesize(_ctx: &mut Ctx) -> u32 {
-    debug!("emscripten::_getpagesize");
+    debug!(target: "emscripten::_getpagesize", "called");
     16384
 }
 
@@ -56,7 +56,7 @@ pub fn _times(ctx: &mut Ctx, buffer: u32) -> u32 {
 
 #[allow(clippy::cast_ptr_alignment)]
 pub fn ___build_environment(ctx: &mut Ctx, environ: c_int) {
-    debug!("emscripten::___build_environment {}", environ);
+    debug!(target: "emscripten::___build_environment", "{}", environ);
     const MAX_ENV_VALUES: u32 = 64;
     const TOTAL_ENV_SIZE: u32 = 1024;
     let environment = emscripten_memory_pointer!(ctx.memory(0), environ) as *mut c_int;
@@ -78,7 +78,7 @@ pub fn ___build_environment(ctx: &mut Ctx, environ: c_int) {
 }
 
 pub fn ___assert_fail(_ctx: &mut Ctx, _a: c_int, _b: c_int, _c: c_int, _d: c_int) {
-    debug!("emscripten::___assert_fail {} {} {} {}", _a, _b, _c, _d);
+    debug!(target: "emscripten::___assert_fail", "{} {} {} {}", _a, _b, _c, _d);
     // TODO: Implement like emscripten expects regarding memory/page size
     // TODO raise an error
 }
@@ -14,11 +14,11 @@ use wasmer_runtime_core::vm::Ctx;
 // #[no_mangle]
 /// emscripten: _getenv // (name: *const char) -> *const c_char;
 pub fn _getenv(ctx: &mut Ctx, name: i32) -> u32 {
-    debug!("emscripten::_getenv");
+    debug!(target: "emscripten::_getenv", "called");
 
     let name_addr = emscripten_memory_pointer!(ctx.memory(0), name) as *const c_char;
 
-    debug!("=> name({:?})", unsafe { CStr::from_ptr(name_addr) });
+    debug!(target: "emscripten::_getenv", "=> name({:?})", unsafe { CStr::from_ptr(name_addr) });
 
     let c_str = unsafe { getenv(name_addr) };
     if c_str.is_null() {
@@ -30,42 +30,42 @@ pub fn _getenv(ctx: &mut Ctx, name: i32) -> u32 {
 
 /// emscripten: _setenv // (name: *const char, name: *const value, overwrite: int);
 pub fn _setenv(ctx: &mut Ctx, name: c_int, value: c_int, overwrite: c_int) -> c_int {
-    debug!("emscripten::_setenv");
+    debug!(target: "emscripten::_setenv", "called");
 
     let name_addr = emscripten_memory_pointer!(ctx.memory(0), name) as *const c_char;
     let value_addr = emscripten_memory_pointer!(ctx.memory(0), value) as *const c_char;
 
-    debug!("=> name({:?})", unsafe { CStr::from_ptr(name_addr) });
-    debug!("=> value({:?})", unsafe { CStr::from_ptr(value_addr) });
+    debug!(target: "emscripten::_setenv", "=> name({:?})", unsafe { CStr::from_ptr(name_addr) });
+    debug!(target: "emscripten::_setenv", "=> value({:?})", unsafe { CStr::from_ptr(value_addr) });
 
     unsafe { setenv(name_addr, value_addr, overwrite) }
 }
 
 /// emscripten: _putenv // (name: *const char);
 pub fn _putenv(ctx: &mut Ctx, name: c_int) -> c_int {
-    debug!("emscripten::_putenv");
+    debug!(target: "emscripten::_putenv", "called");
 
     let name_addr = emscripten_memory_pointer!(ctx.memory(0), name) as *const c_char;
 
-    debug!("=> name({:?})", unsafe { CStr::from_ptr(name_addr) });
+    debug!(target: "emscripten::_putenv", "=> name({:?})", unsafe { CStr::from_ptr(name_addr) });
 
     unsafe { putenv(name_addr as _) }
 }
 
 /// emscripten: _unsetenv // (name: *const char);
 pub fn _unsetenv(ctx: &mut Ctx, name: c_int) -> c_int {
-    debug!("emscripten::_unsetenv");
+    debug!(target: "emscripten::_unsetenv", "called");
 
     let name_addr = emscripten_memory_pointer!(ctx.memory(0), name) as *const c_char;
 
-    debug!("=> name({:?})", unsafe { CStr::from_ptr(name_addr) });
+    debug!(target: "emscripten::_unsetenv", "=> name({:?})", unsafe { CStr::from_ptr(name_addr) });
 
     unsafe { unsetenv(name_addr) }
 }
 
 #[allow(clippy::cast_ptr_alignment)]
 pub fn _getpwnam(ctx: &mut Ctx, name_ptr: c_int) -> c_int {
-    debug!("emscripten::_getpwnam {}", name_ptr);
+    debug!(target: "emscripten::_getpwnam", "{}", name_ptr);
     #[cfg(feature = "debug")]
     let _ = name_ptr;
 
@@ -105,7 +105,7 @@ pub fn _getpwnam(ctx: &mut Ctx, name_ptr: c_int) -> c_int {
 
 #[allow(clippy::cast_ptr_alignment)]
 pub fn _getgrnam(ctx: &mut Ctx, name_ptr: c_int) -> c_int {
-    debug!("emscripten::_getgrnam {}", name_ptr);
+    debug!(target: "emscripten::_getgrnam", "{}", name_ptr);
 
     #[repr(C)]
     struct GuestGroup {
@@ -136,7 +136,7 @@ pub fn _getgrnam(ctx: &mut Ctx, name_ptr: c_int) -> c_int {
 }
 
 pub fn _sysconf(_ctx: &mut Ctx, name: c_int) -> i32 {
-    debug!("emscripten::_sysconf {}", name);
+    debug!(target: "emscripten::_sysconf", "{}", name);
     // TODO: Implement like emscripten expects regarding memory/page size
     unsafe { sysconf(name) as i32 } // TODO review i64
 }
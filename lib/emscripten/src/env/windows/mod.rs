@@ -17,9 +17,9 @@ extern "C" {
 // #[no_mangle]
 /// emscripten: _getenv // (name: *const char) -> *const c_char;
 pub fn _getenv(ctx: &mut Ctx, name: u32) -> u32 {
-    debug!("emscripten::_getenv");
+    debug!(target: "emscripten::_getenv", "called");
     let name_string = read_string_from_wasm(ctx.memory(0), name);
-    debug!("=> name({:?})", name_string);
+    debug!(target: "emscripten::_getenv", "=> name({:?})", name_string);
     let c_str = unsafe { getenv(name_string.as_ptr() as *const libc::c_char) };
     if c_str.is_null() {
         return 0;
@@ -29,23 +29,23 @@ pub fn _getenv(ctx: &mut Ctx, name: u32) -> u32 {
 
 /// emscripten: _setenv // (name: *const char, name: *const value, overwrite: int);
 pub fn _setenv(ctx: &mut Ctx, name: u32, value: u32, _overwrite: u32) -> c_int {
-    debug!("emscripten::_setenv");
+    debug!(target: "emscripten::_setenv", "called");
     // setenv does not exist on windows, so we hack it with _putenv
     let name = read_string_from_wasm(ctx.memory(0), name);
     let value = read_string_from_wasm(ctx.memory(0), value);
     let putenv_string = format!("{}={}", name, value);
     let putenv_cstring = CString::new(putenv_string).unwrap();
     let putenv_raw_ptr = putenv_cstring.as_ptr();
-    debug!("=> name({:?})", name);
-    debug!("=> value({:?})", value);
+    debug!(target: "emscripten::_setenv", "=> name({:?})", name);
+    debug!(target: "emscripten::_setenv", "=> value({:?})", value);
     unsafe { putenv(putenv_raw_ptr) }
 }
 
 /// emscripten: _putenv // (name: *const char);
 pub fn _putenv(ctx: &mut Ctx, name: c_int) -> c_int {
-    debug!("emscripten::_putenv");
+    debug!(target: "emscripten::_putenv", "called");
     let name_addr = emscripten_memory_pointer!(ctx.memory(0), name) as *const c_char;
-    debug!("=> name({:?})", unsafe {
+    debug!(target: "emscripten::_putenv", "=> name({:?})", unsafe {
         std::ffi::CStr::from_ptr(name_addr)
     });
     unsafe { putenv(name_addr) }
@@ -53,19 +53,19 @@ pub fn _putenv(ctx: &mut Ctx, name: c_int) -> c_int {
 
 /// emscripten: _unsetenv // (name: *const char);
 pub fn _unsetenv(ctx: &mut Ctx, name: u32) -> c_int {
-    debug!("emscripten::_unsetenv");
+    debug!(target: "emscripten::_unsetenv", "called");
     let name = read_string_from_wasm(ctx.memory(0), name);
     // no unsetenv on windows, so use putenv with an empty value
     let unsetenv_string = format!("{}=", name);
     let unsetenv_cstring = CString::new(unsetenv_string).unwrap();
     let unsetenv_raw_ptr = unsetenv_cstring.as_ptr();
-    debug!("=> name({:?})", name);
+    debug!(target: "emscripten::_unsetenv", "=> name({:?})", name);
     unsafe { putenv(unsetenv_raw_ptr) }
 }
 
 #[allow(clippy::cast_ptr_alignment)]
 pub fn _getpwnam(ctx: &mut Ctx, name_ptr: c_int) -> c_int {
-    debug!("emscripten::_getpwnam {}", name_ptr);
+    debug!(target: "emscripten::_getpwnam", "{}", name_ptr);
     #[cfg(not(feature = "debug"))]
     let _ = name_ptr;
 
@@ -99,7 +99,7 @@ pub fn _getpwnam(ctx: &mut Ctx, name_ptr: c_int) -> c_int {
 
 #[allow(clippy::cast_ptr_alignment)]
 pub fn _getgrnam(ctx: &mut Ctx, name_ptr: c_int) -> c_int {
-    debug!("emscripten::_getgrnam {}", name_ptr);
+    debug!(target: "emscripten::_getgrnam", "{}", name_ptr);
     #[cfg(not(feature = "debug"))]
     let _ = name_ptr;
 
@@ -125,7 +125,7 @@ pub fn _getgrnam(ctx: &mut Ctx, name_ptr: c_int) -> c_int {
 }
 
 pub fn _sysconf(_ctx: &mut Ctx, name: c_int) -> c_long {
-    debug!("emscripten::_sysconf {}", name);
+    debug!(target: "emscripten::_sysconf", "{}", name);
     #[cfg(not(feature = "debug"))]
     let _ = name;
     // stub because sysconf is not valid on windows
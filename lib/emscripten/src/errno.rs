@@ -2,7 +2,7 @@
 use wasmer_runtime_core::vm::Ctx;
 
 pub fn ___seterrno(_ctx: &mut Ctx, _value: i32) {
-    debug!("emscripten::___seterrno {}", _value);
+    debug!(target: "emscripten::___seterrno", "{}", _value);
     // TODO: Incomplete impl
     eprintln!("failed to set errno!");
     // value
@@ -42,12 +42,12 @@ pub fn execvp(ctx: &mut Ctx, command_name_offset: u32, argv_offset: u32) -> i32
 
 /// execl
 pub fn execl(_ctx: &mut Ctx, _path_ptr: i32, _arg0_ptr: i32, _varargs: VarArgs) -> i32 {
-    debug!("emscripten::execl");
+    debug!(target: "emscripten::execl", "called");
     -1
 }
 
 /// execle
 pub fn execle(_ctx: &mut Ctx, _path_ptr: i32, _arg0_ptr: i32, _varargs: VarArgs) -> i32 {
-    debug!("emscripten::execle");
+    debug!(target: "emscripten::execle", "called");
     -1
 }
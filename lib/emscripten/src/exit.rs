@@ -1,7 +1,9 @@
+use crate::ExitCode;
+use std::convert::Infallible;
 use wasmer_runtime_core::vm::Ctx;
 
 // __exit
-pub fn exit(_ctx: &mut Ctx, value: i32) {
-    debug!("emscripten::exit {}", value);
-    ::std::process::exit(value);
+pub fn exit(_ctx: &mut Ctx, value: i32) -> Result<Infallible, ExitCode> {
+    debug!(target: "emscripten::exit", "{}", value);
+    Err(ExitCode { code: value })
 }
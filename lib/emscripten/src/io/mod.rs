@@ -14,19 +14,19 @@ use wasmer_runtime_core::vm::Ctx;
 
 /// getprotobyname
 pub fn getprotobyname(_ctx: &mut Ctx, _name_ptr: i32) -> i32 {
-    debug!("emscripten::getprotobyname");
+    debug!(target: "emscripten::getprotobyname", "called");
     unimplemented!()
 }
 
 /// getprotobynumber
 pub fn getprotobynumber(_ctx: &mut Ctx, _one: i32) -> i32 {
-    debug!("emscripten::getprotobynumber");
+    debug!(target: "emscripten::getprotobynumber", "called");
     unimplemented!()
 }
 
 /// sigdelset
 pub fn sigdelset(ctx: &mut Ctx, set: i32, signum: i32) -> i32 {
-    debug!("emscripten::sigdelset");
+    debug!(target: "emscripten::sigdelset", "called");
     let memory = ctx.memory(0);
     #[allow(clippy::cast_ptr_alignment)]
     let ptr = emscripten_memory_pointer!(memory, set) as *mut i32;
@@ -38,7 +38,7 @@ pub fn sigdelset(ctx: &mut Ctx, set: i32, signum: i32) -> i32 {
 
 /// sigfillset
 pub fn sigfillset(ctx: &mut Ctx, set: i32) -> i32 {
-    debug!("emscripten::sigfillset");
+    debug!(target: "emscripten::sigfillset", "called");
     let memory = ctx.memory(0);
     #[allow(clippy::cast_ptr_alignment)]
     let ptr = emscripten_memory_pointer!(memory, set) as *mut i32;
@@ -52,12 +52,12 @@ pub fn sigfillset(ctx: &mut Ctx, set: i32) -> i32 {
 
 /// tzset
 pub fn tzset(_ctx: &mut Ctx) {
-    debug!("emscripten::tzset - stub");
+    debug!(target: "emscripten::tzset", "- stub");
     //unimplemented!()
 }
 
 /// strptime
 pub fn strptime(_ctx: &mut Ctx, _one: i32, _two: i32, _three: i32) -> i32 {
-    debug!("emscripten::strptime");
+    debug!(target: "emscripten::strptime", "called");
     unimplemented!()
 }
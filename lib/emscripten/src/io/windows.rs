@@ -20,7 +20,7 @@ pub fn putchar(_ctx: &mut Ctx, chr: i32) {
 
 /// printf
 pub fn printf(_ctx: &mut Ctx, memory_offset: i32, extra: i32) -> i32 {
-    debug!("emscripten::printf {}, {}", memory_offset, extra);
+    debug!(target: "emscripten::printf", "{}, {}", memory_offset, extra);
     #[cfg(not(feature = "debug"))]
     {
         let _ = memory_offset;
@@ -35,12 +35,12 @@ pub fn printf(_ctx: &mut Ctx, memory_offset: i32, extra: i32) -> i32 {
 
 /// chroot
 pub fn chroot(_ctx: &mut Ctx, _name_ptr: i32) -> i32 {
-    debug!("emscripten::chroot");
+    debug!(target: "emscripten::chroot", "called");
     unimplemented!()
 }
 
 /// getpwuid
 pub fn getpwuid(_ctx: &mut Ctx, _uid: i32) -> i32 {
-    debug!("emscripten::getpwuid");
+    debug!(target: "emscripten::getpwuid", "called");
     unimplemented!()
 }
@@ -6,7 +6,7 @@ use wasmer_runtime_core::vm::Ctx;
 
 /// setjmp
 pub fn __setjmp(ctx: &mut Ctx, _env_addr: u32) -> c_int {
-    debug!("emscripten::__setjmp (setjmp)");
+    debug!(target: "emscripten::__setjmp", "(setjmp)");
     abort_with_message(ctx, "missing function: _longjmp");
     unreachable!()
     // unsafe {
@@ -29,7 +29,7 @@ pub fn __setjmp(ctx: &mut Ctx, _env_addr: u32) -> c_int {
 /// longjmp
 #[allow(unreachable_code)]
 pub fn __longjmp(ctx: &mut Ctx, _env_addr: u32, _val: c_int) {
-    debug!("emscripten::__longjmp (longmp)");
+    debug!(target: "emscripten::__longjmp", "(longmp)");
     abort_with_message(ctx, "missing function: _longjmp");
     // unsafe {
     //     // We retrieve the jump index from the env address
@@ -1,5 +1,7 @@
 #[macro_use]
 extern crate wasmer_runtime_core;
+#[macro_use]
+extern crate log;
 
 use lazy_static::lazy_static;
 use std::cell::UnsafeCell;
@@ -29,6 +31,8 @@ pub mod stdio;
 // EMSCRIPTEN APIS
 mod bitwise;
 mod emscripten_target;
+#[cfg(feature = "vfs")]
+pub mod emscripten_vfs;
 mod env;
 mod errno;
 mod exception;
@@ -48,6 +52,8 @@ mod time;
 mod utils;
 mod varargs;
 
+#[cfg(feature = "vfs")]
+pub use self::emscripten_vfs::EmscriptenVfs;
 pub use self::storage::{align_memory, static_alloc};
 pub use self::utils::{
     allocate_cstr_on_stack, allocate_on_stack, get_emscripten_memory_size,
@@ -71,6 +77,12 @@ lazy_static! {
 const GLOBAL_BASE: u32 = 1024;
 const STATIC_BASE: u32 = GLOBAL_BASE;
 
+/// This is returned in the Box<dyn Any> RuntimeError::Error variant when the
+/// program calls `exit`. Use `downcast` or `downcast_ref` to retrieve the `ExitCode`.
+pub struct ExitCode {
+    pub code: i32,
+}
+
 pub struct EmscriptenData<'a> {
     pub malloc: Func<'a, u32, u32>,
     pub free: Func<'a, u32>,
@@ -140,6 +152,10 @@ pub struct EmscriptenData<'a> {
     pub stack_save: Option<Func<'a, (), i32>>,
     pub stack_restore: Option<Func<'a, (i32)>>,
     pub set_threw: Option<Func<'a, (i32, i32)>>,
+
+    /// The sandboxed filesystem of the module, if it doesn't use the host's.
+    #[cfg(feature = "vfs")]
+    pub vfs: Option<EmscriptenVfs>,
 }
 
 impl<'a> EmscriptenData<'a> {
@@ -273,6 +289,9 @@ impl<'a> EmscriptenData<'a> {
             stack_save,
             stack_restore,
             set_threw,
+
+            #[cfg(feature = "vfs")]
+            vfs: None,
         }
     }
 }
@@ -287,6 +306,28 @@ pub fn run_emscripten_instance(
     let data_ptr = &mut data as *mut _ as *mut c_void;
     instance.context_mut().data = data_ptr;
 
+    call_main(instance, path, args)
+}
+
+/// Like `run_emscripten_instance`, except that the file syscalls of the module
+/// are served from `vfs` instead of the host filesystem.
+#[cfg(feature = "vfs")]
+pub fn run_emscripten_instance_with_vfs(
+    _module: &Module,
+    instance: &mut Instance,
+    path: &str,
+    args: Vec<&str>,
+    vfs: EmscriptenVfs,
+) -> CallResult<()> {
+    let mut data = EmscriptenData::new(instance);
+    data.vfs = Some(vfs);
+    let data_ptr = &mut data as *mut _ as *mut c_void;
+    instance.context_mut().data = data_ptr;
+
+    call_main(instance, path, args)
+}
+
+fn call_main(instance: &mut Instance, path: &str, args: Vec<&str>) -> CallResult<()> {
     // ATINIT
     // (used by C++)
     if let Ok(_func) = instance.dyn_func("globalCtors") {
@@ -815,7 +856,7 @@ pub fn generate_emscripten_env(globals: &mut EmscriptenGlobals) -> ImportObject
 
 pub fn nullfunc(ctx: &mut Ctx, _x: u32) {
     use crate::process::abort_with_message;
-    debug!("emscripten::nullfunc_i {}", _x);
+    debug!(target: "emscripten::nullfunc", "emscripten::nullfunc_i {}", _x);
     abort_with_message(ctx, "Invalid function pointer. Perhaps this is an invalid value \
     (e.g. caused by calling a virtual method on a NULL pointer)? Or calling a function with an \
     incorrect type, which will fail? (it is worth building your source files with -Werror (\
@@ -2,56 +2,56 @@ use wasmer_runtime_core::vm::Ctx;
 
 /// emscripten: _llvm_log10_f64
 pub fn _llvm_log10_f64(_ctx: &mut Ctx, value: f64) -> f64 {
-    debug!("emscripten::_llvm_log10_f64");
+    debug!(target: "emscripten::_llvm_log10_f64", "called");
     value.log10()
 }
 
 /// emscripten: _llvm_log2_f64
 pub fn _llvm_log2_f64(_ctx: &mut Ctx, value: f64) -> f64 {
-    debug!("emscripten::_llvm_log2_f64");
+    debug!(target: "emscripten::_llvm_log2_f64", "called");
     value.log2()
 }
 
 /// emscripten: _llvm_sin_f64
 pub fn _llvm_sin_f64(_ctx: &mut Ctx, value: f64) -> f64 {
-    debug!("emscripten::_llvm_sin_f64");
+    debug!(target: "emscripten::_llvm_sin_f64", "called");
     value.sin()
 }
 
 /// emscripten: _llvm_cos_f64
 pub fn _llvm_cos_f64(_ctx: &mut Ctx, value: f64) -> f64 {
-    debug!("emscripten::_llvm_cos_f64");
+    debug!(target: "emscripten::_llvm_cos_f64", "called");
     value.cos()
 }
 
 pub fn _llvm_log10_f32(_ctx: &mut Ctx, _value: f64) -> f64 {
-    debug!("emscripten::_llvm_log10_f32");
+    debug!(target: "emscripten::_llvm_log10_f32", "called");
     -1.0
 }
 
 pub fn _llvm_log2_f32(_ctx: &mut Ctx, _value: f64) -> f64 {
-    debug!("emscripten::_llvm_log10_f32");
+    debug!(target: "emscripten::_llvm_log2_f32", "emscripten::_llvm_log10_f32");
     -1.0
 }
 
 pub fn _llvm_exp2_f32(_ctx: &mut Ctx, value: f32) -> f32 {
-    debug!("emscripten::_llvm_exp2_f32");
+    debug!(target: "emscripten::_llvm_exp2_f32", "called");
     2f32.powf(value)
 }
 
 pub fn _llvm_exp2_f64(_ctx: &mut Ctx, value: f64) -> f64 {
-    debug!("emscripten::_llvm_exp2_f64");
+    debug!(target: "emscripten::_llvm_exp2_f64", "called");
     2f64.powf(value)
 }
 
 pub fn _emscripten_random(_ctx: &mut Ctx) -> f64 {
-    debug!("emscripten::_emscripten_random");
+    debug!(target: "emscripten::_emscripten_random", "called");
     -1.0
 }
 
 // emscripten: asm2wasm.f64-rem
 pub fn f64_rem(_ctx: &mut Ctx, x: f64, y: f64) -> f64 {
-    debug!("emscripten::f64-rem");
+    debug!(target: "emscripten::f64_rem", "emscripten::f64-rem");
     x % y
 }
 
@@ -72,6 +72,6 @@ pub fn log(_ctx: &mut Ctx, value: f64) -> f64 {
 
 // emscripten: asm2wasm.f64-to-int
 pub fn f64_to_int(_ctx: &mut Ctx, value: f64) -> i32 {
-    debug!("emscripten::f64_to_int {}", value);
+    debug!(target: "emscripten::f64_to_int", "{}", value);
     value as i32
 }
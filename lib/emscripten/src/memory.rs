@@ -8,7 +8,8 @@ use wasmer_runtime_core::{
 /// emscripten: _emscripten_memcpy_big
 pub fn _emscripten_memcpy_big(ctx: &mut Ctx, dest: u32, src: u32, len: u32) -> u32 {
     debug!(
-        "emscripten::_emscripten_memcpy_big {}, {}, {}",
+        target: "emscripten::_emscripten_memcpy_big",
+        "{}, {}, {}",
         dest, src, len
     );
     let dest_addr = emscripten_memory_pointer!(ctx.memory(0), dest) as *mut c_void;
@@ -21,7 +22,7 @@ pub fn _emscripten_memcpy_big(ctx: &mut Ctx, dest: u32, src: u32, len: u32) -> u
 
 /// emscripten: _emscripten_get_heap_size
 pub fn _emscripten_get_heap_size(ctx: &mut Ctx) -> u32 {
-    debug!("emscripten::_emscripten_get_heap_size",);
+    debug!(target: "emscripten::_emscripten_get_heap_size", "called",);
     ctx.memory(0).size().bytes().0 as u32
 }
 
@@ -36,7 +37,7 @@ fn align_up(mut val: usize, multiple: usize) -> usize {
 /// emscripten: _emscripten_resize_heap
 /// Note: this function only allows growing the size of heap
 pub fn _emscripten_resize_heap(ctx: &mut Ctx, requested_size: u32) -> u32 {
-    debug!("emscripten::_emscripten_resize_heap {}", requested_size);
+    debug!(target: "emscripten::_emscripten_resize_heap", "{}", requested_size);
     let current_memory_pages = ctx.memory(0).size();
     let current_memory = current_memory_pages.bytes().0 as u32;
 
@@ -55,7 +56,7 @@ pub fn _emscripten_resize_heap(ctx: &mut Ctx, requested_size: u32) -> u32 {
 
     let amount_to_grow = (new_size - current_memory as usize) / WASM_PAGE_SIZE;
     if let Ok(_pages_allocated) = ctx.memory(0).grow(Pages(amount_to_grow as u32)) {
-        debug!("{} pages allocated", _pages_allocated.0);
+        debug!(target: "emscripten::_emscripten_resize_heap", "{} pages allocated", _pages_allocated.0);
         1
     } else {
         0
@@ -64,7 +65,7 @@ pub fn _emscripten_resize_heap(ctx: &mut Ctx, requested_size: u32) -> u32 {
 
 /// emscripten: getTotalMemory
 pub fn get_total_memory(_ctx: &mut Ctx) -> u32 {
-    debug!("emscripten::get_total_memory");
+    debug!(target: "emscripten::get_total_memory", "called");
     // instance.memories[0].current_pages()
     // TODO: Fix implementation
     _ctx.memory(0).size().bytes().0 as u32
@@ -72,7 +73,7 @@ pub fn get_total_memory(_ctx: &mut Ctx) -> u32 {
 
 /// emscripten: enlargeMemory
 pub fn enlarge_memory(_ctx: &mut Ctx) -> u32 {
-    debug!("emscripten::enlarge_memory");
+    debug!(target: "emscripten::enlarge_memory", "called");
     // instance.memories[0].grow(100);
     // TODO: Fix implementation
     0
@@ -81,7 +82,8 @@ pub fn enlarge_memory(_ctx: &mut Ctx) -> u32 {
 /// emscripten: abortOnCannotGrowMemory
 pub fn abort_on_cannot_grow_memory(ctx: &mut Ctx, _requested_size: u32) -> u32 {
     debug!(
-        "emscripten::abort_on_cannot_grow_memory {}",
+        target: "emscripten::abort_on_cannot_grow_memory",
+        "{}",
         _requested_size
     );
     abort_with_message(ctx, "Cannot enlarge memory arrays!");
@@ -90,14 +92,14 @@ pub fn abort_on_cannot_grow_memory(ctx: &mut Ctx, _requested_size: u32) -> u32 {
 
 /// emscripten: abortOnCannotGrowMemory
 pub fn abort_on_cannot_grow_memory_old(ctx: &mut Ctx) -> u32 {
-    debug!("emscripten::abort_on_cannot_grow_memory");
+    debug!(target: "emscripten::abort_on_cannot_grow_memory_old", "emscripten::abort_on_cannot_grow_memory");
     abort_with_message(ctx, "Cannot enlarge memory arrays!");
     0
 }
 
 /// emscripten: ___map_file
 pub fn ___map_file(_ctx: &mut Ctx, _one: u32, _two: u32) -> c_int {
-    debug!("emscripten::___map_file");
+    debug!(target: "emscripten::___map_file", "called");
     // NOTE: TODO: Em returns -1 here as well. May need to implement properly
     -1
 }
@@ -1,28 +1,30 @@
-use libc::{abort, c_char, c_int, exit, EAGAIN};
+use libc::{abort, c_char, c_int, EAGAIN};
 
 #[cfg(not(target_os = "windows"))]
 type PidT = libc::pid_t;
 #[cfg(target_os = "windows")]
 type PidT = c_int;
 
+use crate::ExitCode;
+use std::convert::Infallible;
 use std::ffi::CStr;
 use wasmer_runtime_core::vm::Ctx;
 
 pub fn abort_with_message(ctx: &mut Ctx, message: &str) {
-    debug!("emscripten::abort_with_message");
+    debug!(target: "emscripten::abort_with_message", "called");
     println!("{}", message);
     _abort(ctx);
 }
 
 pub fn _abort(_ctx: &mut Ctx) {
-    debug!("emscripten::_abort");
+    debug!(target: "emscripten::_abort", "called");
     unsafe {
         abort();
     }
 }
 
 pub fn _fork(_ctx: &mut Ctx) -> PidT {
-    debug!("emscripten::_fork");
+    debug!(target: "emscripten::_fork", "called");
     // unsafe {
     //     fork()
     // }
@@ -30,23 +32,21 @@ pub fn _fork(_ctx: &mut Ctx) -> PidT {
 }
 
 pub fn _endgrent(_ctx: &mut Ctx) {
-    debug!("emscripten::_endgrent");
+    debug!(target: "emscripten::_endgrent", "called");
 }
 
 pub fn _execve(_ctx: &mut Ctx, _one: i32, _two: i32, _three: i32) -> i32 {
-    debug!("emscripten::_execve");
+    debug!(target: "emscripten::_execve", "called");
     -1
 }
 
-#[allow(unreachable_code)]
-pub fn _exit(_ctx: &mut Ctx, status: c_int) {
-    // -> !
-    debug!("emscripten::_exit {}", status);
-    unsafe { exit(status) }
+pub fn _exit(_ctx: &mut Ctx, status: c_int) -> Result<Infallible, ExitCode> {
+    debug!(target: "emscripten::_exit", "{}", status);
+    Err(ExitCode { code: status })
 }
 
 pub fn em_abort(ctx: &mut Ctx, message: u32) {
-    debug!("emscripten::em_abort {}", message);
+    debug!(target: "emscripten::em_abort", "{}", message);
     let message_addr = emscripten_memory_pointer!(ctx.memory(0), message) as *mut c_char;
     unsafe {
         let message = CStr::from_ptr(message_addr)
@@ -58,86 +58,86 @@ pub fn em_abort(ctx: &mut Ctx, message: u32) {
 }
 
 pub fn _kill(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::_kill");
+    debug!(target: "emscripten::_kill", "called");
     -1
 }
 
 pub fn _sched_yield(_ctx: &mut Ctx) -> i32 {
-    debug!("emscripten::_sched_yield");
+    debug!(target: "emscripten::_sched_yield", "called");
     -1
 }
 
 pub fn _llvm_stacksave(_ctx: &mut Ctx) -> i32 {
-    debug!("emscripten::_llvm_stacksave");
+    debug!(target: "emscripten::_llvm_stacksave", "called");
     -1
 }
 
 pub fn _llvm_stackrestore(_ctx: &mut Ctx, _one: i32) {
-    debug!("emscripten::_llvm_stackrestore");
+    debug!(target: "emscripten::_llvm_stackrestore", "called");
 }
 
 pub fn _raise(_ctx: &mut Ctx, _one: i32) -> i32 {
-    debug!("emscripten::_raise");
+    debug!(target: "emscripten::_raise", "called");
     -1
 }
 
 pub fn _sem_init(_ctx: &mut Ctx, _one: i32, _two: i32, _three: i32) -> i32 {
-    debug!("emscripten::_sem_init");
+    debug!(target: "emscripten::_sem_init", "called");
     -1
 }
 
 pub fn _sem_post(_ctx: &mut Ctx, _one: i32) -> i32 {
-    debug!("emscripten::_sem_post");
+    debug!(target: "emscripten::_sem_post", "called");
     -1
 }
 
 pub fn _sem_wait(_ctx: &mut Ctx, _one: i32) -> i32 {
-    debug!("emscripten::_sem_post");
+    debug!(target: "emscripten::_sem_wait", "emscripten::_sem_post");
     -1
 }
 
 #[allow(clippy::cast_ptr_alignment)]
 pub fn _getgrent(_ctx: &mut Ctx) -> c_int {
-    debug!("emscripten::_getgrent");
+    debug!(target: "emscripten::_getgrent", "called");
     -1
 }
 
 pub fn _setgrent(_ctx: &mut Ctx) {
-    debug!("emscripten::_setgrent");
+    debug!(target: "emscripten::_setgrent", "called");
 }
 
 pub fn _setgroups(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::_setgroups");
+    debug!(target: "emscripten::_setgroups", "called");
     -1
 }
 
 pub fn _setitimer(_ctx: &mut Ctx, _one: i32, _two: i32, _three: i32) -> i32 {
-    debug!("emscripten::_setitimer");
+    debug!(target: "emscripten::_setitimer", "called");
     -1
 }
 
 pub fn _usleep(_ctx: &mut Ctx, _one: i32) -> i32 {
-    debug!("emscripten::_usleep");
+    debug!(target: "emscripten::_usleep", "called");
     -1
 }
 
 pub fn _nanosleep(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::_nanosleep");
+    debug!(target: "emscripten::_nanosleep", "called");
     -1
 }
 
 pub fn _utimes(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::_utimes");
+    debug!(target: "emscripten::_utimes", "called");
     -1
 }
 
 pub fn _waitpid(_ctx: &mut Ctx, _one: i32, _two: i32, _three: i32) -> i32 {
-    debug!("emscripten::_waitpid");
+    debug!(target: "emscripten::_waitpid", "called");
     -1
 }
 
 pub fn abort_stack_overflow(ctx: &mut Ctx, _what: c_int) {
-    debug!("emscripten::abort_stack_overflow");
+    debug!(target: "emscripten::abort_stack_overflow", "called");
     // TODO: Message incomplete. Need to finish em runtime data first
     abort_with_message(
         ctx,
@@ -146,24 +146,24 @@ pub fn abort_stack_overflow(ctx: &mut Ctx, _what: c_int) {
 }
 
 pub fn _llvm_trap(ctx: &mut Ctx) {
-    debug!("emscripten::_llvm_trap");
+    debug!(target: "emscripten::_llvm_trap", "called");
     abort_with_message(ctx, "abort!");
 }
 
 pub fn _llvm_eh_typeid_for(_ctx: &mut Ctx, _type_info_addr: u32) -> i32 {
-    debug!("emscripten::_llvm_eh_typeid_for");
+    debug!(target: "emscripten::_llvm_eh_typeid_for", "called");
     -1
 }
 
 pub fn _system(_ctx: &mut Ctx, _one: i32) -> c_int {
-    debug!("emscripten::_system");
+    debug!(target: "emscripten::_system", "called");
     // TODO: May need to change this Em impl to a working version
     eprintln!("Can't call external programs");
     return EAGAIN;
 }
 
 pub fn _popen(_ctx: &mut Ctx, _one: i32, _two: i32) -> c_int {
-    debug!("emscripten::_popen");
+    debug!(target: "emscripten::_popen", "called");
     // TODO: May need to change this Em impl to a working version
     eprintln!("Missing function: popen");
     unsafe {
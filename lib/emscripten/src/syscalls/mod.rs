@@ -10,6 +10,9 @@ pub use self::unix::*;
 #[cfg(windows)]
 pub use self::windows::*;
 
+#[cfg(feature = "vfs")]
+mod vfs;
+
 use super::utils::copy_stat_into_wasm;
 use super::varargs::VarArgs;
 use byteorder::{ByteOrder, LittleEndian};
@@ -23,7 +26,6 @@ use libc::{
     // fcntl, setsockopt, getppid
     close,
     dup2,
-    exit,
     fstat,
     getpid,
     // iovec,
@@ -41,101 +43,150 @@ use libc::{
 use wasmer_runtime_core::vm::Ctx;
 
 use super::env;
+use crate::ExitCode;
 use std::cell::Cell;
+use std::convert::Infallible;
 #[allow(unused_imports)]
 use std::io::Error;
 use std::mem;
 use std::slice;
 
 /// exit
-pub fn ___syscall1(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) {
-    debug!("emscripten::___syscall1 (exit) {}", _which);
+pub fn ___syscall1(
+    ctx: &mut Ctx,
+    _which: c_int,
+    mut varargs: VarArgs,
+) -> Result<Infallible, ExitCode> {
+    debug!(target: "emscripten::___syscall1", "(exit) {}", _which);
     let status: i32 = varargs.get(ctx);
-    unsafe {
-        exit(status);
-    }
+    Err(ExitCode { code: status })
 }
 
 /// read
 pub fn ___syscall3(ctx: &mut Ctx, _which: i32, mut varargs: VarArgs) -> i32 {
     // -> ssize_t
-    debug!("emscripten::___syscall3 (read) {}", _which);
+    debug!(target: "emscripten::___syscall3", "(read) {}", _which);
     let fd: i32 = varargs.get(ctx);
     let buf: u32 = varargs.get(ctx);
     let count: i32 = varargs.get(ctx);
-    debug!("=> fd: {}, buf_offset: {}, count: {}", fd, buf, count);
+    debug!(target: "emscripten::___syscall3", "=> fd: {}, buf_offset: {}, count: {}", fd, buf, count);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::read(ctx, fd, buf, count as u32) {
+            return ret;
+        }
+    }
     let buf_addr = emscripten_memory_pointer!(ctx.memory(0), buf) as *mut c_void;
     let ret = unsafe { read(fd, buf_addr, count as _) };
-    debug!("=> ret: {}", ret);
+    debug!(target: "emscripten::___syscall3", "=> ret: {}", ret);
     ret as _
 }
 
 /// write
 pub fn ___syscall4(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall4 (write) {}", _which);
+    debug!(target: "emscripten::___syscall4", "(write) {}", _which);
     let fd: i32 = varargs.get(ctx);
     let buf: u32 = varargs.get(ctx);
     let count: i32 = varargs.get(ctx);
-    debug!("=> fd: {}, buf: {}, count: {}", fd, buf, count);
+    debug!(target: "emscripten::___syscall4", "=> fd: {}, buf: {}, count: {}", fd, buf, count);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::write(ctx, fd, buf, count as u32) {
+            return ret;
+        }
+    }
     let buf_addr = emscripten_memory_pointer!(ctx.memory(0), buf) as *const c_void;
     unsafe { write(fd, buf_addr, count as _) as i32 }
 }
 
 /// close
 pub fn ___syscall6(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall6 (close) {}", _which);
+    debug!(target: "emscripten::___syscall6", "(close) {}", _which);
     let fd: i32 = varargs.get(ctx);
-    debug!("fd: {}", fd);
+    debug!(target: "emscripten::___syscall6", "fd: {}", fd);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::close(ctx, fd) {
+            return ret;
+        }
+    }
     unsafe { close(fd) }
 }
 
 // chdir
 pub fn ___syscall12(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall12 (chdir) {}", _which);
+    debug!(target: "emscripten::___syscall12", "(chdir) {}", _which);
     let path_addr: i32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::with_path(ctx, path_addr as u32, |vfs, path| vfs.chdir(path)) {
+            return ret;
+        }
+    }
     unsafe {
         let path_ptr = emscripten_memory_pointer!(ctx.memory(0), path_addr) as *const i8;
         let _path = std::ffi::CStr::from_ptr(path_ptr);
         let ret = chdir(path_ptr);
-        debug!("=> path: {:?}, ret: {}", _path, ret);
+        debug!(target: "emscripten::___syscall12", "=> path: {:?}, ret: {}", _path, ret);
         ret
     }
 }
 
-pub fn ___syscall10(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall10");
+// unlink
+pub fn ___syscall10(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
+    debug!(target: "emscripten::___syscall10", "(unlink)");
+    let _pathname: u32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::with_path(ctx, _pathname, |vfs, path| vfs.unlink(path)) {
+            return ret;
+        }
+    }
     -1
 }
 
 pub fn ___syscall15(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall15");
+    debug!(target: "emscripten::___syscall15", "called");
     -1
 }
 
 // getpid
 pub fn ___syscall20(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall20 (getpid)");
+    debug!(target: "emscripten::___syscall20", "(getpid)");
     unsafe { getpid() }
 }
 
 pub fn ___syscall38(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall38");
+    debug!(target: "emscripten::___syscall38", "called");
     -1
 }
 
 // rmdir
 pub fn ___syscall40(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall40 (rmdir)");
+    debug!(target: "emscripten::___syscall40", "(rmdir)");
     let pathname: u32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::with_path(ctx, pathname, |vfs, path| vfs.rmdir(path)) {
+            return ret;
+        }
+    }
     let pathname_addr = emscripten_memory_pointer!(ctx.memory(0), pathname) as *const i8;
     unsafe { rmdir(pathname_addr) }
 }
 
 // pipe
 pub fn ___syscall42(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall42 (pipe)");
+    debug!(target: "emscripten::___syscall42", "(pipe)");
     // offset to a file descriptor, which contains a read end and write end, 2 integers
     let fd_offset: u32 = varargs.get(ctx);
+    // Pipes are host fds, which a guest using the vfs can't be given.
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
 
     let emscripten_memory = ctx.memory(0);
 
@@ -158,62 +209,74 @@ pub fn ___syscall42(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int
 }
 
 pub fn ___syscall60(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall60");
+    debug!(target: "emscripten::___syscall60", "called");
     -1
 }
 
 // dup2
 pub fn ___syscall63(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall63 (dup2) {}", _which);
+    debug!(target: "emscripten::___syscall63", "(dup2) {}", _which);
 
     let src: i32 = varargs.get(ctx);
     let dst: i32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::dup2(ctx, src, dst) {
+            return ret;
+        }
+    }
 
     unsafe { dup2(src, dst) }
 }
 
 // getppid
 pub fn ___syscall64(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall64 (getppid)");
+    debug!(target: "emscripten::___syscall64", "(getppid)");
     unsafe { getpid() }
 }
 
 pub fn ___syscall66(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall66");
+    debug!(target: "emscripten::___syscall66", "called");
     -1
 }
 
 pub fn ___syscall75(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall75");
+    debug!(target: "emscripten::___syscall75", "called");
     -1
 }
 
 pub fn ___syscall85(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall85");
+    debug!(target: "emscripten::___syscall85", "called");
     -1
 }
 
 pub fn ___syscall91(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall91 - stub");
+    debug!(target: "emscripten::___syscall91", "- stub");
     0
 }
 
 pub fn ___syscall97(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall97");
+    debug!(target: "emscripten::___syscall97", "called");
     -1
 }
 
 pub fn ___syscall110(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall110");
+    debug!(target: "emscripten::___syscall110", "called");
     -1
 }
 
 // getcwd
 pub fn ___syscall183(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> i32 {
-    debug!("emscripten::___syscall183");
+    debug!(target: "emscripten::___syscall183", "called");
     use std::env;
     let buf_offset: c_int = varargs.get(ctx);
     let _size: c_int = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::getcwd(ctx, buf_offset as u32, _size as u32) {
+            return ret;
+        }
+    }
     let path = env::current_dir();
     let path_string = path.unwrap().display().to_string();
     let len = path_string.len();
@@ -231,7 +294,7 @@ pub fn ___syscall183(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> i32
 
 // mmap2
 pub fn ___syscall192(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall192 (mmap2) {}", _which);
+    debug!(target: "emscripten::___syscall192", "(mmap2) {}", _which);
     let _addr: i32 = varargs.get(ctx);
     let len: u32 = varargs.get(ctx);
     let _prot: i32 = varargs.get(ctx);
@@ -239,6 +302,7 @@ pub fn ___syscall192(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
     let fd: i32 = varargs.get(ctx);
     let _off: i32 = varargs.get(ctx);
     debug!(
+        target: "emscripten::___syscall192",
         "=> addr: {}, len: {}, prot: {}, flags: {}, fd: {}, off: {}",
         _addr, len, _prot, _flags, fd, _off
     );
@@ -251,6 +315,13 @@ pub fn ___syscall192(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
         env::call_memset(ctx, ptr, 0, len);
         ptr as _
     } else {
+        #[cfg(feature = "vfs")]
+        {
+            // mmap2 takes the offset in 4096-byte units.
+            if let Some(ret) = vfs::mmap(ctx, fd, len, i64::from(_off) * 4096) {
+                return ret;
+            }
+        }
         -1
     }
 }
@@ -258,13 +329,20 @@ pub fn ___syscall192(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 /// lseek
 pub fn ___syscall140(ctx: &mut Ctx, _which: i32, mut varargs: VarArgs) -> i32 {
     // -> c_int
-    debug!("emscripten::___syscall140 (lseek) {}", _which);
+    debug!(target: "emscripten::___syscall140", "(lseek) {}", _which);
     let fd: i32 = varargs.get(ctx);
     let _ = varargs.get::<i32>(ctx); // ignore high offset
     let offset_low: i32 = varargs.get(ctx);
     let result_ptr_value = varargs.get::<i32>(ctx);
     let whence: i32 = varargs.get(ctx);
     let offset = offset_low as off_t;
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::llseek(ctx, fd, offset_low as i64, result_ptr_value as u32, whence)
+        {
+            return ret;
+        }
+    }
     let ret = unsafe { lseek(fd, offset, whence) as i32 };
     #[allow(clippy::cast_ptr_alignment)]
     let result_ptr = emscripten_memory_pointer!(ctx.memory(0), result_ptr_value) as *mut i32;
@@ -273,6 +351,7 @@ pub fn ___syscall140(ctx: &mut Ctx, _which: i32, mut varargs: VarArgs) -> i32 {
         *result_ptr = ret;
     }
     debug!(
+        target: "emscripten::___syscall140",
         "=> fd: {}, offset: {}, result_ptr: {}, whence: {} = {}\nlast os error: {}",
         fd,
         offset,
@@ -288,11 +367,19 @@ pub fn ___syscall140(ctx: &mut Ctx, _which: i32, mut varargs: VarArgs) -> i32 {
 #[allow(clippy::cast_ptr_alignment)]
 pub fn ___syscall145(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> i32 {
     // -> ssize_t
-    debug!("emscripten::___syscall145 (readv) {}", _which);
+    debug!(target: "emscripten::___syscall145", "(readv) {}", _which);
 
     let fd: i32 = varargs.get(ctx);
     let iov: i32 = varargs.get(ctx);
     let iovcnt: i32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::vectored(ctx, iov as u32, iovcnt as u32, |ctx, base, len| {
+            vfs::read(ctx, fd, base, len)
+        }) {
+            return ret;
+        }
+    }
 
     #[repr(C)]
     struct GuestIovec {
@@ -300,7 +387,7 @@ pub fn ___syscall145(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> i32
         iov_len: i32,
     }
 
-    debug!("=> fd: {}, iov: {}, iovcnt = {}", fd, iov, iovcnt);
+    debug!(target: "emscripten::___syscall145", "=> fd: {}, iov: {}, iovcnt = {}", fd, iov, iovcnt);
     let mut ret = 0;
     unsafe {
         for i in 0..iovcnt {
@@ -325,10 +412,18 @@ pub fn ___syscall145(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> i32
 #[allow(clippy::cast_ptr_alignment)]
 pub fn ___syscall146(ctx: &mut Ctx, _which: i32, mut varargs: VarArgs) -> i32 {
     // -> ssize_t
-    debug!("emscripten::___syscall146 (writev) {}", _which);
+    debug!(target: "emscripten::___syscall146", "(writev) {}", _which);
     let fd: i32 = varargs.get(ctx);
     let iov: i32 = varargs.get(ctx);
     let iovcnt: i32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::vectored(ctx, iov as u32, iovcnt as u32, |ctx, base, len| {
+            vfs::write(ctx, fd, base, len)
+        }) {
+            return ret;
+        }
+    }
 
     #[repr(C)]
     struct GuestIovec {
@@ -336,7 +431,7 @@ pub fn ___syscall146(ctx: &mut Ctx, _which: i32, mut varargs: VarArgs) -> i32 {
         iov_len: i32,
     }
 
-    debug!("=> fd: {}, iov: {}, iovcnt = {}", fd, iov, iovcnt);
+    debug!(target: "emscripten::___syscall146", "=> fd: {}, iov: {}, iovcnt = {}", fd, iov, iovcnt);
     let mut ret = 0;
     unsafe {
         for i in 0..iovcnt {
@@ -358,25 +453,31 @@ pub fn ___syscall146(ctx: &mut Ctx, _which: i32, mut varargs: VarArgs) -> i32 {
 }
 
 pub fn ___syscall168(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall168");
+    debug!(target: "emscripten::___syscall168", "called");
     -1
 }
 
 pub fn ___syscall191(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall191 - stub");
+    debug!(target: "emscripten::___syscall191", "- stub");
     -1
 }
 
 pub fn ___syscall199(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall199 - stub");
+    debug!(target: "emscripten::___syscall199", "- stub");
     -1
 }
 
 // stat64
 pub fn ___syscall195(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall195 (stat64) {}", _which);
+    debug!(target: "emscripten::___syscall195", "(stat64) {}", _which);
     let pathname: u32 = varargs.get(ctx);
     let buf: u32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::stat(ctx, pathname, buf) {
+            return ret;
+        }
+    }
 
     let pathname_addr = emscripten_memory_pointer!(ctx.memory(0), pathname) as *const i8;
 
@@ -384,6 +485,7 @@ pub fn ___syscall195(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
         let mut _stat: stat = std::mem::zeroed();
         let ret = stat(pathname_addr, &mut _stat);
         debug!(
+            target: "emscripten::___syscall195",
             "=> pathname: {}, buf: {}, path: {} = {}\nlast os error: {}",
             pathname,
             buf,
@@ -401,14 +503,20 @@ pub fn ___syscall195(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 
 // fstat64
 pub fn ___syscall197(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall197 (fstat64) {}", _which);
+    debug!(target: "emscripten::___syscall197", "(fstat64) {}", _which);
     let fd: c_int = varargs.get(ctx);
     let buf: u32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::fstat(ctx, fd, buf) {
+            return ret;
+        }
+    }
 
     unsafe {
         let mut stat = std::mem::zeroed();
         let ret = fstat(fd, &mut stat);
-        debug!("ret: {}", ret);
+        debug!(target: "emscripten::___syscall197", "ret: {}", ret);
         if ret != 0 {
             return ret;
         }
@@ -418,14 +526,24 @@ pub fn ___syscall197(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
     0
 }
 
-pub fn ___syscall220(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall220");
+// getdents64
+pub fn ___syscall220(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
+    debug!(target: "emscripten::___syscall220", "(getdents64)");
+    let _fd: i32 = varargs.get(ctx);
+    let _dirp: u32 = varargs.get(ctx);
+    let _count: u32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::getdents(ctx, _fd, _dirp, _count) {
+            return ret;
+        }
+    }
     -1
 }
 
 // fcntl64
 pub fn ___syscall221(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall221 (fcntl64) {}", _which);
+    debug!(target: "emscripten::___syscall221", "(fcntl64) {}", _which);
     // fcntl64
     let _fd: i32 = varargs.get(ctx);
     let cmd: u32 = varargs.get(ctx);
@@ -433,7 +551,14 @@ pub fn ___syscall221(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
     // |FASYNC    - 0x40
     // |FFSYNC    - 0x80
     // |FNONBLOCK - 0x04
-    debug!("=> fd: {}, cmd: {}", _fd, cmd);
+    debug!(target: "emscripten::___syscall221", "=> fd: {}, cmd: {}", _fd, cmd);
+    #[cfg(feature = "vfs")]
+    {
+        let arg: i32 = varargs.get(ctx);
+        if let Some(ret) = vfs::fcntl(ctx, _fd, cmd as i32, arg) {
+            return ret;
+        }
+    }
     match cmd {
         2 => 0,
         13 | 14 => 0, // pretend file locking worked
@@ -442,33 +567,33 @@ pub fn ___syscall221(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 }
 
 pub fn ___syscall268(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall268");
+    debug!(target: "emscripten::___syscall268", "called");
     -1
 }
 
 pub fn ___syscall272(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall272");
+    debug!(target: "emscripten::___syscall272", "called");
     -1
 }
 
 pub fn ___syscall295(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall295");
+    debug!(target: "emscripten::___syscall295", "called");
     -1
 }
 
 pub fn ___syscall300(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall300");
+    debug!(target: "emscripten::___syscall300", "called");
     -1
 }
 
 pub fn ___syscall334(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall334");
+    debug!(target: "emscripten::___syscall334", "called");
     -1
 }
 
 // prlimit64
 pub fn ___syscall340(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall340 (prlimit64), {}", _which);
+    debug!(target: "emscripten::___syscall340", "(prlimit64), {}", _which);
     // NOTE: Doesn't really matter. Wasm modules cannot exceed WASM_PAGE_SIZE anyway.
     let _pid: i32 = varargs.get(ctx);
     let _resource: i32 = varargs.get(ctx);
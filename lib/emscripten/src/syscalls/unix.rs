@@ -74,6 +74,8 @@ use libc::{
 };
 use wasmer_runtime_core::vm::Ctx;
 
+#[cfg(feature = "vfs")]
+use super::vfs;
 use crate::utils;
 #[allow(unused_imports)]
 use std::io::Error;
@@ -102,14 +104,21 @@ const SO_NOSIGPIPE: c_int = 0;
 
 /// open
 pub fn ___syscall5(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall5 (open) {}", _which);
+    debug!(target: "emscripten::___syscall5", "(open) {}", _which);
     let pathname: u32 = varargs.get(ctx);
     let flags: i32 = varargs.get(ctx);
     let mode: u32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::with_path(ctx, pathname, |vfs, path| vfs.open(path, flags)) {
+            return ret;
+        }
+    }
     let pathname_addr = emscripten_memory_pointer!(ctx.memory(0), pathname) as *const i8;
     let _path_str = unsafe { std::ffi::CStr::from_ptr(pathname_addr).to_str().unwrap() };
     let fd = unsafe { open(pathname_addr, flags, mode) };
     debug!(
+        target: "emscripten::___syscall5",
         "=> pathname: {}, flags: {}, mode: {} = fd: {}\npath: {}\nlast os error: {}",
         pathname,
         flags,
@@ -123,14 +132,21 @@ pub fn ___syscall5(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int
 
 /// link
 pub fn ___syscall9(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall9 (link) {}", _which);
+    debug!(target: "emscripten::___syscall9", "(link) {}", _which);
 
     let oldname: c_int = varargs.get(ctx);
     let newname: c_int = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
     let oldname_ptr = emscripten_memory_pointer!(ctx.memory(0), oldname) as *const i8;
     let newname_ptr = emscripten_memory_pointer!(ctx.memory(0), newname) as *const i8;
     let result = unsafe { link(oldname_ptr, newname_ptr) };
     debug!(
+        target: "emscripten::___syscall9",
         "=> oldname: {}, newname: {}, result: {}",
         unsafe { std::ffi::CStr::from_ptr(oldname_ptr).to_str().unwrap() },
         unsafe { std::ffi::CStr::from_ptr(newname_ptr).to_str().unwrap() },
@@ -141,7 +157,7 @@ pub fn ___syscall9(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int
 
 /// getrusage
 pub fn ___syscall77(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall77 (getrusage) {}", _which);
+    debug!(target: "emscripten::___syscall77", "(getrusage) {}", _which);
 
     let resource: c_int = varargs.get(ctx);
     let rusage_ptr: c_int = varargs.get(ctx);
@@ -153,14 +169,21 @@ pub fn ___syscall77(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int
 
 /// symlink
 pub fn ___syscall83(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall83 (symlink) {}", _which);
+    debug!(target: "emscripten::___syscall83", "(symlink) {}", _which);
 
     let path1_ptr: c_int = varargs.get(ctx);
     let path2_ptr: c_int = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
     let path1 = emscripten_memory_pointer!(ctx.memory(0), path1_ptr) as *mut i8;
     let path2 = emscripten_memory_pointer!(ctx.memory(0), path2_ptr) as *mut i8;
     let result = unsafe { symlink(path1, path2) };
     debug!(
+        target: "emscripten::___syscall83",
         "=> path1: {}, path2: {}, result: {}",
         unsafe { std::ffi::CStr::from_ptr(path1).to_str().unwrap() },
         unsafe { std::ffi::CStr::from_ptr(path2).to_str().unwrap() },
@@ -171,9 +194,15 @@ pub fn ___syscall83(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int
 
 /// ftruncate64
 pub fn ___syscall194(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall194 (ftruncate64) {}", _which);
+    debug!(target: "emscripten::___syscall194", "(ftruncate64) {}", _which);
     let _fd: c_int = varargs.get(ctx);
     let _length: i64 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::ftruncate(ctx, _fd, _length) {
+            return ret;
+        }
+    }
     #[cfg(not(target_os = "macos"))]
     unsafe {
         ftruncate64(_fd, _length)
@@ -184,13 +213,20 @@ pub fn ___syscall194(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 
 /// lchown
 pub fn ___syscall198(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall198 (lchown) {}", _which);
+    debug!(target: "emscripten::___syscall198", "(lchown) {}", _which);
     let path: c_int = varargs.get(ctx);
     let uid: uid_t = varargs.get(ctx);
     let gid: gid_t = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
     let path_ptr = emscripten_memory_pointer!(ctx.memory(0), path) as *const i8;
     let result = unsafe { lchown(path_ptr, uid, gid) };
     debug!(
+        target: "emscripten::___syscall198",
         "=> path: {}, uid: {}, gid: {}, result: {}",
         unsafe { std::ffi::CStr::from_ptr(path_ptr).to_str().unwrap() },
         uid,
@@ -202,7 +238,7 @@ pub fn ___syscall198(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 
 /// getgroups
 pub fn ___syscall205(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall205 (getgroups) {}", _which);
+    debug!(target: "emscripten::___syscall205", "(getgroups) {}", _which);
     let ngroups_max: c_int = varargs.get(ctx);
     let groups: c_int = varargs.get(ctx);
 
@@ -211,6 +247,7 @@ pub fn ___syscall205(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
     assert_eq!(4, mem::align_of_val(&gid_ptr));
     let result = unsafe { getgroups(ngroups_max, gid_ptr) };
     debug!(
+        target: "emscripten::___syscall205",
         "=> ngroups_max: {}, gid_ptr: {:?}, result: {}",
         ngroups_max, gid_ptr, result,
     );
@@ -219,11 +256,17 @@ pub fn ___syscall205(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 
 // chown
 pub fn ___syscall212(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall212 (chown) {}", _which);
+    debug!(target: "emscripten::___syscall212", "(chown) {}", _which);
 
     let pathname: u32 = varargs.get(ctx);
     let owner: u32 = varargs.get(ctx);
     let group: u32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
 
     let pathname_addr = emscripten_memory_pointer!(ctx.memory(0), pathname) as *const i8;
 
@@ -232,7 +275,7 @@ pub fn ___syscall212(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 
 /// madvise
 pub fn ___syscall219(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall212 (chown) {}", _which);
+    debug!(target: "emscripten::___syscall219", "emscripten::___syscall212 (chown) {}", _which);
 
     let addr_ptr: c_int = varargs.get(ctx);
     let len: usize = varargs.get(ctx);
@@ -245,12 +288,19 @@ pub fn ___syscall219(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 
 /// access
 pub fn ___syscall33(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall33 (access) {}", _which);
+    debug!(target: "emscripten::___syscall33", "(access) {}", _which);
     let path_ptr: c_int = varargs.get(ctx);
     let amode: c_int = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::with_path(ctx, path_ptr as u32, |vfs, path| vfs.access(path)) {
+            return ret;
+        }
+    }
     let path = emscripten_memory_pointer!(ctx.memory(0), path_ptr) as *const i8;
     let result = unsafe { access(path, amode) };
     debug!(
+        target: "emscripten::___syscall33",
         "=> path: {}, result: {}",
         unsafe { std::ffi::CStr::from_ptr(path).to_str().unwrap() },
         result
@@ -260,36 +310,48 @@ pub fn ___syscall33(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int
 
 /// nice
 pub fn ___syscall34(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall34 (nice) {}", _which);
+    debug!(target: "emscripten::___syscall34", "(nice) {}", _which);
     let inc_r: c_int = varargs.get(ctx);
     unsafe { nice(inc_r) }
 }
 
 // mkdir
 pub fn ___syscall39(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall39 (mkdir) {}", _which);
+    debug!(target: "emscripten::___syscall39", "(mkdir) {}", _which);
     let pathname: u32 = varargs.get(ctx);
     let mode: u32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::with_path(ctx, pathname, |vfs, path| vfs.mkdir(path)) {
+            return ret;
+        }
+    }
     let pathname_addr = emscripten_memory_pointer!(ctx.memory(0), pathname) as *const i8;
     unsafe { mkdir(pathname_addr, mode as _) }
 }
 
 /// dup
 pub fn ___syscall41(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall41 (dup) {}", _which);
+    debug!(target: "emscripten::___syscall41", "(dup) {}", _which);
     let fd: c_int = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::dup(ctx, fd) {
+            return ret;
+        }
+    }
     unsafe { dup(fd) }
 }
 
 /// getgid
 pub fn ___syscall200(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall200 (getgid)");
+    debug!(target: "emscripten::___syscall200", "(getgid)");
     unsafe { getgid() as i32 }
 }
 
 // getgid
 pub fn ___syscall201(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall201 (getgid)");
+    debug!(target: "emscripten::___syscall201", "(getgid)");
     unsafe {
         // Maybe fix: Emscripten returns 0 always
         getgid() as i32
@@ -299,7 +361,7 @@ pub fn ___syscall201(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
 // getgid32
 pub fn ___syscall202(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
     // gid_t
-    debug!("emscripten::___syscall202 (getgid32)");
+    debug!(target: "emscripten::___syscall202", "(getgid32)");
     unsafe {
         // Maybe fix: Emscripten returns 0 always
         getgid() as _
@@ -308,20 +370,32 @@ pub fn ___syscall202(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
 
 /// fchown
 pub fn ___syscall207(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall207 (fchown) {}", _which);
+    debug!(target: "emscripten::___syscall207", "(fchown) {}", _which);
     let fd: c_int = varargs.get(ctx);
     let owner: uid_t = varargs.get(ctx);
     let group: gid_t = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
     unsafe { fchown(fd, owner, group) }
 }
 
 /// dup3
 pub fn ___syscall330(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> pid_t {
     // Implementation based on description at https://linux.die.net/man/2/dup3
-    debug!("emscripten::___syscall330 (dup3)");
+    debug!(target: "emscripten::___syscall330", "(dup3)");
     let oldfd: c_int = varargs.get(ctx);
     let newfd: c_int = varargs.get(ctx);
     let flags: c_int = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
 
     if oldfd == newfd {
         return EINVAL;
@@ -343,6 +417,7 @@ pub fn ___syscall330(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> pid_
     }
 
     debug!(
+        target: "emscripten::___syscall330",
         "=> oldfd: {}, newfd: {}, flags: {} = pid: {}",
         oldfd, newfd, flags, res
     );
@@ -351,10 +426,17 @@ pub fn ___syscall330(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> pid_
 
 /// ioctl
 pub fn ___syscall54(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall54 (ioctl) {}", _which);
+    debug!(target: "emscripten::___syscall54", "(ioctl) {}", _which);
     let fd: i32 = varargs.get(ctx);
     let request: u32 = varargs.get(ctx);
-    debug!("fd: {}, op: {}", fd, request);
+    debug!(target: "emscripten::___syscall54", "fd: {}, op: {}", fd, request);
+    // The fd is a vfs fd, and must not reach the host's `ioctl`.
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
     // Got the equivalents here: https://code.woboq.org/linux/linux/include/uapi/asm-generic/ioctls.h.html
     match request as _ {
         21537 => {
@@ -362,7 +444,7 @@ pub fn ___syscall54(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int
             let argp: u32 = varargs.get(ctx);
             let argp_ptr = emscripten_memory_pointer!(ctx.memory(0), argp) as *mut c_void;
             let ret = unsafe { ioctl(fd, FIONBIO, argp_ptr) };
-            debug!("ret(FIONBIO): {}", ret);
+            debug!(target: "emscripten::___syscall54", "ret(FIONBIO): {}", ret);
             ret
             // 0
         }
@@ -371,7 +453,7 @@ pub fn ___syscall54(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int
             let argp: u32 = varargs.get(ctx);
             let argp_ptr = emscripten_memory_pointer!(ctx.memory(0), argp) as *mut c_void;
             let ret = unsafe { ioctl(fd, TIOCGWINSZ, argp_ptr) };
-            debug!("ret(TIOCGWINSZ): {} (harcoded to 0)", ret);
+            debug!(target: "emscripten::___syscall54", "ret(TIOCGWINSZ): {} (harcoded to 0)", ret);
             // ret
             // TODO: We hardcode the value to have emscripten tests pass, as for some reason
             // when the capturer is active, ioctl returns -1 instead of 0
@@ -383,7 +465,8 @@ pub fn ___syscall54(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int
         }
         _ => {
             debug!(
-                "emscripten::___syscall54 -> non implemented case {}",
+                target: "emscripten::___syscall54",
+                "-> non implemented case {}",
                 request
             );
             0
@@ -394,9 +477,16 @@ pub fn ___syscall54(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int
 // socketcall
 #[allow(clippy::cast_ptr_alignment)]
 pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall102 (socketcall) {}", _which);
+    debug!(target: "emscripten::___syscall102", "(socketcall) {}", _which);
     let call: u32 = varargs.get(ctx);
     let mut socket_varargs: VarArgs = varargs.get(ctx);
+    // Sockets are host fds, which a guest using the vfs can't be given.
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
 
     #[repr(C)]
     pub struct GuestSockaddrIn {
@@ -421,7 +511,7 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 
     match call {
         1 => {
-            debug!("socket: socket");
+            debug!(target: "emscripten::___syscall102", "socket: socket");
             // socket (domain: c_int, ty: c_int, protocol: c_int) -> c_int
             let domain: i32 = socket_varargs.get(ctx);
             let ty: i32 = socket_varargs.get(ctx);
@@ -445,13 +535,14 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             };
 
             debug!(
+                target: "emscripten::___syscall102",
                 "=> domain: {} (AF_INET/2), type: {} (SOCK_STREAM/1), protocol: {} = fd: {}",
                 domain, ty, protocol, fd
             );
             fd as _
         }
         2 => {
-            debug!("socket: bind");
+            debug!(target: "emscripten::___syscall102", "socket: bind");
             // bind (socket: c_int, address: *const sockaddr, address_len: socklen_t) -> c_int
             // TODO: Emscripten has a different signature.
             let socket = socket_varargs.get(ctx);
@@ -462,13 +553,15 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             // Debug received address
             let _proper_address = address as *const GuestSockaddrIn;
             debug!(
-                    "=> address.sin_family: {:?}, address.sin_port: {:?}, address.sin_addr.s_addr: {:?}",
-                unsafe { (*_proper_address).sin_family }, unsafe { (*_proper_address).sin_port }, unsafe { (*_proper_address).sin_addr.s_addr }
-                );
+            target: "emscripten::___syscall102",
+                "=> address.sin_family: {:?}, address.sin_port: {:?}, address.sin_addr.s_addr: {:?}",
+            unsafe { (*_proper_address).sin_family }, unsafe { (*_proper_address).sin_port }, unsafe { (*_proper_address).sin_addr.s_addr }
+            );
 
             let status = unsafe { bind(socket, address, address_len) };
             // debug!("=> status: {}", status);
             debug!(
+                target: "emscripten::___syscall102",
                 "=> socketfd: {}, address: {:?}, address_len: {} = status: {}",
                 socket, address, address_len, status
             );
@@ -476,7 +569,7 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             // -1
         }
         3 => {
-            debug!("socket: connect");
+            debug!(target: "emscripten::___syscall102", "socket: connect");
             // connect (socket: c_int, address: *const sockaddr, len: socklen_t) -> c_int
             // TODO: Emscripten has a different signature.
             let socket = socket_varargs.get(ctx);
@@ -486,19 +579,20 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             unsafe { connect(socket, address, address_len) }
         }
         4 => {
-            debug!("socket: listen");
+            debug!(target: "emscripten::___syscall102", "socket: listen");
             // listen (socket: c_int, backlog: c_int) -> c_int
             let socket = socket_varargs.get(ctx);
             let backlog: i32 = socket_varargs.get(ctx);
             let status = unsafe { listen(socket, backlog) };
             debug!(
+                target: "emscripten::___syscall102",
                 "=> socketfd: {}, backlog: {} = status: {}",
                 socket, backlog, status
             );
             status
         }
         5 => {
-            debug!("socket: accept");
+            debug!(target: "emscripten::___syscall102", "socket: accept");
             // accept (socket: c_int, address: *mut sockaddr, address_len: *mut socklen_t) -> c_int
             let socket = socket_varargs.get(ctx);
             let address_addr: u32 = socket_varargs.get(ctx);
@@ -506,6 +600,7 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             let address = emscripten_memory_pointer!(ctx.memory(0), address_addr) as *mut sockaddr;
 
             debug!(
+                target: "emscripten::___syscall102",
                 "=> socket: {}, address: {:?}, address_len: {}",
                 socket, address, address_len
             );
@@ -527,12 +622,12 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
                 ioctl(fd, FIOCLEX);
             };
 
-            debug!("fd: {}", fd);
+            debug!(target: "emscripten::___syscall102", "fd: {}", fd);
 
             fd as _
         }
         6 => {
-            debug!("socket: getsockname");
+            debug!(target: "emscripten::___syscall102", "socket: getsockname");
             // getsockname (socket: c_int, address: *mut sockaddr, address_len: *mut socklen_t) -> c_int
             let socket = socket_varargs.get(ctx);
             let address: u32 = socket_varargs.get(ctx);
@@ -543,7 +638,7 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             unsafe { getsockname(socket, address, address_len_addr) }
         }
         7 => {
-            debug!("socket: getpeername");
+            debug!(target: "emscripten::___syscall102", "socket: getpeername");
             // getpeername (socket: c_int, address: *mut sockaddr, address_len: *mut socklen_t) -> c_int
             let socket = socket_varargs.get(ctx);
             let address: u32 = socket_varargs.get(ctx);
@@ -554,7 +649,7 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             unsafe { getpeername(socket, address, address_len_addr) }
         }
         11 => {
-            debug!("socket: sendto");
+            debug!(target: "emscripten::___syscall102", "socket: sendto");
             // sendto (socket: c_int, buf: *const c_void, len: size_t, flags: c_int, addr: *const sockaddr, addrlen: socklen_t) -> ssize_t
             let socket = socket_varargs.get(ctx);
             let buf: u32 = socket_varargs.get(ctx);
@@ -567,7 +662,7 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             unsafe { sendto(socket, buf_addr, flags, len, address, address_len) as i32 }
         }
         12 => {
-            debug!("socket: recvfrom");
+            debug!(target: "emscripten::___syscall102", "socket: recvfrom");
             // recvfrom (socket: c_int, buf: *const c_void, len: size_t, flags: c_int, addr: *const sockaddr, addrlen: socklen_t) -> ssize_t
             let socket = socket_varargs.get(ctx);
             let buf: u32 = socket_varargs.get(ctx);
@@ -582,7 +677,7 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             unsafe { recvfrom(socket, buf_addr, flags, len, address, address_len_addr) as i32 }
         }
         14 => {
-            debug!("socket: setsockopt");
+            debug!(target: "emscripten::___syscall102", "socket: setsockopt");
             // NOTE: Emscripten seems to be passing the wrong values to this syscall
             //      level: Em passes 1 as SOL_SOCKET; SOL_SOCKET is 0xffff in BSD
             //      name: Em passes SO_ACCEPTCONN, but Nginx complains about REUSEADDR
@@ -601,11 +696,11 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             let value_addr = emscripten_memory_pointer!(ctx.memory(0), value) as _; // Endian problem
             let ret = unsafe { setsockopt(socket, level, name, value_addr, option_len) };
 
-            debug!("=> socketfd: {}, level: {} (SOL_SOCKET/0xffff), name: {} (SO_REUSEADDR/4), value_addr: {:?}, option_len: {} = status: {}", socket, level, name, value_addr, option_len, ret);
+            debug!(target: "emscripten::___syscall102", "=> socketfd: {}, level: {} (SOL_SOCKET/0xffff), name: {} (SO_REUSEADDR/4), value_addr: {:?}, option_len: {} = status: {}", socket, level, name, value_addr, option_len, ret);
             ret
         }
         15 => {
-            debug!("socket: getsockopt");
+            debug!(target: "emscripten::___syscall102", "socket: getsockopt");
             // getsockopt (sockfd: c_int, level: c_int, optname: c_int, optval: *mut c_void, optlen: *mut socklen_t) -> c_int
             let socket = socket_varargs.get(ctx);
             let level: i32 = socket_varargs.get(ctx);
@@ -618,7 +713,7 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             unsafe { getsockopt(socket, level, name, value_addr, option_len_addr) }
         }
         16 => {
-            debug!("socket: sendmsg");
+            debug!(target: "emscripten::___syscall102", "socket: sendmsg");
             // sendmsg (fd: c_int, msg: *const msghdr, flags: c_int) -> ssize_t
             let socket: i32 = socket_varargs.get(ctx);
             let msg: u32 = socket_varargs.get(ctx);
@@ -627,7 +722,7 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
             unsafe { sendmsg(socket, msg_addr, flags) as i32 }
         }
         17 => {
-            debug!("socket: recvmsg");
+            debug!(target: "emscripten::___syscall102", "socket: recvmsg");
             // recvmsg (fd: c_int, msg: *mut msghdr, flags: c_int) -> ssize_t
             let socket: i32 = socket_varargs.get(ctx);
             let msg: u32 = socket_varargs.get(ctx);
@@ -644,7 +739,7 @@ pub fn ___syscall102(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 
 // pread
 pub fn ___syscall180(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall180 (pread) {}", _which);
+    debug!(target: "emscripten::___syscall180", "(pread) {}", _which);
     let fd: i32 = varargs.get(ctx);
     let buf: u32 = varargs.get(ctx);
     let count: u32 = varargs.get(ctx);
@@ -653,6 +748,12 @@ pub fn ___syscall180(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
         assert_eq!(zero, 0);
     }
     let offset: i64 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::pread(ctx, fd, buf, count, offset) {
+            return ret;
+        }
+    }
 
     let buf_ptr = emscripten_memory_pointer!(ctx.memory(0), buf) as _;
 
@@ -661,7 +762,7 @@ pub fn ___syscall180(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 
 // pwrite
 pub fn ___syscall181(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall181 (pwrite) {}", _which);
+    debug!(target: "emscripten::___syscall181", "(pwrite) {}", _which);
     let fd: i32 = varargs.get(ctx);
     let buf: u32 = varargs.get(ctx);
     let count: u32 = varargs.get(ctx);
@@ -670,10 +771,17 @@ pub fn ___syscall181(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
         assert_eq!(zero, 0);
     }
     let offset: i64 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::pwrite(ctx, fd, buf, count, offset) {
+            return ret;
+        }
+    }
 
     let buf_ptr = emscripten_memory_pointer!(ctx.memory(0), buf) as _;
     let status = unsafe { pwrite(fd, buf_ptr, count as _, offset) as _ };
     debug!(
+        target: "emscripten::___syscall181",
         "=> fd: {}, buf: {}, count: {}, offset: {} = status:{}",
         fd, buf, count, offset, status
     );
@@ -682,16 +790,22 @@ pub fn ___syscall181(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 
 /// fchmod
 pub fn ___syscall94(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall118 (fchmod) {}", _which);
+    debug!(target: "emscripten::___syscall94", "emscripten::___syscall118 (fchmod) {}", _which);
     let fd: c_int = varargs.get(ctx);
     let mode: mode_t = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
     unsafe { fchmod(fd, mode) }
 }
 
 /// wait4
 #[allow(clippy::cast_ptr_alignment)]
 pub fn ___syscall114(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> pid_t {
-    debug!("emscripten::___syscall114 (wait4)");
+    debug!(target: "emscripten::___syscall114", "(wait4)");
     let pid: pid_t = varargs.get(ctx);
     let status: u32 = varargs.get(ctx);
     let options: c_int = varargs.get(ctx);
@@ -701,6 +815,7 @@ pub fn ___syscall114(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> pid_
     let rusage_addr = emscripten_memory_pointer!(ctx.memory(0), rusage) as *mut rusage;
     let res = unsafe { wait4(pid, status_addr, options, rusage_addr) };
     debug!(
+        target: "emscripten::___syscall114",
         "=> pid: {}, status: {:?}, options: {}, rusage: {:?} = pid: {}",
         pid, status_addr, options, rusage_addr, res
     );
@@ -709,21 +824,34 @@ pub fn ___syscall114(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> pid_
 
 /// fsync
 pub fn ___syscall118(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall118 (fsync) {}", _which);
+    debug!(target: "emscripten::___syscall118", "(fsync) {}", _which);
     let fd: c_int = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::sync(ctx) {
+            return ret;
+        }
+    }
     unsafe { fsync(fd) }
 }
 
 // select
 #[allow(clippy::cast_ptr_alignment)]
 pub fn ___syscall142(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall142 (newselect) {}", _which);
+    debug!(target: "emscripten::___syscall142", "(newselect) {}", _which);
 
     let nfds: i32 = varargs.get(ctx);
     let readfds: u32 = varargs.get(ctx);
     let writefds: u32 = varargs.get(ctx);
     let exceptfds: u32 = varargs.get(ctx);
     let _timeout: i32 = varargs.get(ctx);
+    // The fd sets hold vfs fds, which must not be polled on the host.
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
 
     assert!(nfds <= 64, "`nfds` must be less than or equal to 64");
     assert!(exceptfds == 0, "`exceptfds` is not supporrted");
@@ -736,16 +864,22 @@ pub fn ___syscall142(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_in
 
 /// fdatasync
 pub fn ___syscall148(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall148 (fdatasync) {}", _which);
+    debug!(target: "emscripten::___syscall148", "(fdatasync) {}", _which);
 
     let fd: i32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::sync(ctx) {
+            return ret;
+        }
+    }
 
     unsafe { fdatasync(fd) }
 }
 
 // setpgid
 pub fn ___syscall57(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall57 (setpgid) {}", _which);
+    debug!(target: "emscripten::___syscall57", "(setpgid) {}", _which);
     let pid: i32 = varargs.get(ctx);
     let pgid: i32 = varargs.get(ctx);
     unsafe { setpgid(pid, pgid) }
@@ -754,18 +888,24 @@ pub fn ___syscall57(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int
 /// uname
 // NOTE: Wondering if we should return custom utsname, like Emscripten.
 pub fn ___syscall122(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall122 (uname) {}", _which);
+    debug!(target: "emscripten::___syscall122", "(uname) {}", _which);
     let buf: u32 = varargs.get(ctx);
-    debug!("=> buf: {}", buf);
+    debug!(target: "emscripten::___syscall122", "=> buf: {}", buf);
     let buf_addr = emscripten_memory_pointer!(ctx.memory(0), buf) as *mut utsname;
     unsafe { uname(buf_addr) }
 }
 
 /// lstat64
 pub fn ___syscall196(ctx: &mut Ctx, _which: i32, mut varargs: VarArgs) -> i32 {
-    debug!("emscripten::___syscall196 (lstat64) {}", _which);
+    debug!(target: "emscripten::___syscall196", "(lstat64) {}", _which);
     let path_ptr: c_int = varargs.get(ctx);
     let buf_ptr: u32 = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::stat(ctx, path_ptr as u32, buf_ptr) {
+            return ret;
+        }
+    }
     let path = emscripten_memory_pointer!(ctx.memory(0), path_ptr) as *const i8;
     unsafe {
         let mut stat: stat = std::mem::zeroed();
@@ -780,7 +920,7 @@ pub fn ___syscall196(ctx: &mut Ctx, _which: i32, mut varargs: VarArgs) -> i32 {
         #[cfg(not(target_os = "macos"))]
         let ret = lstat(path, stat_ptr);
 
-        debug!("ret: {}", ret);
+        debug!(target: "emscripten::___syscall196", "ret: {}", ret);
         if ret != 0 {
             return ret;
         }
@@ -791,11 +931,17 @@ pub fn ___syscall196(ctx: &mut Ctx, _which: i32, mut varargs: VarArgs) -> i32 {
 
 /// fallocate
 pub fn ___syscall324(ctx: &mut Ctx, _which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall324 (fallocate) {}", _which);
+    debug!(target: "emscripten::___syscall324", "(fallocate) {}", _which);
     let _fd: c_int = varargs.get(ctx);
     let _mode: c_int = varargs.get(ctx);
     let _offset: off_t = varargs.get(ctx);
     let _len: off_t = varargs.get(ctx);
+    #[cfg(feature = "vfs")]
+    {
+        if let Some(ret) = vfs::unsupported(ctx) {
+            return ret;
+        }
+    }
     #[cfg(not(target_os = "macos"))]
     unsafe {
         fallocate(_fd, _mode, _offset, _len)
@@ -0,0 +1,275 @@
+//! The file syscalls of modules that run in an `EmscriptenVfs`.
+//!
+//! Each function returns `None` when the module uses the host filesystem,
+//! in which case the caller carries on with the host syscall.
+use crate::emscripten_vfs::{EmscriptenVfs, EFAULT, EINVAL, ENOMEM, ENOSYS, ERANGE};
+use crate::env::{self, get_emscripten_data};
+use crate::utils::{copy_stat_into_wasm, read_string_from_wasm, GuestStat};
+use byteorder::{ByteOrder, LittleEndian};
+use std::{cell::Cell, mem, ops::Range};
+use wasmer_runtime_core::vm::Ctx;
+
+/// The most bytes copied between the module and the vfs by one syscall.
+/// Larger reads and writes are partial, which the syscalls allow.
+const MAX_IO_SIZE: u32 = 64 * 1024;
+/// The most buffers `readv` and `writev` take, like on Linux.
+const IOV_MAX: u32 = 1024;
+
+fn vfs(ctx: &mut Ctx) -> Option<&mut EmscriptenVfs> {
+    if ctx.data.is_null() {
+        return None;
+    }
+    get_emscripten_data(ctx).vfs.as_mut()
+}
+
+/// Returns the range of the `len` bytes at `offset` in the memory of the
+/// module, or `-EFAULT` if they aren't all inside it.
+fn wasm_range(ctx: &mut Ctx, offset: u32, len: u32) -> Result<Range<usize>, i32> {
+    let start = offset as usize;
+    let end = start.checked_add(len as usize).ok_or(-EFAULT)?;
+    if end > ctx.memory(0).size().bytes().0 {
+        return Err(-EFAULT);
+    }
+    Ok(start..end)
+}
+
+fn read_from_wasm(ctx: &mut Ctx, offset: u32, len: u32) -> Result<Vec<u8>, i32> {
+    let range = wasm_range(ctx, offset, len)?;
+    Ok(ctx.memory(0).view::<u8>()[range]
+        .iter()
+        .map(Cell::get)
+        .collect())
+}
+
+fn write_to_wasm(ctx: &mut Ctx, offset: u32, bytes: &[u8]) -> Result<(), i32> {
+    let range = wasm_range(ctx, offset, bytes.len() as u32)?;
+    for (cell, &byte) in ctx.memory(0).view::<u8>()[range].iter().zip(bytes) {
+        cell.set(byte);
+    }
+    Ok(())
+}
+
+fn read_path_from_wasm(ctx: &mut Ctx, offset: u32) -> Result<String, i32> {
+    wasm_range(ctx, offset, 1)?;
+    Ok(read_string_from_wasm(ctx.memory(0), offset))
+}
+
+/// Runs a syscall that reads into the `count` bytes at `buf`, such as `read`.
+///
+/// `f` fills a buffer of at most `MAX_IO_SIZE` bytes, and returns how many it filled.
+fn read_into_wasm<F>(ctx: &mut Ctx, buf: u32, count: u32, f: F) -> Option<i32>
+where
+    F: FnOnce(&mut EmscriptenVfs, &mut [u8]) -> i32,
+{
+    vfs(ctx)?;
+    if let Err(errno) = wasm_range(ctx, buf, count) {
+        return Some(errno);
+    }
+    let mut buffer = vec![0; count.min(MAX_IO_SIZE) as usize];
+    let ret = f(vfs(ctx)?, &mut buffer);
+    if ret > 0 {
+        if let Err(errno) = write_to_wasm(ctx, buf, &buffer[..ret as usize]) {
+            return Some(errno);
+        }
+    }
+    Some(ret)
+}
+
+/// Runs a syscall that writes from the `count` bytes at `buf`, such as `write`.
+///
+/// `f` is given at most `MAX_IO_SIZE` bytes, and returns how many it wrote.
+fn write_from_wasm<F>(ctx: &mut Ctx, buf: u32, count: u32, f: F) -> Option<i32>
+where
+    F: FnOnce(&mut EmscriptenVfs, &[u8]) -> i32,
+{
+    vfs(ctx)?;
+    if let Err(errno) = wasm_range(ctx, buf, count) {
+        return Some(errno);
+    }
+    let buffer = match read_from_wasm(ctx, buf, count.min(MAX_IO_SIZE)) {
+        Ok(buffer) => buffer,
+        Err(errno) => return Some(errno),
+    };
+    Some(f(vfs(ctx)?, &buffer))
+}
+
+/// Fails the syscalls that the vfs doesn't support, so that they don't reach the host.
+pub fn unsupported(ctx: &mut Ctx) -> Option<i32> {
+    vfs(ctx).map(|_| -ENOSYS)
+}
+
+/// Writes to the vfs are committed right away, so there is nothing to sync.
+pub fn sync(ctx: &mut Ctx) -> Option<i32> {
+    vfs(ctx).map(|_| 0)
+}
+
+pub fn close(ctx: &mut Ctx, fd: i32) -> Option<i32> {
+    Some(vfs(ctx)?.close(fd))
+}
+
+pub fn dup(ctx: &mut Ctx, fd: i32) -> Option<i32> {
+    Some(vfs(ctx)?.dup(fd))
+}
+
+pub fn dup2(ctx: &mut Ctx, src: i32, dst: i32) -> Option<i32> {
+    Some(vfs(ctx)?.dup2(src, dst))
+}
+
+pub fn read(ctx: &mut Ctx, fd: i32, buf: u32, count: u32) -> Option<i32> {
+    read_into_wasm(ctx, buf, count, |vfs, buffer| vfs.read(fd, buffer))
+}
+
+pub fn write(ctx: &mut Ctx, fd: i32, buf: u32, count: u32) -> Option<i32> {
+    write_from_wasm(ctx, buf, count, |vfs, buffer| vfs.write(fd, buffer))
+}
+
+pub fn pread(ctx: &mut Ctx, fd: i32, buf: u32, count: u32, offset: i64) -> Option<i32> {
+    read_into_wasm(ctx, buf, count, |vfs, buffer| vfs.pread(fd, buffer, offset))
+}
+
+pub fn pwrite(ctx: &mut Ctx, fd: i32, buf: u32, count: u32, offset: i64) -> Option<i32> {
+    write_from_wasm(ctx, buf, count, |vfs, buffer| {
+        vfs.pwrite(fd, buffer, offset)
+    })
+}
+
+/// Reads into, or writes from, every buffer of a guest `iovec` array, like `readv` and `writev`.
+pub fn vectored<F>(ctx: &mut Ctx, iov: u32, iovcnt: u32, mut f: F) -> Option<i32>
+where
+    F: FnMut(&mut Ctx, u32, u32) -> Option<i32>,
+{
+    vfs(ctx)?;
+    if iovcnt > IOV_MAX {
+        return Some(-EINVAL);
+    }
+    let mut total = 0;
+    for i in 0..iovcnt {
+        let iovec = iov
+            .checked_add(i * 8)
+            .ok_or(-EFAULT)
+            .and_then(|offset| read_from_wasm(ctx, offset, 8));
+        let iovec = match iovec {
+            Ok(iovec) => iovec,
+            Err(errno) => return Some(errno),
+        };
+        let iov_base = LittleEndian::read_u32(&iovec[0..]);
+        let iov_len = LittleEndian::read_u32(&iovec[4..]);
+        let ret = f(ctx, iov_base, iov_len)?;
+        if ret < 0 {
+            return Some(ret);
+        }
+        total += ret;
+        if (ret as u32) < iov_len {
+            break;
+        }
+    }
+    Some(total)
+}
+
+/// Seeks like `_llseek`, storing the new 64-bit position at `result`.
+pub fn llseek(ctx: &mut Ctx, fd: i32, offset: i64, result: u32, whence: i32) -> Option<i32> {
+    let position = vfs(ctx)?.seek(fd, offset, whence);
+    if position < 0 {
+        return Some(position as i32);
+    }
+    let mut bytes = [0; 8];
+    LittleEndian::write_i64(&mut bytes, position);
+    match write_to_wasm(ctx, result, &bytes) {
+        Ok(()) => Some(0),
+        Err(errno) => Some(errno),
+    }
+}
+
+pub fn ftruncate(ctx: &mut Ctx, fd: i32, length: i64) -> Option<i32> {
+    Some(vfs(ctx)?.ftruncate(fd, length))
+}
+
+pub fn stat(ctx: &mut Ctx, pathname: u32, buf: u32) -> Option<i32> {
+    vfs(ctx)?;
+    let path = match read_path_from_wasm(ctx, pathname) {
+        Ok(path) => path,
+        Err(errno) => return Some(errno),
+    };
+    let stat = vfs(ctx)?.stat(&path);
+    Some(copy_stat_result(ctx, buf, stat))
+}
+
+pub fn fstat(ctx: &mut Ctx, fd: i32, buf: u32) -> Option<i32> {
+    let stat = vfs(ctx)?.fstat(fd);
+    Some(copy_stat_result(ctx, buf, stat))
+}
+
+fn copy_stat_result(ctx: &mut Ctx, buf: u32, stat: Result<libc::stat, i32>) -> i32 {
+    match stat {
+        Ok(stat) => match wasm_range(ctx, buf, mem::size_of::<GuestStat>() as u32) {
+            Ok(_) => {
+                unsafe { copy_stat_into_wasm(ctx, buf, &stat) };
+                0
+            }
+            Err(errno) => errno,
+        },
+        Err(errno) => errno,
+    }
+}
+
+/// Runs a syscall that only takes a path, such as `mkdir` or `unlink`.
+pub fn with_path<F>(ctx: &mut Ctx, pathname: u32, f: F) -> Option<i32>
+where
+    F: FnOnce(&mut EmscriptenVfs, &str) -> i32,
+{
+    vfs(ctx)?;
+    match read_path_from_wasm(ctx, pathname) {
+        Ok(path) => Some(f(vfs(ctx)?, &path)),
+        Err(errno) => Some(errno),
+    }
+}
+
+pub fn getcwd(ctx: &mut Ctx, buf: u32, size: u32) -> Option<i32> {
+    let mut cwd = vfs(ctx)?.getcwd().into_bytes();
+    cwd.push(0);
+    if cwd.len() > size as usize {
+        return Some(-ERANGE);
+    }
+    match write_to_wasm(ctx, buf, &cwd) {
+        Ok(()) => Some(buf as i32),
+        Err(errno) => Some(errno),
+    }
+}
+
+pub fn getdents(ctx: &mut Ctx, fd: i32, dirp: u32, count: u32) -> Option<i32> {
+    read_into_wasm(ctx, dirp, count, |vfs, buffer| vfs.getdents(fd, buffer))
+}
+
+pub fn fcntl(ctx: &mut Ctx, fd: i32, cmd: i32, arg: i32) -> Option<i32> {
+    Some(vfs(ctx)?.fcntl(fd, cmd, arg))
+}
+
+/// Maps `len` bytes of a file from `offset`, by copying them into newly
+/// allocated memory like Emscripten's own in-memory filesystem does.
+pub fn mmap(ctx: &mut Ctx, fd: i32, len: u32, offset: i64) -> Option<i32> {
+    vfs(ctx)?;
+    let ptr = env::call_memalign(ctx, 16384, len);
+    if ptr == 0 {
+        return Some(-ENOMEM);
+    }
+    env::call_memset(ctx, ptr, 0, len);
+
+    let mut copied = 0;
+    while copied < len {
+        let ret = pread(
+            ctx,
+            fd,
+            ptr + copied,
+            len - copied,
+            offset + i64::from(copied),
+        )?;
+        if ret < 0 {
+            return Some(ret);
+        }
+        if ret == 0 {
+            break;
+        }
+        copied += ret as u32;
+    }
+    Some(ptr as i32)
+}
@@ -15,7 +15,7 @@ type pid_t = c_int;
 
 /// open
 pub fn ___syscall5(ctx: &mut Ctx, which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall5 (open) {}", which);
+    debug!(target: "emscripten::___syscall5", "(open) {}", which);
     #[cfg(not(feature = "debug"))]
     let _ = which;
     let pathname: u32 = varargs.get(ctx);
@@ -42,6 +42,7 @@ pub fn ___syscall5(ctx: &mut Ctx, which: c_int, mut varargs: VarArgs) -> c_int {
                 emscripten_memory_pointer!(ctx.memory(0), urandom_file_offset) as *const i8;
             let fd = unsafe { open(raw_pointer_to_urandom_file, flags, mode) };
             debug!(
+                target: "emscripten::___syscall5",
                 "=> pathname: {}, flags: {}, mode: {} = fd: {}",
                 pathname, flags, mode, fd
             );
@@ -50,6 +51,7 @@ pub fn ___syscall5(ctx: &mut Ctx, which: c_int, mut varargs: VarArgs) -> c_int {
         _ => {
             let fd = unsafe { open(pathname_addr, flags, mode) };
             debug!(
+                target: "emscripten::___syscall5",
                 "=> pathname: {}, flags: {}, mode: {} = fd: {}\npath: {}",
                 pathname, flags, mode, fd, path_str
             );
@@ -60,19 +62,19 @@ pub fn ___syscall5(ctx: &mut Ctx, which: c_int, mut varargs: VarArgs) -> c_int {
 
 /// link
 pub fn ___syscall9(_ctx: &mut Ctx, _which: c_int, mut _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall9 (link) {}", _which);
+    debug!(target: "emscripten::___syscall9", "(link) {}", _which);
     unimplemented!()
 }
 
 /// ftruncate64
 pub fn ___syscall194(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall194 - stub");
+    debug!(target: "emscripten::___syscall194", "- stub");
     unimplemented!()
 }
 
 // chown
 pub fn ___syscall212(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall212 (chown) {}", which);
+    debug!(target: "emscripten::___syscall212", "(chown) {}", which);
     #[cfg(not(feature = "debug"))]
     let _ = which;
     -1
@@ -80,19 +82,19 @@ pub fn ___syscall212(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_i
 
 /// access
 pub fn ___syscall33(_ctx: &mut Ctx, _which: c_int, mut _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall33 (access) {}", _which);
+    debug!(target: "emscripten::___syscall33", "(access) {}", _which);
     unimplemented!()
 }
 
 /// nice
 pub fn ___syscall34(_ctx: &mut Ctx, _which: c_int, mut _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall34 (nice) {}", _which);
+    debug!(target: "emscripten::___syscall34", "(nice) {}", _which);
     unimplemented!()
 }
 
 // mkdir
 pub fn ___syscall39(ctx: &mut Ctx, which: c_int, mut varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall39 (mkdir) {}", which);
+    debug!(target: "emscripten::___syscall39", "(mkdir) {}", which);
     #[cfg(not(feature = "debug"))]
     let _ = which;
     let pathname: u32 = varargs.get(ctx);
@@ -102,68 +104,68 @@ pub fn ___syscall39(ctx: &mut Ctx, which: c_int, mut varargs: VarArgs) -> c_int
 
 /// dup
 pub fn ___syscall41(_ctx: &mut Ctx, _which: c_int, _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall41 (dup) {}", _which);
+    debug!(target: "emscripten::___syscall41", "(dup) {}", _which);
     unimplemented!()
 }
 
 /// getrusage
 pub fn ___syscall77(_ctx: &mut Ctx, _which: c_int, _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall77 (getrusage) {}", _which);
+    debug!(target: "emscripten::___syscall77", "(getrusage) {}", _which);
     unimplemented!()
 }
 
 /// symlink
 pub fn ___syscall83(_ctx: &mut Ctx, _which: c_int, _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall83 (symlink) {}", _which);
+    debug!(target: "emscripten::___syscall83", "(symlink) {}", _which);
     unimplemented!()
 }
 
 /// lchown
 pub fn ___syscall198(_ctx: &mut Ctx, _which: c_int, _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall198 (lchown) {}", _which);
+    debug!(target: "emscripten::___syscall198", "(lchown) {}", _which);
     unimplemented!()
 }
 
 /// getgid
 pub fn ___syscall200(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall200 (getgid)");
+    debug!(target: "emscripten::___syscall200", "(getgid)");
     unimplemented!()
 }
 
 // getgid
 pub fn ___syscall201(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall201 (getgid)");
+    debug!(target: "emscripten::___syscall201", "(getgid)");
     -1
 }
 
 // getgid32
 pub fn ___syscall202(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
     // gid_t
-    debug!("emscripten::___syscall202 (getgid32)");
+    debug!(target: "emscripten::___syscall202", "(getgid32)");
     -1
 }
 
 /// getgroups
 pub fn ___syscall205(_ctx: &mut Ctx, _which: c_int, _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall205 (getgroups) {}", _which);
+    debug!(target: "emscripten::___syscall205", "(getgroups) {}", _which);
     unimplemented!()
 }
 
 /// madvise
 pub fn ___syscall219(_ctx: &mut Ctx, _which: c_int, _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall212 (chown) {}", _which);
+    debug!(target: "emscripten::___syscall219", "emscripten::___syscall212 (chown) {}", _which);
     unimplemented!()
 }
 
 /// dup3
 pub fn ___syscall330(_ctx: &mut Ctx, _which: c_int, mut _varargs: VarArgs) -> pid_t {
-    debug!("emscripten::___syscall330 (dup3)");
+    debug!(target: "emscripten::___syscall330", "(dup3)");
     -1
 }
 
 /// ioctl
 pub fn ___syscall54(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall54 (ioctl) {}", which);
+    debug!(target: "emscripten::___syscall54", "(ioctl) {}", which);
     #[cfg(not(feature = "debug"))]
     let _ = which;
     -1
@@ -171,14 +173,14 @@ pub fn ___syscall54(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_in
 
 /// fchmod
 pub fn ___syscall94(_ctx: &mut Ctx, _which: c_int, _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall118 (fchmod) {}", _which);
+    debug!(target: "emscripten::___syscall94", "emscripten::___syscall118 (fchmod) {}", _which);
     unimplemented!()
 }
 
 // socketcall
 #[allow(clippy::cast_ptr_alignment)]
 pub fn ___syscall102(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall102 (socketcall) {}", which);
+    debug!(target: "emscripten::___syscall102", "(socketcall) {}", which);
     #[cfg(not(feature = "debug"))]
     let _ = which;
     -1
@@ -186,13 +188,13 @@ pub fn ___syscall102(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_i
 
 /// fsync
 pub fn ___syscall118(_ctx: &mut Ctx, _which: c_int, _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall118 (fsync) {}", _which);
+    debug!(target: "emscripten::___syscall118", "(fsync) {}", _which);
     unimplemented!()
 }
 
 // pread
 pub fn ___syscall180(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall180 (pread) {}", which);
+    debug!(target: "emscripten::___syscall180", "(pread) {}", which);
     #[cfg(not(feature = "debug"))]
     let _ = which;
     -1
@@ -200,7 +202,7 @@ pub fn ___syscall180(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_i
 
 // pwrite
 pub fn ___syscall181(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall181 (pwrite) {}", which);
+    debug!(target: "emscripten::___syscall181", "(pwrite) {}", which);
     #[cfg(not(feature = "debug"))]
     let _ = which;
     -1
@@ -209,14 +211,14 @@ pub fn ___syscall181(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_i
 /// wait4
 #[allow(clippy::cast_ptr_alignment)]
 pub fn ___syscall114(_ctx: &mut Ctx, _which: c_int, mut _varargs: VarArgs) -> pid_t {
-    debug!("emscripten::___syscall114 (wait4)");
+    debug!(target: "emscripten::___syscall114", "(wait4)");
     -1
 }
 
 // select
 #[allow(clippy::cast_ptr_alignment)]
 pub fn ___syscall142(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall142 (newselect) {}", which);
+    debug!(target: "emscripten::___syscall142", "(newselect) {}", which);
     #[cfg(not(feature = "debug"))]
     let _ = which;
     -1
@@ -224,13 +226,13 @@ pub fn ___syscall142(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_i
 
 /// fdatasync
 pub fn ___syscall148(_ctx: &mut Ctx, _which: c_int, _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall148 (fdatasync) {}", _which);
+    debug!(target: "emscripten::___syscall148", "(fdatasync) {}", _which);
     unimplemented!();
 }
 
 // setpgid
 pub fn ___syscall57(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall57 (setpgid) {}", which);
+    debug!(target: "emscripten::___syscall57", "(setpgid) {}", which);
     #[cfg(not(feature = "debug"))]
     let _ = which;
     -1
@@ -239,7 +241,7 @@ pub fn ___syscall57(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_in
 /// uname
 // NOTE: Wondering if we should return custom utsname, like Emscripten.
 pub fn ___syscall122(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall122 (uname) {}", which);
+    debug!(target: "emscripten::___syscall122", "(uname) {}", which);
     #[cfg(not(feature = "debug"))]
     let _ = which;
     -1
@@ -247,18 +249,18 @@ pub fn ___syscall122(_ctx: &mut Ctx, which: c_int, mut _varargs: VarArgs) -> c_i
 
 /// lstat64
 pub fn ___syscall196(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::___syscall196 (lstat64) - stub");
+    debug!(target: "emscripten::___syscall196", "(lstat64) - stub");
     -1
 }
 
 /// fchown
 pub fn ___syscall207(_ctx: &mut Ctx, _which: c_int, _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall207 (fchown) {}", _which);
+    debug!(target: "emscripten::___syscall207", "(fchown) {}", _which);
     unimplemented!()
 }
 
 /// fallocate
 pub fn ___syscall324(_ctx: &mut Ctx, _which: c_int, _varargs: VarArgs) -> c_int {
-    debug!("emscripten::___syscall324 (fallocate) {}", _which);
+    debug!(target: "emscripten::___syscall324", "(fallocate) {}", _which);
     unimplemented!()
 }
@@ -43,7 +43,7 @@ const CLOCK_MONOTONIC_COARSE: clockid_t = 6;
 /// emscripten: _gettimeofday
 #[allow(clippy::cast_ptr_alignment)]
 pub fn _gettimeofday(ctx: &mut Ctx, tp: c_int, tz: c_int) -> c_int {
-    debug!("emscripten::_gettimeofday {} {}", tp, tz);
+    debug!(target: "emscripten::_gettimeofday", "{} {}", tp, tz);
     #[repr(C)]
     struct GuestTimeVal {
         tv_sec: i32,
@@ -68,7 +68,7 @@ pub fn _gettimeofday(ctx: &mut Ctx, tp: c_int, tz: c_int) -> c_int {
 /// emscripten: _clock_gettime
 #[allow(clippy::cast_ptr_alignment)]
 pub fn _clock_gettime(ctx: &mut Ctx, clk_id: clockid_t, tp: c_int) -> c_int {
-    debug!("emscripten::_clock_gettime {} {}", clk_id, tp);
+    debug!(target: "emscripten::_clock_gettime", "{} {}", clk_id, tp);
     // debug!("Memory {:?}", ctx.memory(0)[..]);
     #[repr(C)]
     struct GuestTimeSpec {
@@ -101,34 +101,34 @@ pub fn _clock_gettime(ctx: &mut Ctx, clk_id: clockid_t, tp: c_int) -> c_int {
 
 /// emscripten: ___clock_gettime
 pub fn ___clock_gettime(ctx: &mut Ctx, clk_id: clockid_t, tp: c_int) -> c_int {
-    debug!("emscripten::___clock_gettime {} {}", clk_id, tp);
+    debug!(target: "emscripten::___clock_gettime", "{} {}", clk_id, tp);
     _clock_gettime(ctx, clk_id, tp)
 }
 
 /// emscripten: _clock
 pub fn _clock(_ctx: &mut Ctx) -> c_int {
-    debug!("emscripten::_clock");
+    debug!(target: "emscripten::_clock", "called");
     0 // TODO: unimplemented
 }
 
 /// emscripten: _difftime
 pub fn _difftime(_ctx: &mut Ctx, t0: u32, t1: u32) -> f64 {
-    debug!("emscripten::_difftime");
+    debug!(target: "emscripten::_difftime", "called");
     (t0 - t1) as _
 }
 
 pub fn _gmtime_r(_ctx: &mut Ctx, _one: i32, _two: i32) -> i32 {
-    debug!("emscripten::_gmtime_r");
+    debug!(target: "emscripten::_gmtime_r", "called");
     -1
 }
 
 pub fn _mktime(_ctx: &mut Ctx, _one: i32) -> i32 {
-    debug!("emscripten::_mktime");
+    debug!(target: "emscripten::_mktime", "called");
     -1
 }
 
 pub fn _gmtime(_ctx: &mut Ctx, _one: i32) -> i32 {
-    debug!("emscripten::_gmtime");
+    debug!(target: "emscripten::_gmtime", "called");
     -1
 }
 
@@ -149,7 +149,7 @@ struct guest_tm {
 
 /// emscripten: _tvset
 pub fn _tvset(_ctx: &mut Ctx) {
-    debug!("emscripten::_tvset UNIMPLEMENTED");
+    debug!(target: "emscripten::_tvset", "UNIMPLEMENTED");
 }
 
 /// formats time as a C string
@@ -180,7 +180,7 @@ unsafe fn fmt_time(ctx: &mut Ctx, time: u32) -> *const c_char {
 
 /// emscripten: _asctime
 pub fn _asctime(ctx: &mut Ctx, time: u32) -> u32 {
-    debug!("emscripten::_asctime {}", time);
+    debug!(target: "emscripten::_asctime", "{}", time);
 
     unsafe {
         let time_str_ptr = fmt_time(ctx, time);
@@ -194,7 +194,7 @@ pub fn _asctime(ctx: &mut Ctx, time: u32) -> u32 {
 
 /// emscripten: _asctime_r
 pub fn _asctime_r(ctx: &mut Ctx, time: u32, buf: u32) -> u32 {
-    debug!("emscripten::_asctime_r {}, {}", time, buf);
+    debug!(target: "emscripten::_asctime_r", "{}, {}", time, buf);
 
     unsafe {
         // NOTE: asctime_r is specced to behave in an undefined manner if the algorithm would attempt
@@ -213,7 +213,7 @@ pub fn _asctime_r(ctx: &mut Ctx, time: u32, buf: u32) -> u32 {
 /// emscripten: _localtime
 #[allow(clippy::cast_ptr_alignment)]
 pub fn _localtime(ctx: &mut Ctx, time_p: u32) -> c_int {
-    debug!("emscripten::_localtime {}", time_p);
+    debug!(target: "emscripten::_localtime", "{}", time_p);
     // NOTE: emscripten seems to want tzset() called in this function
     //      https://stackoverflow.com/questions/19170721/real-time-awareness-of-timezone-change-in-localtime-vs-localtime-r
 
@@ -251,7 +251,7 @@ pub fn _localtime(ctx: &mut Ctx, time_p: u32) -> c_int {
 /// emscripten: _localtime_r
 #[allow(clippy::cast_ptr_alignment)]
 pub fn _localtime_r(ctx: &mut Ctx, time_p: u32, result: u32) -> c_int {
-    debug!("emscripten::_localtime_r {}", time_p);
+    debug!(target: "emscripten::_localtime_r", "{}", time_p);
 
     // NOTE: emscripten seems to want tzset() called in this function
     //      https://stackoverflow.com/questions/19170721/real-time-awareness-of-timezone-change-in-localtime-vs-localtime-r
@@ -288,7 +288,7 @@ pub fn _localtime_r(ctx: &mut Ctx, time_p: u32, result: u32) -> c_int {
 /// emscripten: _time
 #[allow(clippy::cast_ptr_alignment)]
 pub fn _time(ctx: &mut Ctx, time_p: u32) -> i32 {
-    debug!("emscripten::_time {}", time_p);
+    debug!(target: "emscripten::_time", "{}", time_p);
 
     unsafe {
         let time_p_addr = emscripten_memory_pointer!(ctx.memory(0), time_p) as *mut i64;
@@ -305,7 +305,8 @@ pub fn _strftime(
     tm_ptr: c_int,
 ) -> i32 {
     debug!(
-        "emscripten::_strftime {} {} {} {}",
+        target: "emscripten::_strftime",
+        "{} {} {} {}",
         s_ptr, maxsize, format_ptr, tm_ptr
     );
 
@@ -318,7 +319,7 @@ pub fn _strftime(
 
     let format_string = unsafe { std::ffi::CStr::from_ptr(format).to_str().unwrap() };
 
-    debug!("=> format_string: {:?}", format_string);
+    debug!(target: "emscripten::_strftime", "=> format_string: {:?}", format_string);
 
     let tm = unsafe { &*tm };
 
@@ -366,7 +367,8 @@ pub fn _strftime_l(
     _last: c_int,
 ) -> i32 {
     debug!(
-        "emscripten::_strftime_l {} {} {} {}",
+        target: "emscripten::_strftime_l",
+        "{} {} {} {}",
         s_ptr, maxsize, format_ptr, tm_ptr
     );
 
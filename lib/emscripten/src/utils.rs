@@ -102,7 +102,8 @@ pub unsafe fn copy_terminated_array_of_cstrs(_ctx: &mut Ctx, cstrs: *mut *mut c_
         counter
     };
     debug!(
-        "emscripten::copy_terminated_array_of_cstrs::total_num: {}",
+        target: "emscripten::copy_terminated_array_of_cstrs",
+        ":total_num: {}",
         _total_num
     );
     0
@@ -87,6 +87,9 @@ struct WasmTrap : UncatchableException
         MemoryOutOfBounds = 2,
         CallIndirectOOB = 3,
         IllegalArithmetic = 4,
+        MisalignedAtomicAccess = 5,
+        UnsharedMemoryWait = 6,
+        TableOutOfBounds = 7,
         Unknown,
     };
 
@@ -124,6 +127,15 @@ struct WasmTrap : UncatchableException
         case Type::IllegalArithmetic:
             out << "illegal arithmetic operation";
             break;
+        case Type::MisalignedAtomicAccess:
+            out << "misaligned atomic memory access";
+            break;
+        case Type::UnsharedMemoryWait:
+            out << "memory.atomic.wait on an unshared memory";
+            break;
+        case Type::TableOutOfBounds:
+            out << "table access out-of-bounds";
+            break;
         case Type::Unknown:
         default:
             out << "unknown";
@@ -177,10 +189,49 @@ extern "C"
         return RESULT_OK;
     }
 
-    [[noreturn]] void throw_trap(WasmTrap::Type ty) {
+    // Keep in sync with `MAX_TRAP_FRAMES` in runtime-core.
+    const size_t MAX_TRAP_FRAMES = 64;
+
+    // The frames of the last trap thrown on this thread, starting with the
+    // address of the trap and followed by the return addresses.
+    thread_local const void *trap_frames[MAX_TRAP_FRAMES];
+    thread_local size_t trap_frame_count = 0;
+    // The frame of the innermost `invoke_trampoline`, where the walk stops.
+    thread_local const void *trap_stack_end = nullptr;
+
+    // Each frame starts with the frame pointer of its caller, then the return
+    // address. The walk has to happen before the exception unwinds the stack.
+    [[noreturn]] void throw_trap_at(WasmTrap::Type ty, const void *address, const void *frame, const void *stack) {
+        uintptr_t fp = (uintptr_t)frame;
+        uintptr_t lowest = (uintptr_t)stack;
+        uintptr_t stack_end = (uintptr_t)trap_stack_end;
+
+        trap_frames[0] = address;
+        trap_frame_count = 1;
+        while (trap_frame_count < MAX_TRAP_FRAMES && fp >= lowest && fp % 8 == 0 && fp + 16 <= stack_end) {
+            const void *const *record = (const void *const *)fp;
+            trap_frames[trap_frame_count++] = record[1];
+            lowest = fp + 16;
+            fp = (uintptr_t)record[0];
+        }
+
         throw WasmTrap(ty);
     }
 
+    // Called by the generated code, so the trap happened at the call.
+    [[noreturn]] void throw_trap(WasmTrap::Type ty) {
+        throw_trap_at(
+            ty,
+            (const char *)__builtin_return_address(0) - 1,
+            __builtin_frame_address(1),
+            __builtin_frame_address(0));
+    }
+
+    size_t get_trap_frames(const void *const **frames_out) {
+        *frames_out = trap_frames;
+        return trap_frame_count;
+    }
+
     void module_delete(WasmModule *module)
     {
         delete module;
@@ -202,6 +253,15 @@ extern "C"
         box_any_t *user_error,
         void *invoke_env) throw()
     {
+        // Restores the stack end of the enclosing call on every return path.
+        struct StackEndGuard
+        {
+            const void *prev = trap_stack_end;
+            ~StackEndGuard() { trap_stack_end = prev; }
+        } guard;
+        trap_stack_end = __builtin_frame_address(0);
+        trap_frame_count = 0;
+
         try
         {
             trampoline(ctx, func, params, results);
@@ -214,6 +274,7 @@ extern "C"
         }
         catch (const UserException &e)
         {
+            trap_frame_count = 0;
             *user_error = e.error_data;
             return false;
         }
@@ -20,6 +20,7 @@ use std::{
 };
 use wasmer_runtime_core::{
     backend::{
+        record_trap_frames,
         sys::{Memory, Protect},
         CacheGen, RunnableModule,
     },
@@ -78,6 +79,7 @@ extern "C" {
     fn get_func_symbol(module: *mut LLVMModule, name: *const c_char) -> *const vm::Func;
 
     fn throw_trap(ty: i32);
+    fn get_trap_frames(frames_out: &mut *const usize) -> usize;
 
     /// This should be the same as spliting up the fat pointer into two arguments,
     /// but this is cleaner, I think?
@@ -187,8 +189,42 @@ fn get_callbacks() -> Callbacks {
             fn_name!("vm.memory.size.dynamic.local") => vmcalls::local_dynamic_memory_size as _,
             fn_name!("vm.memory.grow.static.local") => vmcalls::local_static_memory_grow as _,
             fn_name!("vm.memory.size.static.local") => vmcalls::local_static_memory_size as _,
+            fn_name!("vm.memory.grow.shared.local") => {
+                vmcalls::local_shared_static_memory_grow as _
+            }
+            fn_name!("vm.memory.size.shared.local") => {
+                vmcalls::local_shared_static_memory_size as _
+            }
+
+            fn_name!("vm.memory.grow.dynamic.import") => vmcalls::imported_dynamic_memory_grow as _,
+            fn_name!("vm.memory.size.dynamic.import") => vmcalls::imported_dynamic_memory_size as _,
+            fn_name!("vm.memory.grow.static.import") => vmcalls::imported_static_memory_grow as _,
+            fn_name!("vm.memory.size.static.import") => vmcalls::imported_static_memory_size as _,
+            fn_name!("vm.memory.grow.shared.import") => {
+                vmcalls::imported_shared_static_memory_grow as _
+            }
+            fn_name!("vm.memory.size.shared.import") => {
+                vmcalls::imported_shared_static_memory_size as _
+            }
+
+            fn_name!("vm.memory.init") => vmcalls::memory_init as _,
+            fn_name!("vm.data.drop") => vmcalls::data_drop as _,
+            fn_name!("vm.memory.copy") => vmcalls::memory_copy as _,
+            fn_name!("vm.memory.fill") => vmcalls::memory_fill as _,
+            fn_name!("vm.table.init") => vmcalls::table_init as _,
+            fn_name!("vm.elem.drop") => vmcalls::elem_drop as _,
+            fn_name!("vm.table.copy") => vmcalls::table_copy as _,
+            fn_name!("vm.table.get") => vmcalls::table_get as _,
+            fn_name!("vm.table.set") => vmcalls::table_set as _,
+            fn_name!("vm.table.grow") => vmcalls::table_grow as _,
+            fn_name!("vm.table.size") => vmcalls::table_size as _,
+            fn_name!("vm.memory.atomic.wait32") => vmcalls::memory_atomic_wait32 as _,
+            fn_name!("vm.memory.atomic.wait64") => vmcalls::memory_atomic_wait64 as _,
+            fn_name!("vm.memory.atomic.notify") => vmcalls::memory_atomic_notify as _,
 
             fn_name!("vm.exception.trap") => throw_trap as _,
+            fn_name!("vm.fuel.exhausted") => vmcalls::fuel_exhausted as _,
+            fn_name!("vm.interrupted") => vmcalls::interrupted as _,
 
             _ => ptr::null(),
         }
@@ -369,12 +405,47 @@ impl RunnableModule for LLVMBackend {
             mem::transmute(symbol)
         };
 
-        Some(unsafe { Wasm::from_raw_parts(trampoline, invoke_trampoline, None) })
+        Some(unsafe { Wasm::from_raw_parts(trampoline, invoke, None) })
     }
 
     unsafe fn do_early_trap(&self, data: Box<dyn Any>) -> ! {
         throw_any(Box::leak(data))
     }
+
+    fn get_local_func_index_at(&self, info: &ModuleInfo, address: usize) -> Option<LocalFuncIndex> {
+        // The code of a function is assumed to end where the next one starts.
+        (0..info.func_assoc.len() - info.imported_functions.len())
+            .map(LocalFuncIndex::new)
+            .filter_map(|index| Some((index, self.get_func(info, index)?.as_ptr() as usize)))
+            .filter(|&(_, start)| start <= address)
+            .max_by_key(|&(_, start)| start)
+            .map(|(index, _)| index)
+    }
+}
+
+/// Calls `invoke_trampoline`, recording the frames of the trap if the call trapped.
+///
+/// The frames are walked when the trap is thrown, as the stack they're on is gone
+/// once the exception is caught.
+unsafe extern "C" fn invoke(
+    trampoline: unsafe extern "C" fn(*mut vm::Ctx, NonNull<vm::Func>, *const u64, *mut u64),
+    vmctx_ptr: *mut vm::Ctx,
+    func_ptr: NonNull<vm::Func>,
+    params: *const u64,
+    results: *mut u64,
+    trap_out: *mut WasmTrapInfo,
+    user_error: *mut Option<Box<dyn Any>>,
+    invoke_env: Option<NonNull<c_void>>,
+) -> bool {
+    let success = invoke_trampoline(
+        trampoline, vmctx_ptr, func_ptr, params, results, trap_out, user_error, invoke_env,
+    );
+    let mut frames = ptr::null();
+    let len = get_trap_frames(&mut frames);
+    if !success && len != 0 {
+        record_trap_frames(slice::from_raw_parts(frames, len));
+    }
+    success
 }
 
 unsafe impl Send for LLVMCache {}
@@ -1,15 +1,20 @@
 use inkwell::{
+    attributes::AttributeLoc,
     builder::Builder,
     context::Context,
     module::{Linkage, Module},
     passes::PassManager,
-    types::{BasicType, BasicTypeEnum, FunctionType, PointerType},
-    values::{BasicValue, FloatValue, FunctionValue, IntValue, PhiValue, PointerValue},
-    AddressSpace, FloatPredicate, IntPredicate,
+    types::{BasicType, BasicTypeEnum, FunctionType, IntType, PointerType, VectorType},
+    values::{
+        BasicValue, BasicValueEnum, FloatValue, FunctionValue, InstructionValue, IntValue,
+        PhiValue, PointerValue, VectorValue,
+    },
+    AddressSpace, AtomicOrdering, AtomicRMWBinOp, FloatPredicate, IntPredicate,
 };
 use smallvec::SmallVec;
 use wasmer_runtime_core::{
     memory::MemoryType,
+    metering::OperatorCost,
     module::ModuleInfo,
     structures::{Map, SliceMap, TypedIndex},
     types::{
@@ -52,15 +57,18 @@ fn func_sig_to_llvm(context: &Context, intrinsics: &Intrinsics, sig: &FuncSig) -
 fn type_to_llvm(intrinsics: &Intrinsics, ty: Type) -> BasicTypeEnum {
     match ty {
         Type::I32 => intrinsics.i32_ty.as_basic_type_enum(),
-        Type::I64 => intrinsics.i64_ty.as_basic_type_enum(),
+        // References are raw words, see `wasmer_runtime_core::table::AnyRef`.
+        Type::I64 | Type::AnyRef | Type::FuncRef => intrinsics.i64_ty.as_basic_type_enum(),
         Type::F32 => intrinsics.f32_ty.as_basic_type_enum(),
         Type::F64 => intrinsics.f64_ty.as_basic_type_enum(),
+        Type::V128 => intrinsics.i128_ty.as_basic_type_enum(),
     }
 }
 
 pub fn parse_function_bodies(
     info: &ModuleInfo,
     code_reader: CodeSectionReader,
+    fuel_costs: Option<Vec<Vec<OperatorCost>>>,
 ) -> Result<(Module, Intrinsics), BinaryReaderError> {
     let context = Context::create();
     let module = context.create_module("module");
@@ -90,6 +98,11 @@ pub fn parse_function_bodies(
                 Some(Linkage::External),
             );
             func.set_personality_function(personality_func);
+            // Traps walk the stack by following the frame pointers.
+            func.add_attribute(
+                AttributeLoc::Function,
+                context.create_string_attribute("no-frame-pointer-elim", "true"),
+            );
             func
         })
         .collect();
@@ -110,6 +123,9 @@ pub fn parse_function_bodies(
             LocalFuncIndex::new(local_func_index),
             locals_reader,
             op_reader,
+            fuel_costs
+                .as_ref()
+                .map(|fuel_costs| &fuel_costs[local_func_index][..]),
         )
         .map_err(|e| BinaryReaderError {
             message: e.message,
@@ -150,6 +166,7 @@ fn parse_function(
     func_index: LocalFuncIndex,
     locals_reader: LocalsReader,
     op_reader: OperatorsReader,
+    fuel_costs: Option<&[OperatorCost]>,
 ) -> Result<(), BinaryReaderError> {
     let sig_index = info.func_assoc[func_index.convert_up(info)];
     let func_sig = &info.signatures[sig_index];
@@ -197,9 +214,10 @@ fn parse_function(
 
         let default_value = match wasmer_ty {
             Type::I32 => intrinsics.i32_zero.as_basic_value_enum(),
-            Type::I64 => intrinsics.i64_zero.as_basic_value_enum(),
+            Type::I64 | Type::AnyRef | Type::FuncRef => intrinsics.i64_zero.as_basic_value_enum(),
             Type::F32 => intrinsics.f32_zero.as_basic_value_enum(),
             Type::F64 => intrinsics.f64_zero.as_basic_value_enum(),
+            Type::V128 => intrinsics.i128_zero.as_basic_value_enum(),
         };
 
         for _ in 0..count {
@@ -221,7 +239,10 @@ fn parse_function(
     let mut ctx = intrinsics.ctx(info, builder, &function, cache_builder);
     let mut unreachable_depth = 0;
 
-    for op in op_reader {
+    emit_interrupt_check(context, builder, intrinsics, &function, &ctx);
+    let mut fuel_pending = 0;
+
+    for (op_index, op) in op_reader.into_iter().enumerate() {
         let op = op?;
         if !state.reachable {
             match op {
@@ -246,6 +267,38 @@ fn parse_function(
             }
         }
 
+        if let Some(fuel_costs) = fuel_costs {
+            fuel_pending += fuel_costs.get(op_index).map_or(0, |op| op.cost);
+            if is_block_boundary(&op) && fuel_pending > 0 {
+                let (used_ptr, limit_ptr) = ctx.fuel();
+                let used = builder.build_load(used_ptr, "fuel_used").into_int_value();
+                let used = builder.build_int_add(
+                    used,
+                    intrinsics.i64_ty.const_int(fuel_pending, false),
+                    "fuel_used",
+                );
+                builder.build_store(used_ptr, used);
+                let limit = builder.build_load(limit_ptr, "fuel_limit").into_int_value();
+                let exhausted =
+                    builder.build_int_compare(IntPredicate::UGT, used, limit, "fuel_exhausted");
+
+                let exhausted_block = context.append_basic_block(&function, "fuel_exhausted");
+                let continue_block = context.append_basic_block(&function, "fuel_continue");
+                builder.build_conditional_branch(exhausted, &exhausted_block, &continue_block);
+
+                builder.position_at_end(&exhausted_block);
+                builder.build_call(
+                    intrinsics.fuel_exhausted,
+                    &[ctx.basic()],
+                    "fuel_exhausted_call",
+                );
+                builder.build_unreachable();
+
+                builder.position_at_end(&continue_block);
+                fuel_pending = 0;
+            }
+        }
+
         match op {
             /***************************
              * Control Flow instructions.
@@ -291,6 +344,7 @@ fn parse_function(
                 };
 
                 builder.position_at_end(&loop_body);
+                emit_interrupt_check(context, builder, intrinsics, &function, &ctx);
                 state.push_loop(loop_body, loop_next, phis);
             }
             Operator::Br { relative_depth } => {
@@ -533,7 +587,7 @@ fn parse_function(
 
                 let phis = frame.phis().to_vec();
 
-                for phi in phis.iter() {
+                for phi in phis.iter().rev() {
                     let arg = state.pop1()?;
                     phi.add_incoming(&[(&arg, &current_block)]);
                 }
@@ -862,7 +916,19 @@ fn parse_function(
                         let value = call_site.try_as_basic_value().left().unwrap();
                         state.push1(value);
                     }
-                    _ => unimplemented!("multi-value returns"),
+                    returns => {
+                        let struct_value = call_site
+                            .try_as_basic_value()
+                            .left()
+                            .unwrap()
+                            .into_struct_value();
+                        for i in 0..(returns.len() as u32) {
+                            let value = builder
+                                .build_extract_value(struct_value, i, &state.var_name())
+                                .unwrap();
+                            state.push1(value);
+                        }
+                    }
                 }
             }
 
@@ -2139,6 +2205,977 @@ fn parse_function(
                 );
                 state.push1(result.try_as_basic_value().left().unwrap());
             }
+
+            /***************************
+             * Bulk memory operators.
+             * https://github.com/WebAssembly/bulk-memory-operations/blob/master/proposals/bulk-memory-operations/Overview.md
+             ***************************/
+            Operator::MemoryInit { segment } => {
+                let (dst, src, len) = state.pop3()?;
+                let segment = intrinsics.i32_ty.const_int(segment as u64, false);
+                builder.build_call(
+                    intrinsics.memory_init,
+                    &[
+                        ctx.basic(),
+                        intrinsics.i32_zero.as_basic_value_enum(),
+                        segment.as_basic_value_enum(),
+                        dst,
+                        src,
+                        len,
+                    ],
+                    &state.var_name(),
+                );
+            }
+            Operator::DataDrop { segment } => {
+                let segment = intrinsics.i32_ty.const_int(segment as u64, false);
+                builder.build_call(
+                    intrinsics.data_drop,
+                    &[ctx.basic(), segment.as_basic_value_enum()],
+                    &state.var_name(),
+                );
+            }
+            Operator::MemoryCopy => {
+                let (dst, src, len) = state.pop3()?;
+                builder.build_call(
+                    intrinsics.memory_copy,
+                    &[
+                        ctx.basic(),
+                        intrinsics.i32_zero.as_basic_value_enum(),
+                        dst,
+                        src,
+                        len,
+                    ],
+                    &state.var_name(),
+                );
+            }
+            Operator::MemoryFill => {
+                let (dst, value, len) = state.pop3()?;
+                builder.build_call(
+                    intrinsics.memory_fill,
+                    &[
+                        ctx.basic(),
+                        intrinsics.i32_zero.as_basic_value_enum(),
+                        dst,
+                        value,
+                        len,
+                    ],
+                    &state.var_name(),
+                );
+            }
+            Operator::TableInit { segment } => {
+                let (dst, src, len) = state.pop3()?;
+                let segment = intrinsics.i32_ty.const_int(segment as u64, false);
+                builder.build_call(
+                    intrinsics.table_init,
+                    &[
+                        ctx.basic(),
+                        intrinsics.i32_zero.as_basic_value_enum(),
+                        segment.as_basic_value_enum(),
+                        dst,
+                        src,
+                        len,
+                    ],
+                    &state.var_name(),
+                );
+            }
+            Operator::ElemDrop { segment } => {
+                let segment = intrinsics.i32_ty.const_int(segment as u64, false);
+                builder.build_call(
+                    intrinsics.elem_drop,
+                    &[ctx.basic(), segment.as_basic_value_enum()],
+                    &state.var_name(),
+                );
+            }
+            Operator::TableCopy => {
+                let (dst, src, len) = state.pop3()?;
+                builder.build_call(
+                    intrinsics.table_copy,
+                    &[
+                        ctx.basic(),
+                        intrinsics.i32_zero.as_basic_value_enum(),
+                        dst,
+                        src,
+                        len,
+                    ],
+                    &state.var_name(),
+                );
+            }
+
+            /***************************
+             * Atomic memory operators.
+             * https://github.com/WebAssembly/threads/blob/master/proposals/threads/Overview.md
+             *
+             * Atomic accesses are sequentially consistent and trap unless they
+             * are naturally aligned.
+             ***************************/
+            Operator::I32AtomicLoad { memarg }
+            | Operator::I64AtomicLoad { memarg }
+            | Operator::I32AtomicLoad8U { memarg }
+            | Operator::I32AtomicLoad16U { memarg }
+            | Operator::I64AtomicLoad8U { memarg }
+            | Operator::I64AtomicLoad16U { memarg }
+            | Operator::I64AtomicLoad32U { memarg } => {
+                let (narrow_ty, ty) = atomic_access_types(intrinsics, &op);
+                let effective_address = resolve_atomic_ptr(
+                    builder, intrinsics, context, &function, &mut state, &mut ctx, memarg,
+                    narrow_ty,
+                )?;
+                let narrow_result = builder.build_load(effective_address, &state.var_name());
+                make_atomic(narrow_result.as_instruction_value().unwrap(), narrow_ty);
+                let result = builder.build_int_z_extend(
+                    narrow_result.into_int_value(),
+                    ty,
+                    &state.var_name(),
+                );
+                state.push1(result);
+            }
+            Operator::I32AtomicStore { memarg }
+            | Operator::I64AtomicStore { memarg }
+            | Operator::I32AtomicStore8 { memarg }
+            | Operator::I32AtomicStore16 { memarg }
+            | Operator::I64AtomicStore8 { memarg }
+            | Operator::I64AtomicStore16 { memarg }
+            | Operator::I64AtomicStore32 { memarg } => {
+                let (narrow_ty, _) = atomic_access_types(intrinsics, &op);
+                let value = state.pop1()?.into_int_value();
+                let effective_address = resolve_atomic_ptr(
+                    builder, intrinsics, context, &function, &mut state, &mut ctx, memarg,
+                    narrow_ty,
+                )?;
+                let narrow_value = builder.build_int_truncate(value, narrow_ty, &state.var_name());
+                make_atomic(
+                    builder.build_store(effective_address, narrow_value),
+                    narrow_ty,
+                );
+            }
+            Operator::I32AtomicRmwAdd { memarg }
+            | Operator::I64AtomicRmwAdd { memarg }
+            | Operator::I32AtomicRmw8UAdd { memarg }
+            | Operator::I32AtomicRmw16UAdd { memarg }
+            | Operator::I64AtomicRmw8UAdd { memarg }
+            | Operator::I64AtomicRmw16UAdd { memarg }
+            | Operator::I64AtomicRmw32UAdd { memarg }
+            | Operator::I32AtomicRmwSub { memarg }
+            | Operator::I64AtomicRmwSub { memarg }
+            | Operator::I32AtomicRmw8USub { memarg }
+            | Operator::I32AtomicRmw16USub { memarg }
+            | Operator::I64AtomicRmw8USub { memarg }
+            | Operator::I64AtomicRmw16USub { memarg }
+            | Operator::I64AtomicRmw32USub { memarg }
+            | Operator::I32AtomicRmwAnd { memarg }
+            | Operator::I64AtomicRmwAnd { memarg }
+            | Operator::I32AtomicRmw8UAnd { memarg }
+            | Operator::I32AtomicRmw16UAnd { memarg }
+            | Operator::I64AtomicRmw8UAnd { memarg }
+            | Operator::I64AtomicRmw16UAnd { memarg }
+            | Operator::I64AtomicRmw32UAnd { memarg }
+            | Operator::I32AtomicRmwOr { memarg }
+            | Operator::I64AtomicRmwOr { memarg }
+            | Operator::I32AtomicRmw8UOr { memarg }
+            | Operator::I32AtomicRmw16UOr { memarg }
+            | Operator::I64AtomicRmw8UOr { memarg }
+            | Operator::I64AtomicRmw16UOr { memarg }
+            | Operator::I64AtomicRmw32UOr { memarg }
+            | Operator::I32AtomicRmwXor { memarg }
+            | Operator::I64AtomicRmwXor { memarg }
+            | Operator::I32AtomicRmw8UXor { memarg }
+            | Operator::I32AtomicRmw16UXor { memarg }
+            | Operator::I64AtomicRmw8UXor { memarg }
+            | Operator::I64AtomicRmw16UXor { memarg }
+            | Operator::I64AtomicRmw32UXor { memarg }
+            | Operator::I32AtomicRmwXchg { memarg }
+            | Operator::I64AtomicRmwXchg { memarg }
+            | Operator::I32AtomicRmw8UXchg { memarg }
+            | Operator::I32AtomicRmw16UXchg { memarg }
+            | Operator::I64AtomicRmw8UXchg { memarg }
+            | Operator::I64AtomicRmw16UXchg { memarg }
+            | Operator::I64AtomicRmw32UXchg { memarg } => {
+                let (narrow_ty, ty) = atomic_access_types(intrinsics, &op);
+                let rmw_op = match op {
+                    Operator::I32AtomicRmwAdd { .. }
+                    | Operator::I64AtomicRmwAdd { .. }
+                    | Operator::I32AtomicRmw8UAdd { .. }
+                    | Operator::I32AtomicRmw16UAdd { .. }
+                    | Operator::I64AtomicRmw8UAdd { .. }
+                    | Operator::I64AtomicRmw16UAdd { .. }
+                    | Operator::I64AtomicRmw32UAdd { .. } => AtomicRMWBinOp::Add,
+                    Operator::I32AtomicRmwSub { .. }
+                    | Operator::I64AtomicRmwSub { .. }
+                    | Operator::I32AtomicRmw8USub { .. }
+                    | Operator::I32AtomicRmw16USub { .. }
+                    | Operator::I64AtomicRmw8USub { .. }
+                    | Operator::I64AtomicRmw16USub { .. }
+                    | Operator::I64AtomicRmw32USub { .. } => AtomicRMWBinOp::Sub,
+                    Operator::I32AtomicRmwAnd { .. }
+                    | Operator::I64AtomicRmwAnd { .. }
+                    | Operator::I32AtomicRmw8UAnd { .. }
+                    | Operator::I32AtomicRmw16UAnd { .. }
+                    | Operator::I64AtomicRmw8UAnd { .. }
+                    | Operator::I64AtomicRmw16UAnd { .. }
+                    | Operator::I64AtomicRmw32UAnd { .. } => AtomicRMWBinOp::And,
+                    Operator::I32AtomicRmwOr { .. }
+                    | Operator::I64AtomicRmwOr { .. }
+                    | Operator::I32AtomicRmw8UOr { .. }
+                    | Operator::I32AtomicRmw16UOr { .. }
+                    | Operator::I64AtomicRmw8UOr { .. }
+                    | Operator::I64AtomicRmw16UOr { .. }
+                    | Operator::I64AtomicRmw32UOr { .. } => AtomicRMWBinOp::Or,
+                    Operator::I32AtomicRmwXor { .. }
+                    | Operator::I64AtomicRmwXor { .. }
+                    | Operator::I32AtomicRmw8UXor { .. }
+                    | Operator::I32AtomicRmw16UXor { .. }
+                    | Operator::I64AtomicRmw8UXor { .. }
+                    | Operator::I64AtomicRmw16UXor { .. }
+                    | Operator::I64AtomicRmw32UXor { .. } => AtomicRMWBinOp::Xor,
+                    _ => AtomicRMWBinOp::Xchg,
+                };
+                let value = state.pop1()?.into_int_value();
+                let effective_address = resolve_atomic_ptr(
+                    builder, intrinsics, context, &function, &mut state, &mut ctx, memarg,
+                    narrow_ty,
+                )?;
+                let narrow_value = builder.build_int_truncate(value, narrow_ty, &state.var_name());
+                let old = builder
+                    .build_atomicrmw(
+                        rmw_op,
+                        effective_address,
+                        narrow_value,
+                        AtomicOrdering::SequentiallyConsistent,
+                    )
+                    .unwrap();
+                let result = builder.build_int_z_extend(old, ty, &state.var_name());
+                state.push1(result);
+            }
+            Operator::I32AtomicRmwCmpxchg { memarg }
+            | Operator::I64AtomicRmwCmpxchg { memarg }
+            | Operator::I32AtomicRmw8UCmpxchg { memarg }
+            | Operator::I32AtomicRmw16UCmpxchg { memarg }
+            | Operator::I64AtomicRmw8UCmpxchg { memarg }
+            | Operator::I64AtomicRmw16UCmpxchg { memarg }
+            | Operator::I64AtomicRmw32UCmpxchg { memarg } => {
+                let (narrow_ty, ty) = atomic_access_types(intrinsics, &op);
+                let (expected, replacement) = state.pop2()?;
+                let effective_address = resolve_atomic_ptr(
+                    builder, intrinsics, context, &function, &mut state, &mut ctx, memarg,
+                    narrow_ty,
+                )?;
+                let expected = builder.build_int_truncate(
+                    expected.into_int_value(),
+                    narrow_ty,
+                    &state.var_name(),
+                );
+                let replacement = builder.build_int_truncate(
+                    replacement.into_int_value(),
+                    narrow_ty,
+                    &state.var_name(),
+                );
+                // The result is a pair of the old value and whether it was replaced.
+                let pair = builder
+                    .build_cmpxchg(
+                        effective_address,
+                        expected,
+                        replacement,
+                        AtomicOrdering::SequentiallyConsistent,
+                        AtomicOrdering::SequentiallyConsistent,
+                    )
+                    .unwrap();
+                let old = builder
+                    .build_extract_value(pair, 0, &state.var_name())
+                    .unwrap()
+                    .into_int_value();
+                let result = builder.build_int_z_extend(old, ty, &state.var_name());
+                state.push1(result);
+            }
+            Operator::I32Wait { memarg } | Operator::I64Wait { memarg } => {
+                let func_value = match op {
+                    Operator::I32Wait { .. } => intrinsics.memory_atomic_wait32,
+                    _ => intrinsics.memory_atomic_wait64,
+                };
+                let (addr, expected, timeout) = state.pop3()?;
+                let offset = intrinsics.i32_ty.const_int(memarg.offset as u64, false);
+                let result = builder.build_call(
+                    func_value,
+                    &[
+                        ctx.basic(),
+                        intrinsics.i32_zero.as_basic_value_enum(),
+                        offset.as_basic_value_enum(),
+                        addr,
+                        expected,
+                        timeout,
+                    ],
+                    &state.var_name(),
+                );
+                state.push1(result.try_as_basic_value().left().unwrap());
+            }
+            Operator::Wake { memarg } => {
+                let (addr, count) = state.pop2()?;
+                let offset = intrinsics.i32_ty.const_int(memarg.offset as u64, false);
+                let result = builder.build_call(
+                    intrinsics.memory_atomic_notify,
+                    &[
+                        ctx.basic(),
+                        intrinsics.i32_zero.as_basic_value_enum(),
+                        offset.as_basic_value_enum(),
+                        addr,
+                        count,
+                    ],
+                    &state.var_name(),
+                );
+                state.push1(result.try_as_basic_value().left().unwrap());
+            }
+
+            /***************************
+             * Reference types operators.
+             * https://github.com/WebAssembly/reference-types/blob/master/proposals/reference-types/Overview.md
+             ***************************/
+            Operator::RefNull => {
+                // References are the raw words of `wasmer_runtime_core::table::AnyRef`, 0 is null.
+                state.push1(intrinsics.i64_zero);
+            }
+            Operator::RefIsNull => {
+                let input = state.pop1()?.into_int_value();
+                let cond = builder.build_int_compare(
+                    IntPredicate::EQ,
+                    input,
+                    intrinsics.i64_zero,
+                    &state.var_name(),
+                );
+                let res = builder.build_int_z_extend(cond, intrinsics.i32_ty, &state.var_name());
+                state.push1(res);
+            }
+            Operator::TableGet { table } => {
+                let table_index = intrinsics.i32_ty.const_int(table as u64, false);
+                let index = state.pop1()?;
+                let result = builder.build_call(
+                    intrinsics.table_get,
+                    &[ctx.basic(), table_index.as_basic_value_enum(), index],
+                    &state.var_name(),
+                );
+                state.push1(result.try_as_basic_value().left().unwrap());
+            }
+            Operator::TableSet { table } => {
+                let table_index = intrinsics.i32_ty.const_int(table as u64, false);
+                let (index, value) = state.pop2()?;
+                builder.build_call(
+                    intrinsics.table_set,
+                    &[ctx.basic(), table_index.as_basic_value_enum(), index, value],
+                    &state.var_name(),
+                );
+            }
+            Operator::TableGrow { table } => {
+                let table_index = intrinsics.i32_ty.const_int(table as u64, false);
+                let delta = state.pop1()?;
+                let result = builder.build_call(
+                    intrinsics.table_grow,
+                    &[ctx.basic(), table_index.as_basic_value_enum(), delta],
+                    &state.var_name(),
+                );
+                state.push1(result.try_as_basic_value().left().unwrap());
+            }
+            Operator::TableSize { table } => {
+                let table_index = intrinsics.i32_ty.const_int(table as u64, false);
+                let result = builder.build_call(
+                    intrinsics.table_size,
+                    &[ctx.basic(), table_index.as_basic_value_enum()],
+                    &state.var_name(),
+                );
+                state.push1(result.try_as_basic_value().left().unwrap());
+            }
+            /***************************
+             * SIMD operators.
+             * https://github.com/WebAssembly/simd/blob/master/proposals/simd/SIMD.md
+             *
+             * A v128 is an i128 on the value stack, and is bitcast to a vector
+             * of the lanes an operator works on.
+             ***************************/
+            Operator::V128Load { memarg } => {
+                let effective_address = resolve_memory_ptr(
+                    builder,
+                    intrinsics,
+                    context,
+                    &function,
+                    &mut state,
+                    &mut ctx,
+                    memarg,
+                    intrinsics.i128_ptr_ty,
+                )?;
+                let result = builder.build_load(effective_address, &state.var_name());
+                state.push1(result);
+            }
+            Operator::V128Store { memarg } => {
+                let value = state.pop1()?;
+                let effective_address = resolve_memory_ptr(
+                    builder,
+                    intrinsics,
+                    context,
+                    &function,
+                    &mut state,
+                    &mut ctx,
+                    memarg,
+                    intrinsics.i128_ptr_ty,
+                )?;
+                builder.build_store(effective_address, value);
+            }
+            Operator::V128Const { value } => {
+                let bytes = value.bytes();
+                let mut low = [0u8; 8];
+                let mut high = [0u8; 8];
+                low.copy_from_slice(&bytes[..8]);
+                high.copy_from_slice(&bytes[8..]);
+                let i = intrinsics.i128_ty.const_int_arbitrary_precision(&[
+                    u64::from_le_bytes(low),
+                    u64::from_le_bytes(high),
+                ]);
+                state.push1(i);
+            }
+            Operator::V8x16Shuffle { lanes } => {
+                let (v1, v2) = state.pop2()?;
+                let v1 = v128_into_vector(builder, v1, intrinsics.i8x16_ty);
+                let v2 = v128_into_vector(builder, v2, intrinsics.i8x16_ty);
+                let mask: Vec<_> = lanes
+                    .iter()
+                    .map(|&lane| intrinsics.i32_ty.const_int(lane as u64, false))
+                    .collect();
+                let res = builder.build_shuffle_vector(
+                    v1,
+                    v2,
+                    VectorType::const_vector(&mask),
+                    &state.var_name(),
+                );
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::I8x16Splat | Operator::I16x8Splat => {
+                let (vec_ty, lane_ty) = match op {
+                    Operator::I8x16Splat => (intrinsics.i8x16_ty, intrinsics.i8_ty),
+                    _ => (intrinsics.i16x8_ty, intrinsics.i16_ty),
+                };
+                let value = state.pop1()?.into_int_value();
+                let value = builder.build_int_truncate(value, lane_ty, &state.var_name());
+                let res = splat_vector(builder, intrinsics, value.as_basic_value_enum(), vec_ty);
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::I32x4Splat
+            | Operator::I64x2Splat
+            | Operator::F32x4Splat
+            | Operator::F64x2Splat => {
+                let vec_ty = match op {
+                    Operator::I32x4Splat => intrinsics.i32x4_ty,
+                    Operator::I64x2Splat => intrinsics.i64x2_ty,
+                    Operator::F32x4Splat => intrinsics.f32x4_ty,
+                    _ => intrinsics.f64x2_ty,
+                };
+                let value = state.pop1()?;
+                let res = splat_vector(builder, intrinsics, value, vec_ty);
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::I8x16ExtractLaneS { lane }
+            | Operator::I8x16ExtractLaneU { lane }
+            | Operator::I16x8ExtractLaneS { lane }
+            | Operator::I16x8ExtractLaneU { lane } => {
+                let (vec_ty, signed) = match op {
+                    Operator::I8x16ExtractLaneS { .. } => (intrinsics.i8x16_ty, true),
+                    Operator::I8x16ExtractLaneU { .. } => (intrinsics.i8x16_ty, false),
+                    Operator::I16x8ExtractLaneS { .. } => (intrinsics.i16x8_ty, true),
+                    _ => (intrinsics.i16x8_ty, false),
+                };
+                let v = v128_into_vector(builder, state.pop1()?, vec_ty);
+                let index = intrinsics.i32_ty.const_int(lane as u64, false);
+                let value = builder
+                    .build_extract_element(v, index, &state.var_name())
+                    .into_int_value();
+                let res = if signed {
+                    builder.build_int_s_extend(value, intrinsics.i32_ty, &state.var_name())
+                } else {
+                    builder.build_int_z_extend(value, intrinsics.i32_ty, &state.var_name())
+                };
+                state.push1(res);
+            }
+            Operator::I32x4ExtractLane { lane }
+            | Operator::I64x2ExtractLane { lane }
+            | Operator::F32x4ExtractLane { lane }
+            | Operator::F64x2ExtractLane { lane } => {
+                let vec_ty = match op {
+                    Operator::I32x4ExtractLane { .. } => intrinsics.i32x4_ty,
+                    Operator::I64x2ExtractLane { .. } => intrinsics.i64x2_ty,
+                    Operator::F32x4ExtractLane { .. } => intrinsics.f32x4_ty,
+                    _ => intrinsics.f64x2_ty,
+                };
+                let v = v128_into_vector(builder, state.pop1()?, vec_ty);
+                let index = intrinsics.i32_ty.const_int(lane as u64, false);
+                let res = builder.build_extract_element(v, index, &state.var_name());
+                state.push1(res);
+            }
+            Operator::I8x16ReplaceLane { lane } | Operator::I16x8ReplaceLane { lane } => {
+                let (vec_ty, lane_ty) = match op {
+                    Operator::I8x16ReplaceLane { .. } => (intrinsics.i8x16_ty, intrinsics.i8_ty),
+                    _ => (intrinsics.i16x8_ty, intrinsics.i16_ty),
+                };
+                let (v, value) = state.pop2()?;
+                let v = v128_into_vector(builder, v, vec_ty);
+                let value =
+                    builder.build_int_truncate(value.into_int_value(), lane_ty, &state.var_name());
+                let index = intrinsics.i32_ty.const_int(lane as u64, false);
+                let res = builder.build_insert_element(v, value, index, &state.var_name());
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::I32x4ReplaceLane { lane }
+            | Operator::I64x2ReplaceLane { lane }
+            | Operator::F32x4ReplaceLane { lane }
+            | Operator::F64x2ReplaceLane { lane } => {
+                let vec_ty = match op {
+                    Operator::I32x4ReplaceLane { .. } => intrinsics.i32x4_ty,
+                    Operator::I64x2ReplaceLane { .. } => intrinsics.i64x2_ty,
+                    Operator::F32x4ReplaceLane { .. } => intrinsics.f32x4_ty,
+                    _ => intrinsics.f64x2_ty,
+                };
+                let (v, value) = state.pop2()?;
+                let v = v128_into_vector(builder, v, vec_ty);
+                let index = intrinsics.i32_ty.const_int(lane as u64, false);
+                let res = builder.build_insert_element(v, value, index, &state.var_name());
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::I8x16Eq
+            | Operator::I8x16Ne
+            | Operator::I8x16LtS
+            | Operator::I8x16LtU
+            | Operator::I8x16GtS
+            | Operator::I8x16GtU
+            | Operator::I8x16LeS
+            | Operator::I8x16LeU
+            | Operator::I8x16GeS
+            | Operator::I8x16GeU
+            | Operator::I16x8Eq
+            | Operator::I16x8Ne
+            | Operator::I16x8LtS
+            | Operator::I16x8LtU
+            | Operator::I16x8GtS
+            | Operator::I16x8GtU
+            | Operator::I16x8LeS
+            | Operator::I16x8LeU
+            | Operator::I16x8GeS
+            | Operator::I16x8GeU
+            | Operator::I32x4Eq
+            | Operator::I32x4Ne
+            | Operator::I32x4LtS
+            | Operator::I32x4LtU
+            | Operator::I32x4GtS
+            | Operator::I32x4GtU
+            | Operator::I32x4LeS
+            | Operator::I32x4LeU
+            | Operator::I32x4GeS
+            | Operator::I32x4GeU => {
+                let (i8x16, i16x8, i32x4) = (
+                    intrinsics.i8x16_ty,
+                    intrinsics.i16x8_ty,
+                    intrinsics.i32x4_ty,
+                );
+                let (vec_ty, predicate) = match op {
+                    Operator::I8x16Eq => (i8x16, IntPredicate::EQ),
+                    Operator::I8x16Ne => (i8x16, IntPredicate::NE),
+                    Operator::I8x16LtS => (i8x16, IntPredicate::SLT),
+                    Operator::I8x16LtU => (i8x16, IntPredicate::ULT),
+                    Operator::I8x16GtS => (i8x16, IntPredicate::SGT),
+                    Operator::I8x16GtU => (i8x16, IntPredicate::UGT),
+                    Operator::I8x16LeS => (i8x16, IntPredicate::SLE),
+                    Operator::I8x16LeU => (i8x16, IntPredicate::ULE),
+                    Operator::I8x16GeS => (i8x16, IntPredicate::SGE),
+                    Operator::I8x16GeU => (i8x16, IntPredicate::UGE),
+                    Operator::I16x8Eq => (i16x8, IntPredicate::EQ),
+                    Operator::I16x8Ne => (i16x8, IntPredicate::NE),
+                    Operator::I16x8LtS => (i16x8, IntPredicate::SLT),
+                    Operator::I16x8LtU => (i16x8, IntPredicate::ULT),
+                    Operator::I16x8GtS => (i16x8, IntPredicate::SGT),
+                    Operator::I16x8GtU => (i16x8, IntPredicate::UGT),
+                    Operator::I16x8LeS => (i16x8, IntPredicate::SLE),
+                    Operator::I16x8LeU => (i16x8, IntPredicate::ULE),
+                    Operator::I16x8GeS => (i16x8, IntPredicate::SGE),
+                    Operator::I16x8GeU => (i16x8, IntPredicate::UGE),
+                    Operator::I32x4Eq => (i32x4, IntPredicate::EQ),
+                    Operator::I32x4Ne => (i32x4, IntPredicate::NE),
+                    Operator::I32x4LtS => (i32x4, IntPredicate::SLT),
+                    Operator::I32x4LtU => (i32x4, IntPredicate::ULT),
+                    Operator::I32x4GtS => (i32x4, IntPredicate::SGT),
+                    Operator::I32x4GtU => (i32x4, IntPredicate::UGT),
+                    Operator::I32x4LeS => (i32x4, IntPredicate::SLE),
+                    Operator::I32x4LeU => (i32x4, IntPredicate::ULE),
+                    Operator::I32x4GeS => (i32x4, IntPredicate::SGE),
+                    _ => (i32x4, IntPredicate::UGE),
+                };
+                let (v1, v2) = state.pop2()?;
+                let v1 = v128_into_vector(builder, v1, vec_ty);
+                let v2 = v128_into_vector(builder, v2, vec_ty);
+                let cond = builder.build_int_compare(predicate, v1, v2, &state.var_name());
+                // The lanes where the comparison holds are set to all ones.
+                let res = builder.build_int_s_extend(cond, vec_ty, &state.var_name());
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::F32x4Eq
+            | Operator::F32x4Ne
+            | Operator::F32x4Lt
+            | Operator::F32x4Gt
+            | Operator::F32x4Le
+            | Operator::F32x4Ge
+            | Operator::F64x2Eq
+            | Operator::F64x2Ne
+            | Operator::F64x2Lt
+            | Operator::F64x2Gt
+            | Operator::F64x2Le
+            | Operator::F64x2Ge => {
+                let f32x4 = (intrinsics.f32x4_ty, intrinsics.i32x4_ty);
+                let f64x2 = (intrinsics.f64x2_ty, intrinsics.i64x2_ty);
+                let ((vec_ty, int_vec_ty), predicate) = match op {
+                    Operator::F32x4Eq => (f32x4, FloatPredicate::OEQ),
+                    Operator::F32x4Ne => (f32x4, FloatPredicate::UNE),
+                    Operator::F32x4Lt => (f32x4, FloatPredicate::OLT),
+                    Operator::F32x4Gt => (f32x4, FloatPredicate::OGT),
+                    Operator::F32x4Le => (f32x4, FloatPredicate::OLE),
+                    Operator::F32x4Ge => (f32x4, FloatPredicate::OGE),
+                    Operator::F64x2Eq => (f64x2, FloatPredicate::OEQ),
+                    Operator::F64x2Ne => (f64x2, FloatPredicate::UNE),
+                    Operator::F64x2Lt => (f64x2, FloatPredicate::OLT),
+                    Operator::F64x2Gt => (f64x2, FloatPredicate::OGT),
+                    Operator::F64x2Le => (f64x2, FloatPredicate::OLE),
+                    _ => (f64x2, FloatPredicate::OGE),
+                };
+                let (v1, v2) = state.pop2()?;
+                let v1 = v128_into_vector(builder, v1, vec_ty);
+                let v2 = v128_into_vector(builder, v2, vec_ty);
+                let cond = builder.build_float_compare(predicate, v1, v2, &state.var_name());
+                let res = builder.build_int_s_extend(cond, int_vec_ty, &state.var_name());
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::V128Not => {
+                let v = state.pop1()?.into_int_value();
+                let res = builder.build_not(v, &state.var_name());
+                state.push1(res);
+            }
+            Operator::V128And => {
+                let (v1, v2) = state.pop2()?;
+                let (v1, v2) = (v1.into_int_value(), v2.into_int_value());
+                let res = builder.build_and(v1, v2, &state.var_name());
+                state.push1(res);
+            }
+            Operator::V128Or => {
+                let (v1, v2) = state.pop2()?;
+                let (v1, v2) = (v1.into_int_value(), v2.into_int_value());
+                let res = builder.build_or(v1, v2, &state.var_name());
+                state.push1(res);
+            }
+            Operator::V128Xor => {
+                let (v1, v2) = state.pop2()?;
+                let (v1, v2) = (v1.into_int_value(), v2.into_int_value());
+                let res = builder.build_xor(v1, v2, &state.var_name());
+                state.push1(res);
+            }
+            Operator::V128Bitselect => {
+                let (v1, v2, mask) = state.pop3()?;
+                let (v1, v2, mask) = (
+                    v1.into_int_value(),
+                    v2.into_int_value(),
+                    mask.into_int_value(),
+                );
+                let v1 = builder.build_and(v1, mask, &state.var_name());
+                let not_mask = builder.build_not(mask, &state.var_name());
+                let v2 = builder.build_and(v2, not_mask, &state.var_name());
+                let res = builder.build_or(v1, v2, &state.var_name());
+                state.push1(res);
+            }
+            Operator::I8x16Neg | Operator::I16x8Neg | Operator::I32x4Neg | Operator::I64x2Neg => {
+                let vec_ty = match op {
+                    Operator::I8x16Neg => intrinsics.i8x16_ty,
+                    Operator::I16x8Neg => intrinsics.i16x8_ty,
+                    Operator::I32x4Neg => intrinsics.i32x4_ty,
+                    _ => intrinsics.i64x2_ty,
+                };
+                let v = v128_into_vector(builder, state.pop1()?, vec_ty);
+                let res = builder.build_int_neg(v, &state.var_name());
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::I8x16AnyTrue
+            | Operator::I16x8AnyTrue
+            | Operator::I32x4AnyTrue
+            | Operator::I64x2AnyTrue => {
+                // Some lane is non-zero exactly when some bit is set.
+                let v = state.pop1()?.into_int_value();
+                let cond = builder.build_int_compare(
+                    IntPredicate::NE,
+                    v,
+                    intrinsics.i128_zero,
+                    &state.var_name(),
+                );
+                let res = builder.build_int_z_extend(cond, intrinsics.i32_ty, &state.var_name());
+                state.push1(res);
+            }
+            Operator::I8x16AllTrue
+            | Operator::I16x8AllTrue
+            | Operator::I32x4AllTrue
+            | Operator::I64x2AllTrue => {
+                let vec_ty = match op {
+                    Operator::I8x16AllTrue => intrinsics.i8x16_ty,
+                    Operator::I16x8AllTrue => intrinsics.i16x8_ty,
+                    Operator::I32x4AllTrue => intrinsics.i32x4_ty,
+                    _ => intrinsics.i64x2_ty,
+                };
+                let v = v128_into_vector(builder, state.pop1()?, vec_ty);
+                let lane_is_true = builder.build_int_compare(
+                    IntPredicate::NE,
+                    v,
+                    const_splat(
+                        vec_ty
+                            .get_element_type()
+                            .into_int_type()
+                            .const_int(0, false),
+                        vec_ty.get_size(),
+                    ),
+                    &state.var_name(),
+                );
+                // Every lane is non-zero exactly when every lane of the mask is all ones.
+                let mask = builder.build_int_s_extend(lane_is_true, vec_ty, &state.var_name());
+                let mask = builder
+                    .build_bitcast(mask, intrinsics.i128_ty, &state.var_name())
+                    .into_int_value();
+                let cond = builder.build_int_compare(
+                    IntPredicate::EQ,
+                    mask,
+                    intrinsics.i128_ty.const_int(std::u64::MAX, true),
+                    &state.var_name(),
+                );
+                let res = builder.build_int_z_extend(cond, intrinsics.i32_ty, &state.var_name());
+                state.push1(res);
+            }
+            Operator::I8x16Shl
+            | Operator::I8x16ShrS
+            | Operator::I8x16ShrU
+            | Operator::I16x8Shl
+            | Operator::I16x8ShrS
+            | Operator::I16x8ShrU
+            | Operator::I32x4Shl
+            | Operator::I32x4ShrS
+            | Operator::I32x4ShrU
+            | Operator::I64x2Shl
+            | Operator::I64x2ShrS
+            | Operator::I64x2ShrU => {
+                let vec_ty = match op {
+                    Operator::I8x16Shl | Operator::I8x16ShrS | Operator::I8x16ShrU => {
+                        intrinsics.i8x16_ty
+                    }
+                    Operator::I16x8Shl | Operator::I16x8ShrS | Operator::I16x8ShrU => {
+                        intrinsics.i16x8_ty
+                    }
+                    Operator::I32x4Shl | Operator::I32x4ShrS | Operator::I32x4ShrU => {
+                        intrinsics.i32x4_ty
+                    }
+                    _ => intrinsics.i64x2_ty,
+                };
+                let (v, amount) = state.pop2()?;
+                let v = v128_into_vector(builder, v, vec_ty);
+                let amount =
+                    shift_amount_vector(builder, intrinsics, amount.into_int_value(), vec_ty);
+                let res = match op {
+                    Operator::I8x16Shl
+                    | Operator::I16x8Shl
+                    | Operator::I32x4Shl
+                    | Operator::I64x2Shl => builder.build_left_shift(v, amount, &state.var_name()),
+                    Operator::I8x16ShrS
+                    | Operator::I16x8ShrS
+                    | Operator::I32x4ShrS
+                    | Operator::I64x2ShrS => {
+                        builder.build_right_shift(v, amount, true, &state.var_name())
+                    }
+                    _ => builder.build_right_shift(v, amount, false, &state.var_name()),
+                };
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::I8x16Add | Operator::I16x8Add | Operator::I32x4Add | Operator::I64x2Add => {
+                let vec_ty = match op {
+                    Operator::I8x16Add => intrinsics.i8x16_ty,
+                    Operator::I16x8Add => intrinsics.i16x8_ty,
+                    Operator::I32x4Add => intrinsics.i32x4_ty,
+                    _ => intrinsics.i64x2_ty,
+                };
+                let (v1, v2) = state.pop2()?;
+                let v1 = v128_into_vector(builder, v1, vec_ty);
+                let v2 = v128_into_vector(builder, v2, vec_ty);
+                let res = builder.build_int_add(v1, v2, &state.var_name());
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::I8x16Sub | Operator::I16x8Sub | Operator::I32x4Sub | Operator::I64x2Sub => {
+                let vec_ty = match op {
+                    Operator::I8x16Sub => intrinsics.i8x16_ty,
+                    Operator::I16x8Sub => intrinsics.i16x8_ty,
+                    Operator::I32x4Sub => intrinsics.i32x4_ty,
+                    _ => intrinsics.i64x2_ty,
+                };
+                let (v1, v2) = state.pop2()?;
+                let v1 = v128_into_vector(builder, v1, vec_ty);
+                let v2 = v128_into_vector(builder, v2, vec_ty);
+                let res = builder.build_int_sub(v1, v2, &state.var_name());
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::I8x16Mul | Operator::I16x8Mul | Operator::I32x4Mul => {
+                let vec_ty = match op {
+                    Operator::I8x16Mul => intrinsics.i8x16_ty,
+                    Operator::I16x8Mul => intrinsics.i16x8_ty,
+                    _ => intrinsics.i32x4_ty,
+                };
+                let (v1, v2) = state.pop2()?;
+                let v1 = v128_into_vector(builder, v1, vec_ty);
+                let v2 = v128_into_vector(builder, v2, vec_ty);
+                let res = builder.build_int_mul(v1, v2, &state.var_name());
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::I8x16AddSaturateS
+            | Operator::I8x16AddSaturateU
+            | Operator::I8x16SubSaturateS
+            | Operator::I8x16SubSaturateU
+            | Operator::I16x8AddSaturateS
+            | Operator::I16x8AddSaturateU
+            | Operator::I16x8SubSaturateS
+            | Operator::I16x8SubSaturateU => {
+                let (vec_ty, signed, add) = match op {
+                    Operator::I8x16AddSaturateS => (intrinsics.i8x16_ty, true, true),
+                    Operator::I8x16AddSaturateU => (intrinsics.i8x16_ty, false, true),
+                    Operator::I8x16SubSaturateS => (intrinsics.i8x16_ty, true, false),
+                    Operator::I8x16SubSaturateU => (intrinsics.i8x16_ty, false, false),
+                    Operator::I16x8AddSaturateS => (intrinsics.i16x8_ty, true, true),
+                    Operator::I16x8AddSaturateU => (intrinsics.i16x8_ty, false, true),
+                    Operator::I16x8SubSaturateS => (intrinsics.i16x8_ty, true, false),
+                    _ => (intrinsics.i16x8_ty, false, false),
+                };
+                let (v1, v2) = state.pop2()?;
+                let v1 = v128_into_vector(builder, v1, vec_ty);
+                let v2 = v128_into_vector(builder, v2, vec_ty);
+                let res = saturating_arithmetic(builder, intrinsics, v1, v2, signed, add);
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::F32x4Abs | Operator::F64x2Abs => {
+                let (vec_ty, fabs) = match op {
+                    Operator::F32x4Abs => (intrinsics.f32x4_ty, intrinsics.fabs_f32x4),
+                    _ => (intrinsics.f64x2_ty, intrinsics.fabs_f64x2),
+                };
+                let v = v128_into_vector(builder, state.pop1()?, vec_ty);
+                let res = builder
+                    .build_call(fabs, &[v.as_basic_value_enum()], &state.var_name())
+                    .try_as_basic_value()
+                    .left()
+                    .unwrap();
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::F32x4Neg | Operator::F64x2Neg => {
+                let vec_ty = match op {
+                    Operator::F32x4Neg => intrinsics.f32x4_ty,
+                    _ => intrinsics.f64x2_ty,
+                };
+                let v = v128_into_vector(builder, state.pop1()?, vec_ty);
+                let res = builder.build_float_neg(v, &state.var_name());
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::F32x4Sqrt | Operator::F64x2Sqrt => {
+                let (vec_ty, sqrt) = match op {
+                    Operator::F32x4Sqrt => (intrinsics.f32x4_ty, intrinsics.sqrt_f32x4),
+                    _ => (intrinsics.f64x2_ty, intrinsics.sqrt_f64x2),
+                };
+                let v = v128_into_vector(builder, state.pop1()?, vec_ty);
+                let res = builder
+                    .build_call(sqrt, &[v.as_basic_value_enum()], &state.var_name())
+                    .try_as_basic_value()
+                    .left()
+                    .unwrap();
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::F32x4Add
+            | Operator::F32x4Sub
+            | Operator::F32x4Mul
+            | Operator::F32x4Div
+            | Operator::F64x2Add
+            | Operator::F64x2Sub
+            | Operator::F64x2Mul
+            | Operator::F64x2Div => {
+                let vec_ty = match op {
+                    Operator::F32x4Add
+                    | Operator::F32x4Sub
+                    | Operator::F32x4Mul
+                    | Operator::F32x4Div => intrinsics.f32x4_ty,
+                    _ => intrinsics.f64x2_ty,
+                };
+                let (v1, v2) = state.pop2()?;
+                let v1 = v128_into_vector(builder, v1, vec_ty);
+                let v2 = v128_into_vector(builder, v2, vec_ty);
+                let res = match op {
+                    Operator::F32x4Add | Operator::F64x2Add => {
+                        builder.build_float_add(v1, v2, &state.var_name())
+                    }
+                    Operator::F32x4Sub | Operator::F64x2Sub => {
+                        builder.build_float_sub(v1, v2, &state.var_name())
+                    }
+                    Operator::F32x4Mul | Operator::F64x2Mul => {
+                        builder.build_float_mul(v1, v2, &state.var_name())
+                    }
+                    _ => builder.build_float_div(v1, v2, &state.var_name()),
+                };
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::F32x4Min | Operator::F32x4Max | Operator::F64x2Min | Operator::F64x2Max => {
+                let (vec_ty, int_vec_ty) = match op {
+                    Operator::F32x4Min | Operator::F32x4Max => {
+                        (intrinsics.f32x4_ty, intrinsics.i32x4_ty)
+                    }
+                    _ => (intrinsics.f64x2_ty, intrinsics.i64x2_ty),
+                };
+                let min = match op {
+                    Operator::F32x4Min | Operator::F64x2Min => true,
+                    _ => false,
+                };
+                let (v1, v2) = state.pop2()?;
+                let v1 = v128_into_vector(builder, v1, vec_ty);
+                let v2 = v128_into_vector(builder, v2, vec_ty);
+                let res = min_max_vector(builder, v1, v2, int_vec_ty, min);
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::I32x4TruncSF32x4Sat
+            | Operator::I32x4TruncUF32x4Sat
+            | Operator::I64x2TruncSF64x2Sat
+            | Operator::I64x2TruncUF64x2Sat => {
+                let (vec_ty, int_vec_ty, signed) = match op {
+                    Operator::I32x4TruncSF32x4Sat => {
+                        (intrinsics.f32x4_ty, intrinsics.i32x4_ty, true)
+                    }
+                    Operator::I32x4TruncUF32x4Sat => {
+                        (intrinsics.f32x4_ty, intrinsics.i32x4_ty, false)
+                    }
+                    Operator::I64x2TruncSF64x2Sat => {
+                        (intrinsics.f64x2_ty, intrinsics.i64x2_ty, true)
+                    }
+                    _ => (intrinsics.f64x2_ty, intrinsics.i64x2_ty, false),
+                };
+                let v = v128_into_vector(builder, state.pop1()?, vec_ty);
+                let res = trunc_sat_vector(builder, v, int_vec_ty, signed);
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
+            Operator::F32x4ConvertSI32x4
+            | Operator::F32x4ConvertUI32x4
+            | Operator::F64x2ConvertSI64x2
+            | Operator::F64x2ConvertUI64x2 => {
+                let (int_vec_ty, vec_ty, signed) = match op {
+                    Operator::F32x4ConvertSI32x4 => {
+                        (intrinsics.i32x4_ty, intrinsics.f32x4_ty, true)
+                    }
+                    Operator::F32x4ConvertUI32x4 => {
+                        (intrinsics.i32x4_ty, intrinsics.f32x4_ty, false)
+                    }
+                    Operator::F64x2ConvertSI64x2 => {
+                        (intrinsics.i64x2_ty, intrinsics.f64x2_ty, true)
+                    }
+                    _ => (intrinsics.i64x2_ty, intrinsics.f64x2_ty, false),
+                };
+                let v = v128_into_vector(builder, state.pop1()?, int_vec_ty);
+                let res = if signed {
+                    builder.build_signed_int_to_float(v, vec_ty, &state.var_name())
+                } else {
+                    builder.build_unsigned_int_to_float(v, vec_ty, &state.var_name())
+                };
+                state.push1(builder.build_bitcast(res, intrinsics.i128_ty, &state.var_name()));
+            }
             op @ _ => {
                 unimplemented!("{:?}", op);
             }
@@ -2155,9 +3192,7 @@ fn parse_function(
             builder.build_return(Some(one_value));
         }
         _ => {
-            // let struct_ty = llvm_sig.get_return_type().as_struct_type();
-            // let ret_struct = struct_ty.const_zero();
-            unimplemented!("multi-value returns not yet implemented")
+            builder.build_aggregate_return(&results);
         }
     }
 
@@ -2460,3 +3495,422 @@ fn resolve_memory_ptr(
         builder.build_int_add(mem_base_int, effective_offset, &state.var_name());
     Ok(builder.build_int_to_ptr(effective_address_int, ptr_ty, &state.var_name()))
 }
+
+/// Resolves the address of an atomic access of an integer of type `ty`, which traps
+/// unless it is naturally aligned.
+fn resolve_atomic_ptr(
+    builder: &Builder,
+    intrinsics: &Intrinsics,
+    context: &Context,
+    function: &FunctionValue,
+    state: &mut State,
+    ctx: &mut CtxType,
+    memarg: MemoryImmediate,
+    ty: IntType,
+) -> Result<PointerValue, BinaryReaderError> {
+    let ptr = resolve_memory_ptr(
+        builder,
+        intrinsics,
+        context,
+        function,
+        state,
+        ctx,
+        memarg,
+        ty.ptr_type(AddressSpace::Generic),
+    )?;
+
+    // Linear memories are page aligned, so the host address is aligned
+    // exactly when the wasm address is.
+    let size = u64::from(ty.get_bit_width() / 8);
+    let ptr_as_int = builder.build_ptr_to_int(ptr, intrinsics.i64_ty, "ptr_as_int");
+    let misalignment = builder.build_and(
+        ptr_as_int,
+        intrinsics.i64_ty.const_int(size - 1, false),
+        "misalignment",
+    );
+    let aligned = builder.build_int_compare(
+        IntPredicate::EQ,
+        misalignment,
+        intrinsics.i64_zero,
+        "aligned",
+    );
+    let aligned = builder
+        .build_call(
+            intrinsics.expect_i1,
+            &[
+                aligned.as_basic_value_enum(),
+                intrinsics.i1_ty.const_int(1, false).as_basic_value_enum(),
+            ],
+            "aligned_expect",
+        )
+        .try_as_basic_value()
+        .left()
+        .unwrap()
+        .into_int_value();
+
+    let aligned_continue_block = context.append_basic_block(function, "aligned_continue_block");
+    let not_aligned_block = context.append_basic_block(function, "not_aligned_block");
+    builder.build_conditional_branch(aligned, &aligned_continue_block, &not_aligned_block);
+    builder.position_at_end(&not_aligned_block);
+    builder.build_call(
+        intrinsics.throw_trap,
+        &[intrinsics.trap_misaligned_atomic],
+        "throw",
+    );
+    builder.build_unreachable();
+    builder.position_at_end(&aligned_continue_block);
+
+    Ok(ptr)
+}
+
+/// The type an atomic operator accesses memory with, and the type of its result.
+fn atomic_access_types(intrinsics: &Intrinsics, op: &Operator) -> (IntType, IntType) {
+    match *op {
+        Operator::I32AtomicLoad { .. }
+        | Operator::I32AtomicStore { .. }
+        | Operator::I32AtomicRmwAdd { .. }
+        | Operator::I32AtomicRmwSub { .. }
+        | Operator::I32AtomicRmwAnd { .. }
+        | Operator::I32AtomicRmwOr { .. }
+        | Operator::I32AtomicRmwXor { .. }
+        | Operator::I32AtomicRmwXchg { .. }
+        | Operator::I32AtomicRmwCmpxchg { .. } => (intrinsics.i32_ty, intrinsics.i32_ty),
+        Operator::I32AtomicLoad8U { .. }
+        | Operator::I32AtomicStore8 { .. }
+        | Operator::I32AtomicRmw8UAdd { .. }
+        | Operator::I32AtomicRmw8USub { .. }
+        | Operator::I32AtomicRmw8UAnd { .. }
+        | Operator::I32AtomicRmw8UOr { .. }
+        | Operator::I32AtomicRmw8UXor { .. }
+        | Operator::I32AtomicRmw8UXchg { .. }
+        | Operator::I32AtomicRmw8UCmpxchg { .. } => (intrinsics.i8_ty, intrinsics.i32_ty),
+        Operator::I32AtomicLoad16U { .. }
+        | Operator::I32AtomicStore16 { .. }
+        | Operator::I32AtomicRmw16UAdd { .. }
+        | Operator::I32AtomicRmw16USub { .. }
+        | Operator::I32AtomicRmw16UAnd { .. }
+        | Operator::I32AtomicRmw16UOr { .. }
+        | Operator::I32AtomicRmw16UXor { .. }
+        | Operator::I32AtomicRmw16UXchg { .. }
+        | Operator::I32AtomicRmw16UCmpxchg { .. } => (intrinsics.i16_ty, intrinsics.i32_ty),
+        Operator::I64AtomicLoad8U { .. }
+        | Operator::I64AtomicStore8 { .. }
+        | Operator::I64AtomicRmw8UAdd { .. }
+        | Operator::I64AtomicRmw8USub { .. }
+        | Operator::I64AtomicRmw8UAnd { .. }
+        | Operator::I64AtomicRmw8UOr { .. }
+        | Operator::I64AtomicRmw8UXor { .. }
+        | Operator::I64AtomicRmw8UXchg { .. }
+        | Operator::I64AtomicRmw8UCmpxchg { .. } => (intrinsics.i8_ty, intrinsics.i64_ty),
+        Operator::I64AtomicLoad16U { .. }
+        | Operator::I64AtomicStore16 { .. }
+        | Operator::I64AtomicRmw16UAdd { .. }
+        | Operator::I64AtomicRmw16USub { .. }
+        | Operator::I64AtomicRmw16UAnd { .. }
+        | Operator::I64AtomicRmw16UOr { .. }
+        | Operator::I64AtomicRmw16UXor { .. }
+        | Operator::I64AtomicRmw16UXchg { .. }
+        | Operator::I64AtomicRmw16UCmpxchg { .. } => (intrinsics.i16_ty, intrinsics.i64_ty),
+        Operator::I64AtomicLoad32U { .. }
+        | Operator::I64AtomicStore32 { .. }
+        | Operator::I64AtomicRmw32UAdd { .. }
+        | Operator::I64AtomicRmw32USub { .. }
+        | Operator::I64AtomicRmw32UAnd { .. }
+        | Operator::I64AtomicRmw32UOr { .. }
+        | Operator::I64AtomicRmw32UXor { .. }
+        | Operator::I64AtomicRmw32UXchg { .. }
+        | Operator::I64AtomicRmw32UCmpxchg { .. } => (intrinsics.i32_ty, intrinsics.i64_ty),
+        _ => (intrinsics.i64_ty, intrinsics.i64_ty),
+    }
+}
+
+/// Makes a load or store sequentially consistent. Atomic accesses must state their alignment.
+fn make_atomic(instruction: InstructionValue, ty: IntType) {
+    instruction.set_alignment(ty.get_bit_width() / 8).unwrap();
+    instruction
+        .set_atomic_ordering(AtomicOrdering::SequentiallyConsistent)
+        .unwrap();
+}
+
+/// Reinterprets a `v128`, which is an `i128` on the value stack, as a vector of lanes.
+fn v128_into_vector(builder: &Builder, value: BasicValueEnum, vec_ty: VectorType) -> VectorValue {
+    builder.build_bitcast(value, vec_ty, "").into_vector_value()
+}
+
+/// A constant vector with `value` in each of its `lanes`.
+fn const_splat<T: BasicValue + Copy>(value: T, lanes: u32) -> VectorValue {
+    VectorType::const_vector(&vec![value; lanes as usize])
+}
+
+/// Copies `value` into every lane of a vector of type `vec_ty`.
+fn splat_vector(
+    builder: &Builder,
+    intrinsics: &Intrinsics,
+    value: BasicValueEnum,
+    vec_ty: VectorType,
+) -> VectorValue {
+    let undef = vec_ty.get_undef();
+    let vec = builder.build_insert_element(undef, value, intrinsics.i32_zero, "");
+    builder.build_shuffle_vector(
+        vec,
+        undef,
+        const_splat(intrinsics.i32_zero, vec_ty.get_size()),
+        "splat",
+    )
+}
+
+/// Takes a shift amount modulo the width of the lanes of `vec_ty`, and copies it
+/// into every lane.
+fn shift_amount_vector(
+    builder: &Builder,
+    intrinsics: &Intrinsics,
+    amount: IntValue,
+    vec_ty: VectorType,
+) -> VectorValue {
+    let lane_ty = vec_ty.get_element_type().into_int_type();
+    let lane_bits = 128 / vec_ty.get_size();
+    let amount = builder.build_and(
+        amount,
+        intrinsics.i32_ty.const_int(lane_bits as u64 - 1, false),
+        "shift_amount",
+    );
+    let amount = if lane_bits < 32 {
+        builder.build_int_truncate(amount, lane_ty, "shift_amount")
+    } else if lane_bits > 32 {
+        builder.build_int_z_extend(amount, lane_ty, "shift_amount")
+    } else {
+        amount
+    };
+    splat_vector(builder, intrinsics, amount.as_basic_value_enum(), vec_ty)
+}
+
+/// Adds or subtracts the lanes of `v1` and `v2` with saturation. The results are
+/// computed with lanes twice as wide, then clamped to the range of the lanes.
+fn saturating_arithmetic(
+    builder: &Builder,
+    intrinsics: &Intrinsics,
+    v1: VectorValue,
+    v2: VectorValue,
+    signed: bool,
+    add: bool,
+) -> VectorValue {
+    let vec_ty = v1.get_type();
+    let lanes = vec_ty.get_size();
+    let lane_bits = 128 / lanes;
+    let wide_lane_ty = if lane_bits == 8 {
+        intrinsics.i16_ty
+    } else {
+        intrinsics.i32_ty
+    };
+    let wide_ty = wide_lane_ty.vec_type(lanes);
+
+    let (v1, v2) = if signed {
+        (
+            builder.build_int_s_extend(v1, wide_ty, "wide"),
+            builder.build_int_s_extend(v2, wide_ty, "wide"),
+        )
+    } else {
+        (
+            builder.build_int_z_extend(v1, wide_ty, "wide"),
+            builder.build_int_z_extend(v2, wide_ty, "wide"),
+        )
+    };
+    let res = if add {
+        builder.build_int_add(v1, v2, "wide_res")
+    } else {
+        builder.build_int_sub(v1, v2, "wide_res")
+    };
+
+    let (min, max) = if signed {
+        (-(1i64 << (lane_bits - 1)), (1i64 << (lane_bits - 1)) - 1)
+    } else {
+        (0, (1i64 << lane_bits) - 1)
+    };
+    let min = const_splat(wide_lane_ty.const_int(min as u64, true), lanes);
+    let max = const_splat(wide_lane_ty.const_int(max as u64, true), lanes);
+
+    let below = builder.build_int_compare(IntPredicate::SLT, res, min, "below");
+    let res = builder
+        .build_select(below, min, res, "clamped")
+        .into_vector_value();
+    let above = builder.build_int_compare(IntPredicate::SGT, res, max, "above");
+    let res = builder
+        .build_select(above, max, res, "clamped")
+        .into_vector_value();
+    builder.build_int_truncate(res, vec_ty, "saturated")
+}
+
+/// Picks the lesser lanes of `v1` and `v2`, or the greater ones unless `min` is set,
+/// the way wasm does: a NaN in either lane gives a NaN, and -0 is less than +0.
+/// LLVM's `minnum` and `maxnum` return the other lane instead of a NaN.
+fn min_max_vector(
+    builder: &Builder,
+    v1: VectorValue,
+    v2: VectorValue,
+    int_vec_ty: VectorType,
+    min: bool,
+) -> VectorValue {
+    let vec_ty = v1.get_type();
+    let predicate = if min {
+        FloatPredicate::OLT
+    } else {
+        FloatPredicate::OGT
+    };
+    let pick_v1 = builder.build_float_compare(predicate, v1, v2, "pick_v1");
+    let res = builder
+        .build_select(pick_v1, v1, v2, "min_max")
+        .into_vector_value();
+
+    // Equal lanes only differ when they are zeros of opposite signs, and or-ing
+    // their bits gives -0 while and-ing them gives +0.
+    let bits1 = builder
+        .build_bitcast(v1, int_vec_ty, "")
+        .into_vector_value();
+    let bits2 = builder
+        .build_bitcast(v2, int_vec_ty, "")
+        .into_vector_value();
+    let zeros = if min {
+        builder.build_or(bits1, bits2, "zeros")
+    } else {
+        builder.build_and(bits1, bits2, "zeros")
+    };
+    let zeros = builder.build_bitcast(zeros, vec_ty, "").into_vector_value();
+    let equal = builder.build_float_compare(FloatPredicate::OEQ, v1, v2, "equal");
+    let res = builder
+        .build_select(equal, zeros, res, "min_max")
+        .into_vector_value();
+
+    // Adding a NaN to anything gives a NaN.
+    let is_nan = builder.build_float_compare(FloatPredicate::UNO, v1, v2, "is_nan");
+    let nans = builder.build_float_add(v1, v2, "nans");
+    builder
+        .build_select(is_nan, nans, res, "min_max")
+        .into_vector_value()
+}
+
+/// Converts the float lanes of `value` to the integer lanes of `int_vec_ty` the way
+/// the `trunc_sat` operators do: lanes out of range saturate, and NaNs become 0.
+fn trunc_sat_vector(
+    builder: &Builder,
+    value: VectorValue,
+    int_vec_ty: VectorType,
+    signed: bool,
+) -> VectorValue {
+    let vec_ty = value.get_type();
+    let lanes = vec_ty.get_size();
+    let lane_bits = 128 / lanes;
+    let float_ty = vec_ty.get_element_type().into_float_type();
+    let int_ty = int_vec_ty.get_element_type().into_int_type();
+
+    // The bounds are powers of two, so they are exact in either float type.
+    let (lower, upper) = if signed {
+        (
+            -2f64.powi(lane_bits as i32 - 1),
+            2f64.powi(lane_bits as i32 - 1),
+        )
+    } else {
+        (0.0, 2f64.powi(lane_bits as i32))
+    };
+    let (int_min, int_max) = if signed {
+        (1u64 << (lane_bits - 1), (1u64 << (lane_bits - 1)) - 1)
+    } else {
+        (0, std::u64::MAX >> (64 - lane_bits))
+    };
+
+    let below = builder.build_float_compare(
+        FloatPredicate::OLT,
+        value,
+        const_splat(float_ty.const_float(lower), lanes),
+        "below",
+    );
+    let above = builder.build_float_compare(
+        FloatPredicate::OGE,
+        value,
+        const_splat(float_ty.const_float(upper), lanes),
+        "above",
+    );
+    let is_nan = builder.build_float_compare(FloatPredicate::UNO, value, value, "is_nan");
+
+    // Converting the lanes that are out of range is undefined, so they are zeroed first.
+    let invalid = builder.build_or(below, above, "invalid");
+    let invalid = builder.build_or(invalid, is_nan, "invalid");
+    let value = builder
+        .build_select(
+            invalid,
+            const_splat(float_ty.const_float(0.0), lanes),
+            value,
+            "valid",
+        )
+        .into_vector_value();
+
+    let res = if signed {
+        builder.build_float_to_signed_int(value, int_vec_ty, "trunc")
+    } else {
+        builder.build_float_to_unsigned_int(value, int_vec_ty, "trunc")
+    };
+    let res = builder
+        .build_select(
+            below,
+            const_splat(int_ty.const_int(int_min, false), lanes),
+            res,
+            "trunc_sat",
+        )
+        .into_vector_value();
+    builder
+        .build_select(
+            above,
+            const_splat(int_ty.const_int(int_max, false), lanes),
+            res,
+            "trunc_sat",
+        )
+        .into_vector_value()
+}
+
+/// Stops the function if the interrupt flag of the instance is set.
+fn emit_interrupt_check(
+    context: &Context,
+    builder: &Builder,
+    intrinsics: &Intrinsics,
+    function: &FunctionValue,
+    ctx: &CtxType,
+) {
+    let interrupted = builder
+        .build_load(ctx.interrupted(), "interrupted")
+        .into_int_value();
+    let interrupted = builder.build_int_compare(
+        IntPredicate::NE,
+        interrupted,
+        intrinsics.i8_ty.const_int(0, false),
+        "is_interrupted",
+    );
+
+    let interrupted_block = context.append_basic_block(function, "interrupted");
+    let continue_block = context.append_basic_block(function, "interrupt_continue");
+    builder.build_conditional_branch(interrupted, &interrupted_block, &continue_block);
+
+    builder.position_at_end(&interrupted_block);
+    builder.build_call(intrinsics.interrupted, &[ctx.basic()], "interrupted_call");
+    builder.build_unreachable();
+
+    builder.position_at_end(&continue_block);
+}
+
+/// Fuel is charged right before the operators that end a basic block,
+/// or that may hand control over to other code.
+fn is_block_boundary(op: &Operator) -> bool {
+    match *op {
+        Operator::Unreachable
+        | Operator::Block { .. }
+        | Operator::Loop { .. }
+        | Operator::If { .. }
+        | Operator::Else
+        | Operator::End
+        | Operator::Br { .. }
+        | Operator::BrIf { .. }
+        | Operator::BrTable { .. }
+        | Operator::Return
+        | Operator::Call { .. }
+        | Operator::CallIndirect { .. } => true,
+        _ => false,
+    }
+}
@@ -3,7 +3,9 @@ use inkwell::{
     builder::Builder,
     context::Context,
     module::Module,
-    types::{BasicType, FloatType, FunctionType, IntType, PointerType, StructType, VoidType},
+    types::{
+        BasicType, FloatType, FunctionType, IntType, PointerType, StructType, VectorType, VoidType,
+    },
     values::{BasicValue, BasicValueEnum, FloatValue, FunctionValue, IntValue, PointerValue},
     AddressSpace,
 };
@@ -21,9 +23,10 @@ use wasmer_runtime_core::{
 fn type_to_llvm_ptr(intrinsics: &Intrinsics, ty: Type) -> PointerType {
     match ty {
         Type::I32 => intrinsics.i32_ptr_ty,
-        Type::I64 => intrinsics.i64_ptr_ty,
+        Type::I64 | Type::AnyRef | Type::FuncRef => intrinsics.i64_ptr_ty,
         Type::F32 => intrinsics.f32_ptr_ty,
         Type::F64 => intrinsics.f64_ptr_ty,
+        Type::V128 => intrinsics.i128_ptr_ty,
     }
 }
 
@@ -64,6 +67,12 @@ pub struct Intrinsics {
     pub copysign_f32: FunctionValue,
     pub copysign_f64: FunctionValue,
 
+    pub sqrt_f32x4: FunctionValue,
+    pub sqrt_f64x2: FunctionValue,
+
+    pub fabs_f32x4: FunctionValue,
+    pub fabs_f64x2: FunctionValue,
+
     pub expect_i1: FunctionValue,
     pub trap: FunctionValue,
 
@@ -73,13 +82,22 @@ pub struct Intrinsics {
     pub i16_ty: IntType,
     pub i32_ty: IntType,
     pub i64_ty: IntType,
+    pub i128_ty: IntType,
     pub f32_ty: FloatType,
     pub f64_ty: FloatType,
 
+    pub i8x16_ty: VectorType,
+    pub i16x8_ty: VectorType,
+    pub i32x4_ty: VectorType,
+    pub i64x2_ty: VectorType,
+    pub f32x4_ty: VectorType,
+    pub f64x2_ty: VectorType,
+
     pub i8_ptr_ty: PointerType,
     pub i16_ptr_ty: PointerType,
     pub i32_ptr_ty: PointerType,
     pub i64_ptr_ty: PointerType,
+    pub i128_ptr_ty: PointerType,
     pub f32_ptr_ty: PointerType,
     pub f64_ptr_ty: PointerType,
 
@@ -88,6 +106,7 @@ pub struct Intrinsics {
     pub i1_zero: IntValue,
     pub i32_zero: IntValue,
     pub i64_zero: IntValue,
+    pub i128_zero: IntValue,
     pub f32_zero: FloatValue,
     pub f64_zero: FloatValue,
 
@@ -96,6 +115,7 @@ pub struct Intrinsics {
     pub trap_call_indirect_oob: BasicValueEnum,
     pub trap_memory_oob: BasicValueEnum,
     pub trap_illegal_arithmetic: BasicValueEnum,
+    pub trap_misaligned_atomic: BasicValueEnum,
 
     // VM intrinsics.
     pub memory_grow_dynamic_local: FunctionValue,
@@ -112,7 +132,26 @@ pub struct Intrinsics {
     pub memory_size_static_import: FunctionValue,
     pub memory_size_shared_import: FunctionValue,
 
+    pub memory_init: FunctionValue,
+    pub data_drop: FunctionValue,
+    pub memory_copy: FunctionValue,
+    pub memory_fill: FunctionValue,
+    pub table_init: FunctionValue,
+    pub elem_drop: FunctionValue,
+    pub table_copy: FunctionValue,
+
+    pub table_get: FunctionValue,
+    pub table_set: FunctionValue,
+    pub table_grow: FunctionValue,
+    pub table_size: FunctionValue,
+
+    pub memory_atomic_wait32: FunctionValue,
+    pub memory_atomic_wait64: FunctionValue,
+    pub memory_atomic_notify: FunctionValue,
+
     pub throw_trap: FunctionValue,
+    pub fuel_exhausted: FunctionValue,
+    pub interrupted: FunctionValue,
 
     pub ctx_ptr_ty: PointerType,
 }
@@ -125,19 +164,29 @@ impl Intrinsics {
         let i16_ty = context.i16_type();
         let i32_ty = context.i32_type();
         let i64_ty = context.i64_type();
+        let i128_ty = context.i128_type();
         let f32_ty = context.f32_type();
         let f64_ty = context.f64_type();
 
+        let i8x16_ty = i8_ty.vec_type(16);
+        let i16x8_ty = i16_ty.vec_type(8);
+        let i32x4_ty = i32_ty.vec_type(4);
+        let i64x2_ty = i64_ty.vec_type(2);
+        let f32x4_ty = f32_ty.vec_type(4);
+        let f64x2_ty = f64_ty.vec_type(2);
+
         let i8_ptr_ty = i8_ty.ptr_type(AddressSpace::Generic);
         let i16_ptr_ty = i16_ty.ptr_type(AddressSpace::Generic);
         let i32_ptr_ty = i32_ty.ptr_type(AddressSpace::Generic);
         let i64_ptr_ty = i64_ty.ptr_type(AddressSpace::Generic);
+        let i128_ptr_ty = i128_ty.ptr_type(AddressSpace::Generic);
         let f32_ptr_ty = f32_ty.ptr_type(AddressSpace::Generic);
         let f64_ptr_ty = f64_ty.ptr_type(AddressSpace::Generic);
 
         let i1_zero = i1_ty.const_int(0, false);
         let i32_zero = i32_ty.const_int(0, false);
         let i64_zero = i64_ty.const_int(0, false);
+        let i128_zero = i128_ty.const_int(0, false);
         let f32_zero = f32_ty.const_float(0.0);
         let f64_zero = f64_ty.const_float(0.0);
 
@@ -146,6 +195,8 @@ impl Intrinsics {
         let i64_ty_basic = i64_ty.as_basic_type_enum();
         let f32_ty_basic = f32_ty.as_basic_type_enum();
         let f64_ty_basic = f64_ty.as_basic_type_enum();
+        let f32x4_ty_basic = f32x4_ty.as_basic_type_enum();
+        let f64x2_ty_basic = f64x2_ty.as_basic_type_enum();
         let i8_ptr_ty_basic = i8_ptr_ty.as_basic_type_enum();
 
         let ctx_ty = context.opaque_struct_type("ctx");
@@ -201,6 +252,9 @@ impl Intrinsics {
                 sigindex_ty
                     .ptr_type(AddressSpace::Generic)
                     .as_basic_type_enum(),
+                i8_ptr_ty_basic,
+                i64_ty_basic,
+                i64_ty_basic,
                 local_function_ty
                     .ptr_type(AddressSpace::Generic)
                     .as_basic_type_enum(),
@@ -220,6 +274,9 @@ impl Intrinsics {
         let ret_f32_take_f32_f32 = f32_ty.fn_type(&[f32_ty_basic, f32_ty_basic], false);
         let ret_f64_take_f64_f64 = f64_ty.fn_type(&[f64_ty_basic, f64_ty_basic], false);
 
+        let ret_f32x4_take_f32x4 = f32x4_ty.fn_type(&[f32x4_ty_basic], false);
+        let ret_f64x2_take_f64x2 = f64x2_ty.fn_type(&[f64x2_ty_basic], false);
+
         let ret_i32_take_ctx_i32_i32 = i32_ty.fn_type(
             &[ctx_ptr_ty.as_basic_type_enum(), i32_ty_basic, i32_ty_basic],
             false,
@@ -227,6 +284,77 @@ impl Intrinsics {
         let ret_i32_take_ctx_i32 =
             i32_ty.fn_type(&[ctx_ptr_ty.as_basic_type_enum(), i32_ty_basic], false);
 
+        let ret_void_take_ctx_i32 =
+            void_ty.fn_type(&[ctx_ptr_ty.as_basic_type_enum(), i32_ty_basic], false);
+        let ret_void_take_ctx_i32_i32_i32_i32 = void_ty.fn_type(
+            &[
+                ctx_ptr_ty.as_basic_type_enum(),
+                i32_ty_basic,
+                i32_ty_basic,
+                i32_ty_basic,
+                i32_ty_basic,
+            ],
+            false,
+        );
+        let ret_void_take_ctx_i32_i32_i32_i32_i32 = void_ty.fn_type(
+            &[
+                ctx_ptr_ty.as_basic_type_enum(),
+                i32_ty_basic,
+                i32_ty_basic,
+                i32_ty_basic,
+                i32_ty_basic,
+                i32_ty_basic,
+            ],
+            false,
+        );
+
+        let ret_i64_take_ctx_i32_i32 = i64_ty.fn_type(
+            &[ctx_ptr_ty.as_basic_type_enum(), i32_ty_basic, i32_ty_basic],
+            false,
+        );
+        let ret_void_take_ctx_i32_i32_i64 = void_ty.fn_type(
+            &[
+                ctx_ptr_ty.as_basic_type_enum(),
+                i32_ty_basic,
+                i32_ty_basic,
+                i64_ty_basic,
+            ],
+            false,
+        );
+
+        let ret_i32_take_ctx_i32_i32_i32_i32 = i32_ty.fn_type(
+            &[
+                ctx_ptr_ty.as_basic_type_enum(),
+                i32_ty_basic,
+                i32_ty_basic,
+                i32_ty_basic,
+                i32_ty_basic,
+            ],
+            false,
+        );
+        let ret_i32_take_ctx_i32_i32_i32_i32_i64 = i32_ty.fn_type(
+            &[
+                ctx_ptr_ty.as_basic_type_enum(),
+                i32_ty_basic,
+                i32_ty_basic,
+                i32_ty_basic,
+                i32_ty_basic,
+                i64_ty_basic,
+            ],
+            false,
+        );
+        let ret_i32_take_ctx_i32_i32_i32_i64_i64 = i32_ty.fn_type(
+            &[
+                ctx_ptr_ty.as_basic_type_enum(),
+                i32_ty_basic,
+                i32_ty_basic,
+                i32_ty_basic,
+                i64_ty_basic,
+                i64_ty_basic,
+            ],
+            false,
+        );
+
         let ret_i1_take_i1_i1 = i1_ty.fn_type(&[i1_ty_basic, i1_ty_basic], false);
 
         Self {
@@ -266,6 +394,12 @@ impl Intrinsics {
             copysign_f32: module.add_function("llvm.copysign.f32", ret_f32_take_f32_f32, None),
             copysign_f64: module.add_function("llvm.copysign.f64", ret_f64_take_f64_f64, None),
 
+            sqrt_f32x4: module.add_function("llvm.sqrt.v4f32", ret_f32x4_take_f32x4, None),
+            sqrt_f64x2: module.add_function("llvm.sqrt.v2f64", ret_f64x2_take_f64x2, None),
+
+            fabs_f32x4: module.add_function("llvm.fabs.v4f32", ret_f32x4_take_f32x4, None),
+            fabs_f64x2: module.add_function("llvm.fabs.v2f64", ret_f64x2_take_f64x2, None),
+
             expect_i1: module.add_function("llvm.expect.i1", ret_i1_take_i1_i1, None),
             trap: module.add_function("llvm.trap", void_ty.fn_type(&[], false), None),
 
@@ -275,13 +409,22 @@ impl Intrinsics {
             i16_ty,
             i32_ty,
             i64_ty,
+            i128_ty,
             f32_ty,
             f64_ty,
 
+            i8x16_ty,
+            i16x8_ty,
+            i32x4_ty,
+            i64x2_ty,
+            f32x4_ty,
+            f64x2_ty,
+
             i8_ptr_ty,
             i16_ptr_ty,
             i32_ptr_ty,
             i64_ptr_ty,
+            i128_ptr_ty,
             f32_ptr_ty,
             f64_ptr_ty,
 
@@ -290,6 +433,7 @@ impl Intrinsics {
             i1_zero,
             i32_zero,
             i64_zero,
+            i128_zero,
             f32_zero,
             f64_zero,
 
@@ -298,6 +442,7 @@ impl Intrinsics {
             trap_call_indirect_oob: i32_ty.const_int(3, false).as_basic_value_enum(),
             trap_memory_oob: i32_ty.const_int(2, false).as_basic_value_enum(),
             trap_illegal_arithmetic: i32_ty.const_int(4, false).as_basic_value_enum(),
+            trap_misaligned_atomic: i32_ty.const_int(5, false).as_basic_value_enum(),
 
             // VM intrinsics.
             memory_grow_dynamic_local: module.add_function(
@@ -361,11 +506,71 @@ impl Intrinsics {
                 ret_i32_take_ctx_i32,
                 None,
             ),
+
+            memory_init: module.add_function(
+                "vm.memory.init",
+                ret_void_take_ctx_i32_i32_i32_i32_i32,
+                None,
+            ),
+            data_drop: module.add_function("vm.data.drop", ret_void_take_ctx_i32, None),
+            memory_copy: module.add_function(
+                "vm.memory.copy",
+                ret_void_take_ctx_i32_i32_i32_i32,
+                None,
+            ),
+            memory_fill: module.add_function(
+                "vm.memory.fill",
+                ret_void_take_ctx_i32_i32_i32_i32,
+                None,
+            ),
+            table_init: module.add_function(
+                "vm.table.init",
+                ret_void_take_ctx_i32_i32_i32_i32_i32,
+                None,
+            ),
+            elem_drop: module.add_function("vm.elem.drop", ret_void_take_ctx_i32, None),
+            table_copy: module.add_function(
+                "vm.table.copy",
+                ret_void_take_ctx_i32_i32_i32_i32,
+                None,
+            ),
+
+            table_get: module.add_function("vm.table.get", ret_i64_take_ctx_i32_i32, None),
+            table_set: module.add_function("vm.table.set", ret_void_take_ctx_i32_i32_i64, None),
+            table_grow: module.add_function("vm.table.grow", ret_i32_take_ctx_i32_i32, None),
+            table_size: module.add_function("vm.table.size", ret_i32_take_ctx_i32, None),
+
+            memory_atomic_wait32: module.add_function(
+                "vm.memory.atomic.wait32",
+                ret_i32_take_ctx_i32_i32_i32_i32_i64,
+                None,
+            ),
+            memory_atomic_wait64: module.add_function(
+                "vm.memory.atomic.wait64",
+                ret_i32_take_ctx_i32_i32_i32_i64_i64,
+                None,
+            ),
+            memory_atomic_notify: module.add_function(
+                "vm.memory.atomic.notify",
+                ret_i32_take_ctx_i32_i32_i32_i32,
+                None,
+            ),
+
             throw_trap: module.add_function(
                 "vm.exception.trap",
                 void_ty.fn_type(&[i32_ty_basic], false),
                 None,
             ),
+            fuel_exhausted: module.add_function(
+                "vm.fuel.exhausted",
+                void_ty.fn_type(&[ctx_ptr_ty.as_basic_type_enum()], false),
+                None,
+            ),
+            interrupted: module.add_function(
+                "vm.interrupted",
+                void_ty.fn_type(&[ctx_ptr_ty.as_basic_type_enum()], false),
+                None,
+            ),
             ctx_ptr_ty,
         }
     }
@@ -575,10 +780,33 @@ impl<'a> CtxType<'a> {
         )
     }
 
+    /// Returns a pointer to the interrupt flag of the instance.
+    pub fn interrupted(&self) -> PointerValue {
+        let interrupted_ptr_ptr = unsafe {
+            self.builder
+                .build_struct_gep(self.ctx_ptr_value, 8, "interrupted_ptr_ptr")
+        };
+        self.builder
+            .build_load(interrupted_ptr_ptr, "interrupted_ptr")
+            .into_pointer_value()
+    }
+
+    /// Returns pointers to the fuel consumed so far and to the fuel limit.
+    pub fn fuel(&self) -> (PointerValue, PointerValue) {
+        unsafe {
+            (
+                self.builder
+                    .build_struct_gep(self.ctx_ptr_value, 9, "fuel_used_ptr"),
+                self.builder
+                    .build_struct_gep(self.ctx_ptr_value, 10, "fuel_limit_ptr"),
+            )
+        }
+    }
+
     pub fn local_func(&mut self, index: LocalFuncIndex, fn_ty: FunctionType) -> PointerValue {
         let local_func_array_ptr_ptr = unsafe {
             self.builder
-                .build_struct_gep(self.ctx_ptr_value, 8, "local_func_array_ptr_ptr")
+                .build_struct_gep(self.ctx_ptr_value, 11, "local_func_array_ptr_ptr")
         };
         let local_func_array_ptr = self
             .builder
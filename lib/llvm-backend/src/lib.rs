@@ -1,7 +1,7 @@
 #![cfg_attr(nightly, feature(unwind_attributes))]
 
 use wasmer_runtime_core::{
-    backend::{Compiler, CompilerConfig, Token},
+    backend::{Backend, Compiler, CompilerConfig, Token},
     cache::{Artifact, Error as CacheError},
     error::CompileError,
     module::ModuleInner,
@@ -35,8 +35,13 @@ impl Compiler for LLVMCompiler {
     ) -> Result<ModuleInner, CompileError> {
         validate(wasm)?;
 
+        let fuel_costs = match compiler_config.metering {
+            Some(ref costs) => Some(costs.module_costs(wasm)?),
+            None => None,
+        };
         let (info, code_reader) = read_info::read_module(wasm, compiler_config).unwrap();
-        let (module, intrinsics) = code::parse_function_bodies(&info, code_reader).unwrap();
+        let (module, intrinsics) =
+            code::parse_function_bodies(&info, code_reader, fuel_costs).unwrap();
 
         let (backend, cache_gen) = backend::LLVMBackend::new(module, intrinsics);
 
@@ -60,6 +65,10 @@ impl Compiler for LLVMCompiler {
             info,
         })
     }
+
+    fn backend(&self) -> Backend {
+        Backend::LLVM
+    }
 }
 
 fn validate(bytes: &[u8]) -> Result<(), CompileError> {
@@ -67,15 +76,19 @@ fn validate(bytes: &[u8]) -> Result<(), CompileError> {
         bytes,
         Some(wasmparser::ValidatingParserConfig {
             operator_config: wasmparser::OperatorValidatorConfig {
-                enable_threads: false,
-                enable_reference_types: false,
-                enable_simd: false,
-                enable_bulk_memory: false,
+                enable_threads: true,
+                enable_reference_types: true,
+                enable_simd: true,
+                enable_bulk_memory: true,
             },
             mutable_global_imports: false,
         }),
     );
 
+    // Host functions return their results as a C struct, which is not laid
+    // out the way llvm returns several values, so they can't be imported.
+    let mut is_multi_value = vec![];
+
     loop {
         let state = parser.read();
         match *state {
@@ -83,6 +96,17 @@ fn validate(bytes: &[u8]) -> Result<(), CompileError> {
             wasmparser::ParserState::Error(err) => Err(CompileError::ValidationError {
                 msg: err.message.to_string(),
             })?,
+            wasmparser::ParserState::TypeSectionEntry(ref func_type) => {
+                is_multi_value.push(func_type.returns.len() > 1);
+            }
+            wasmparser::ParserState::ImportSectionEntry {
+                ty: wasmparser::ImportSectionEntryType::Function(type_index),
+                ..
+            } if is_multi_value.get(type_index as usize) == Some(&true) => {
+                Err(CompileError::ValidationError {
+                    msg: "importing functions with multiple results is not supported".to_string(),
+                })?
+            }
             _ => {}
         }
     }
@@ -110,7 +134,7 @@ fn test_read_module() {
 
     let (info, code_reader) = read_info::read_module(&wasm, Default::default()).unwrap();
 
-    let (module, intrinsics) = code::parse_function_bodies(&info, code_reader).unwrap();
+    let (module, intrinsics) = code::parse_function_bodies(&info, code_reader, None).unwrap();
 
     let (backend, _) = backend::LLVMBackend::new(module, intrinsics);
 
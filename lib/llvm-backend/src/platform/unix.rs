@@ -40,7 +40,12 @@ pub unsafe fn visit_fde(addr: *mut u8, size: usize, visitor: extern "C" fn(*mut
 
 extern "C" {
     #[cfg_attr(nightly, unwind(allowed))]
-    fn throw_trap(ty: i32) -> !;
+    fn throw_trap_at(
+        ty: i32,
+        address: *const c_void,
+        frame: *const c_void,
+        stack: *const c_void,
+    ) -> !;
 }
 
 pub unsafe fn install_signal_handler() {
@@ -57,7 +62,7 @@ pub unsafe fn install_signal_handler() {
 extern "C" fn signal_trap_handler(
     _signum: ::nix::libc::c_int,
     _siginfo: *mut siginfo_t,
-    _ucontext: *mut c_void,
+    ucontext: *mut c_void,
 ) {
     unsafe {
         // Apparently, we can unwind from arbitary instructions, as long
@@ -65,6 +70,67 @@ extern "C" fn signal_trap_handler(
         // was interrupted.
         //
         // This works on macos, not sure about linux.
-        throw_trap(2);
+        let (ip, fp, sp) = get_trap_registers(ucontext);
+        throw_trap_at(2, ip, fp, sp);
     }
 }
+
+/// Returns the instruction, frame and stack pointers of the trapping code.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+unsafe fn get_trap_registers(
+    ucontext: *const c_void,
+) -> (*const c_void, *const c_void, *const c_void) {
+    use libc::{ucontext_t, REG_RBP, REG_RIP, REG_RSP};
+
+    let ucontext = ucontext as *const ucontext_t;
+    let gregs = &(*ucontext).uc_mcontext.gregs;
+    (
+        gregs[REG_RIP as usize] as _,
+        gregs[REG_RBP as usize] as _,
+        gregs[REG_RSP as usize] as _,
+    )
+}
+
+#[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+unsafe fn get_trap_registers(
+    ucontext: *const c_void,
+) -> (*const c_void, *const c_void, *const c_void) {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct ucontext_t {
+        uc_onstack: u32,
+        uc_sigmask: u32,
+        uc_stack: libc::stack_t,
+        uc_link: *const ucontext_t,
+        uc_mcsize: u64,
+        uc_mcontext: *const mcontext_t,
+    }
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct mcontext_t {
+        exception_state: [u64; 2],
+        // rax to r15
+        gprs: [u64; 16],
+        rip: u64,
+        // ...
+    }
+
+    let ucontext = ucontext as *const ucontext_t;
+    let mcontext = &*(*ucontext).uc_mcontext;
+    // rbp and rsp are the 7th and 8th general purpose registers
+    (
+        mcontext.rip as _,
+        mcontext.gprs[6] as _,
+        mcontext.gprs[7] as _,
+    )
+}
+
+#[cfg(not(any(
+    all(target_os = "linux", target_arch = "x86_64"),
+    all(target_os = "macos", target_arch = "x86_64"),
+)))]
+unsafe fn get_trap_registers(
+    _ucontext: *const c_void,
+) -> (*const c_void, *const c_void, *const c_void) {
+    (std::ptr::null(), std::ptr::null(), std::ptr::null())
+}
@@ -1,14 +1,15 @@
 use wasmer_runtime_core::{
     backend::{Backend, CompilerConfig},
     module::{
-        DataInitializer, ExportIndex, ImportName, ModuleInfo, StringTable, StringTableBuilder,
-        TableInitializer,
+        DataInitializer, ExportIndex, ImportName, ModuleInfo, NameSection, StringTable,
+        StringTableBuilder, TableInitializer,
     },
     structures::{Map, TypedIndex},
+    table::{AnyRef, FuncRef},
     types::{
-        ElementType, FuncIndex, FuncSig, GlobalDescriptor, GlobalIndex, GlobalInit,
-        ImportedGlobalIndex, Initializer, MemoryDescriptor, MemoryIndex, SigIndex, TableDescriptor,
-        TableIndex, Type, Value,
+        DataIndex, ElemIndex, ElementType, FuncIndex, FuncSig, GlobalDescriptor, GlobalIndex,
+        GlobalInit, ImportedGlobalIndex, Initializer, MemoryDescriptor, MemoryIndex, SigIndex,
+        TableDescriptor, TableIndex, Type, Value,
     },
     units::Pages,
 };
@@ -39,6 +40,9 @@ pub fn read_module(
         data_initializers: Vec::new(),
         elem_initializers: Vec::new(),
 
+        passive_data: HashMap::new(),
+        passive_elements: HashMap::new(),
+
         start_func: None,
 
         func_assoc: Map::new(),
@@ -51,6 +55,8 @@ pub fn read_module(
         em_symbol_map: compiler_config.symbol_map.clone(),
 
         custom_sections: HashMap::new(),
+
+        name_section: NameSection::default(),
     };
 
     let mut reader = ModuleReader::new(wasm)?;
@@ -94,9 +100,8 @@ pub fn read_module(
                             info.func_assoc.push(sigindex);
                         }
                         ImportSectionEntryType::Table(table_ty) => {
-                            assert_eq!(table_ty.element_type, WpType::AnyFunc);
                             let table_desc = TableDescriptor {
-                                element: ElementType::Anyfunc,
+                                element: type_to_element_type(table_ty.element_type)?,
                                 minimum: table_ty.limits.initial,
                                 maximum: table_ty.limits.maximum,
                             };
@@ -141,7 +146,7 @@ pub fn read_module(
                     let table_ty = table_ty?;
 
                     let table_desc = TableDescriptor {
-                        element: ElementType::Anyfunc,
+                        element: type_to_element_type(table_ty.element_type)?,
                         minimum: table_ty.limits.initial,
                         maximum: table_ty.limits.maximum,
                     };
@@ -175,11 +180,16 @@ pub fn read_module(
                         ty: type_to_type(global.ty.content_type)?,
                     };
 
-                    let global_init = GlobalInit {
-                        desc,
-                        init: eval_init_expr(&global.init_expr)?,
+                    let init = match eval_init_expr(&global.init_expr)? {
+                        // `ref.null` takes the type of the global it initializes.
+                        Initializer::Const(Value::AnyRef(_)) if desc.ty == Type::FuncRef => {
+                            Initializer::Const(Value::FuncRef(FuncRef::null()))
+                        }
+                        init => init,
                     };
 
+                    let global_init = GlobalInit { desc, init };
+
                     info.globals.push(global_init);
                 }
             }
@@ -211,8 +221,14 @@ pub fn read_module(
             SectionCode::Element => {
                 let element_reader = section.get_element_section_reader()?;
 
-                for element in element_reader {
+                for (elem_index, element) in element_reader.into_iter().enumerate() {
                     let Element { kind, items } = element?;
+                    let items_reader = items.get_items_reader()?;
+
+                    let elements: Vec<_> = items_reader
+                        .into_iter()
+                        .map(|res| res.map(|index| FuncIndex::new(index as usize)))
+                        .collect::<Result<_, _>>()?;
 
                     match kind {
                         ElementKind::Active {
@@ -221,12 +237,6 @@ pub fn read_module(
                         } => {
                             let table_index = TableIndex::new(table_index as usize);
                             let base = eval_init_expr(&init_expr)?;
-                            let items_reader = items.get_items_reader()?;
-
-                            let elements: Vec<_> = items_reader
-                                .into_iter()
-                                .map(|res| res.map(|index| FuncIndex::new(index as usize)))
-                                .collect::<Result<_, _>>()?;
 
                             let table_init = TableInitializer {
                                 table_index,
@@ -237,10 +247,8 @@ pub fn read_module(
                             info.elem_initializers.push(table_init);
                         }
                         ElementKind::Passive(_ty) => {
-                            return Err(BinaryReaderError {
-                                message: "passive tables are not yet supported",
-                                offset: -1isize as usize,
-                            });
+                            info.passive_elements
+                                .insert(ElemIndex::new(elem_index), elements);
                         }
                     }
                 }
@@ -251,7 +259,7 @@ pub fn read_module(
             SectionCode::Data => {
                 let data_reader = section.get_data_section_reader()?;
 
-                for data in data_reader {
+                for (data_index, data) in data_reader.into_iter().enumerate() {
                     let Data { kind, data } = data?;
 
                     match kind {
@@ -271,10 +279,8 @@ pub fn read_module(
                             info.data_initializers.push(data_init);
                         }
                         DataKind::Passive => {
-                            return Err(BinaryReaderError {
-                                message: "passive memories are not yet supported",
-                                offset: -1isize as usize,
-                            });
+                            info.passive_data
+                                .insert(DataIndex::new(data_index), data.to_vec());
                         }
                     }
                 }
@@ -291,12 +297,9 @@ pub fn type_to_type(ty: WpType) -> Result<Type, BinaryReaderError> {
         WpType::I64 => Type::I64,
         WpType::F32 => Type::F32,
         WpType::F64 => Type::F64,
-        WpType::V128 => {
-            return Err(BinaryReaderError {
-                message: "the wasmer llvm backend does not yet support the simd extension",
-                offset: -1isize as usize,
-            });
-        }
+        WpType::AnyRef => Type::AnyRef,
+        WpType::AnyFunc => Type::FuncRef,
+        WpType::V128 => Type::V128,
         _ => {
             return Err(BinaryReaderError {
                 message: "that type is not supported as a wasmer type",
@@ -306,6 +309,17 @@ pub fn type_to_type(ty: WpType) -> Result<Type, BinaryReaderError> {
     })
 }
 
+fn type_to_element_type(ty: WpType) -> Result<ElementType, BinaryReaderError> {
+    match ty {
+        WpType::AnyFunc => Ok(ElementType::Anyfunc),
+        WpType::AnyRef => Ok(ElementType::AnyRef),
+        _ => Err(BinaryReaderError {
+            message: "that type is not supported as a table element type",
+            offset: -1isize as usize,
+        }),
+    }
+}
+
 fn func_type_to_func_sig(func_ty: FuncType) -> Result<FuncSig, BinaryReaderError> {
     assert_eq!(func_ty.form, WpType::Func);
 
@@ -340,6 +354,10 @@ fn eval_init_expr(expr: &InitExpr) -> Result<Initializer, BinaryReaderError> {
         Operator::F64Const { value } => {
             Initializer::Const(Value::F64(f64::from_bits(value.bits())))
         }
+        Operator::RefNull => Initializer::Const(Value::AnyRef(AnyRef::null())),
+        Operator::V128Const { value } => {
+            Initializer::Const(Value::V128(u128::from_le_bytes(*value.bytes())))
+        }
         _ => {
             return Err(BinaryReaderError {
                 message: "init expr evaluation failed: unsupported opcode",
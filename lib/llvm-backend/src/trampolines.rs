@@ -1,5 +1,6 @@
 use crate::intrinsics::Intrinsics;
 use inkwell::{
+    attributes::AttributeLoc,
     builder::Builder,
     context::Context,
     module::{Linkage, Module},
@@ -41,6 +42,10 @@ pub fn generate_trampolines(
             trampoline_sig,
             Some(Linkage::External),
         );
+        trampoline_func.add_attribute(
+            AttributeLoc::Function,
+            context.create_string_attribute("no-frame-pointer-elim", "true"),
+        );
 
         generate_trampoline(trampoline_func, sig, context, builder, intrinsics);
     }
@@ -69,16 +74,24 @@ fn generate_trampoline(
 
     let cast_ptr_ty = |wasmer_ty| match wasmer_ty {
         Type::I32 => intrinsics.i32_ptr_ty,
-        Type::I64 => intrinsics.i64_ptr_ty,
+        Type::I64 | Type::AnyRef | Type::FuncRef => intrinsics.i64_ptr_ty,
         Type::F32 => intrinsics.f32_ptr_ty,
         Type::F64 => intrinsics.f64_ptr_ty,
+        Type::V128 => intrinsics.i128_ptr_ty,
+    };
+
+    // A `v128` takes two of the 64-bit slots of the argument and result arrays.
+    let slot_count = |wasmer_ty| match wasmer_ty {
+        Type::V128 => 2,
+        _ => 1,
     };
 
     let mut args_vec = Vec::with_capacity(func_sig.params().len() + 1);
     args_vec.push(vmctx_ptr);
 
-    for (i, param_ty) in func_sig.params().iter().enumerate() {
-        let index = intrinsics.i32_ty.const_int(i as _, false);
+    let mut slot = 0;
+    for param_ty in func_sig.params() {
+        let index = intrinsics.i32_ty.const_int(slot as _, false);
         let item_pointer = unsafe { builder.build_in_bounds_gep(args_ptr, &[index], "arg_ptr") };
 
         let casted_pointer_type = cast_ptr_ty(*param_ty);
@@ -88,6 +101,7 @@ fn generate_trampoline(
 
         let arg = builder.build_load(typed_item_pointer, "arg");
         args_vec.push(arg);
+        slot += slot_count(*param_ty);
     }
 
     let call_site = builder.build_call(func_ptr, &args_vec, "call");
@@ -104,7 +118,29 @@ fn generate_trampoline(
                 call_site.try_as_basic_value().left().unwrap(),
             );
         }
-        _ => unimplemented!("multi-value returns"),
+        returns => {
+            let struct_value = call_site
+                .try_as_basic_value()
+                .left()
+                .unwrap()
+                .into_struct_value();
+
+            let mut slot = 0;
+            for (i, ret_ty) in returns.iter().enumerate() {
+                let index = intrinsics.i32_ty.const_int(slot as _, false);
+                let item_pointer =
+                    unsafe { builder.build_in_bounds_gep(returns_ptr, &[index], "ret_ptr") };
+
+                let typed_item_pointer =
+                    builder.build_pointer_cast(item_pointer, cast_ptr_ty(*ret_ty), "typed_ret_ptr");
+
+                let value = builder
+                    .build_extract_value(struct_value, i as u32, "ret")
+                    .unwrap();
+                builder.build_store(typed_item_pointer, value);
+                slot += slot_count(*ret_ty);
+            }
+        }
     }
 
     builder.build_return(None);
@@ -1,8 +1,9 @@
-use crate::vfs::file_like::FileLike;
+use crate::vfs::file_like::{FileLike, Metadata};
 use crate::vfs::vfs_header::{header_from_bytes, ArchiveType, CompressionType};
 use crate::vfs::virtual_file::VirtualFile;
 use hashbrown::HashMap;
 use std::cell::RefCell;
+use std::fs;
 use std::io;
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -112,6 +113,101 @@ impl Vfs {
         }
     }
 
+    /// Like `open_file`, except that an empty file is created if none exists at `path`.
+    pub fn create_file<P: AsRef<Path>>(&mut self, path: P) -> Option<Rc<RefCell<dyn FileLike>>> {
+        init_env();
+        let path = convert_to_absolute_path(path);
+        match OpenOptions::new()
+            .write(true)
+            .create(true)
+            .open(&mut self.repo, &path)
+        {
+            Ok(file) => Some(Rc::new(RefCell::new(VirtualFile::new(file)))),
+            Err(_) => None,
+        }
+    }
+
+    /// Get the metadata of the file or directory at `path`.
+    pub fn metadata<P: AsRef<Path>>(&self, path: P) -> Option<Metadata> {
+        let path = convert_to_absolute_path(path);
+        if self.device_files.contains_key(&path) {
+            return Some(Metadata {
+                len: 0,
+                is_file: true,
+            });
+        }
+        self.repo.metadata(&path).ok().map(|m| Metadata {
+            len: m.content_len(),
+            is_file: m.is_file(),
+        })
+    }
+
+    /// List the names and metadata of the entries of the directory at `path`.
+    pub fn read_dir<P: AsRef<Path>>(&self, path: P) -> Option<Vec<(String, Metadata)>> {
+        let path = convert_to_absolute_path(path);
+        let entries = self.repo.read_dir(&path).ok()?;
+        Some(
+            entries
+                .iter()
+                .map(|entry| {
+                    let metadata = entry.metadata();
+                    (
+                        entry.file_name().to_string(),
+                        Metadata {
+                            len: metadata.content_len(),
+                            is_file: metadata.is_file(),
+                        },
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    pub fn remove_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), failure::Error> {
+        let path = convert_to_absolute_path(path);
+        self.repo.remove_file(&path).map_err(|e| e.into())
+    }
+
+    pub fn remove_dir<P: AsRef<Path>>(&mut self, path: P) -> Result<(), failure::Error> {
+        let path = convert_to_absolute_path(path);
+        self.repo.remove_dir(&path).map_err(|e| e.into())
+    }
+
+    /// Copy the host directory at `host_path`, and everything in it, to `guest_path`.
+    ///
+    /// This is a snapshot: files written through the vfs are not written back to
+    /// the host, and later changes on the host are not visible in the vfs.
+    pub fn map_host_dir<P: AsRef<Path>, Q: AsRef<Path>>(
+        &mut self,
+        host_path: P,
+        guest_path: Q,
+    ) -> Result<(), failure::Error> {
+        init_env();
+        let guest_path = convert_to_absolute_path(guest_path);
+        if let Err(e) = self.repo.create_dir_all(&guest_path) {
+            if e != zbox::Error::AlreadyExists && e != zbox::Error::IsRoot {
+                return Err(e.into());
+            }
+        }
+        for entry in fs::read_dir(host_path)? {
+            let entry = entry?;
+            let entry_guest_path = guest_path.join(entry.file_name());
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                self.map_host_dir(entry.path(), &entry_guest_path)?;
+            } else if file_type.is_file() {
+                let mut file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(&mut self.repo, &entry_guest_path)?;
+                io::copy(&mut fs::File::open(entry.path())?, &mut file)?;
+                file.finish()?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn make_dir<P: AsRef<Path>>(&mut self, path: P) {
         self.repo.create_dir_all(path).unwrap();
     }
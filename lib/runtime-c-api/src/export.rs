@@ -7,7 +7,7 @@ use crate::{
     memory::wasmer_memory_t,
     module::wasmer_module_t,
     table::wasmer_table_t,
-    value::{wasmer_value, wasmer_value_t, wasmer_value_tag},
+    value::{wasmer_value_t, wasmer_value_tag},
     wasmer_byte_array, wasmer_result_t,
 };
 use libc::{c_int, uint32_t};
@@ -228,8 +228,14 @@ pub unsafe extern "C" fn wasmer_export_func_params(
     if let Export::Function { ref signature, .. } = *export {
         let params: &mut [wasmer_value_tag] =
             slice::from_raw_parts_mut(params, params_len as usize);
-        for (i, item) in signature.params().iter().enumerate() {
-            params[i] = item.into();
+        for (i, &item) in signature.params().iter().enumerate() {
+            match wasmer_value_tag::try_from_type(item) {
+                Ok(tag) => params[i] = tag,
+                Err(err) => {
+                    update_last_error(err);
+                    return wasmer_result_t::WASMER_ERROR;
+                }
+            }
         }
         wasmer_result_t::WASMER_OK
     } else {
@@ -258,8 +264,14 @@ pub unsafe extern "C" fn wasmer_export_func_returns(
     if let Export::Function { ref signature, .. } = *export {
         let returns: &mut [wasmer_value_tag] =
             slice::from_raw_parts_mut(returns, returns_len as usize);
-        for (i, item) in signature.returns().iter().enumerate() {
-            returns[i] = item.into();
+        for (i, &item) in signature.returns().iter().enumerate() {
+            match wasmer_value_tag::try_from_type(item) {
+                Ok(tag) => returns[i] = tag,
+                Err(err) => {
+                    update_last_error(err);
+                    return wasmer_result_t::WASMER_ERROR;
+                }
+            }
         }
         wasmer_result_t::WASMER_OK
     } else {
@@ -384,25 +396,13 @@ pub unsafe extern "C" fn wasmer_export_func_call(
     match result {
         Ok(results_vec) => {
             if !results_vec.is_empty() {
-                let ret = match results_vec[0] {
-                    Value::I32(x) => wasmer_value_t {
-                        tag: wasmer_value_tag::WASM_I32,
-                        value: wasmer_value { I32: x },
-                    },
-                    Value::I64(x) => wasmer_value_t {
-                        tag: wasmer_value_tag::WASM_I64,
-                        value: wasmer_value { I64: x },
-                    },
-                    Value::F32(x) => wasmer_value_t {
-                        tag: wasmer_value_tag::WASM_F32,
-                        value: wasmer_value { F32: x },
-                    },
-                    Value::F64(x) => wasmer_value_t {
-                        tag: wasmer_value_tag::WASM_F64,
-                        value: wasmer_value { F64: x },
-                    },
-                };
-                results[0] = ret;
+                match wasmer_value_t::try_from_value(results_vec[0].clone()) {
+                    Ok(ret) => results[0] = ret,
+                    Err(err) => {
+                        update_last_error(err);
+                        return wasmer_result_t::WASMER_ERROR;
+                    }
+                }
             }
             wasmer_result_t::WASMER_OK
         }
@@ -1,6 +1,9 @@
 //! Wasm global.
 
-use crate::value::{wasmer_value_t, wasmer_value_tag};
+use crate::{
+    error::update_last_error,
+    value::{wasmer_value, wasmer_value_t, wasmer_value_tag},
+};
 use wasmer_runtime::Global;
 
 #[repr(C)]
@@ -30,12 +33,24 @@ pub unsafe extern "C" fn wasmer_global_new(
 }
 
 /// Gets the value stored by the given Global
+///
+/// Globals created by `wasmer_global_new` always have a `wasmer_value_tag` type.
+/// For other globals, an `i32` zero is returned and the error is recorded, use
+/// `wasmer_last_error_length` and `wasmer_last_error_message` to get it.
 #[allow(clippy::cast_ptr_alignment)]
 #[no_mangle]
 pub extern "C" fn wasmer_global_get(global: *mut wasmer_global_t) -> wasmer_value_t {
     let global = unsafe { &*(global as *mut Global) };
-    let value: wasmer_value_t = global.get().into();
-    value
+    match wasmer_value_t::try_from_value(global.get()) {
+        Ok(value) => value,
+        Err(err) => {
+            update_last_error(err);
+            wasmer_value_t {
+                tag: wasmer_value_tag::WASM_I32,
+                value: wasmer_value { I32: 0 },
+            }
+        }
+    }
 }
 
 /// Sets the value stored by the given Global
@@ -47,6 +62,9 @@ pub extern "C" fn wasmer_global_set(global: *mut wasmer_global_t, value: wasmer_
 }
 
 /// Returns a descriptor (type, mutability) of the given Global
+///
+/// Like `wasmer_global_get`, the kind is `i32` for globals of other types, and
+/// the error is recorded.
 #[allow(clippy::cast_ptr_alignment)]
 #[no_mangle]
 pub extern "C" fn wasmer_global_get_descriptor(
@@ -54,9 +72,13 @@ pub extern "C" fn wasmer_global_get_descriptor(
 ) -> wasmer_global_descriptor_t {
     let global = unsafe { &*(global as *mut Global) };
     let descriptor = global.descriptor();
+    let kind = wasmer_value_tag::try_from_type(descriptor.ty).unwrap_or_else(|err| {
+        update_last_error(err);
+        wasmer_value_tag::WASM_I32
+    });
     wasmer_global_descriptor_t {
         mutable: descriptor.mutable,
-        kind: descriptor.ty.into(),
+        kind,
     }
 }
 
@@ -256,6 +256,7 @@ pub unsafe extern "C" fn wasmer_import_func_new(
         func: FuncPointer::new(func as _),
         ctx: Context::Internal,
         signature: Arc::new(FuncSig::new(params, returns)),
+        func_env: None,
     });
     Box::into_raw(export) as *mut wasmer_import_func_t
 }
@@ -277,8 +278,14 @@ pub unsafe extern "C" fn wasmer_import_func_params(
     if let Export::Function { ref signature, .. } = *export {
         let params: &mut [wasmer_value_tag] =
             slice::from_raw_parts_mut(params, params_len as usize);
-        for (i, item) in signature.params().iter().enumerate() {
-            params[i] = item.into();
+        for (i, &item) in signature.params().iter().enumerate() {
+            match wasmer_value_tag::try_from_type(item) {
+                Ok(tag) => params[i] = tag,
+                Err(err) => {
+                    update_last_error(err);
+                    return wasmer_result_t::WASMER_ERROR;
+                }
+            }
         }
         wasmer_result_t::WASMER_OK
     } else {
@@ -306,8 +313,14 @@ pub unsafe extern "C" fn wasmer_import_func_returns(
     if let Export::Function { ref signature, .. } = *export {
         let returns: &mut [wasmer_value_tag] =
             slice::from_raw_parts_mut(returns, returns_len as usize);
-        for (i, item) in signature.returns().iter().enumerate() {
-            returns[i] = item.into();
+        for (i, &item) in signature.returns().iter().enumerate() {
+            match wasmer_value_tag::try_from_type(item) {
+                Ok(tag) => returns[i] = tag,
+                Err(err) => {
+                    update_last_error(err);
+                    return wasmer_result_t::WASMER_ERROR;
+                }
+            }
         }
         wasmer_result_t::WASMER_OK
     } else {
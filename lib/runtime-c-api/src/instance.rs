@@ -5,7 +5,7 @@ use crate::{
     export::{wasmer_exports_t, wasmer_import_export_kind, NamedExport, NamedExports},
     import::wasmer_import_t,
     memory::wasmer_memory_t,
-    value::{wasmer_value, wasmer_value_t, wasmer_value_tag},
+    value::wasmer_value_t,
     wasmer_result_t,
 };
 use libc::{c_char, c_int, c_void, uint32_t, uint8_t};
@@ -160,25 +160,13 @@ pub unsafe extern "C" fn wasmer_instance_call(
     match result {
         Ok(results_vec) => {
             if !results_vec.is_empty() {
-                let ret = match results_vec[0] {
-                    Value::I32(x) => wasmer_value_t {
-                        tag: wasmer_value_tag::WASM_I32,
-                        value: wasmer_value { I32: x },
-                    },
-                    Value::I64(x) => wasmer_value_t {
-                        tag: wasmer_value_tag::WASM_I64,
-                        value: wasmer_value { I64: x },
-                    },
-                    Value::F32(x) => wasmer_value_t {
-                        tag: wasmer_value_tag::WASM_F32,
-                        value: wasmer_value { F32: x },
-                    },
-                    Value::F64(x) => wasmer_value_t {
-                        tag: wasmer_value_tag::WASM_F64,
-                        value: wasmer_value { F64: x },
-                    },
-                };
-                results[0] = ret;
+                match wasmer_value_t::try_from_value(results_vec[0].clone()) {
+                    Ok(ret) => results[0] = ret,
+                    Err(err) => {
+                        update_last_error(err);
+                        return wasmer_result_t::WASMER_ERROR;
+                    }
+                }
             }
             wasmer_result_t::WASMER_OK
         }
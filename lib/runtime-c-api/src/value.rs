@@ -1,5 +1,6 @@
 //! Wasm values.
 
+use crate::error::CApiError;
 use libc::{int32_t, int64_t};
 use wasmer_runtime::Value;
 use wasmer_runtime_core::types::Type;
@@ -34,7 +35,7 @@ pub struct wasmer_value_t {
 impl From<wasmer_value_t> for Value {
     fn from(v: wasmer_value_t) -> Self {
         unsafe {
-            #[allow(unreachable_patterns, non_snake_case)]
+            #[allow(non_snake_case)]
             match v {
                 wasmer_value_t {
                     tag: wasmer_value_tag::WASM_I32,
@@ -52,15 +53,15 @@ impl From<wasmer_value_t> for Value {
                     tag: wasmer_value_tag::WASM_F64,
                     value: wasmer_value { F64 },
                 } => Value::F64(F64),
-                _ => panic!("not implemented"),
             }
         }
     }
 }
 
-impl From<Value> for wasmer_value_t {
-    fn from(val: Value) -> Self {
-        match val {
+impl wasmer_value_t {
+    /// Converts `val`, or fails for the types that have no `wasmer_value_tag`.
+    pub(crate) fn try_from_value(val: Value) -> Result<Self, CApiError> {
+        Ok(match val {
             Value::I32(x) => wasmer_value_t {
                 tag: wasmer_value_tag::WASM_I32,
                 value: wasmer_value { I32: x },
@@ -77,43 +78,37 @@ impl From<Value> for wasmer_value_t {
                 tag: wasmer_value_tag::WASM_F64,
                 value: wasmer_value { F64: x },
             },
-        }
+            _ => return Err(unsupported_type(val.ty())),
+        })
     }
 }
 
-impl From<Type> for wasmer_value_tag {
-    fn from(ty: Type) -> Self {
-        #[allow(unreachable_patterns)]
-        match ty {
+impl wasmer_value_tag {
+    /// Converts `ty`, or fails for the types that have no `wasmer_value_tag`.
+    pub(crate) fn try_from_type(ty: Type) -> Result<Self, CApiError> {
+        Ok(match ty {
             Type::I32 => wasmer_value_tag::WASM_I32,
             Type::I64 => wasmer_value_tag::WASM_I64,
             Type::F32 => wasmer_value_tag::WASM_F32,
             Type::F64 => wasmer_value_tag::WASM_F64,
-            _ => panic!("not implemented"),
-        }
+            _ => return Err(unsupported_type(ty)),
+        })
     }
 }
 
 impl From<wasmer_value_tag> for Type {
     fn from(v: wasmer_value_tag) -> Self {
-        #[allow(unreachable_patterns)]
         match v {
             wasmer_value_tag::WASM_I32 => Type::I32,
             wasmer_value_tag::WASM_I64 => Type::I64,
             wasmer_value_tag::WASM_F32 => Type::F32,
             wasmer_value_tag::WASM_F64 => Type::F64,
-            _ => panic!("not implemented"),
         }
     }
 }
 
-impl From<&wasmer_runtime::wasm::Type> for wasmer_value_tag {
-    fn from(ty: &Type) -> Self {
-        match *ty {
-            Type::I32 => wasmer_value_tag::WASM_I32,
-            Type::I64 => wasmer_value_tag::WASM_I64,
-            Type::F32 => wasmer_value_tag::WASM_F32,
-            Type::F64 => wasmer_value_tag::WASM_F64,
-        }
+fn unsupported_type(ty: Type) -> CApiError {
+    CApiError {
+        msg: format!("the C API does not support values of type {}", ty),
     }
 }
@@ -229,9 +229,16 @@ int wasmer_exports_len(wasmer_exports_t *exports);
 void wasmer_global_destroy(wasmer_global_t *global);
 
 /// Gets the value stored by the given Global
+///
+/// Globals created by `wasmer_global_new` always have a `wasmer_value_tag` type.
+/// For other globals, an `i32` zero is returned and the error is recorded, use
+/// `wasmer_last_error_length` and `wasmer_last_error_message` to get it.
 wasmer_value_t wasmer_global_get(wasmer_global_t *global);
 
 /// Returns a descriptor (type, mutability) of the given Global
+///
+/// Like `wasmer_global_get`, the kind is `i32` for globals of other types, and
+/// the error is recorded.
 wasmer_global_descriptor_t wasmer_global_get_descriptor(wasmer_global_t *global);
 
 /// Creates a new Global and returns a pointer to it.
@@ -1,5 +1,6 @@
 use crate::{
     error::CompileResult,
+    metering::CostTable,
     module::ModuleInner,
     typed_func::Wasm,
     types::{LocalFuncIndex, SigIndex},
@@ -11,7 +12,7 @@ use crate::{
     module::ModuleInfo,
     sys::Memory,
 };
-use std::{any::Any, ptr::NonNull};
+use std::{any::Any, cell::RefCell, mem, ptr::NonNull};
 
 use hashbrown::HashMap;
 
@@ -43,11 +44,17 @@ impl Token {
 pub struct CompilerConfig {
     /// Symbol information generated from emscripten; used for more detailed debug messages
     pub symbol_map: Option<HashMap<u32, String>>,
+    /// When set, the generated code consumes fuel according to this table
+    /// and stops once the instance runs out of fuel.
+    pub metering: Option<CostTable>,
 }
 
 impl Default for CompilerConfig {
     fn default() -> CompilerConfig {
-        CompilerConfig { symbol_map: None }
+        CompilerConfig {
+            symbol_map: None,
+            metering: None,
+        }
     }
 }
 
@@ -63,6 +70,9 @@ pub trait Compiler {
     ) -> CompileResult<ModuleInner>;
 
     unsafe fn from_cache(&self, cache: Artifact, _: Token) -> Result<ModuleInner, CacheError>;
+
+    /// The backend that this compiler generates code with.
+    fn backend(&self) -> Backend;
 }
 
 pub trait RunnableModule: Send + Sync {
@@ -80,6 +90,62 @@ pub trait RunnableModule: Send + Sync {
     fn get_trampoline(&self, info: &ModuleInfo, sig_index: SigIndex) -> Option<Wasm>;
 
     unsafe fn do_early_trap(&self, data: Box<dyn Any>) -> !;
+
+    /// Finds the local function whose code contains `address`, so that
+    /// traps can report the function they happened in.
+    fn get_local_func_index_at(
+        &self,
+        _info: &ModuleInfo,
+        _address: usize,
+    ) -> Option<LocalFuncIndex> {
+        None
+    }
+}
+
+/// The maximum number of frames collected for the backtrace of a trap.
+pub const MAX_TRAP_FRAMES: usize = 64;
+
+thread_local! {
+    static TRAP_FRAMES: RefCell<Vec<usize>> = RefCell::new(Vec::new());
+}
+
+/// Records the frames of the trap that happened on this thread, starting with the
+/// address of the instruction that trapped and followed by the return addresses.
+///
+/// Backends call this when they catch a trap, before returning from `invoke`.
+pub fn record_trap_frames(frames: &[usize]) {
+    TRAP_FRAMES.with(|cell| *cell.borrow_mut() = frames.to_vec());
+}
+
+pub(crate) fn take_trap_frames() -> Vec<usize> {
+    TRAP_FRAMES.with(|cell| mem::replace(&mut *cell.borrow_mut(), Vec::new()))
+}
+
+/// Collects the frames of a trap at `ip` into `frames`, by following the frame
+/// pointers from `fp`, and returns how many were written.
+///
+/// Only the stack between `sp` and `stack_end` is read, and nothing is allocated,
+/// so this can be called from a signal handler with the registers of the trapping code.
+pub unsafe fn walk_trap_frames(
+    ip: usize,
+    fp: usize,
+    sp: usize,
+    stack_end: usize,
+    frames: &mut [usize; MAX_TRAP_FRAMES],
+) -> usize {
+    frames[0] = ip;
+    let mut len = 1;
+    let mut fp = fp;
+    let mut lowest = sp;
+    // each frame starts with the frame pointer of its caller, then the return address
+    while len < MAX_TRAP_FRAMES && fp >= lowest && fp % 8 == 0 && fp + 16 <= stack_end {
+        let frame = fp as *const usize;
+        frames[len] = *frame.add(1);
+        len += 1;
+        lowest = fp + 16;
+        fp = *frame;
+    }
+    len
 }
 
 pub trait CacheGen: Send + Sync {
@@ -1,21 +1,27 @@
 use crate::{
-    error::{LinkError, LinkResult},
+    error::{CreationError, LinkError, LinkResult},
     export::{Context, Export},
     global::Global,
     import::ImportObject,
+    limits::ResourceLimiter,
     memory::Memory,
     module::{ImportName, ModuleInfo, ModuleInner},
     sig_registry::SigRegistry,
     structures::{BoxedMap, Map, SliceMap, TypedIndex},
-    table::Table,
+    table::{AnyRefRoots, FuncRefs, Table},
     types::{
-        ImportedFuncIndex, ImportedGlobalIndex, ImportedMemoryIndex, ImportedTableIndex,
-        Initializer, LocalFuncIndex, LocalGlobalIndex, LocalMemoryIndex, LocalOrImport,
-        LocalTableIndex, SigIndex, Value,
+        DataIndex, ElemIndex, FuncIndex, ImportedFuncIndex, ImportedGlobalIndex,
+        ImportedMemoryIndex, ImportedTableIndex, Initializer, LocalFuncIndex, LocalGlobalIndex,
+        LocalMemoryIndex, LocalOrImport, LocalTableIndex, SigIndex, Value,
     },
+    units::Pages,
     vm,
 };
-use std::slice;
+use hashbrown::HashMap;
+use std::{
+    slice,
+    sync::{atomic::AtomicBool, Arc},
+};
 
 #[derive(Debug)]
 pub struct LocalBacking {
@@ -29,6 +35,18 @@ pub struct LocalBacking {
 
     pub(crate) dynamic_sigindices: BoxedMap<SigIndex, vm::SigId>,
     pub(crate) local_functions: BoxedMap<LocalFuncIndex, *const vm::Func>,
+
+    /// The passive segments of the module, until `data.drop` or `elem.drop` drops them.
+    pub(crate) passive_data: HashMap<DataIndex, Vec<u8>>,
+    pub(crate) passive_elements: HashMap<ElemIndex, Vec<FuncIndex>>,
+
+    /// The host objects passed to the instance, as arguments or results of calls.
+    pub(crate) anyref_roots: AnyRefRoots,
+    /// The functions the instance handed out references to, see `FuncRef`.
+    pub(crate) func_refs: FuncRefs,
+
+    /// Shared with the `InterruptHandle`s of the instance.
+    pub(crate) interrupted: Arc<AtomicBool>,
 }
 
 // impl LocalBacking {
@@ -42,19 +60,25 @@ pub struct LocalBacking {
 // }
 
 impl LocalBacking {
-    pub(crate) fn new(module: &ModuleInner, imports: &ImportBacking, vmctx: *mut vm::Ctx) -> Self {
-        let mut memories = Self::generate_memories(module);
-        let mut tables = Self::generate_tables(module);
+    pub(crate) fn new(
+        module: &ModuleInner,
+        imports: &ImportBacking,
+        vmctx: *mut vm::Ctx,
+        limiter: Option<Arc<dyn ResourceLimiter>>,
+    ) -> Result<Self, CreationError> {
+        let mut memories = Self::generate_memories(module, &limiter)?;
+        let mut tables = Self::generate_tables(module, &limiter)?;
         let mut globals = Self::generate_globals(module, imports);
 
         let vm_memories = Self::finalize_memories(module, imports, &mut memories);
-        let vm_tables = Self::finalize_tables(module, imports, &mut tables, vmctx);
+        let mut func_refs = FuncRefs::default();
+        let vm_tables = Self::finalize_tables(module, imports, &mut tables, vmctx, &mut func_refs);
         let vm_globals = Self::finalize_globals(&mut globals);
 
         let dynamic_sigindices = Self::generate_sigindices(&module.info);
         let local_functions = Self::generate_local_functions(module);
 
-        Self {
+        Ok(Self {
             memories,
             tables,
             globals,
@@ -65,7 +89,15 @@ impl LocalBacking {
 
             dynamic_sigindices,
             local_functions,
-        }
+
+            passive_data: module.info.passive_data.clone(),
+            passive_elements: module.info.passive_elements.clone(),
+
+            anyref_roots: AnyRefRoots::default(),
+            func_refs,
+
+            interrupted: Arc::new(AtomicBool::new(false)),
+        })
     }
 
     fn generate_local_functions(module: &ModuleInner) -> BoxedMap<LocalFuncIndex, *const vm::Func> {
@@ -93,13 +125,29 @@ impl LocalBacking {
             .into_boxed_map()
     }
 
-    fn generate_memories(module: &ModuleInner) -> BoxedMap<LocalMemoryIndex, Memory> {
+    fn generate_memories(
+        module: &ModuleInner,
+        limiter: &Option<Arc<dyn ResourceLimiter>>,
+    ) -> Result<BoxedMap<LocalMemoryIndex, Memory>, CreationError> {
         let mut memories = Map::with_capacity(module.info.memories.len());
         for (_, &desc) in &module.info.memories {
-            memories.push(Memory::new(desc).expect("unable to create memory"));
+            if let Some(limiter) = limiter {
+                if !limiter.memory_growing(Pages(0), desc.minimum, desc.maximum) {
+                    return Err(CreationError::ResourceLimitExceeded);
+                }
+            }
+
+            let memory = Memory::new(desc).map_err(|err| {
+                // Give back what the limiter reserved for the memory.
+                if let Some(limiter) = limiter {
+                    limiter.memory_grow_failed(Pages(0), desc.minimum);
+                }
+                err
+            })?;
+            memories.push(memory.with_limiter(limiter.clone()));
         }
 
-        memories.into_boxed_map()
+        Ok(memories.into_boxed_map())
     }
 
     fn finalize_memories(
@@ -163,15 +211,29 @@ impl LocalBacking {
             .into_boxed_map()
     }
 
-    fn generate_tables(module: &ModuleInner) -> BoxedMap<LocalTableIndex, Table> {
+    fn generate_tables(
+        module: &ModuleInner,
+        limiter: &Option<Arc<dyn ResourceLimiter>>,
+    ) -> Result<BoxedMap<LocalTableIndex, Table>, CreationError> {
         let mut tables = Map::with_capacity(module.info.tables.len());
 
         for (_, &table_desc) in module.info.tables.iter() {
-            let table = Table::new(table_desc).unwrap();
-            tables.push(table);
+            if let Some(limiter) = limiter {
+                if !limiter.table_growing(0, table_desc.minimum, table_desc.maximum) {
+                    return Err(CreationError::ResourceLimitExceeded);
+                }
+            }
+
+            let table = Table::new(table_desc).map_err(|err| {
+                if let Some(limiter) = limiter {
+                    limiter.table_grow_failed(0, table_desc.minimum);
+                }
+                err
+            })?;
+            tables.push(table.with_limiter(limiter.clone()));
         }
 
-        tables.into_boxed_map()
+        Ok(tables.into_boxed_map())
     }
 
     #[allow(clippy::cast_ptr_alignment)]
@@ -180,6 +242,7 @@ impl LocalBacking {
         imports: &ImportBacking,
         tables: &mut SliceMap<LocalTableIndex, Table>,
         vmctx: *mut vm::Ctx,
+        func_refs: &mut FuncRefs,
     ) -> BoxedMap<LocalTableIndex, *mut vm::LocalTable> {
         for init in &module.info.elem_initializers {
             let init_base = match init.base {
@@ -194,85 +257,47 @@ impl LocalBacking {
                 }
             } as usize;
 
-            match init.table_index.local_or_import(&module.info) {
-                LocalOrImport::Local(local_table_index) => {
-                    let table = &tables[local_table_index];
-
-                    if (table.size() as usize) < init_base + init.elements.len() {
-                        let delta = (init_base + init.elements.len()) - table.size() as usize;
-                        // Grow the table if it's too small.
-                        table.grow(delta as u32).expect("couldn't grow table");
-                    }
+            let table = match init.table_index.local_or_import(&module.info) {
+                LocalOrImport::Local(local_table_index) => &tables[local_table_index],
+                LocalOrImport::Import(import_table_index) => &imports.tables[import_table_index],
+            };
 
-                    table.anyfunc_direct_access_mut(|elements| {
-                        for (i, &func_index) in init.elements.iter().enumerate() {
-                            let sig_index = module.info.func_assoc[func_index];
-                            // let signature = &module.info.signatures[sig_index];
-                            let signature = SigRegistry
-                                .lookup_signature_ref(&module.info.signatures[sig_index]);
-                            let sig_id =
-                                vm::SigId(SigRegistry.lookup_sig_index(signature).index() as u32);
-
-                            let (func, ctx) = match func_index.local_or_import(&module.info) {
-                                LocalOrImport::Local(local_func_index) => (
-                                    module
-                                        .runnable_module
-                                        .get_func(&module.info, local_func_index)
-                                        .unwrap()
-                                        .as_ptr()
-                                        as *const vm::Func,
-                                    vmctx,
-                                ),
-                                LocalOrImport::Import(imported_func_index) => {
-                                    let vm::ImportedFunc { func, vmctx } =
-                                        imports.vm_functions[imported_func_index];
-                                    (func, vmctx)
-                                }
-                            };
-
-                            elements[init_base + i] = vm::Anyfunc { func, ctx, sig_id };
-                        }
-                    });
-                }
-                LocalOrImport::Import(import_table_index) => {
-                    let table = &imports.tables[import_table_index];
+            if (table.size() as usize) < init_base + init.elements.len() {
+                let delta = (init_base + init.elements.len()) - table.size() as usize;
+                // Grow the table if it's too small.
+                table.grow(delta as u32).expect("couldn't grow table");
+            }
 
-                    if (table.size() as usize) < init_base + init.elements.len() {
-                        let delta = (init_base + init.elements.len()) - table.size() as usize;
-                        // Grow the table if it's too small.
-                        table.grow(delta as u32).expect("couldn't grow table");
+            for (i, &func_index) in init.elements.iter().enumerate() {
+                let sig_index = module.info.func_assoc[func_index];
+                let signature =
+                    SigRegistry.lookup_signature_ref(&module.info.signatures[sig_index]);
+                let sig_id = vm::SigId(SigRegistry.lookup_sig_index(signature).index() as u32);
+
+                let (func, ctx) = match func_index.local_or_import(&module.info) {
+                    LocalOrImport::Local(local_func_index) => (
+                        module
+                            .runnable_module
+                            .get_func(&module.info, local_func_index)
+                            .unwrap()
+                            .as_ptr() as *const vm::Func,
+                        vmctx,
+                    ),
+                    LocalOrImport::Import(imported_func_index) => {
+                        let vm::ImportedFunc { func, vmctx } =
+                            imports.vm_functions[imported_func_index];
+                        (func, vmctx)
                     }
-
-                    table.anyfunc_direct_access_mut(|elements| {
-                        for (i, &func_index) in init.elements.iter().enumerate() {
-                            let sig_index = module.info.func_assoc[func_index];
-                            let signature = SigRegistry
-                                .lookup_signature_ref(&module.info.signatures[sig_index]);
-                            // let signature = &module.info.signatures[sig_index];
-                            let sig_id =
-                                vm::SigId(SigRegistry.lookup_sig_index(signature).index() as u32);
-
-                            let (func, ctx) = match func_index.local_or_import(&module.info) {
-                                LocalOrImport::Local(local_func_index) => (
-                                    module
-                                        .runnable_module
-                                        .get_func(&module.info, local_func_index)
-                                        .unwrap()
-                                        .as_ptr()
-                                        as *const vm::Func,
-                                    vmctx,
-                                ),
-                                LocalOrImport::Import(imported_func_index) => {
-                                    let vm::ImportedFunc { func, vmctx } =
-                                        imports.vm_functions[imported_func_index];
-                                    (func, vmctx)
-                                }
-                            };
-
-                            elements[init_base + i] = vm::Anyfunc { func, ctx, sig_id };
-                        }
-                    });
-                }
+                };
+
+                // The table was grown to fit the segment.
+                table
+                    .set_anyfunc(
+                        (init_base + i) as u32,
+                        vm::Anyfunc { func, ctx, sig_id },
+                        func_refs,
+                    )
+                    .unwrap();
             }
         }
 
@@ -330,6 +355,11 @@ pub struct ImportBacking {
     pub(crate) vm_memories: BoxedMap<ImportedMemoryIndex, *mut vm::LocalMemory>,
     pub(crate) vm_tables: BoxedMap<ImportedTableIndex, *mut vm::LocalTable>,
     pub(crate) vm_globals: BoxedMap<ImportedGlobalIndex, *mut vm::LocalGlobal>,
+
+    /// The contexts that imported host functions with an environment are called with.
+    pub(crate) func_ctxs: Vec<Box<vm::FuncCtx>>,
+    /// The environments the `func_ctxs` point to, kept alive for as long as the instance.
+    pub(crate) func_envs: Vec<vm::FuncEnvRef>,
 }
 
 impl ImportBacking {
@@ -341,11 +371,12 @@ impl ImportBacking {
         let mut failed = false;
         let mut link_errors = vec![];
 
-        let vm_functions = import_functions(module, imports, vmctx).unwrap_or_else(|le| {
-            failed = true;
-            link_errors.extend(le);
-            Map::new().into_boxed_map()
-        });
+        let (vm_functions, func_ctxs, func_envs) = import_functions(module, imports, vmctx)
+            .unwrap_or_else(|le| {
+                failed = true;
+                link_errors.extend(le);
+                (Map::new().into_boxed_map(), Vec::new(), Vec::new())
+            });
 
         let (memories, vm_memories) = import_memories(module, imports).unwrap_or_else(|le| {
             failed = true;
@@ -377,6 +408,9 @@ impl ImportBacking {
                 vm_memories,
                 vm_tables,
                 vm_globals,
+
+                func_ctxs,
+                func_envs,
             })
         }
     }
@@ -390,9 +424,15 @@ fn import_functions(
     module: &ModuleInner,
     imports: &ImportObject,
     vmctx: *mut vm::Ctx,
-) -> LinkResult<BoxedMap<ImportedFuncIndex, vm::ImportedFunc>> {
+) -> LinkResult<(
+    BoxedMap<ImportedFuncIndex, vm::ImportedFunc>,
+    Vec<Box<vm::FuncCtx>>,
+    Vec<vm::FuncEnvRef>,
+)> {
     let mut link_errors = vec![];
     let mut functions = Map::with_capacity(module.info.imported_functions.len());
+    let mut func_ctxs = vec![];
+    let mut func_envs = vec![];
     for (
         index,
         ImportName {
@@ -415,14 +455,27 @@ fn import_functions(
                 func,
                 ctx,
                 signature,
+                func_env,
             }) => {
                 if *expected_sig == *signature {
+                    let vmctx = match (ctx, func_env) {
+                        (Context::External(ctx), _) => ctx,
+                        (Context::Internal, None) => vmctx,
+                        // The function finds its environment next to the vmctx.
+                        (Context::Internal, Some(func_env)) => {
+                            let mut func_ctx = Box::new(vm::FuncCtx {
+                                vmctx,
+                                func_env: func_env.as_ptr(),
+                            });
+                            let func_ctx_ptr = &mut *func_ctx as *mut vm::FuncCtx as *mut vm::Ctx;
+                            func_ctxs.push(func_ctx);
+                            func_envs.push(func_env.clone());
+                            func_ctx_ptr
+                        }
+                    };
                     functions.push(vm::ImportedFunc {
                         func: func.inner(),
-                        vmctx: match ctx {
-                            Context::External(ctx) => ctx,
-                            Context::Internal => vmctx,
-                        },
+                        vmctx,
                     });
                 } else {
                     link_errors.push(LinkError::IncorrectImportSignature {
@@ -460,7 +513,7 @@ fn import_functions(
     if link_errors.len() > 0 {
         Err(link_errors)
     } else {
-        Ok(functions.into_boxed_map())
+        Ok((functions.into_boxed_map(), func_ctxs, func_envs))
     }
 }
 
@@ -1,4 +1,5 @@
 use crate::{
+    backend::Backend,
     module::{Module, ModuleInfo},
     sys::Memory,
 };
@@ -19,6 +20,15 @@ pub enum Error {
     Unknown(String),
     InvalidFile(InvalidFileType),
     InvalidatedCache,
+    /// The artifact was compiled with a different backend than
+    /// the one that was asked to load it.
+    BackendMismatch {
+        expected: Backend,
+        found: Backend,
+    },
+    /// The artifact was compiled for a different target or for a cpu
+    /// with a different set of features than the current host.
+    TargetMismatch,
 }
 
 impl From<io::Error> for Error {
@@ -70,7 +80,7 @@ impl WasmHash {
     }
 }
 
-const CURRENT_CACHE_VERSION: u64 = 0;
+const CURRENT_CACHE_VERSION: u64 = 1;
 static WASMER_CACHE_MAGIC: [u8; 8] = *b"WASMER\0\0";
 
 /// The header of a cache file.
@@ -79,6 +89,59 @@ struct ArtifactHeader {
     magic: [u8; 8], // [W, A, S, M, E, R, \0, \0]
     version: u64,
     data_len: u64,
+    backend: u64,
+    target: [u8; 32],
+}
+
+fn backend_to_id(backend: Backend) -> u64 {
+    match backend {
+        Backend::Cranelift => 0,
+        Backend::Singlepass => 1,
+        Backend::LLVM => 2,
+    }
+}
+
+fn backend_from_id(id: u64) -> Option<Backend> {
+    match id {
+        0 => Some(Backend::Cranelift),
+        1 => Some(Backend::Singlepass),
+        2 => Some(Backend::LLVM),
+        _ => None,
+    }
+}
+
+/// A fingerprint of the target that code is compiled for.
+///
+/// This covers the architecture, the operating system and, on x86_64,
+/// the cpu features that the backends may generate code for.
+fn target_fingerprint() -> [u8; 32] {
+    let mut state = blake2bp::State::new();
+    state.update(std::env::consts::ARCH.as_bytes());
+    state.update(std::env::consts::OS.as_bytes());
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        let features = [
+            is_x86_feature_detected!("sse2"),
+            is_x86_feature_detected!("sse3"),
+            is_x86_feature_detected!("ssse3"),
+            is_x86_feature_detected!("sse4.1"),
+            is_x86_feature_detected!("sse4.2"),
+            is_x86_feature_detected!("popcnt"),
+            is_x86_feature_detected!("avx"),
+            is_x86_feature_detected!("avx2"),
+            is_x86_feature_detected!("bmi1"),
+            is_x86_feature_detected!("bmi2"),
+            is_x86_feature_detected!("lzcnt"),
+        ];
+        for &feature in features.iter() {
+            state.update(&[feature as u8]);
+        }
+    }
+
+    let mut fingerprint = [0u8; 32];
+    fingerprint.copy_from_slice(&state.finalize().as_bytes()[0..32]);
+    fingerprint
 }
 
 impl ArtifactHeader {
@@ -154,12 +217,50 @@ impl Artifact {
         }
     }
 
+    /// Deserialize an artifact.
+    ///
+    /// This fails with `Error::TargetMismatch` if the artifact was
+    /// compiled for a different target than the current host.
     pub fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
-        let (_, body_slice) = ArtifactHeader::read_from_slice(bytes)?;
+        Self::deserialize_checked(bytes, None)
+    }
+
+    /// Deserialize an artifact that must have been compiled with `backend`.
+    ///
+    /// Like [`deserialize`], but this fails with `Error::BackendMismatch`
+    /// before reading the body if the header names another backend.
+    ///
+    /// [`deserialize`]: #method.deserialize
+    pub fn deserialize_for(bytes: &[u8], backend: Backend) -> Result<Self, Error> {
+        Self::deserialize_checked(bytes, Some(backend))
+    }
 
-        let inner = serde_bench::deserialize(body_slice)
+    fn deserialize_checked(bytes: &[u8], expected: Option<Backend>) -> Result<Self, Error> {
+        let (header, body_slice) = ArtifactHeader::read_from_slice(bytes)?;
+
+        if header.target != target_fingerprint() {
+            return Err(Error::TargetMismatch);
+        }
+        let header_backend = backend_from_id(header.backend)
+            .ok_or_else(|| Error::DeserializeError("unknown backend in header".to_string()))?;
+        if let Some(expected) = expected {
+            if header_backend != expected {
+                return Err(Error::BackendMismatch {
+                    expected,
+                    found: header_backend,
+                });
+            }
+        }
+
+        let inner: ArtifactInner = serde_bench::deserialize(body_slice)
             .map_err(|e| Error::DeserializeError(format!("{:#?}", e)))?;
 
+        if inner.info.backend != header_backend {
+            return Err(Error::DeserializeError(
+                "the backend in the header doesn't match the module".to_string(),
+            ));
+        }
+
         Ok(Artifact { inner })
     }
 
@@ -167,6 +268,11 @@ impl Artifact {
         &self.inner.info
     }
 
+    /// The backend that was used to compile this artifact.
+    pub fn backend(&self) -> Backend {
+        self.inner.info.backend
+    }
+
     #[doc(hidden)]
     pub fn consume(self) -> (ModuleInfo, Box<[u8]>, Memory) {
         (
@@ -181,6 +287,8 @@ impl Artifact {
             magic: WASMER_CACHE_MAGIC,
             version: CURRENT_CACHE_VERSION,
             data_len: 0,
+            backend: backend_to_id(self.backend()),
+            target: target_fingerprint(),
         };
 
         let mut buffer = cache_header.as_slice().to_vec();
@@ -199,7 +307,12 @@ impl Artifact {
 
 /// A generic cache for storing and loading compiled wasm modules.
 ///
+/// Implementations should load an artifact with a compiler for the backend
+/// recorded in it (see [`Artifact::backend`]) or fail with `Error::BackendMismatch`.
+///
 /// The `wasmer-runtime` supplies a naive `FileSystemCache` api.
+///
+/// [`Artifact::backend`]: struct.Artifact.html#method.backend
 pub trait Cache {
     type LoadError: fmt::Debug;
     type StoreError: fmt::Debug;
@@ -211,3 +324,22 @@ pub trait Cache {
 /// A unique ID generated from the version of Wasmer for use with cache versioning
 pub const WASMER_VERSION_HASH: &'static str =
     include_str!(concat!(env!("OUT_DIR"), "/wasmer_version_hash.txt"));
+
+#[cfg(test)]
+mod cache_tests {
+    use super::{backend_from_id, backend_to_id, target_fingerprint};
+    use crate::backend::Backend;
+
+    #[test]
+    fn test_backend_id_roundtrip() {
+        for &backend in &[Backend::Cranelift, Backend::Singlepass, Backend::LLVM] {
+            assert_eq!(backend_from_id(backend_to_id(backend)), Some(backend));
+        }
+        assert_eq!(backend_from_id(42), None);
+    }
+
+    #[test]
+    fn test_target_fingerprint_is_stable() {
+        assert_eq!(target_fingerprint(), target_fingerprint());
+    }
+}
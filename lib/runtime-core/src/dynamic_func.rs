@@ -0,0 +1,360 @@
+//! Host functions whose signature is only known at runtime.
+//!
+//! Imports are called with the native calling convention of their signature,
+//! so each `DynamicFunc` gets a small x86-64 trampoline when it is created.
+//! The trampoline spills the arguments it was called with into an array,
+//! which is handed to the host function as `Value`s.
+use crate::{
+    error::CreationError,
+    export::{Context, Export, FuncPointer},
+    import::IsExport,
+    sys::{Memory, Protect},
+    table::{AnyRef, FuncRef},
+    types::{FuncSig, Type, Value},
+    vm::{self, Ctx},
+};
+use std::{any::Any, cell::Cell, panic, slice, sync::Arc};
+
+/// The registers integer arguments are passed in after the vmctx, which is in `rdi`.
+const INT_ARG_REGS: [u8; 5] = [
+    6, /* rsi */
+    2, /* rdx */
+    1, /* rcx */
+    8, /* r8 */
+    9, /* r9 */
+];
+/// Float arguments are passed in `xmm0` to `xmm7`.
+const FLOAT_ARG_REGS: u8 = 8;
+
+const RAX: u8 = 0;
+const RDI: u8 = 7;
+
+thread_local! {
+    static PENDING_TRAP: Cell<Option<Box<dyn Any>>> = Cell::new(None);
+}
+
+type HostFn = dyn Fn(&mut Ctx, &[Value]) -> Result<Vec<Value>, Box<dyn Any>>;
+
+struct DynamicContext {
+    signature: Arc<FuncSig>,
+    func: Box<HostFn>,
+}
+
+/// A host function that takes and returns `Value`s, created with a
+/// signature that is only known at runtime.
+///
+/// This is only available on x86-64 Unix systems.
+pub struct DynamicFunc {
+    signature: Arc<FuncSig>,
+    trampoline: Memory,
+    // The trampoline refers to the context by address.
+    _context: Box<DynamicContext>,
+}
+
+impl DynamicFunc {
+    /// Creates a host function with the given signature.
+    ///
+    /// The function is called with the parameters of the signature, and must
+    /// return values of the types of its results, or the call traps. An error
+    /// returned by the function traps the call just like a statically typed
+    /// host function returning an error does.
+    ///
+    /// Signatures with more than one result, or with `v128` values, are not
+    /// supported and return `CreationError::UnsupportedSignature`.
+    pub fn new<F, E>(signature: FuncSig, func: F) -> Result<DynamicFunc, CreationError>
+    where
+        F: Fn(&mut Ctx, &[Value]) -> Result<Vec<Value>, E> + 'static,
+        E: 'static,
+    {
+        let has_v128 = signature
+            .params()
+            .iter()
+            .chain(signature.returns())
+            .any(|&ty| ty == Type::V128);
+        if signature.returns().len() > 1 || has_v128 {
+            return Err(CreationError::UnsupportedSignature(signature));
+        }
+
+        let signature = Arc::new(signature);
+        let context = Box::new(DynamicContext {
+            signature: Arc::clone(&signature),
+            func: Box::new(move |ctx, params| {
+                func(ctx, params).map_err(|err| Box::new(err) as Box<dyn Any>)
+            }),
+        });
+
+        let code = emit_trampoline(&signature, &*context);
+        let mut trampoline = Memory::with_size_protect(code.len(), Protect::ReadWrite)
+            .expect("Could not allocate the trampoline of a dynamic function");
+        unsafe {
+            trampoline.as_slice_mut()[..code.len()].copy_from_slice(&code);
+            trampoline
+                .protect(.., Protect::ReadExec)
+                .expect("Could not make the trampoline of a dynamic function executable");
+        }
+
+        Ok(DynamicFunc {
+            signature,
+            trampoline,
+            _context: context,
+        })
+    }
+
+    pub fn signature(&self) -> &FuncSig {
+        &self.signature
+    }
+}
+
+impl IsExport for DynamicFunc {
+    fn to_export(&self) -> Export {
+        Export::Function {
+            func: unsafe { FuncPointer::new(self.trampoline.as_ptr() as *const vm::Func) },
+            ctx: Context::Internal,
+            signature: Arc::clone(&self.signature),
+            func_env: None,
+        }
+    }
+}
+
+/// Called by the trampolines, with the vmctx, the arguments and a slot for
+/// the return value in `slots`.
+///
+/// When the call fails, the trap is left in `PENDING_TRAP` and a non-zero
+/// value is returned, so that the trampoline can raise it once its frame is gone.
+extern "C" fn enter_host(context: *const DynamicContext, slots: *mut u64) -> u64 {
+    let context = unsafe { &*context };
+    let params_len = context.signature.params().len();
+    let slots = unsafe { slice::from_raw_parts_mut(slots, params_len + 2) };
+    let ctx = unsafe { &mut *(slots[0] as *mut Ctx) };
+
+    let params: Vec<Value> = context
+        .signature
+        .params()
+        .iter()
+        .zip(&slots[1..=params_len])
+        .map(|(ty, &raw)| match ty {
+            Type::I32 => Value::I32(raw as i32),
+            Type::I64 => Value::I64(raw as i64),
+            Type::F32 => Value::F32(f32::from_bits(raw as u32)),
+            Type::F64 => Value::F64(f64::from_bits(raw)),
+            Type::AnyRef => Value::AnyRef(unsafe { AnyRef::from_raw(raw) }),
+            Type::FuncRef => Value::FuncRef(unsafe { FuncRef::from_raw(raw) }),
+            Type::V128 => unreachable!("checked by DynamicFunc::new"),
+        })
+        .collect();
+
+    let err = match panic::catch_unwind(panic::AssertUnwindSafe(|| (context.func)(ctx, &params))) {
+        Ok(Ok(returns)) => {
+            if returns
+                .iter()
+                .map(Value::ty)
+                .eq(context.signature.returns().iter().cloned())
+            {
+                if let Some(value) = returns.first() {
+                    slots[params_len + 1] = match value {
+                        Value::I32(i) => *i as u32 as u64,
+                        Value::I64(i) => *i as u64,
+                        Value::F32(f) => f.to_bits() as u64,
+                        Value::F64(f) => f.to_bits(),
+                        // The caller may keep the object, so it lives as long as the instance.
+                        Value::AnyRef(r) => unsafe { (*ctx.local_backing).anyref_roots.root(r) },
+                        Value::FuncRef(r) => r.as_raw(),
+                        Value::V128(_) => unreachable!("checked by DynamicFunc::new"),
+                    };
+                }
+                return 0;
+            }
+            let types: Vec<Type> = returns.iter().map(Value::ty).collect();
+            Box::new(format!(
+                "dynamic function returned values of types {:?}, expected {:?}",
+                types,
+                context.signature.returns()
+            )) as Box<dyn Any>
+        }
+        Ok(Err(err)) => err,
+        Err(err) => err,
+    };
+
+    PENDING_TRAP.with(|cell| cell.set(Some(err)));
+    1
+}
+
+/// Raises the trap left by `enter_host`. The trampolines jump here as if they
+/// had never been called, as the llvm backend cannot unwind through them.
+#[cfg_attr(nightly, unwind(allowed))]
+extern "C" fn raise_pending_trap(ctx: &mut Ctx) -> ! {
+    let err = PENDING_TRAP
+        .with(|cell| cell.take())
+        .expect("no pending trap to raise");
+    unsafe { (&*ctx.module).runnable_module.do_early_trap(err) }
+}
+
+/// Emits the trampoline of a function with `signature`, following the System V calling convention.
+///
+/// Its frame holds an array of slots: the vmctx, then the arguments, then the return value.
+fn emit_trampoline(signature: &FuncSig, context: *const DynamicContext) -> Vec<u8> {
+    let params_len = signature.params().len();
+    let return_slot = (params_len as u32 + 1) * 8;
+    let frame_size = (return_slot + 8 + 15) & !15;
+
+    let mut code = vec![];
+    // push rbp; mov rbp, rsp; sub rsp, frame_size
+    code.extend_from_slice(&[0x55, 0x48, 0x89, 0xe5, 0x48, 0x81, 0xec]);
+    code.extend_from_slice(&frame_size.to_le_bytes());
+
+    emit_store_gpr(&mut code, RDI, 0);
+    let mut int_regs = INT_ARG_REGS.iter().cloned();
+    let mut float_regs = 0..FLOAT_ARG_REGS;
+    let mut stack_offset: u32 = 16;
+    for (i, ty) in signature.params().iter().enumerate() {
+        let slot = (i as u32 + 1) * 8;
+        let reg = match ty {
+            Type::I32 | Type::I64 | Type::AnyRef | Type::FuncRef => int_regs
+                .next()
+                .map(|reg| emit_store_gpr(&mut code, reg, slot)),
+            Type::F32 | Type::F64 => float_regs
+                .next()
+                .map(|reg| emit_store_xmm(&mut code, reg, slot)),
+            Type::V128 => unreachable!("checked by DynamicFunc::new"),
+        };
+        if reg.is_none() {
+            // mov rax, [rbp + stack_offset]
+            code.extend_from_slice(&[0x48, 0x8b, 0x85]);
+            code.extend_from_slice(&stack_offset.to_le_bytes());
+            emit_store_gpr(&mut code, RAX, slot);
+            stack_offset += 8;
+        }
+    }
+
+    // mov rdi, context; mov rsi, rsp
+    code.extend_from_slice(&[0x48, 0xbf]);
+    code.extend_from_slice(&(context as u64).to_le_bytes());
+    code.extend_from_slice(&[0x48, 0x89, 0xe6]);
+    // mov rax, enter_host; call rax
+    code.extend_from_slice(&[0x48, 0xb8]);
+    code.extend_from_slice(&(enter_host as usize as u64).to_le_bytes());
+    code.extend_from_slice(&[0xff, 0xd0]);
+
+    // mov rdi, [rsp]; mov rcx, [rsp + return_slot]
+    code.extend_from_slice(&[0x48, 0x8b, 0x3c, 0x24, 0x48, 0x8b, 0x8c, 0x24]);
+    code.extend_from_slice(&return_slot.to_le_bytes());
+    // mov rsp, rbp; pop rbp; test rax, rax; jnz trap
+    code.extend_from_slice(&[0x48, 0x89, 0xec, 0x5d, 0x48, 0x85, 0xc0, 0x75, 0x09]);
+    // mov rax, rcx; movq xmm0, rax; ret
+    code.extend_from_slice(&[0x48, 0x89, 0xc8, 0x66, 0x48, 0x0f, 0x6e, 0xc0, 0xc3]);
+    // trap: mov rax, raise_pending_trap; jmp rax
+    code.extend_from_slice(&[0x48, 0xb8]);
+    code.extend_from_slice(&(raise_pending_trap as usize as u64).to_le_bytes());
+    code.extend_from_slice(&[0xff, 0xe0]);
+
+    code
+}
+
+/// mov [rsp + offset], reg
+fn emit_store_gpr(code: &mut Vec<u8>, reg: u8, offset: u32) {
+    let rex_r = if reg >= 8 { 0x04 } else { 0x00 };
+    code.extend_from_slice(&[0x48 | rex_r, 0x89, 0x84 | ((reg & 7) << 3), 0x24]);
+    code.extend_from_slice(&offset.to_le_bytes());
+}
+
+/// movsd [rsp + offset], xmm
+fn emit_store_xmm(code: &mut Vec<u8>, xmm: u8, offset: u32) {
+    code.extend_from_slice(&[0xf2, 0x0f, 0x11, 0x84 | (xmm << 3), 0x24]);
+    code.extend_from_slice(&offset.to_le_bytes());
+}
+
+#[cfg(test)]
+mod dynamic_func_tests {
+    use super::{emit_store_gpr, emit_store_xmm, DynamicFunc};
+    use crate::{
+        error::CreationError,
+        types::{FuncSig, Type, Value},
+        vm::Ctx,
+    };
+    use std::{mem, ptr::NonNull};
+
+    #[test]
+    fn test_store_encodings() {
+        let mut code = vec![];
+        emit_store_gpr(&mut code, 6, 8);
+        emit_store_gpr(&mut code, 9, 16);
+        emit_store_xmm(&mut code, 1, 24);
+        assert_eq!(
+            code,
+            [
+                0x48, 0x89, 0xb4, 0x24, 8, 0, 0, 0, // mov [rsp + 8], rsi
+                0x4c, 0x89, 0x8c, 0x24, 16, 0, 0, 0, // mov [rsp + 16], r9
+                0xf2, 0x0f, 0x11, 0x8c, 0x24, 24, 0, 0, 0, // movsd [rsp + 24], xmm1
+            ]
+        );
+    }
+
+    #[test]
+    fn test_call_trampoline() {
+        let signature = FuncSig::new(
+            vec![
+                Type::I32,
+                Type::I64,
+                Type::F32,
+                Type::F64,
+                Type::I32,
+                Type::I32,
+                Type::I32,
+                Type::I32,
+                Type::I64,
+                Type::F64,
+            ],
+            vec![Type::F64],
+        );
+        let func = DynamicFunc::new(signature, |_ctx: &mut Ctx, params: &[Value]| {
+            let sum = params
+                .iter()
+                .map(|value| match value {
+                    Value::I32(i) => *i as f64,
+                    Value::I64(i) => *i as f64,
+                    Value::F32(f) => *f as f64,
+                    Value::F64(f) => *f,
+                    _ => unreachable!("the signature has no reference parameters"),
+                })
+                .sum::<f64>();
+            Ok::<_, ()>(vec![Value::F64(sum)])
+        })
+        .unwrap();
+
+        type Native =
+            extern "C" fn(*mut Ctx, i32, i64, f32, f64, i32, i32, i32, i32, i64, f64) -> f64;
+        let native: Native = unsafe { mem::transmute(func.trampoline.as_ptr()) };
+        // the last two integers are passed on the stack
+        let sum = native(
+            NonNull::dangling().as_ptr(),
+            1,
+            2,
+            0.5,
+            0.25,
+            3,
+            4,
+            5,
+            6,
+            7,
+            8.0,
+        );
+        assert_eq!(sum, 36.75);
+    }
+
+    #[test]
+    fn test_unsupported_signatures() {
+        let unsupported = vec![
+            FuncSig::new(vec![], vec![Type::I32, Type::I32]),
+            FuncSig::new(vec![Type::V128], vec![]),
+            FuncSig::new(vec![], vec![Type::V128]),
+        ];
+        for signature in unsupported {
+            match DynamicFunc::new(signature, |_ctx: &mut Ctx, _params: &[Value]| {
+                Ok::<_, ()>(vec![])
+            }) {
+                Err(CreationError::UnsupportedSignature(_)) => (),
+                _ => panic!("expected the signature to be unsupported"),
+            }
+        }
+    }
+}
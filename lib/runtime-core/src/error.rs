@@ -1,4 +1,10 @@
-use crate::types::{FuncSig, GlobalDescriptor, MemoryDescriptor, TableDescriptor, Type};
+use crate::backend::{take_trap_frames, RunnableModule};
+use crate::instance::Interrupted;
+use crate::metering::FuelExhausted;
+use crate::module::ModuleInfo;
+use crate::structures::TypedIndex;
+use crate::typed_func::WasmTrapInfo;
+use crate::types::{FuncIndex, FuncSig, GlobalDescriptor, MemoryDescriptor, TableDescriptor, Type};
 use core::borrow::Borrow;
 use std::any::Any;
 
@@ -113,6 +119,77 @@ impl std::fmt::Display for LinkError {
 
 impl std::error::Error for LinkError {}
 
+/// A trap that happened while running WebAssembly code.
+#[derive(Debug, Clone)]
+pub struct Trap {
+    pub info: WasmTrapInfo,
+    /// The wasm frames that were on the stack, starting with the one that trapped.
+    ///
+    /// It is empty when the backend could not tell where the trap happened.
+    pub backtrace: Vec<Frame>,
+}
+
+impl Trap {
+    /// The index of the function that trapped.
+    pub fn func_index(&self) -> Option<FuncIndex> {
+        self.backtrace.first().map(|frame| frame.func_index)
+    }
+
+    /// The offset of the instruction that trapped in the code of its function.
+    pub fn code_offset(&self) -> Option<usize> {
+        self.backtrace.first().map(|frame| frame.code_offset)
+    }
+
+    /// Formats the backtrace like the one of a panic, a frame per line.
+    pub fn format_backtrace(&self) -> String {
+        let mut s = String::from("wasm backtrace:\n");
+        for (i, frame) in self.backtrace.iter().enumerate() {
+            s += &format!("{:4}: {}\n", i, frame);
+        }
+        s
+    }
+}
+
+impl std::fmt::Display for Trap {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.info)?;
+        match self.backtrace.first() {
+            Some(Frame {
+                func_name: Some(name),
+                ..
+            }) => write!(f, " in function `{}`", name),
+            Some(frame) => write!(f, " in function #{}", frame.func_index.index()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A frame of the backtrace of a `Trap`.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub func_index: FuncIndex,
+    /// The name of the function, from the name section or the emscripten symbol map.
+    pub func_name: Option<String>,
+    /// The offset in the machine code of the function, of the instruction that
+    /// trapped in the first frame, and of the return address in the others.
+    pub code_offset: usize,
+}
+
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.func_name {
+            Some(name) => write!(f, "{}", name)?,
+            None => write!(f, "<unnamed>")?,
+        }
+        write!(
+            f,
+            " (function #{}) + {:#x}",
+            self.func_index.index(),
+            self.code_offset
+        )
+    }
+}
+
 /// This is the error type returned when calling
 /// a webassembly function.
 ///
@@ -120,8 +197,72 @@ impl std::error::Error for LinkError {}
 ///
 /// Comparing two `RuntimeError`s always evaluates to false.
 pub enum RuntimeError {
-    Trap { msg: Box<str> },
-    Error { data: Box<dyn Any> },
+    Trap {
+        trap: Trap,
+    },
+    Error {
+        data: Box<dyn Any>,
+    },
+    /// The instance consumed all of the fuel it was given.
+    FuelExhausted,
+    /// The instance was stopped through an `InterruptHandle`.
+    Interrupted,
+}
+
+impl RuntimeError {
+    /// Converts the data an early trap was raised with back into an error.
+    pub(crate) fn from_early_trap(data: Box<dyn Any>) -> RuntimeError {
+        if data.is::<FuelExhausted>() {
+            RuntimeError::FuelExhausted
+        } else if data.is::<Interrupted>() {
+            RuntimeError::Interrupted
+        } else if let Some(&info) = data.downcast_ref::<WasmTrapInfo>() {
+            // Raised by the runtime functions the generated code calls, such as `memory.copy`.
+            RuntimeError::Trap {
+                trap: Trap {
+                    info,
+                    backtrace: vec![],
+                },
+            }
+        } else {
+            RuntimeError::Error { data }
+        }
+    }
+
+    /// Builds the error of a trap, with the backtrace of the frames
+    /// that the backend recorded when it caught the trap.
+    pub(crate) fn from_trap(
+        info: WasmTrapInfo,
+        module_info: &ModuleInfo,
+        runnable: &dyn RunnableModule,
+    ) -> RuntimeError {
+        let mut backtrace = vec![];
+        for (i, &address) in take_trap_frames().iter().enumerate() {
+            // return addresses may be right after the end of the calling function
+            let lookup_address = if i == 0 {
+                address
+            } else {
+                address.saturating_sub(1)
+            };
+            let local_func_index =
+                match runnable.get_local_func_index_at(module_info, lookup_address) {
+                    Some(local_func_index) => local_func_index,
+                    None => break,
+                };
+            let start = runnable
+                .get_func(module_info, local_func_index)
+                .map_or(lookup_address, |func| func.as_ptr() as usize);
+            let func_index = local_func_index.convert_up(module_info);
+            backtrace.push(Frame {
+                func_index,
+                func_name: module_info.func_name(func_index).map(str::to_string),
+                code_offset: address - start,
+            });
+        }
+        RuntimeError::Trap {
+            trap: Trap { info, backtrace },
+        }
+    }
 }
 
 impl PartialEq for RuntimeError {
@@ -133,8 +274,8 @@ impl PartialEq for RuntimeError {
 impl std::fmt::Display for RuntimeError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            RuntimeError::Trap { ref msg } => {
-                write!(f, "WebAssembly trap occured during runtime: {}", msg)
+            RuntimeError::Trap { ref trap } => {
+                write!(f, "WebAssembly trap occured during runtime: {}", trap)
             }
             RuntimeError::Error { data } => {
                 if let Some(s) = data.downcast_ref::<String>() {
@@ -145,6 +286,8 @@ impl std::fmt::Display for RuntimeError {
                     write!(f, "unknown error")
                 }
             }
+            RuntimeError::FuelExhausted => write!(f, "the instance ran out of fuel"),
+            RuntimeError::Interrupted => write!(f, "the instance was interrupted"),
         }
     }
 }
@@ -244,6 +387,8 @@ pub enum CreationError {
     UnableToCreateMemory,
     UnableToCreateTable,
     InvalidDescriptor(String),
+    ResourceLimitExceeded,
+    UnsupportedSignature(FuncSig),
 }
 
 impl PartialEq for CreationError {
@@ -257,11 +402,22 @@ impl std::fmt::Display for CreationError {
         match self {
             CreationError::UnableToCreateMemory => write!(f, "Unable to Create Memory"),
             CreationError::UnableToCreateTable => write!(f, "Unable to Create Table"),
+            CreationError::ResourceLimitExceeded => {
+                write!(
+                    f,
+                    "Unable to create because the resource limit was exceeded"
+                )
+            }
             CreationError::InvalidDescriptor(msg) => write!(
                 f,
                 "Unable to create because the supplied descriptor is invalid: \"{}\"",
                 msg
             ),
+            CreationError::UnsupportedSignature(signature) => write!(
+                f,
+                "Unable to create a function with the unsupported signature {}",
+                signature
+            ),
         }
     }
 }
@@ -370,6 +526,7 @@ pub enum GrowError {
     ExceededMaxPagesForMemory(usize, usize),
     CouldNotProtectMemory(MemoryProtectionError),
     CouldNotCreateMemory(MemoryCreationError),
+    ResourceLimitExceeded,
 }
 
 impl std::fmt::Display for GrowError {
@@ -381,6 +538,9 @@ impl std::fmt::Display for GrowError {
             GrowError::ExceededMaxPagesForMemory(left, added) => write!(f, "Failed to add pages because would exceed maximum number of pages for the memory. Left: {}, Added: {}", left, added),
             GrowError::CouldNotCreateMemory(e) => write!(f, "Grow Error: {}", e),
             GrowError::CouldNotProtectMemory(e) => write!(f, "Grow Error: {}", e),
+            GrowError::ResourceLimitExceeded => {
+                write!(f, "Grow Error: the resource limit was exceeded")
+            }
         }
     }
 }
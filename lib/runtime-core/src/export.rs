@@ -1,6 +1,12 @@
 use crate::{
-    global::Global, instance::InstanceInner, memory::Memory, module::ExportIndex,
-    module::ModuleInner, table::Table, types::FuncSig, vm,
+    global::Global,
+    instance::InstanceInner,
+    memory::Memory,
+    module::ExportIndex,
+    module::ModuleInner,
+    table::Table,
+    types::{ExternDescriptor, FuncSig},
+    vm,
 };
 use hashbrown::hash_map;
 use std::sync::Arc;
@@ -17,12 +23,26 @@ pub enum Export {
         func: FuncPointer,
         ctx: Context,
         signature: Arc<FuncSig>,
+        /// The environment of a host function, shared with the `Func` it was created from.
+        func_env: Option<vm::FuncEnvRef>,
     },
     Memory(Memory),
     Table(Table),
     Global(Global),
 }
 
+impl Export {
+    /// The type of the export, to be compared with the imports of a module.
+    pub fn descriptor(&self) -> ExternDescriptor {
+        match self {
+            Export::Function { signature, .. } => ExternDescriptor::Function((**signature).clone()),
+            Export::Memory(memory) => ExternDescriptor::Memory(memory.descriptor()),
+            Export::Table(table) => ExternDescriptor::Table(table.descriptor()),
+            Export::Global(global) => ExternDescriptor::Global(global.descriptor()),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FuncPointer(*const vm::Func);
 
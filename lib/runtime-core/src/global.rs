@@ -1,6 +1,7 @@
 use crate::{
     export::Export,
     import::IsExport,
+    table::{AnyRef, AnyRefRoots, FuncRef},
     types::{GlobalDescriptor, Type, Value},
     vm,
 };
@@ -9,6 +10,8 @@ use std::{cell::RefCell, fmt, rc::Rc};
 pub struct Global {
     desc: GlobalDescriptor,
     storage: Rc<RefCell<vm::LocalGlobal>>,
+    /// The host objects this global was set to.
+    roots: Rc<RefCell<AnyRefRoots>>,
 }
 
 impl Global {
@@ -44,18 +47,23 @@ impl Global {
             ty: value.ty(),
         };
 
+        let mut roots = AnyRefRoots::default();
         let local_global = vm::LocalGlobal {
             data: match value {
-                Value::I32(x) => x as u64,
-                Value::I64(x) => x as u64,
-                Value::F32(x) => x.to_bits() as u64,
-                Value::F64(x) => x.to_bits(),
+                Value::I32(x) => x as u64 as u128,
+                Value::I64(x) => x as u64 as u128,
+                Value::F32(x) => x.to_bits() as u128,
+                Value::F64(x) => x.to_bits() as u128,
+                Value::AnyRef(x) => roots.root(&x) as u128,
+                Value::FuncRef(x) => x.as_raw() as u128,
+                Value::V128(x) => x,
             },
         };
 
         Self {
             desc,
             storage: Rc::new(RefCell::new(local_global)),
+            roots: Rc::new(RefCell::new(roots)),
         }
     }
 
@@ -75,10 +83,13 @@ impl Global {
             if self.desc.ty == value.ty() {
                 let local_global = vm::LocalGlobal {
                     data: match value {
-                        Value::I32(x) => x as u64,
-                        Value::I64(x) => x as u64,
-                        Value::F32(x) => x.to_bits() as u64,
-                        Value::F64(x) => x.to_bits(),
+                        Value::I32(x) => x as u64 as u128,
+                        Value::I64(x) => x as u64 as u128,
+                        Value::F32(x) => x.to_bits() as u128,
+                        Value::F64(x) => x.to_bits() as u128,
+                        Value::AnyRef(x) => self.roots.borrow_mut().root(&x) as u128,
+                        Value::FuncRef(x) => x.as_raw() as u128,
+                        Value::V128(x) => x,
                     },
                 };
                 *self.storage.borrow_mut() = local_global;
@@ -98,7 +109,10 @@ impl Global {
             Type::I32 => Value::I32(data as i32),
             Type::I64 => Value::I64(data as i64),
             Type::F32 => Value::F32(f32::from_bits(data as u32)),
-            Type::F64 => Value::F64(f64::from_bits(data)),
+            Type::F64 => Value::F64(f64::from_bits(data as u64)),
+            Type::AnyRef => Value::AnyRef(unsafe { AnyRef::from_raw(data as u64) }),
+            Type::FuncRef => Value::FuncRef(unsafe { FuncRef::from_raw(data as u64) }),
+            Type::V128 => Value::V128(data),
         }
     }
 
@@ -118,6 +132,7 @@ impl Clone for Global {
         Self {
             desc: self.desc,
             storage: Rc::clone(&self.storage),
+            roots: Rc::clone(&self.roots),
         }
     }
 }
@@ -130,3 +145,19 @@ impl fmt::Debug for Global {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod global_tests {
+    use super::Global;
+    use crate::types::Value;
+
+    #[test]
+    fn test_v128_global() {
+        let value = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210u128;
+        let global = Global::new_mutable(Value::V128(value));
+        assert_eq!(global.get(), Value::V128(value));
+
+        global.set(Value::V128(!value));
+        assert_eq!(global.get(), Value::V128(!value));
+    }
+}
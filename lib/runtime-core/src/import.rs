@@ -1,10 +1,11 @@
-use crate::export::Export;
+use crate::{export::Export, limits::ResourceLimiter};
 use hashbrown::{hash_map::Entry, HashMap};
 use std::collections::VecDeque;
 use std::{
     cell::{Ref, RefCell},
     ffi::c_void,
     rc::Rc,
+    sync::Arc,
 };
 
 pub trait LikeNamespace {
@@ -47,6 +48,7 @@ impl IsExport for Export {
 pub struct ImportObject {
     map: Rc<RefCell<HashMap<String, Box<dyn LikeNamespace>>>>,
     state_creator: Option<Rc<Fn() -> (*mut c_void, fn(*mut c_void))>>,
+    limiter_creator: Option<Rc<Fn() -> Arc<dyn ResourceLimiter>>>,
 }
 
 impl ImportObject {
@@ -55,6 +57,7 @@ impl ImportObject {
         Self {
             map: Rc::new(RefCell::new(HashMap::new())),
             state_creator: None,
+            limiter_creator: None,
         }
     }
 
@@ -65,6 +68,7 @@ impl ImportObject {
         Self {
             map: Rc::new(RefCell::new(HashMap::new())),
             state_creator: Some(Rc::new(state_creator)),
+            limiter_creator: None,
         }
     }
 
@@ -72,6 +76,37 @@ impl ImportObject {
         self.state_creator.as_ref().map(|state_gen| state_gen())
     }
 
+    /// Limits the memory and tables of the instances created with this `ImportObject`.
+    ///
+    /// `limiter_creator` is called once per instantiation, so that every
+    /// instance gets a limiter of its own.
+    ///
+    /// # Usage:
+    /// ```
+    /// # use wasmer_runtime_core::import::ImportObject;
+    /// # use wasmer_runtime_core::limits::MemoryLimit;
+    /// # use wasmer_runtime_core::units::Pages;
+    /// let mut import_object = ImportObject::new();
+    ///
+    /// // Allow each instance to allocate up to 16 MiB.
+    /// import_object.set_resource_limiter(|| MemoryLimit::new(Pages(256)));
+    /// ```
+    pub fn set_resource_limiter<F, L>(&mut self, limiter_creator: F)
+    where
+        F: Fn() -> L + 'static,
+        L: ResourceLimiter + 'static,
+    {
+        self.limiter_creator = Some(Rc::new(move || {
+            Arc::new(limiter_creator()) as Arc<dyn ResourceLimiter>
+        }));
+    }
+
+    pub(crate) fn create_resource_limiter(&self) -> Option<Arc<dyn ResourceLimiter>> {
+        self.limiter_creator
+            .as_ref()
+            .map(|limiter_gen| limiter_gen())
+    }
+
     /// Register anything that implements `LikeNamespace` as a namespace.
     ///
     /// # Usage:
@@ -116,6 +151,7 @@ impl ImportObject {
         Self {
             map: Rc::clone(&self.map),
             state_creator: self.state_creator.clone(),
+            limiter_creator: self.limiter_creator.clone(),
         }
     }
 
@@ -8,13 +8,20 @@ use crate::{
     memory::Memory,
     module::{ExportIndex, Module, ModuleInfo, ModuleInner},
     sig_registry::SigRegistry,
-    table::Table,
+    table::{AnyRef, FuncRef, Table},
     typed_func::{Func, Wasm, WasmTrapInfo, WasmTypeList},
     types::{FuncIndex, FuncSig, GlobalIndex, LocalOrImport, MemoryIndex, TableIndex, Type, Value},
     vm,
 };
 use smallvec::{smallvec, SmallVec};
-use std::{mem, ptr::NonNull, sync::Arc};
+use std::{
+    mem,
+    ptr::NonNull,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 pub(crate) struct InstanceInner {
     #[allow(dead_code)]
@@ -52,7 +59,12 @@ impl Instance {
         let mut vmctx = unsafe { Box::new(mem::uninitialized()) };
 
         let import_backing = ImportBacking::new(&module, &imports, &mut *vmctx)?;
-        let backing = LocalBacking::new(&module, &import_backing, &mut *vmctx);
+        let backing = LocalBacking::new(
+            &module,
+            &import_backing,
+            &mut *vmctx,
+            imports.create_resource_limiter(),
+        )?;
 
         // When Pin is stablized, this will use `Box::pinned` instead of `Box::new`.
         let mut inner = Box::new(InstanceInner {
@@ -269,8 +281,8 @@ impl Instance {
     /// [`Value`]: enum.Value.html
     ///
     /// # Note:
-    /// This returns `CallResult<Vec<Value>>` in order to support
-    /// the future multi-value returns webassembly feature.
+    /// This returns `CallResult<Vec<Value>>` as functions may
+    /// return several values with the multi-value feature.
     ///
     /// # Usage:
     /// ```
@@ -334,6 +346,58 @@ impl Instance {
         unsafe { &mut *self.inner.vmctx }
     }
 
+    /// Gives this instance `fuel` units of fuel to consume and
+    /// resets the amount of fuel consumed so far.
+    ///
+    /// Once the fuel runs out, calls into the instance fail with
+    /// [`RuntimeError::FuelExhausted`]. Fuel is only consumed by modules
+    /// compiled with [`CompilerConfig::metering`] set.
+    ///
+    /// [`RuntimeError::FuelExhausted`]: error/enum.RuntimeError.html#variant.FuelExhausted
+    /// [`CompilerConfig::metering`]: backend/struct.CompilerConfig.html#structfield.metering
+    pub fn set_fuel(&mut self, fuel: u64) {
+        let internal = &mut self.context_mut().internal;
+        internal.fuel_used = 0;
+        internal.fuel_limit = fuel;
+    }
+
+    /// Returns the amount of fuel consumed since the last call to `set_fuel`.
+    pub fn fuel_consumed(&self) -> u64 {
+        self.context().internal.fuel_used
+    }
+
+    /// Returns the amount of fuel this instance has left.
+    pub fn fuel_remaining(&self) -> u64 {
+        let internal = &self.context().internal;
+        internal.fuel_limit.saturating_sub(internal.fuel_used)
+    }
+
+    /// Returns a handle that can stop this instance from another thread.
+    ///
+    /// # Usage:
+    /// ```
+    /// # use wasmer_runtime_core::Instance;
+    /// # use wasmer_runtime_core::error::{CallError, RuntimeError};
+    /// # fn run_forever(instance: &Instance) {
+    /// let handle = instance.interrupt_handle();
+    ///
+    /// std::thread::spawn(move || {
+    ///     std::thread::sleep(std::time::Duration::from_secs(1));
+    ///     handle.interrupt();
+    /// });
+    ///
+    /// match instance.call("run_forever", &[]) {
+    ///     Err(CallError::Runtime(RuntimeError::Interrupted)) => println!("interrupted"),
+    ///     _ => {}
+    /// }
+    /// # }
+    /// ```
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle {
+            interrupted: Arc::clone(&self.inner.backing.interrupted),
+        }
+    }
+
     /// Returns an iterator over all of the items
     /// exported from this instance.
     pub fn exports(&self) -> ExportIter {
@@ -363,6 +427,8 @@ impl InstanceInner {
                         ctx @ Context::External(_) => ctx,
                     },
                     signature,
+                    // The environment of an imported host function is found through its context.
+                    func_env: None,
                 }
             }
             ExportIndex::Memory(memory_index) => {
@@ -508,15 +574,20 @@ fn call_func_with_index(
         }
     };
 
-    let raw_args: SmallVec<[u64; 8]> = args
-        .iter()
-        .map(|v| match v {
-            Value::I32(i) => *i as u64,
-            Value::I64(i) => *i as u64,
-            Value::F32(f) => f.to_bits() as u64,
-            Value::F64(f) => f.to_bits(),
-        })
-        .collect();
+    // Host objects passed as arguments live as long as the instance, as the callee may keep them.
+    let anyref_roots = unsafe { &mut (*(*local_ctx).local_backing).anyref_roots };
+    let mut raw_args: SmallVec<[u64; 8]> = SmallVec::with_capacity(args.len());
+    for arg in args {
+        match arg {
+            Value::I32(i) => raw_args.push(*i as u64),
+            Value::I64(i) => raw_args.push(*i as u64),
+            Value::F32(f) => raw_args.push(f.to_bits() as u64),
+            Value::F64(f) => raw_args.push(f.to_bits()),
+            Value::AnyRef(r) => raw_args.push(anyref_roots.root(r)),
+            Value::FuncRef(r) => raw_args.push(r.as_raw()),
+            Value::V128(v) => raw_args.extend_from_slice(&v128_to_raw(*v)),
+        }
+    }
 
     let Wasm {
         trampoline,
@@ -545,20 +616,21 @@ fn call_func_with_index(
             Ok(())
         } else {
             if let Some(data) = user_error {
-                Err(RuntimeError::Error { data })
+                Err(RuntimeError::from_early_trap(data))
             } else {
-                Err(RuntimeError::Trap {
-                    msg: trap_info.to_string().into(),
-                })
+                Err(RuntimeError::from_trap(trap_info, info, runnable))
             }
         }
     };
 
-    let raw_to_value = |raw, ty| match ty {
-        Type::I32 => Value::I32(raw as i32),
-        Type::I64 => Value::I64(raw as i64),
-        Type::F32 => Value::F32(f32::from_bits(raw as u32)),
-        Type::F64 => Value::F64(f64::from_bits(raw)),
+    let raw_to_value = |raw: &[u64], ty| match ty {
+        Type::I32 => Value::I32(raw[0] as i32),
+        Type::I64 => Value::I64(raw[0] as i64),
+        Type::F32 => Value::F32(f32::from_bits(raw[0] as u32)),
+        Type::F64 => Value::F64(f64::from_bits(raw[0])),
+        Type::AnyRef => Value::AnyRef(unsafe { AnyRef::from_raw(raw[0]) }),
+        Type::FuncRef => Value::FuncRef(unsafe { FuncRef::from_raw(raw[0]) }),
+        Type::V128 => Value::V128(raw_to_v128([raw[0], raw[1]])),
     };
 
     match signature.returns() {
@@ -566,32 +638,42 @@ fn call_func_with_index(
             run_wasm(0 as *mut u64)?;
             Ok(())
         }
-        &[ty] => {
-            let mut result = 0u64;
-
-            run_wasm(&mut result)?;
-
-            rets.push(raw_to_value(result, ty));
-
-            Ok(())
-        }
         result_tys @ _ => {
-            let mut results: SmallVec<[u64; 8]> = smallvec![0; num_results];
+            let num_slots = result_tys.iter().map(|&ty| raw_slots(ty)).sum();
+            let mut results: SmallVec<[u64; 8]> = smallvec![0; num_slots];
 
             run_wasm(results.as_mut_ptr())?;
 
-            rets.extend(
-                results
-                    .iter()
-                    .zip(result_tys.iter())
-                    .map(|(&raw, &ty)| raw_to_value(raw, ty)),
-            );
+            let mut raw = &results[..];
+            for &ty in result_tys {
+                let (value, rest) = raw.split_at(raw_slots(ty));
+                rets.push(raw_to_value(value, ty));
+                raw = rest;
+            }
 
             Ok(())
         }
     }
 }
 
+/// The number of 64-bit slots a value of this type takes in the
+/// argument and result arrays of a trampoline.
+fn raw_slots(ty: Type) -> usize {
+    match ty {
+        Type::V128 => 2,
+        _ => 1,
+    }
+}
+
+/// Splits a `v128` into two slots, the low half first.
+fn v128_to_raw(v: u128) -> [u64; 2] {
+    [v as u64, (v >> 64) as u64]
+}
+
+fn raw_to_v128(raw: [u64; 2]) -> u128 {
+    raw[0] as u128 | (raw[1] as u128) << 64
+}
+
 /// A representation of an exported WebAssembly function.
 pub struct DynFunc<'a> {
     pub(crate) signature: Arc<FuncSig>,
@@ -609,8 +691,8 @@ impl<'a> DynFunc<'a> {
     /// [`Value`]: enum.Value.html
     ///
     /// # Note:
-    /// This returns `CallResult<Vec<Value>>` in order to support
-    /// the future multi-value returns webassembly feature.
+    /// This returns `CallResult<Vec<Value>>` as functions may
+    /// return several values with the multi-value feature.
     ///
     /// # Usage:
     /// ```
@@ -658,6 +740,35 @@ impl<'a> DynFunc<'a> {
     }
 }
 
+/// A handle that stops the code running in an [`Instance`].
+///
+/// Handles are obtained with [`Instance::interrupt_handle`], and can
+/// be sent to and used from any thread, even after the instance
+/// has been dropped.
+///
+/// [`Instance`]: struct.Instance.html
+/// [`Instance::interrupt_handle`]: struct.Instance.html#method.interrupt_handle
+#[derive(Debug, Clone)]
+pub struct InterruptHandle {
+    interrupted: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    /// Asks the instance to stop.
+    ///
+    /// The running code unwinds at the next function entry or loop header,
+    /// and the call fails with [`RuntimeError::Interrupted`]. If the instance
+    /// isn't running, its next call is stopped instead.
+    ///
+    /// [`RuntimeError::Interrupted`]: error/enum.RuntimeError.html#variant.Interrupted
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Passed to `RunnableModule::do_early_trap` when an instance is interrupted.
+pub(crate) struct Interrupted;
+
 #[doc(hidden)]
 impl Instance {
     pub fn memory_offset_addr(&self, _: u32, _: usize) -> *const u8 {
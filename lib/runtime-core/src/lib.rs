@@ -14,13 +14,18 @@ pub mod backend;
 mod backing;
 
 pub mod cache;
+#[cfg(all(unix, target_arch = "x86_64"))]
+pub mod dynamic_func;
 pub mod error;
 pub mod export;
 pub mod global;
 pub mod import;
 pub mod instance;
+pub mod limits;
 pub mod memory;
+pub mod metering;
 pub mod module;
+pub mod proposals;
 mod sig_registry;
 pub mod structures;
 mod sys;
@@ -38,7 +43,7 @@ pub use self::error::Result;
 #[doc(inline)]
 pub use self::import::IsExport;
 #[doc(inline)]
-pub use self::instance::{DynFunc, Instance};
+pub use self::instance::{DynFunc, Instance, InterruptHandle};
 #[doc(inline)]
 pub use self::module::Module;
 #[doc(inline)]
@@ -88,7 +93,11 @@ pub fn compile_with_config(
     let token = backend::Token::generate();
     compiler
         .compile(wasm, compiler_config, token)
-        .map(|inner| module::Module::new(Arc::new(inner)))
+        .map(|mut inner| {
+            let inner_info: &mut crate::module::ModuleInfo = &mut inner.info;
+            inner_info.import_custom_sections(wasm).unwrap();
+            module::Module::new(Arc::new(inner))
+        })
 }
 
 /// Perform validation as defined by the
@@ -112,10 +121,24 @@ pub fn validate_and_report_errors(wasm: &[u8]) -> ::std::result::Result<(), Stri
     }
 }
 
+/// Load a [`Module`] from a cached [`Artifact`] using the provided compiler.
+///
+/// This fails with a `BackendMismatch` error if the artifact wasn't
+/// compiled with the same backend as `compiler`.
+///
+/// [`Module`]: struct.Module.html
+/// [`Artifact`]: cache/struct.Artifact.html
 pub unsafe fn load_cache_with(
     cache: Artifact,
     compiler: &dyn backend::Compiler,
 ) -> std::result::Result<module::Module, CacheError> {
+    if cache.backend() != compiler.backend() {
+        return Err(CacheError::BackendMismatch {
+            expected: compiler.backend(),
+            found: cache.backend(),
+        });
+    }
+
     let token = backend::Token::generate();
     compiler
         .from_cache(cache, token)
@@ -0,0 +1,135 @@
+//! Resource limiters bound the amount of memory an instance can allocate.
+//!
+//! A limiter is set on the [`ImportObject`] used to instantiate a module, and
+//! is consulted before each memory and table defined by the module is created
+//! or grown, whether the grow comes from `memory.grow` or from the host.
+//!
+//! [`ImportObject`]: ../import/struct.ImportObject.html
+use crate::{
+    units::{Bytes, Pages},
+    vm,
+};
+use std::{
+    mem,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Observes and vetoes the allocations of an instance.
+pub trait ResourceLimiter: Send + Sync {
+    /// Called before a memory grows from `current` to `desired` pages.
+    ///
+    /// When a memory is created, `current` is zero. Returning `false`
+    /// makes the grow, or the instantiation, fail.
+    fn memory_growing(&self, current: Pages, desired: Pages, maximum: Option<Pages>) -> bool;
+
+    /// Called before a table grows from `current` to `desired` elements.
+    ///
+    /// When a table is created, `current` is zero. Returning `false`
+    /// makes the grow, or the instantiation, fail.
+    fn table_growing(&self, current: u32, desired: u32, maximum: Option<u32>) -> bool;
+
+    /// Called when a memory grow that `memory_growing` allowed failed anyway,
+    /// e.g. because the pages couldn't be mapped.
+    fn memory_grow_failed(&self, _current: Pages, _desired: Pages) {}
+
+    /// Called when a table grow that `table_growing` allowed failed anyway.
+    fn table_grow_failed(&self, _current: u32, _desired: u32) {}
+}
+
+/// A limiter that caps the memory used by the memories and tables of an instance.
+#[derive(Debug)]
+pub struct MemoryLimit {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl MemoryLimit {
+    /// Allows allocating up to `limit` pages across all memories and tables.
+    pub fn new(limit: Pages) -> Self {
+        Self {
+            limit: limit.bytes().0,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// The amount of memory allocated so far.
+    pub fn used(&self) -> Bytes {
+        Bytes(self.used.load(Ordering::SeqCst))
+    }
+
+    fn reserve(&self, bytes: usize) -> bool {
+        let mut used = self.used.load(Ordering::SeqCst);
+        loop {
+            let new_used = match used.checked_add(bytes) {
+                Some(new_used) if new_used <= self.limit => new_used,
+                _ => return false,
+            };
+            match self
+                .used
+                .compare_exchange(used, new_used, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => return true,
+                Err(actual) => used = actual,
+            }
+        }
+    }
+
+    fn release(&self, bytes: usize) {
+        self.used.fetch_sub(bytes, Ordering::SeqCst);
+    }
+}
+
+impl ResourceLimiter for MemoryLimit {
+    fn memory_growing(&self, current: Pages, desired: Pages, _maximum: Option<Pages>) -> bool {
+        self.reserve(desired.bytes().0.saturating_sub(current.bytes().0))
+    }
+
+    fn table_growing(&self, current: u32, desired: u32, _maximum: Option<u32>) -> bool {
+        let elements = desired.saturating_sub(current) as usize;
+        self.reserve(elements * mem::size_of::<vm::Anyfunc>())
+    }
+
+    fn memory_grow_failed(&self, current: Pages, desired: Pages) {
+        self.release(desired.bytes().0.saturating_sub(current.bytes().0))
+    }
+
+    fn table_grow_failed(&self, current: u32, desired: u32) {
+        let elements = desired.saturating_sub(current) as usize;
+        self.release(elements * mem::size_of::<vm::Anyfunc>())
+    }
+}
+
+#[cfg(test)]
+mod limits_tests {
+    use super::{MemoryLimit, ResourceLimiter};
+    use crate::units::Pages;
+
+    #[test]
+    fn test_memory_limit() {
+        let limit = MemoryLimit::new(Pages(10));
+
+        assert!(limit.memory_growing(Pages(0), Pages(4), None));
+        assert!(limit.memory_growing(Pages(4), Pages(10), Some(Pages(20))));
+        assert!(!limit.memory_growing(Pages(10), Pages(11), Some(Pages(20))));
+        assert_eq!(limit.used(), Pages(10).bytes());
+    }
+
+    #[test]
+    fn test_memory_limit_counts_tables() {
+        let limit = MemoryLimit::new(Pages(1));
+
+        assert!(limit.table_growing(0, 10, None));
+        assert!(!limit.memory_growing(Pages(0), Pages(1), None));
+        assert!(limit.memory_growing(Pages(0), Pages(0), None));
+    }
+
+    #[test]
+    fn test_memory_limit_releases_failed_grows() {
+        let limit = MemoryLimit::new(Pages(10));
+
+        assert!(limit.memory_growing(Pages(0), Pages(8), None));
+        limit.memory_grow_failed(Pages(0), Pages(8));
+        assert_eq!(limit.used(), Pages(0).bytes());
+        assert!(limit.memory_growing(Pages(0), Pages(10), None));
+    }
+}
@@ -2,6 +2,7 @@ use crate::{
     error::{CreationError, GrowError},
     export::Export,
     import::IsExport,
+    limits::ResourceLimiter,
     memory::dynamic::DYNAMIC_GUARD_SIZE,
     memory::static_::{SAFE_STATIC_GUARD_SIZE, SAFE_STATIC_HEAP_SIZE},
     types::{MemoryDescriptor, ValueType},
@@ -9,9 +10,10 @@ use crate::{
     vm,
 };
 use std::{
-    cell::{Cell, RefCell},
+    cell::{Cell, RefCell, UnsafeCell},
     fmt, mem, ptr,
     rc::Rc,
+    sync::Arc,
 };
 
 pub use self::atomic::Atomic;
@@ -23,6 +25,7 @@ mod atomic;
 mod dynamic;
 mod static_;
 mod view;
+pub(crate) mod wait;
 
 #[derive(Clone)]
 enum MemoryVariant {
@@ -37,6 +40,7 @@ enum MemoryVariant {
 pub struct Memory {
     desc: MemoryDescriptor,
     variant: MemoryVariant,
+    limiter: Option<Arc<dyn ResourceLimiter>>,
 }
 
 impl Memory {
@@ -72,13 +76,28 @@ impl Memory {
             }
         }
 
+        if desc.shared && desc.maximum.is_none() {
+            return Err(CreationError::InvalidDescriptor(
+                "Max number of pages is required for shared memory".to_string(),
+            ));
+        }
+
         let variant = if !desc.shared {
             MemoryVariant::Unshared(UnsharedMemory::new(desc)?)
         } else {
             MemoryVariant::Shared(SharedMemory::new(desc)?)
         };
 
-        Ok(Memory { desc, variant })
+        Ok(Memory {
+            desc,
+            variant,
+            limiter: None,
+        })
+    }
+
+    /// Makes every grow of this memory go through `limiter`.
+    pub(crate) fn with_limiter(self, limiter: Option<Arc<dyn ResourceLimiter>>) -> Self {
+        Memory { limiter, ..self }
     }
 
     /// Return the [`MemoryDescriptor`] that this memory
@@ -91,10 +110,10 @@ impl Memory {
 
     /// Grow this memory by the specified number of pages.
     pub fn grow(&self, delta: Pages) -> Result<Pages, GrowError> {
-        match &self.variant {
+        self.limit_grow(delta, || match &self.variant {
             MemoryVariant::Unshared(unshared_mem) => unshared_mem.grow(delta),
             MemoryVariant::Shared(shared_mem) => shared_mem.grow(delta),
-        }
+        })
     }
 
     /// The size, in wasm pages, of this memory.
@@ -148,20 +167,54 @@ impl Memory {
 
     /// Convert this memory to a shared memory if the shared flag
     /// is present in the description used to create it.
+    ///
+    /// Unlike a `Memory`, a [`SharedMemory`] can be sent to other threads.
+    ///
+    /// [`SharedMemory`]: struct.SharedMemory.html
     pub fn shared(self) -> Option<SharedMemory> {
-        if self.desc.shared {
-            Some(SharedMemory { desc: self.desc })
-        } else {
-            None
+        match self.variant {
+            MemoryVariant::Shared(shared_mem) => Some(shared_mem),
+            MemoryVariant::Unshared(_) => None,
         }
     }
 
     pub(crate) fn vm_local_memory(&self) -> *mut vm::LocalMemory {
         match &self.variant {
             MemoryVariant::Unshared(unshared_mem) => unshared_mem.vm_local_memory(),
-            MemoryVariant::Shared(_) => unimplemented!(),
+            MemoryVariant::Shared(shared_mem) => shared_mem.vm_local_memory(),
         }
     }
+
+    /// Grows this memory by `delta` with `grow`, if the resource limiter
+    /// of this memory, if any, allows it.
+    ///
+    /// Grows that would fail anyway because they exceed the maximum size
+    /// of the memory aren't passed on to the limiter. If `grow` fails, the
+    /// limiter is told so that it can release what it reserved.
+    pub(crate) fn limit_grow(
+        &self,
+        delta: Pages,
+        grow: impl FnOnce() -> Result<Pages, GrowError>,
+    ) -> Result<Pages, GrowError> {
+        let limiter = match &self.limiter {
+            Some(limiter) if delta != Pages(0) => limiter,
+            _ => return grow(),
+        };
+
+        let current = self.size();
+        let desired = current.checked_add(delta).map_err(|e| e.into())?;
+        if self.desc.maximum.map_or(false, |max| desired > max) {
+            return grow();
+        }
+
+        if !limiter.memory_growing(current, desired, self.desc.maximum) {
+            return Err(GrowError::ResourceLimitExceeded);
+        }
+        grow().map_err(|e| {
+            limiter.memory_grow_failed(current, desired);
+            e
+        })
+    }
 }
 
 impl IsExport for Memory {
@@ -283,28 +336,106 @@ impl Clone for UnsharedMemory {
     }
 }
 
+/// A wasm linear memory that can be shared between threads.
+///
+/// Cloning a `SharedMemory` creates another reference to the same memory.
 pub struct SharedMemory {
-    #[allow(dead_code)]
     desc: MemoryDescriptor,
+    internal: Arc<SharedMemoryInternal>,
+}
+
+struct SharedMemoryInternal {
+    memory: Box<SharedStaticMemory>,
+    local: UnsafeCell<vm::LocalMemory>,
 }
 
+// The `vm::LocalMemory` is only written to while the lock inside of
+// `SharedStaticMemory` is held, and the backing memory never moves.
+unsafe impl Send for SharedMemoryInternal {}
+unsafe impl Sync for SharedMemoryInternal {}
+
 impl SharedMemory {
     fn new(desc: MemoryDescriptor) -> Result<Self, CreationError> {
-        Ok(Self { desc })
+        let mut local = vm::LocalMemory {
+            base: ptr::null_mut(),
+            bound: 0,
+            memory: ptr::null_mut(),
+        };
+
+        let memory = match desc.memory_type() {
+            MemoryType::SharedStatic => SharedStaticMemory::new(desc, &mut local)?,
+            MemoryType::Dynamic | MemoryType::Static => {
+                panic!("attempting to create unshared shared memory")
+            }
+        };
+
+        Ok(Self {
+            desc,
+            internal: Arc::new(SharedMemoryInternal {
+                memory,
+                local: UnsafeCell::new(local),
+            }),
+        })
     }
 
-    pub fn grow(&self, _delta: Pages) -> Result<Pages, GrowError> {
-        unimplemented!()
+    /// Return the [`MemoryDescriptor`] that this memory
+    /// was created with.
+    ///
+    /// [`MemoryDescriptor`]: struct.MemoryDescriptor.html
+    pub fn descriptor(&self) -> MemoryDescriptor {
+        self.desc
     }
 
+    /// Grow this memory by the specified number of pages.
+    pub fn grow(&self, delta: Pages) -> Result<Pages, GrowError> {
+        unsafe {
+            self.internal
+                .memory
+                .grow(delta, &mut *self.internal.local.get())
+        }
+    }
+
+    /// The size, in wasm pages, of this memory.
     pub fn size(&self) -> Pages {
-        unimplemented!()
+        self.internal.memory.size()
+    }
+
+    pub(crate) fn vm_local_memory(&self) -> *mut vm::LocalMemory {
+        self.internal.local.get()
     }
 }
 
 impl Clone for SharedMemory {
     fn clone(&self) -> Self {
-        unimplemented!()
+        SharedMemory {
+            desc: self.desc,
+            internal: Arc::clone(&self.internal),
+        }
+    }
+}
+
+impl From<SharedMemory> for Memory {
+    fn from(shared_mem: SharedMemory) -> Self {
+        Memory {
+            desc: shared_mem.desc,
+            variant: MemoryVariant::Shared(shared_mem),
+            limiter: None,
+        }
+    }
+}
+
+impl IsExport for SharedMemory {
+    fn to_export(&self) -> Export {
+        Export::Memory(self.clone().into())
+    }
+}
+
+impl fmt::Debug for SharedMemory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SharedMemory")
+            .field("desc", &self.desc)
+            .field("size", &self.size())
+            .finish()
     }
 }
 
@@ -324,4 +455,67 @@ mod memory_tests {
         assert_eq!(unshared_memory.size(), Pages(10));
     }
 
+    #[test]
+    fn test_shared_memory_requires_max() {
+        let result = Memory::new(MemoryDescriptor {
+            minimum: Pages(10),
+            maximum: None,
+            shared: true,
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_shared_memory_grow_across_threads() {
+        let shared_memory = Memory::new(MemoryDescriptor {
+            minimum: Pages(1),
+            maximum: Some(Pages(10)),
+            shared: true,
+        })
+        .unwrap()
+        .shared()
+        .unwrap();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let shared_memory = shared_memory.clone();
+                std::thread::spawn(move || shared_memory.grow(Pages(2)).unwrap())
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(shared_memory.size(), Pages(9));
+        assert!(shared_memory.grow(Pages(2)).is_err());
+
+        let memory: Memory = shared_memory.into();
+        assert_eq!(memory.size(), Pages(9));
+        assert_eq!(memory.view::<u8>().len(), Pages(9).bytes().0);
+    }
+
+    #[test]
+    fn test_failed_grow_releases_limit() {
+        use crate::{
+            error::GrowError,
+            limits::{MemoryLimit, ResourceLimiter},
+        };
+        use std::sync::Arc;
+
+        let limit = Arc::new(MemoryLimit::new(Pages(4)));
+        let memory = Memory::new(MemoryDescriptor {
+            minimum: Pages(1),
+            maximum: None,
+            shared: false,
+        })
+        .unwrap()
+        .with_limiter(Some(limit.clone() as Arc<dyn ResourceLimiter>));
+
+        let result = memory.limit_grow(Pages(3), || Err(GrowError::MemoryGrowError));
+        assert!(result.is_err());
+        assert_eq!(limit.used(), Pages(0).bytes());
+
+        assert_eq!(memory.grow(Pages(3)).unwrap(), Pages(1));
+        assert_eq!(limit.used(), Pages(3).bytes());
+    }
 }
@@ -1,11 +1,106 @@
-use crate::sys;
+use crate::error::GrowError;
+use crate::{
+    error::CreationError,
+    memory::static_::{SAFE_STATIC_GUARD_SIZE, SAFE_STATIC_HEAP_SIZE},
+    sys,
+    types::MemoryDescriptor,
+    units::Pages,
+    vm,
+};
 use parking_lot::Mutex;
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-// Remove this attribute once this is used.
-#[allow(dead_code)]
+/// This is an internal-only api.
+///
+/// A shared static memory is a static memory that may be accessed
+/// from several threads at the same time.
+///
+/// Like a regular static memory, it reserves its whole address range up-front
+/// and never moves, so other threads can keep executing while it grows.
+/// Growing is serialized by a lock, while the current size can be read without locking.
 pub struct SharedStaticMemory {
-    memory: sys::Memory,
+    memory: Mutex<sys::Memory>,
     current: AtomicUsize,
-    lock: Mutex<()>,
+    max: Pages,
+}
+
+impl SharedStaticMemory {
+    pub(in crate::memory) fn new(
+        desc: MemoryDescriptor,
+        local: &mut vm::LocalMemory,
+    ) -> Result<Box<Self>, CreationError> {
+        let max = desc.maximum.ok_or_else(|| {
+            CreationError::InvalidDescriptor(
+                "A shared memory must have a maximum number of pages".to_string(),
+            )
+        })?;
+
+        let memory = {
+            let mut memory = sys::Memory::with_size(SAFE_STATIC_HEAP_SIZE + SAFE_STATIC_GUARD_SIZE)
+                .map_err(|_| CreationError::UnableToCreateMemory)?;
+            if desc.minimum != Pages(0) {
+                unsafe {
+                    memory
+                        .protect(0..desc.minimum.bytes().0, sys::Protect::ReadWrite)
+                        .map_err(|_| CreationError::UnableToCreateMemory)?;
+                }
+            }
+
+            memory
+        };
+
+        local.base = memory.as_ptr();
+
+        let mut storage = Box::new(SharedStaticMemory {
+            memory: Mutex::new(memory),
+            current: AtomicUsize::new(desc.minimum.0 as usize),
+            max,
+        });
+        let storage_ptr: *mut SharedStaticMemory = &mut *storage;
+
+        local.bound = desc.minimum.bytes().0;
+        local.memory = storage_ptr as *mut ();
+
+        Ok(storage)
+    }
+
+    pub fn size(&self) -> Pages {
+        Pages(self.current.load(Ordering::SeqCst) as u32)
+    }
+
+    pub fn grow(&self, delta: Pages, local: &mut vm::LocalMemory) -> Result<Pages, GrowError> {
+        let mut memory = self.memory.lock();
+
+        let current = self.size();
+
+        if delta == Pages(0) {
+            return Ok(current);
+        }
+
+        let new_pages = current.checked_add(delta).map_err(|e| e.into())?;
+
+        if new_pages > self.max {
+            return Err(GrowError::ExceededMaxPagesForMemory(
+                new_pages.0 as usize,
+                self.max.0 as usize,
+            ));
+        }
+
+        // The memory is never moved, so only the newly accessible
+        // pages need to be made readable and writable.
+        let _ = unsafe {
+            memory
+                .protect(
+                    current.bytes().0..new_pages.bytes().0,
+                    sys::Protect::ReadWrite,
+                )
+                .map_err(|e| e.into())
+        }?;
+
+        local.bound = new_pages.bytes().0;
+
+        self.current.store(new_pages.0 as usize, Ordering::SeqCst);
+
+        Ok(current)
+    }
 }
@@ -0,0 +1,172 @@
+//! The wait queues behind `memory.atomic.wait` and `memory.atomic.notify`.
+//!
+//! Waiters are keyed by the host address of the memory cell they wait on.
+//! Shared memories never move, so the same cell has the same address in
+//! every instance and thread that uses the memory.
+
+use hashbrown::HashMap;
+use lazy_static::lazy_static;
+use parking_lot::{Condvar, Mutex};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+lazy_static! {
+    /// The threads waiting on each address, in the order they started waiting.
+    static ref WAITERS: Mutex<HashMap<usize, VecDeque<Arc<Waiter>>>> = Mutex::new(HashMap::new());
+}
+
+/// How long a wait blocks at a time before checking whether the instance was interrupted.
+const INTERRUPT_INTERVAL: Duration = Duration::from_millis(10);
+
+struct Waiter {
+    woken: AtomicBool,
+    condvar: Condvar,
+}
+
+/// How a wait ended, as returned by `memory.atomic.wait`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(i32)]
+pub(crate) enum WaitResult {
+    Woken = 0,
+    NotEqual = 1,
+    TimedOut = 2,
+    /// The instance was interrupted. The caller traps instead of returning this to wasm.
+    Interrupted = 3,
+}
+
+/// Blocks the current thread until `address` is notified, `timeout` elapses or
+/// `is_interrupted` returns `true`.
+///
+/// `is_expected` reads the memory cell and compares it with the expected value.
+/// It is called with the queues locked, so a notification can't be missed
+/// between the comparison and the wait.
+pub(crate) fn wait(
+    address: usize,
+    is_expected: impl FnOnce() -> bool,
+    timeout: Option<Duration>,
+    is_interrupted: impl Fn() -> bool,
+) -> WaitResult {
+    let mut waiters = WAITERS.lock();
+    if !is_expected() {
+        return WaitResult::NotEqual;
+    }
+
+    let waiter = Arc::new(Waiter {
+        woken: AtomicBool::new(false),
+        condvar: Condvar::new(),
+    });
+    waiters
+        .entry(address)
+        .or_insert_with(VecDeque::new)
+        .push_back(waiter.clone());
+
+    // Even an infinite wait wakes up regularly, so that it can be interrupted.
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let result = loop {
+        if waiter.woken.load(Ordering::SeqCst) {
+            return WaitResult::Woken;
+        }
+        if is_interrupted() {
+            break WaitResult::Interrupted;
+        }
+        let slice = match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    break WaitResult::TimedOut;
+                }
+                (deadline - now).min(INTERRUPT_INTERVAL)
+            }
+            None => INTERRUPT_INTERVAL,
+        };
+        waiter.condvar.wait_for(&mut waiters, slice);
+    };
+
+    if let Some(queue) = waiters.get_mut(&address) {
+        queue.retain(|other| !Arc::ptr_eq(other, &waiter));
+        if queue.is_empty() {
+            waiters.remove(&address);
+        }
+    }
+    result
+}
+
+/// Wakes up to `count` of the threads waiting on `address`, oldest first,
+/// and returns how many were woken.
+pub(crate) fn notify(address: usize, count: u32) -> u32 {
+    let mut waiters = WAITERS.lock();
+    let queue = match waiters.get_mut(&address) {
+        Some(queue) => queue,
+        None => return 0,
+    };
+
+    let mut woken = 0;
+    while woken < count {
+        match queue.pop_front() {
+            Some(waiter) => {
+                waiter.woken.store(true, Ordering::SeqCst);
+                waiter.condvar.notify_one();
+                woken += 1;
+            }
+            None => break,
+        }
+    }
+
+    if queue.is_empty() {
+        waiters.remove(&address);
+    }
+    woken
+}
+
+#[cfg(test)]
+mod wait_tests {
+    use super::{notify, wait, WaitResult};
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn test_wait_not_equal() {
+        assert_eq!(wait(0x10, || false, None, || false), WaitResult::NotEqual);
+    }
+
+    #[test]
+    fn test_wait_timeout() {
+        let timeout = Some(Duration::from_millis(10));
+        assert_eq!(wait(0x20, || true, timeout, || false), WaitResult::TimedOut);
+        assert_eq!(notify(0x20, 1), 0);
+    }
+
+    #[test]
+    fn test_notify() {
+        let waiter = thread::spawn(|| wait(0x30, || true, None, || false));
+        while notify(0x30, 1) == 0 {
+            thread::yield_now();
+        }
+        assert_eq!(waiter.join().unwrap(), WaitResult::Woken);
+    }
+
+    #[test]
+    fn test_wait_interrupted() {
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&interrupted);
+        let waiter =
+            thread::spawn(move || wait(0x40, || true, None, || flag.load(Ordering::SeqCst)));
+        thread::sleep(Duration::from_millis(20));
+        interrupted.store(true, Ordering::SeqCst);
+        assert_eq!(waiter.join().unwrap(), WaitResult::Interrupted);
+        // The interrupted waiter no longer waits.
+        assert_eq!(notify(0x40, 1), 0);
+    }
+}
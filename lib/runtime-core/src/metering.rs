@@ -0,0 +1,157 @@
+//! Fuel metering bounds the amount of work a WebAssembly instance can do.
+//!
+//! When a module is compiled with [`CompilerConfig::metering`] set, the
+//! generated code adds the cost of the operators it executed to a counter
+//! at every basic-block boundary. Once the counter exceeds the limit set
+//! with [`Instance::set_fuel`], the call is aborted with
+//! [`RuntimeError::FuelExhausted`].
+//!
+//! [`CompilerConfig::metering`]: ../backend/struct.CompilerConfig.html#structfield.metering
+//! [`Instance::set_fuel`]: ../struct.Instance.html#method.set_fuel
+//! [`RuntimeError::FuelExhausted`]: ../error/enum.RuntimeError.html#variant.FuelExhausted
+
+use crate::error::{CompileError, CompileResult};
+use std::{fmt, sync::Arc};
+use wasmparser::{BinaryReaderError, ModuleReader, SectionCode};
+
+pub use wasmparser::Operator;
+
+/// The amount of fuel consumed by each operator.
+#[derive(Clone)]
+pub struct CostTable {
+    cost: Arc<dyn Fn(&Operator) -> u64 + Send + Sync>,
+}
+
+impl CostTable {
+    /// A table where the cost of each operator is given by `cost`.
+    pub fn new<F>(cost: F) -> Self
+    where
+        F: Fn(&Operator) -> u64 + Send + Sync + 'static,
+    {
+        Self {
+            cost: Arc::new(cost),
+        }
+    }
+
+    /// A table where every operator costs `cost`.
+    pub fn uniform(cost: u64) -> Self {
+        Self::new(move |_| cost)
+    }
+
+    /// Returns the cost of `op`.
+    pub fn cost(&self, op: &Operator) -> u64 {
+        (self.cost)(op)
+    }
+
+    /// Returns the cost of every operator in the body of each local function
+    /// of `wasm`, in the order the operators appear.
+    ///
+    /// The backends use their own parsers, so they look up the cost of the
+    /// n-th operator of a function here instead of calling [`cost`] themselves.
+    ///
+    /// [`cost`]: #method.cost
+    pub fn module_costs(&self, wasm: &[u8]) -> CompileResult<Vec<Vec<OperatorCost>>> {
+        self.read_module_costs(wasm)
+            .map_err(|e| CompileError::ValidationError {
+                msg: format!("{} at offset {}", e.message, e.offset),
+            })
+    }
+
+    fn read_module_costs(&self, wasm: &[u8]) -> Result<Vec<Vec<OperatorCost>>, BinaryReaderError> {
+        let mut functions = vec![];
+
+        let mut module = ModuleReader::new(wasm)?;
+        while !module.eof() {
+            let section = module.read()?;
+            match section.code {
+                SectionCode::Code => {}
+                _ => continue,
+            }
+
+            for body in section.get_code_section_reader()? {
+                let mut op_reader = body?.get_operators_reader()?;
+                let mut ops = vec![];
+                while !op_reader.eof() {
+                    let offset = op_reader.original_position();
+                    let op = op_reader.read()?;
+                    ops.push(OperatorCost {
+                        offset,
+                        cost: self.cost(&op),
+                    });
+                }
+                functions.push(ops);
+            }
+        }
+
+        Ok(functions)
+    }
+}
+
+impl Default for CostTable {
+    fn default() -> Self {
+        Self::uniform(1)
+    }
+}
+
+impl fmt::Debug for CostTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CostTable").finish()
+    }
+}
+
+/// The cost of one operator of a function body.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OperatorCost {
+    /// The offset of the operator from the start of the module.
+    pub offset: usize,
+    pub cost: u64,
+}
+
+/// Passed to `RunnableModule::do_early_trap` when an instance runs out of fuel.
+pub(crate) struct FuelExhausted;
+
+#[cfg(test)]
+mod metering_tests {
+    use super::{CostTable, Operator};
+
+    #[test]
+    fn test_cost_table() {
+        let table = CostTable::new(|op| match *op {
+            Operator::I32Load { .. } => 3,
+            Operator::Call { .. } => 10,
+            _ => 1,
+        });
+
+        assert_eq!(table.cost(&Operator::Call { function_index: 0 }), 10);
+        assert_eq!(table.cost(&Operator::I32Add), 1);
+        assert_eq!(CostTable::uniform(0).cost(&Operator::Nop), 0);
+    }
+
+    #[test]
+    fn test_module_costs() {
+        // (module
+        //   (func)
+        //   (func (result i32) (i32.add (i32.const 1) (i32.const 2))))
+        let wasm = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+            0x01, 0x08, 0x02, 0x60, 0x00, 0x00, 0x60, 0x00, 0x01, 0x7f, // types
+            0x03, 0x03, 0x02, 0x00, 0x01, // functions
+            0x0a, 0x0c, 0x02, // code
+            0x02, 0x00, 0x0b, // (func)
+            0x07, 0x00, 0x41, 0x01, 0x41, 0x02, 0x6a, 0x0b, // (func (result i32) ...)
+        ];
+        let table = CostTable::new(|op| match *op {
+            Operator::I32Add => 5,
+            Operator::End => 0,
+            _ => 1,
+        });
+
+        let costs = table.module_costs(&wasm).unwrap();
+        let per_op: Vec<Vec<_>> = costs
+            .iter()
+            .map(|ops| ops.iter().map(|op| op.cost).collect())
+            .collect();
+        assert_eq!(per_op, vec![vec![0], vec![1, 1, 5, 0]]);
+        assert_eq!(costs[1][0].offset, 0x1f);
+    }
+}
@@ -5,10 +5,10 @@ use crate::{
     import::ImportObject,
     structures::{Map, TypedIndex},
     types::{
-        FuncIndex, FuncSig, GlobalDescriptor, GlobalIndex, GlobalInit, ImportedFuncIndex,
-        ImportedGlobalIndex, ImportedMemoryIndex, ImportedTableIndex, Initializer,
-        LocalGlobalIndex, LocalMemoryIndex, LocalTableIndex, MemoryDescriptor, MemoryIndex,
-        SigIndex, TableDescriptor, TableIndex,
+        DataIndex, ElemIndex, ExternDescriptor, FuncIndex, FuncSig, GlobalDescriptor, GlobalIndex,
+        GlobalInit, ImportedFuncIndex, ImportedGlobalIndex, ImportedMemoryIndex,
+        ImportedTableIndex, Initializer, LocalGlobalIndex, LocalMemoryIndex, LocalOrImport,
+        LocalTableIndex, MemoryDescriptor, MemoryIndex, SigIndex, TableDescriptor, TableIndex,
     },
     Instance,
 };
@@ -45,6 +45,13 @@ pub struct ModuleInfo {
     pub data_initializers: Vec<DataInitializer>,
     pub elem_initializers: Vec<TableInitializer>,
 
+    /// The passive data segments, by their index in the data section.
+    /// They are only written to memory by `memory.init`.
+    pub passive_data: HashMap<DataIndex, Vec<u8>>,
+    /// The passive element segments, by their index in the element section.
+    /// They are only written to tables by `table.init`.
+    pub passive_elements: HashMap<ElemIndex, Vec<FuncIndex>>,
+
     pub start_func: Option<FuncIndex>,
 
     pub func_assoc: Map<FuncIndex, SigIndex>,
@@ -58,9 +65,63 @@ pub struct ModuleInfo {
     pub em_symbol_map: Option<HashMap<u32, String>>,
 
     pub custom_sections: HashMap<String, Vec<u8>>,
+    /// The names decoded from the `name` custom section.
+    pub name_section: NameSection,
 }
 
 impl ModuleInfo {
+    fn named_import<'a>(
+        &'a self,
+        name: &ImportName,
+        desc: ExternDescriptor,
+    ) -> (&'a str, &'a str, ExternDescriptor) {
+        (
+            self.namespace_table.get(name.namespace_index),
+            self.name_table.get(name.name_index),
+            desc,
+        )
+    }
+
+    fn export_descriptor(&self, index: ExportIndex) -> ExternDescriptor {
+        match index {
+            ExportIndex::Func(func_index) => {
+                ExternDescriptor::Function(self.signatures[self.func_assoc[func_index]].clone())
+            }
+            ExportIndex::Memory(memory_index) => {
+                ExternDescriptor::Memory(match memory_index.local_or_import(self) {
+                    LocalOrImport::Local(local_index) => self.memories[local_index],
+                    LocalOrImport::Import(import_index) => self.imported_memories[import_index].1,
+                })
+            }
+            ExportIndex::Table(table_index) => {
+                ExternDescriptor::Table(match table_index.local_or_import(self) {
+                    LocalOrImport::Local(local_index) => self.tables[local_index],
+                    LocalOrImport::Import(import_index) => self.imported_tables[import_index].1,
+                })
+            }
+            ExportIndex::Global(global_index) => {
+                ExternDescriptor::Global(match global_index.local_or_import(self) {
+                    LocalOrImport::Local(local_index) => self.globals[local_index].desc,
+                    LocalOrImport::Import(import_index) => self.imported_globals[import_index].1,
+                })
+            }
+        }
+    }
+
+    /// The name of a function, taken from the `name` section,
+    /// or else from the emscripten symbol map.
+    pub fn func_name(&self, func_index: FuncIndex) -> Option<&str> {
+        self.name_section
+            .function_names
+            .get(&func_index)
+            .or_else(|| {
+                self.em_symbol_map
+                    .as_ref()?
+                    .get(&(func_index.index() as u32))
+            })
+            .map(String::as_str)
+    }
+
     pub fn import_custom_sections(&mut self, wasm: &[u8]) -> crate::error::ParseResult<()> {
         let mut parser = wasmparser::ModuleReader::new(wasm)?;
         while !parser.eof() {
@@ -70,6 +131,9 @@ impl ModuleInfo {
                 let len = reader.bytes_remaining();
                 let bytes = reader.read_bytes(len)?;
                 let data = bytes.to_vec();
+                if name == "name" {
+                    self.name_section = NameSection::parse(&data);
+                }
                 let name = name.to_string();
                 self.custom_sections.insert(name, data);
             }
@@ -78,6 +142,109 @@ impl ModuleInfo {
     }
 }
 
+/// The contents of the standard `name` custom section.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct NameSection {
+    pub module_name: Option<String>,
+    pub function_names: HashMap<FuncIndex, String>,
+    /// The names of the locals of each function, by local index.
+    pub local_names: HashMap<FuncIndex, HashMap<u32, String>>,
+}
+
+impl NameSection {
+    /// Decodes the contents of a `name` section.
+    ///
+    /// As the names are only debug information, a malformed subsection
+    /// is skipped instead of failing the whole module.
+    pub fn parse(bytes: &[u8]) -> NameSection {
+        let mut names = NameSection::default();
+        let mut reader = NameReader { bytes };
+        while let Some((id, mut subsection)) = reader.subsection() {
+            match id {
+                0 => names.module_name = subsection.name(),
+                1 => {
+                    if let Some(map) = subsection.name_map() {
+                        names.function_names = map
+                            .into_iter()
+                            .map(|(index, name)| (FuncIndex::new(index as usize), name))
+                            .collect();
+                    }
+                }
+                2 => {
+                    if let Some(map) = subsection.indirect_name_map() {
+                        names.local_names = map
+                            .into_iter()
+                            .map(|(index, locals)| {
+                                (FuncIndex::new(index as usize), locals.into_iter().collect())
+                            })
+                            .collect();
+                    }
+                }
+                _ => {}
+            }
+        }
+        names
+    }
+}
+
+struct NameReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> NameReader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.bytes.len() {
+            return None;
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let mut result = 0u64;
+        for shift in (0..35).step_by(7) {
+            let byte = self.read_bytes(1)?[0];
+            result |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return if result <= u64::from(u32::max_value()) {
+                    Some(result as u32)
+                } else {
+                    None
+                };
+            }
+        }
+        None
+    }
+
+    fn subsection(&mut self) -> Option<(u8, NameReader<'a>)> {
+        let id = self.read_bytes(1)?[0];
+        let len = self.u32()?;
+        let bytes = self.read_bytes(len as usize)?;
+        Some((id, NameReader { bytes }))
+    }
+
+    fn name(&mut self) -> Option<String> {
+        let len = self.u32()?;
+        let bytes = self.read_bytes(len as usize)?;
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+
+    fn name_map(&mut self) -> Option<Vec<(u32, String)>> {
+        let count = self.u32()?;
+        (0..count)
+            .map(|_| Some((self.u32()?, self.name()?)))
+            .collect()
+    }
+
+    fn indirect_name_map(&mut self) -> Option<Vec<(u32, Vec<(u32, String)>)>> {
+        let count = self.u32()?;
+        (0..count)
+            .map(|_| Some((self.u32()?, self.name_map()?)))
+            .collect()
+    }
+}
+
 /// A compiled WebAssembly module.
 ///
 /// `Module` is returned by the [`compile`] and
@@ -132,6 +299,38 @@ impl Module {
     pub fn info(&self) -> &ModuleInfo {
         &self.inner.info
     }
+
+    /// The imports of the module, as their namespace, name and type.
+    ///
+    /// The function imports come first, then the memories, the tables and the globals.
+    pub fn imports(&self) -> impl Iterator<Item = (&str, &str, ExternDescriptor)> {
+        let info = &self.inner.info;
+        let functions = info.imported_functions.iter().map(move |(index, name)| {
+            let signature = &info.signatures[info.func_assoc[index.convert_up(info)]];
+            info.named_import(name, ExternDescriptor::Function(signature.clone()))
+        });
+        let memories = info
+            .imported_memories
+            .iter()
+            .map(move |(_, (name, desc))| info.named_import(name, ExternDescriptor::Memory(*desc)));
+        let tables = info
+            .imported_tables
+            .iter()
+            .map(move |(_, (name, desc))| info.named_import(name, ExternDescriptor::Table(*desc)));
+        let globals = info
+            .imported_globals
+            .iter()
+            .map(move |(_, (name, desc))| info.named_import(name, ExternDescriptor::Global(*desc)));
+        functions.chain(memories).chain(tables).chain(globals)
+    }
+
+    /// The exports of the module, as their name and type, in no particular order.
+    pub fn exports(&self) -> impl Iterator<Item = (&str, ExternDescriptor)> {
+        let info = &self.inner.info;
+        info.exports
+            .iter()
+            .map(move |(name, index)| (name.as_str(), info.export_descriptor(*index)))
+    }
 }
 
 impl Clone for Module {
@@ -285,3 +484,24 @@ impl TypedIndex for NameIndex {
         self.0 as usize
     }
 }
+
+#[cfg(test)]
+mod module_tests {
+    use super::NameSection;
+    use crate::{structures::TypedIndex, types::FuncIndex};
+
+    #[test]
+    fn test_parse_name_section() {
+        let bytes = [
+            0, 4, 3, b'm', b'o', b'd', // module name
+            1, 5, 1, 2, 2, b'f', b'n', // function names
+            2, 6, 1, 2, 1, 0, 1, b'x', // local names
+            1, 9, 1, // truncated subsection
+        ];
+        let names = NameSection::parse(&bytes);
+        assert_eq!(names.module_name.as_ref().map(String::as_str), Some("mod"));
+        assert_eq!(names.function_names.len(), 1);
+        assert_eq!(names.function_names[&FuncIndex::new(2)], "fn");
+        assert_eq!(names.local_names[&FuncIndex::new(2)][&0], "x");
+    }
+}
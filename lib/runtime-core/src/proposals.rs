@@ -0,0 +1,320 @@
+//! Finds the WebAssembly proposals past the MVP that a module uses.
+//!
+//! The backends use their own parsers, and some of them predate these
+//! proposals, so they look up what a module uses here and refuse to compile it
+//! with a clear error instead of failing to parse it.
+
+use crate::error::{CompileError, CompileResult};
+use std::fmt;
+use wasmparser::{
+    BinaryReaderError, DataKind, ElementKind, ImportSectionEntryType, ModuleReader, Operator,
+    SectionCode, Type,
+};
+
+/// A proposal that not every backend supports.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Proposal {
+    /// Passive segments, and the `memory.*`, `table.init`, `table.copy`,
+    /// `data.drop` and `elem.drop` operators.
+    BulkMemory,
+    /// Values of the `anyref` and `funcref` types, `anyref` tables, and the
+    /// `ref.*`, `table.get`, `table.set`, `table.grow` and `table.size` operators.
+    ReferenceTypes,
+    /// Values of the `v128` type and the operators on them.
+    Simd,
+}
+
+impl fmt::Display for Proposal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Proposal::BulkMemory => "bulk memory",
+                Proposal::ReferenceTypes => "reference types",
+                Proposal::Simd => "SIMD",
+            }
+        )
+    }
+}
+
+/// Returns the proposals that `wasm` uses, in the order they are first found.
+pub fn used_proposals(wasm: &[u8]) -> CompileResult<Vec<Proposal>> {
+    read_used_proposals(wasm).map_err(|e| CompileError::ValidationError {
+        msg: format!("{} at offset {}", e.message, e.offset),
+    })
+}
+
+/// Fails with a `ValidationError` naming `backend` if `wasm` uses a proposal
+/// that isn't in `supported`.
+pub fn check_proposals(wasm: &[u8], supported: &[Proposal], backend: &str) -> CompileResult<()> {
+    match used_proposals(wasm)?
+        .into_iter()
+        .find(|proposal| !supported.contains(proposal))
+    {
+        Some(proposal) => Err(CompileError::ValidationError {
+            msg: format!(
+                "the {} proposal is not supported by the {} backend",
+                proposal, backend
+            ),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn read_used_proposals(wasm: &[u8]) -> Result<Vec<Proposal>, BinaryReaderError> {
+    let mut proposals = vec![];
+    let mut uses = |proposal| {
+        if !proposals.contains(&proposal) {
+            proposals.push(proposal);
+        }
+    };
+
+    let mut module = ModuleReader::new(wasm)?;
+    while !module.eof() {
+        let section = module.read()?;
+        match section.code {
+            SectionCode::Type => {
+                for func_type in section.get_type_section_reader()? {
+                    let func_type = func_type?;
+                    let types = func_type.params.iter().chain(func_type.returns.iter());
+                    for proposal in types.cloned().filter_map(value_type_proposal) {
+                        uses(proposal);
+                    }
+                }
+            }
+            SectionCode::Import => {
+                for import in section.get_import_section_reader()? {
+                    match import?.ty {
+                        ImportSectionEntryType::Global(global_type) => {
+                            if let Some(proposal) = value_type_proposal(global_type.content_type) {
+                                uses(proposal);
+                            }
+                        }
+                        ImportSectionEntryType::Table(table_type)
+                            if table_type.element_type == Type::AnyRef =>
+                        {
+                            uses(Proposal::ReferenceTypes)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            SectionCode::Table => {
+                for table_type in section.get_table_section_reader()? {
+                    if table_type?.element_type == Type::AnyRef {
+                        uses(Proposal::ReferenceTypes);
+                    }
+                }
+            }
+            SectionCode::Global => {
+                for global in section.get_global_section_reader()? {
+                    if let Some(proposal) = value_type_proposal(global?.ty.content_type) {
+                        uses(proposal);
+                    }
+                }
+            }
+            SectionCode::Element => {
+                for element in section.get_element_section_reader()? {
+                    if let ElementKind::Passive(_) = element?.kind {
+                        uses(Proposal::BulkMemory);
+                    }
+                }
+            }
+            SectionCode::Data => {
+                for data in section.get_data_section_reader()? {
+                    if let DataKind::Passive = data?.kind {
+                        uses(Proposal::BulkMemory);
+                    }
+                }
+            }
+            SectionCode::Code => {
+                for body in section.get_code_section_reader()? {
+                    let body = body?;
+                    for local in body.get_locals_reader()? {
+                        if let Some(proposal) = value_type_proposal(local?.1) {
+                            uses(proposal);
+                        }
+                    }
+                    let mut op_reader = body.get_operators_reader()?;
+                    while !op_reader.eof() {
+                        if let Some(proposal) = operator_proposal(&op_reader.read()?) {
+                            uses(proposal);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(proposals)
+}
+
+fn operator_proposal(op: &Operator) -> Option<Proposal> {
+    match op {
+        Operator::MemoryInit { .. }
+        | Operator::DataDrop { .. }
+        | Operator::MemoryCopy
+        | Operator::MemoryFill
+        | Operator::TableInit { .. }
+        | Operator::ElemDrop { .. }
+        | Operator::TableCopy => Some(Proposal::BulkMemory),
+        Operator::RefNull
+        | Operator::RefIsNull
+        | Operator::TableGet { .. }
+        | Operator::TableSet { .. }
+        | Operator::TableGrow { .. }
+        | Operator::TableSize { .. } => Some(Proposal::ReferenceTypes),
+        Operator::F32x4Abs
+        | Operator::F32x4Add
+        | Operator::F32x4ConvertSI32x4
+        | Operator::F32x4ConvertUI32x4
+        | Operator::F32x4Div
+        | Operator::F32x4Eq
+        | Operator::F32x4ExtractLane { .. }
+        | Operator::F32x4Ge
+        | Operator::F32x4Gt
+        | Operator::F32x4Le
+        | Operator::F32x4Lt
+        | Operator::F32x4Max
+        | Operator::F32x4Min
+        | Operator::F32x4Mul
+        | Operator::F32x4Ne
+        | Operator::F32x4Neg
+        | Operator::F32x4ReplaceLane { .. }
+        | Operator::F32x4Splat
+        | Operator::F32x4Sqrt
+        | Operator::F32x4Sub
+        | Operator::F64x2Abs
+        | Operator::F64x2Add
+        | Operator::F64x2ConvertSI64x2
+        | Operator::F64x2ConvertUI64x2
+        | Operator::F64x2Div
+        | Operator::F64x2Eq
+        | Operator::F64x2ExtractLane { .. }
+        | Operator::F64x2Ge
+        | Operator::F64x2Gt
+        | Operator::F64x2Le
+        | Operator::F64x2Lt
+        | Operator::F64x2Max
+        | Operator::F64x2Min
+        | Operator::F64x2Mul
+        | Operator::F64x2Ne
+        | Operator::F64x2Neg
+        | Operator::F64x2ReplaceLane { .. }
+        | Operator::F64x2Splat
+        | Operator::F64x2Sqrt
+        | Operator::F64x2Sub
+        | Operator::I16x8Add
+        | Operator::I16x8AddSaturateS
+        | Operator::I16x8AddSaturateU
+        | Operator::I16x8AllTrue
+        | Operator::I16x8AnyTrue
+        | Operator::I16x8Eq
+        | Operator::I16x8ExtractLaneS { .. }
+        | Operator::I16x8ExtractLaneU { .. }
+        | Operator::I16x8GeS
+        | Operator::I16x8GeU
+        | Operator::I16x8GtS
+        | Operator::I16x8GtU
+        | Operator::I16x8LeS
+        | Operator::I16x8LeU
+        | Operator::I16x8LtS
+        | Operator::I16x8LtU
+        | Operator::I16x8Mul
+        | Operator::I16x8Ne
+        | Operator::I16x8Neg
+        | Operator::I16x8ReplaceLane { .. }
+        | Operator::I16x8Shl
+        | Operator::I16x8ShrS
+        | Operator::I16x8ShrU
+        | Operator::I16x8Splat
+        | Operator::I16x8Sub
+        | Operator::I16x8SubSaturateS
+        | Operator::I16x8SubSaturateU
+        | Operator::I32x4Add
+        | Operator::I32x4AllTrue
+        | Operator::I32x4AnyTrue
+        | Operator::I32x4Eq
+        | Operator::I32x4ExtractLane { .. }
+        | Operator::I32x4GeS
+        | Operator::I32x4GeU
+        | Operator::I32x4GtS
+        | Operator::I32x4GtU
+        | Operator::I32x4LeS
+        | Operator::I32x4LeU
+        | Operator::I32x4LtS
+        | Operator::I32x4LtU
+        | Operator::I32x4Mul
+        | Operator::I32x4Ne
+        | Operator::I32x4Neg
+        | Operator::I32x4ReplaceLane { .. }
+        | Operator::I32x4Shl
+        | Operator::I32x4ShrS
+        | Operator::I32x4ShrU
+        | Operator::I32x4Splat
+        | Operator::I32x4Sub
+        | Operator::I32x4TruncSF32x4Sat
+        | Operator::I32x4TruncUF32x4Sat
+        | Operator::I64x2Add
+        | Operator::I64x2AllTrue
+        | Operator::I64x2AnyTrue
+        | Operator::I64x2ExtractLane { .. }
+        | Operator::I64x2Neg
+        | Operator::I64x2ReplaceLane { .. }
+        | Operator::I64x2Shl
+        | Operator::I64x2ShrS
+        | Operator::I64x2ShrU
+        | Operator::I64x2Splat
+        | Operator::I64x2Sub
+        | Operator::I64x2TruncSF64x2Sat
+        | Operator::I64x2TruncUF64x2Sat
+        | Operator::I8x16Add
+        | Operator::I8x16AddSaturateS
+        | Operator::I8x16AddSaturateU
+        | Operator::I8x16AllTrue
+        | Operator::I8x16AnyTrue
+        | Operator::I8x16Eq
+        | Operator::I8x16ExtractLaneS { .. }
+        | Operator::I8x16ExtractLaneU { .. }
+        | Operator::I8x16GeS
+        | Operator::I8x16GeU
+        | Operator::I8x16GtS
+        | Operator::I8x16GtU
+        | Operator::I8x16LeS
+        | Operator::I8x16LeU
+        | Operator::I8x16LtS
+        | Operator::I8x16LtU
+        | Operator::I8x16Mul
+        | Operator::I8x16Ne
+        | Operator::I8x16Neg
+        | Operator::I8x16ReplaceLane { .. }
+        | Operator::I8x16Shl
+        | Operator::I8x16ShrS
+        | Operator::I8x16ShrU
+        | Operator::I8x16Splat
+        | Operator::I8x16Sub
+        | Operator::I8x16SubSaturateS
+        | Operator::I8x16SubSaturateU
+        | Operator::V128And
+        | Operator::V128Bitselect
+        | Operator::V128Const { .. }
+        | Operator::V128Load { .. }
+        | Operator::V128Not
+        | Operator::V128Or
+        | Operator::V128Store { .. }
+        | Operator::V128Xor
+        | Operator::V8x16Shuffle { .. } => Some(Proposal::Simd),
+        _ => None,
+    }
+}
+
+/// The proposal that introduced values of type `ty`, if any.
+fn value_type_proposal(ty: Type) -> Option<Proposal> {
+    match ty {
+        Type::AnyRef | Type::AnyFunc => Some(Proposal::ReferenceTypes),
+        Type::V128 => Some(Proposal::Simd),
+        _ => None,
+    }
+}
@@ -7,7 +7,9 @@ use crate::{
     vm,
 };
 
-use std::{ptr, sync::Arc};
+use hashbrown::HashMap;
+use serde::{de, ser};
+use std::{fmt, ptr, sync::Arc};
 
 enum AnyfuncInner<'a> {
     Host {
@@ -43,8 +45,128 @@ impl<'a> From<DynFunc<'a>> for Anyfunc<'a> {
     }
 }
 
+/// A reference to a function, the `funcref` of WebAssembly, or null.
+///
+/// A reference points into the `FuncRefs` of the instance or table it was
+/// read from, and is only valid as long as that instance or table is alive.
+#[derive(Clone, Copy)]
+pub struct FuncRef {
+    anyfunc: *const vm::Anyfunc,
+}
+
+// The `vm::Anyfunc` pointed to is immutable.
+unsafe impl Send for FuncRef {}
+unsafe impl Sync for FuncRef {}
+
+impl FuncRef {
+    /// The null reference.
+    pub fn null() -> Self {
+        FuncRef {
+            anyfunc: ptr::null(),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.anyfunc.is_null()
+    }
+
+    /// The table element for this function, `vm::Anyfunc::null()` for null.
+    pub(crate) fn anyfunc(&self) -> vm::Anyfunc {
+        if self.is_null() {
+            vm::Anyfunc::null()
+        } else {
+            unsafe { *self.anyfunc }
+        }
+    }
+
+    /// The word that stands for the reference in WebAssembly code, 0 for null.
+    pub(crate) fn as_raw(&self) -> u64 {
+        self.anyfunc as u64
+    }
+
+    /// # Safety
+    ///
+    /// `raw` must come from `as_raw`.
+    pub(crate) unsafe fn from_raw(raw: u64) -> Self {
+        FuncRef {
+            anyfunc: raw as *const vm::Anyfunc,
+        }
+    }
+}
+
+/// References are equal if they are both null or point to the same function,
+/// even if they were read from different instances or tables.
+impl PartialEq for FuncRef {
+    fn eq(&self, other: &FuncRef) -> bool {
+        let (this, other) = (self.anyfunc(), other.anyfunc());
+        this.func == other.func && this.ctx == other.ctx && this.sig_id.0 == other.sig_id.0
+    }
+}
+
+impl Eq for FuncRef {}
+
+impl fmt::Debug for FuncRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_null() {
+            write!(f, "FuncRef(null)")
+        } else {
+            write!(f, "FuncRef({:p})", self.anyfunc)
+        }
+    }
+}
+
+// Like `AnyRef`, only null references ever need to be serialized.
+impl ser::Serialize for FuncRef {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_null() {
+            serializer.serialize_unit()
+        } else {
+            Err(ser::Error::custom("only null references can be serialized"))
+        }
+    }
+}
+
+impl<'de> de::Deserialize<'de> for FuncRef {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <() as de::Deserialize>::deserialize(deserializer).map(|()| FuncRef::null())
+    }
+}
+
+/// The `vm::Anyfunc`s that the `FuncRef`s handed out by an instance or a
+/// table point to. They are freed along with their owner.
+#[derive(Debug, Default)]
+pub(crate) struct FuncRefs {
+    /// Keyed by the fields of the `vm::Anyfunc`, so that the same function
+    /// always gets the same word.
+    anyfuncs: HashMap<(usize, usize, u32), Box<vm::Anyfunc>>,
+}
+
+impl FuncRefs {
+    /// The reference to the function of `anyfunc`, null if it is null.
+    pub fn get(&mut self, anyfunc: vm::Anyfunc) -> FuncRef {
+        if anyfunc.func.is_null() {
+            return FuncRef::null();
+        }
+
+        let key = (
+            anyfunc.func as usize,
+            anyfunc.ctx as usize,
+            anyfunc.sig_id.0,
+        );
+        let anyfunc = self
+            .anyfuncs
+            .entry(key)
+            .or_insert_with(|| Box::new(anyfunc));
+        FuncRef {
+            anyfunc: &**anyfunc,
+        }
+    }
+}
+
 pub struct AnyfuncTable {
     backing: Vec<vm::Anyfunc>,
+    /// The references that were read from this table.
+    func_refs: FuncRefs,
     max: Option<u32>,
 }
 
@@ -57,6 +179,7 @@ impl AnyfuncTable {
 
         let mut storage = Box::new(AnyfuncTable {
             backing: vec![vm::Anyfunc::null(); initial_table_backing_len],
+            func_refs: FuncRefs::default(),
             max: desc.maximum,
         });
 
@@ -73,10 +196,6 @@ impl AnyfuncTable {
         self.backing.len() as u32
     }
 
-    pub fn internal_buffer(&mut self) -> &mut [vm::Anyfunc] {
-        &mut self.backing
-    }
-
     pub fn grow(&mut self, delta: u32, local: &mut vm::LocalTable) -> Option<u32> {
         let starting_len = self.backing.len() as u32;
 
@@ -96,6 +215,30 @@ impl AnyfuncTable {
         Some(starting_len)
     }
 
+    pub fn get(&self, index: u32) -> Option<vm::Anyfunc> {
+        self.backing.get(index as usize).cloned()
+    }
+
+    /// The reference to the function at index, see `FuncRef`.
+    pub fn get_func_ref(&mut self, index: u32) -> Option<FuncRef> {
+        let anyfunc = self.get(index)?;
+        Some(self.func_refs.get(anyfunc))
+    }
+
+    pub fn set_anyfunc(&mut self, index: u32, anyfunc: vm::Anyfunc) -> Result<(), ()> {
+        if let Some(slot) = self.backing.get_mut(index as usize) {
+            *slot = anyfunc;
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Copies `len` elements from `src` to `dst`, the ranges may overlap.
+    pub fn copy_within(&mut self, dst: u32, src: u32, len: u32) -> Result<(), ()> {
+        super::copy_within(&mut self.backing, dst, src, len)
+    }
+
     pub fn set(&mut self, index: u32, element: Anyfunc) -> Result<(), ()> {
         if let Some(slot) = self.backing.get_mut(index as usize) {
             let anyfunc = match element.inner {
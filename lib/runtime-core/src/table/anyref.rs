@@ -0,0 +1,247 @@
+use super::anyfunc::FuncRef;
+use crate::{error::CreationError, types::TableDescriptor, vm};
+use hashbrown::HashMap;
+use serde::{de, ser};
+use std::{any::Any, fmt, mem, sync::Arc};
+
+type HostObject = Box<dyn Any + Send + Sync>;
+
+/// A reference to a host object or to a function, the `anyref` of WebAssembly, or null.
+///
+/// WebAssembly code cannot look into host objects. It can only pass them
+/// around, store them in tables and globals, and check whether they are null.
+#[derive(Clone, Default)]
+pub struct AnyRef {
+    inner: AnyRefInner,
+}
+
+#[derive(Clone)]
+enum AnyRefInner {
+    Null,
+    Host(Arc<HostObject>),
+    Func(FuncRef),
+}
+
+impl Default for AnyRefInner {
+    fn default() -> Self {
+        AnyRefInner::Null
+    }
+}
+
+impl AnyRef {
+    /// The null reference.
+    pub fn null() -> Self {
+        AnyRef {
+            inner: AnyRefInner::Null,
+        }
+    }
+
+    /// Creates a reference to `object`.
+    pub fn new<T: Any + Send + Sync>(object: T) -> Self {
+        AnyRef {
+            inner: AnyRefInner::Host(Arc::new(Box::new(object))),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        match self.inner {
+            AnyRefInner::Null => true,
+            _ => false,
+        }
+    }
+
+    /// The host object, if the reference points to a `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match &self.inner {
+            AnyRefInner::Host(object) => object.downcast_ref::<T>(),
+            _ => None,
+        }
+    }
+
+    /// The function, if the reference points to one.
+    pub fn func_ref(&self) -> Option<FuncRef> {
+        match self.inner {
+            AnyRefInner::Func(func_ref) => Some(func_ref),
+            _ => None,
+        }
+    }
+
+    /// Whether both references are null or point to the same host object or function.
+    pub fn ptr_eq(&self, other: &AnyRef) -> bool {
+        match (&self.inner, &other.inner) {
+            (AnyRefInner::Func(this), AnyRefInner::Func(other)) => this == other,
+            _ => self.as_raw() == other.as_raw(),
+        }
+    }
+
+    /// The word that stands for the reference in WebAssembly code, 0 for null.
+    ///
+    /// Functions are represented as in `FuncRef::as_raw`, which is always
+    /// aligned, so the lowest bit tells host objects apart. The word of a host
+    /// object is only valid as long as the object is kept alive, see `AnyRefRoots`.
+    pub(crate) fn as_raw(&self) -> u64 {
+        match &self.inner {
+            AnyRefInner::Null => 0,
+            AnyRefInner::Host(object) => &**object as *const HostObject as u64 | 1,
+            AnyRefInner::Func(func_ref) => func_ref.as_raw(),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `raw` must come from `as_raw`, on a reference that is still alive.
+    pub(crate) unsafe fn from_raw(raw: u64) -> Self {
+        if raw == 0 {
+            return AnyRef::null();
+        }
+        if raw & 1 == 0 {
+            return AnyRef::from(FuncRef::from_raw(raw));
+        }
+
+        let object = Arc::from_raw((raw & !1) as *const HostObject);
+        let clone = Arc::clone(&object);
+        mem::forget(object);
+        AnyRef {
+            inner: AnyRefInner::Host(clone),
+        }
+    }
+}
+
+impl From<FuncRef> for AnyRef {
+    fn from(func_ref: FuncRef) -> Self {
+        if func_ref.is_null() {
+            AnyRef::null()
+        } else {
+            AnyRef {
+                inner: AnyRefInner::Func(func_ref),
+            }
+        }
+    }
+}
+
+impl PartialEq for AnyRef {
+    fn eq(&self, other: &AnyRef) -> bool {
+        self.ptr_eq(other)
+    }
+}
+
+impl fmt::Debug for AnyRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.inner {
+            AnyRefInner::Null => write!(f, "AnyRef(null)"),
+            AnyRefInner::Host(_) => write!(f, "AnyRef({:#x})", self.as_raw()),
+            AnyRefInner::Func(func_ref) => write!(f, "AnyRef({:?})", func_ref),
+        }
+    }
+}
+
+// Only null references appear in modules, as the initial value of globals,
+// so host objects never have to be serialized.
+impl ser::Serialize for AnyRef {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_null() {
+            serializer.serialize_unit()
+        } else {
+            Err(ser::Error::custom("only null references can be serialized"))
+        }
+    }
+}
+
+impl<'de> de::Deserialize<'de> for AnyRef {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <() as de::Deserialize>::deserialize(deserializer).map(|()| AnyRef::null())
+    }
+}
+
+/// Keeps the host objects that were handed to WebAssembly code alive.
+///
+/// WebAssembly code may hold on to a reference after the table slot or
+/// global it was read from is overwritten, so the objects are only released
+/// when their owner, a table, a global or an instance, is dropped.
+#[derive(Debug, Default)]
+pub(crate) struct AnyRefRoots {
+    objects: HashMap<u64, AnyRef>,
+}
+
+impl AnyRefRoots {
+    /// Keeps the object of `anyref` alive, and returns its raw word.
+    pub fn root(&mut self, anyref: &AnyRef) -> u64 {
+        let raw = anyref.as_raw();
+        // Functions live as long as the instance or table they were read from, see `FuncRef`.
+        if let AnyRefInner::Host(_) = anyref.inner {
+            self.objects.entry(raw).or_insert_with(|| anyref.clone());
+        }
+        raw
+    }
+}
+
+pub struct AnyRefTable {
+    /// The raw words of the references, see `AnyRef::as_raw`.
+    backing: Vec<u64>,
+    roots: AnyRefRoots,
+    max: Option<u32>,
+}
+
+impl AnyRefTable {
+    pub fn new(
+        desc: TableDescriptor,
+        local: &mut vm::LocalTable,
+    ) -> Result<Box<Self>, CreationError> {
+        let mut storage = Box::new(AnyRefTable {
+            backing: vec![0; desc.minimum as usize],
+            roots: AnyRefRoots::default(),
+            max: desc.maximum,
+        });
+
+        let storage_ptr: *mut AnyRefTable = &mut *storage;
+
+        local.base = storage.backing.as_mut_ptr() as *mut u8;
+        local.count = storage.backing.len();
+        local.table = storage_ptr as *mut ();
+
+        Ok(storage)
+    }
+
+    pub fn current_size(&self) -> u32 {
+        self.backing.len() as u32
+    }
+
+    pub fn grow(&mut self, delta: u32, local: &mut vm::LocalTable) -> Option<u32> {
+        let starting_len = self.backing.len() as u32;
+
+        let new_len = starting_len.checked_add(delta)?;
+
+        if let Some(max) = self.max {
+            if new_len > max {
+                return None;
+            }
+        }
+
+        self.backing.resize(new_len as usize, 0);
+
+        local.base = self.backing.as_mut_ptr() as *mut u8;
+        local.count = self.backing.len();
+
+        Some(starting_len)
+    }
+
+    pub fn get(&self, index: u32) -> Option<AnyRef> {
+        self.backing
+            .get(index as usize)
+            .map(|&raw| unsafe { AnyRef::from_raw(raw) })
+    }
+
+    pub fn set(&mut self, index: u32, element: AnyRef) -> Result<(), ()> {
+        if let Some(slot) = self.backing.get_mut(index as usize) {
+            *slot = self.roots.root(&element);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Copies `len` elements from `src` to `dst`, the ranges may overlap.
+    pub fn copy_within(&mut self, dst: u32, src: u32, len: u32) -> Result<(), ()> {
+        super::copy_within(&mut self.backing, dst, src, len)
+    }
+}
@@ -2,30 +2,41 @@ use crate::{
     error::CreationError,
     export::Export,
     import::IsExport,
+    limits::ResourceLimiter,
     types::{ElementType, TableDescriptor},
     vm,
 };
-use std::{cell::RefCell, fmt, ptr, rc::Rc};
+use std::{cell::RefCell, fmt, ptr, rc::Rc, sync::Arc};
 
 mod anyfunc;
+mod anyref;
 
-pub use self::anyfunc::Anyfunc;
 use self::anyfunc::AnyfuncTable;
+pub(crate) use self::anyfunc::FuncRefs;
+pub use self::anyfunc::{Anyfunc, FuncRef};
+pub use self::anyref::AnyRef;
+pub(crate) use self::anyref::AnyRefRoots;
+use self::anyref::AnyRefTable;
 use crate::error::GrowError;
 
 pub enum Element<'a> {
     Anyfunc(Anyfunc<'a>),
+    FuncRef(FuncRef),
+    AnyRef(AnyRef),
 }
 
 // #[derive(Debug)]
 pub enum TableStorage {
     /// This is intended to be a caller-checked Anyfunc.
     Anyfunc(Box<AnyfuncTable>),
+    /// The raw words of references, see `AnyRef`.
+    AnyRef(Box<AnyRefTable>),
 }
 
 pub struct Table {
     desc: TableDescriptor,
     storage: Rc<RefCell<(TableStorage, vm::LocalTable)>>,
+    limiter: Option<Arc<dyn ResourceLimiter>>,
 }
 
 impl Table {
@@ -67,37 +78,115 @@ impl Table {
 
         let storage = match desc.element {
             ElementType::Anyfunc => TableStorage::Anyfunc(AnyfuncTable::new(desc, &mut local)?),
+            ElementType::AnyRef => TableStorage::AnyRef(AnyRefTable::new(desc, &mut local)?),
         };
 
         Ok(Self {
             desc,
             storage: Rc::new(RefCell::new((storage, local))),
+            limiter: None,
         })
     }
 
+    /// Makes every grow of this table go through `limiter`.
+    pub(crate) fn with_limiter(self, limiter: Option<Arc<dyn ResourceLimiter>>) -> Self {
+        Self { limiter, ..self }
+    }
+
     /// Get the `TableDescriptor` used to create this `Table`.
     pub fn descriptor(&self) -> TableDescriptor {
         self.desc
     }
 
+    /// Get the element at index, or `None` if it is out of bounds.
+    ///
+    /// The elements of `anyfunc` tables are returned as `Element::FuncRef`,
+    /// and those of `anyref` tables as `Element::AnyRef`.
+    pub fn get(&self, index: u32) -> Option<Element<'static>> {
+        match &mut *self.storage.borrow_mut() {
+            (TableStorage::Anyfunc(ref mut anyfunc_table), _) => {
+                anyfunc_table.get_func_ref(index).map(Element::FuncRef)
+            }
+            (TableStorage::AnyRef(ref anyref_table), _) => {
+                anyref_table.get(index).map(Element::AnyRef)
+            }
+        }
+    }
+
     /// Set the element at index.
+    ///
+    /// Fails if the index is out of bounds, or if the element cannot be
+    /// stored in this table: `anyfunc` tables only take functions.
     pub fn set(&self, index: u32, element: Element) -> Result<(), ()> {
+        match &mut *self.storage.borrow_mut() {
+            (TableStorage::Anyfunc(ref mut anyfunc_table), _) => match element {
+                Element::Anyfunc(anyfunc) => anyfunc_table.set(index, anyfunc),
+                Element::FuncRef(func_ref) => anyfunc_table.set_anyfunc(index, func_ref.anyfunc()),
+                Element::AnyRef(_) => Err(()),
+            },
+            (TableStorage::AnyRef(ref mut anyref_table), _) => match element {
+                Element::AnyRef(anyref) => anyref_table.set(index, anyref),
+                Element::FuncRef(func_ref) => anyref_table.set(index, AnyRef::from(func_ref)),
+                Element::Anyfunc(_) => Err(()),
+            },
+        }
+    }
+
+    /// The word that stands for the element at index in WebAssembly code.
+    pub(crate) fn get_raw(&self, index: u32) -> Option<u64> {
+        match &mut *self.storage.borrow_mut() {
+            (TableStorage::Anyfunc(ref mut anyfunc_table), _) => anyfunc_table
+                .get_func_ref(index)
+                .map(|func_ref| func_ref.as_raw()),
+            (TableStorage::AnyRef(ref anyref_table), _) => {
+                anyref_table.get(index).map(|anyref| anyref.as_raw())
+            }
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `raw` must be a word that WebAssembly code got for a reference of the
+    /// element type of this table, see `get_raw`.
+    pub(crate) unsafe fn set_raw(&self, index: u32, raw: u64) -> Result<(), ()> {
         match &mut *self.storage.borrow_mut() {
             (TableStorage::Anyfunc(ref mut anyfunc_table), _) => {
-                match element {
-                    Element::Anyfunc(anyfunc) => anyfunc_table.set(index, anyfunc),
-                    // _ => panic!("wrong element type for anyfunc table"),
-                }
+                anyfunc_table.set_anyfunc(index, FuncRef::from_raw(raw).anyfunc())
+            }
+            (TableStorage::AnyRef(ref mut anyref_table), _) => {
+                anyref_table.set(index, AnyRef::from_raw(raw))
             }
         }
     }
 
-    pub(crate) fn anyfunc_direct_access_mut<F, R>(&self, f: F) -> R
-    where
-        F: FnOnce(&mut [vm::Anyfunc]) -> R,
-    {
+    /// Copies `len` elements from `src` to `dst`, the ranges may overlap.
+    pub(crate) fn copy_within(&self, dst: u32, src: u32, len: u32) -> Result<(), ()> {
         match &mut *self.storage.borrow_mut() {
-            (TableStorage::Anyfunc(ref mut anyfunc_table), _) => f(anyfunc_table.internal_buffer()),
+            (TableStorage::Anyfunc(ref mut anyfunc_table), _) => {
+                anyfunc_table.copy_within(dst, src, len)
+            }
+            (TableStorage::AnyRef(ref mut anyref_table), _) => {
+                anyref_table.copy_within(dst, src, len)
+            }
+        }
+    }
+
+    /// Set the element at index to a function, the way element segments do.
+    ///
+    /// `anyref` tables store a reference to it from `func_refs`.
+    pub(crate) fn set_anyfunc(
+        &self,
+        index: u32,
+        anyfunc: vm::Anyfunc,
+        func_refs: &mut FuncRefs,
+    ) -> Result<(), ()> {
+        match &mut *self.storage.borrow_mut() {
+            (TableStorage::Anyfunc(ref mut anyfunc_table), _) => {
+                anyfunc_table.set_anyfunc(index, anyfunc)
+            }
+            (TableStorage::AnyRef(ref mut anyref_table), _) => {
+                anyref_table.set(index, AnyRef::from(func_refs.get(anyfunc)))
+            }
         }
     }
 
@@ -105,6 +194,7 @@ impl Table {
     pub fn size(&self) -> u32 {
         match &*self.storage.borrow() {
             (TableStorage::Anyfunc(ref anyfunc_table), _) => anyfunc_table.current_size(),
+            (TableStorage::AnyRef(ref anyref_table), _) => anyref_table.current_size(),
         }
     }
 
@@ -114,11 +204,34 @@ impl Table {
             return Ok(self.size());
         }
 
-        match &mut *self.storage.borrow_mut() {
+        // The limiter to tell if the grow fails after it was allowed, and what it allowed.
+        let mut reserved = None;
+        if let Some(limiter) = &self.limiter {
+            let current = self.size();
+            let desired = current
+                .checked_add(delta)
+                .ok_or(GrowError::TableGrowError)?;
+            let within_maximum = self.desc.maximum.map_or(true, |max| desired <= max);
+            if within_maximum {
+                if !limiter.table_growing(current, desired, self.desc.maximum) {
+                    return Err(GrowError::ResourceLimitExceeded);
+                }
+                reserved = Some((limiter, current, desired));
+            }
+        }
+
+        let result = match &mut *self.storage.borrow_mut() {
             (TableStorage::Anyfunc(ref mut anyfunc_table), ref mut local) => anyfunc_table
                 .grow(delta, local)
                 .ok_or(GrowError::TableGrowError),
+            (TableStorage::AnyRef(ref mut anyref_table), ref mut local) => anyref_table
+                .grow(delta, local)
+                .ok_or(GrowError::TableGrowError),
+        };
+        if let (Err(_), Some((limiter, current, desired))) = (&result, reserved) {
+            limiter.table_grow_failed(current, desired);
         }
+        result
     }
 
     pub fn vm_local_table(&mut self) -> *mut vm::LocalTable {
@@ -126,6 +239,24 @@ impl Table {
     }
 }
 
+/// Copies `len` items of `slice` from `src` to `dst`, the ranges may overlap.
+fn copy_within<T: Copy>(slice: &mut [T], dst: u32, src: u32, len: u32) -> Result<(), ()> {
+    let size = slice.len() as u64;
+    let in_bounds = |offset: u32| u64::from(offset) + u64::from(len) <= size;
+    if !in_bounds(src) || !in_bounds(dst) {
+        return Err(());
+    }
+
+    unsafe {
+        ptr::copy(
+            slice.as_ptr().add(src as usize),
+            slice.as_mut_ptr().add(dst as usize),
+            len as usize,
+        );
+    }
+    Ok(())
+}
+
 impl IsExport for Table {
     fn to_export(&self) -> Export {
         Export::Table(self.clone())
@@ -137,6 +268,7 @@ impl Clone for Table {
         Self {
             desc: self.desc,
             storage: Rc::clone(&self.storage),
+            limiter: self.limiter.clone(),
         }
     }
 }
@@ -153,7 +285,8 @@ impl fmt::Debug for Table {
 #[cfg(test)]
 mod table_tests {
 
-    use super::{ElementType, Table, TableDescriptor};
+    use super::{AnyRef, Element, ElementType, FuncRefs, Table, TableDescriptor};
+    use crate::vm;
 
     #[test]
     fn test_initial_table_size() {
@@ -166,4 +299,81 @@ mod table_tests {
         assert_eq!(table.size(), 10);
     }
 
+    #[test]
+    fn test_anyref_table_get_set() {
+        let table = Table::new(TableDescriptor {
+            element: ElementType::AnyRef,
+            minimum: 2,
+            maximum: Some(4),
+        })
+        .unwrap();
+
+        let object = AnyRef::new(String::from("host object"));
+        table.set(1, Element::AnyRef(object.clone())).unwrap();
+        assert!(table.set(2, Element::AnyRef(object.clone())).is_err());
+
+        match table.get(0) {
+            Some(Element::AnyRef(anyref)) => assert!(anyref.is_null()),
+            _ => panic!("expected a null anyref"),
+        }
+        match table.get(1) {
+            Some(Element::AnyRef(anyref)) => {
+                assert!(anyref.ptr_eq(&object));
+                assert_eq!(
+                    anyref.downcast_ref::<String>().map(String::as_str),
+                    Some("host object")
+                );
+            }
+            _ => panic!("expected the host object"),
+        }
+        assert!(table.get(2).is_none());
+    }
+
+    #[test]
+    fn test_anyfunc_table_rejects_anyref() {
+        let table = Table::new(TableDescriptor {
+            element: ElementType::Anyfunc,
+            minimum: 1,
+            maximum: None,
+        })
+        .unwrap();
+        assert!(table.set(0, Element::AnyRef(AnyRef::new(1u32))).is_err());
+        match table.get(0) {
+            Some(Element::FuncRef(func_ref)) => assert!(func_ref.is_null()),
+            _ => panic!("expected a null funcref"),
+        }
+    }
+
+    #[test]
+    fn test_anyref_table_set_anyfunc() {
+        let table = Table::new(TableDescriptor {
+            element: ElementType::AnyRef,
+            minimum: 2,
+            maximum: None,
+        })
+        .unwrap();
+        let mut func_refs = FuncRefs::default();
+        let anyfunc = vm::Anyfunc {
+            func: 0x1000 as *const vm::Func,
+            ctx: 0x2000 as *mut vm::Ctx,
+            sig_id: vm::SigId(3),
+        };
+
+        table.set_anyfunc(0, anyfunc, &mut func_refs).unwrap();
+        table.set_anyfunc(1, anyfunc, &mut func_refs).unwrap();
+        assert!(table.set_anyfunc(2, anyfunc, &mut func_refs).is_err());
+
+        let func_ref = match table.get(0) {
+            Some(Element::AnyRef(anyref)) => anyref.func_ref().unwrap(),
+            _ => panic!("expected a function"),
+        };
+        assert_eq!(func_ref.anyfunc().func, anyfunc.func);
+        assert_eq!(func_ref.anyfunc().ctx, anyfunc.ctx);
+        // The same function is stored once.
+        match table.get(1) {
+            Some(Element::AnyRef(anyref)) => assert_eq!(anyref.func_ref(), Some(func_ref)),
+            _ => panic!("expected a function"),
+        }
+    }
+
 }
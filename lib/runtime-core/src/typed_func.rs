@@ -1,3 +1,5 @@
+#[cfg(all(unix, target_arch = "x86_64"))]
+use crate::{dynamic_func::DynamicFunc, error::CreationError, types::Value};
 use crate::{
     error::RuntimeError,
     export::{Context, Export, FuncPointer},
@@ -16,6 +18,7 @@ use std::{
     sync::Arc,
 };
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(C)]
 pub enum WasmTrapInfo {
     Unreachable = 0,
@@ -23,6 +26,9 @@ pub enum WasmTrapInfo {
     MemoryOutOfBounds = 2,
     CallIndirectOOB = 3,
     IllegalArithmetic = 4,
+    MisalignedAtomicAccess = 5,
+    UnsharedMemoryWait = 6,
+    TableOutOfBounds = 7,
     Unknown,
 }
 
@@ -39,6 +45,9 @@ impl fmt::Display for WasmTrapInfo {
                 WasmTrapInfo::MemoryOutOfBounds => "memory out-of-bounds access",
                 WasmTrapInfo::CallIndirectOOB => "`call_indirect` out-of-bounds",
                 WasmTrapInfo::IllegalArithmetic => "illegal arithmetic operation",
+                WasmTrapInfo::MisalignedAtomicAccess => "misaligned atomic memory access",
+                WasmTrapInfo::UnsharedMemoryWait => "`memory.atomic.wait` on an unshared memory",
+                WasmTrapInfo::TableOutOfBounds => "table out-of-bounds access",
                 WasmTrapInfo::Unknown => "unknown",
             }
         )
@@ -159,6 +168,7 @@ where
 pub struct Func<'a, Args = (), Rets = (), Inner: Kind = Wasm> {
     inner: Inner,
     f: NonNull<vm::Func>,
+    func_env: Option<vm::FuncEnvRef>,
     ctx: *mut Ctx,
     _phantom: PhantomData<(&'a (), Args, Rets)>,
 }
@@ -176,6 +186,7 @@ where
         Func {
             inner,
             f,
+            func_env: None,
             ctx,
             _phantom: PhantomData,
         }
@@ -187,19 +198,48 @@ where
     Args: WasmTypeList,
     Rets: WasmTypeList,
 {
+    /// Creates a host function from a function or a closure.
+    ///
+    /// The state captured by a closure is kept alive as long as the `Func`,
+    /// the import object it is inserted in, or an instance importing it is.
     pub fn new<F>(f: F) -> Func<'a, Args, Rets, Host>
     where
-        F: ExternalFunction<Args, Rets>,
+        F: ExternalFunction<Args, Rets> + 'static,
     {
+        let raw = f.to_raw();
+        let func_env = if mem::size_of::<F>() == 0 {
+            None
+        } else {
+            Some(vm::FuncEnvRef::new(f))
+        };
+
         Func {
             inner: Host(()),
-            f: f.to_raw(),
+            f: raw,
+            func_env,
             ctx: ptr::null_mut(),
             _phantom: PhantomData,
         }
     }
 }
 
+#[cfg(all(unix, target_arch = "x86_64"))]
+impl<'a> Func<'a, (), (), Host> {
+    /// Creates a host function from a signature that is only known at runtime,
+    /// and a function that takes and returns `Value`s.
+    ///
+    /// See [`DynamicFunc`] for more.
+    ///
+    /// [`DynamicFunc`]: ../dynamic_func/struct.DynamicFunc.html
+    pub fn new_dynamic<F, E>(signature: FuncSig, f: F) -> Result<DynamicFunc, CreationError>
+    where
+        F: Fn(&mut Ctx, &[Value]) -> Result<Vec<Value>, E> + 'static,
+        E: 'static,
+    {
+        DynamicFunc::new(signature, f)
+    }
+}
+
 impl<'a, Args, Rets, Inner> Func<'a, Args, Rets, Inner>
 where
     Args: WasmTypeList,
@@ -292,11 +332,14 @@ impl<A: WasmExternType> WasmTypeList for (A,) {
             Ok(Rets::from_ret_array(rets))
         } else {
             if let Some(data) = user_error {
-                Err(RuntimeError::Error { data })
+                Err(RuntimeError::from_early_trap(data))
             } else {
-                Err(RuntimeError::Trap {
-                    msg: trap.to_string().into(),
-                })
+                let module = &*(*ctx).module;
+                Err(RuntimeError::from_trap(
+                    trap,
+                    &module.info,
+                    &*module.runnable_module,
+                ))
             }
         }
     }
@@ -353,9 +396,10 @@ macro_rules! impl_traits {
                     Ok(Rets::from_ret_array(rets))
                 } else {
                     if let Some(data) = user_error {
-                        Err(RuntimeError::Error { data })
+                        Err(RuntimeError::from_early_trap(data))
                     } else {
-                        Err(RuntimeError::Trap { msg: trap.to_string().into() })
+                        let module = &*(*ctx).module;
+                        Err(RuntimeError::from_trap(trap, &module.info, &*module.runnable_module))
                     }
                 }
             }
@@ -364,12 +408,19 @@ macro_rules! impl_traits {
         impl< $( $x: WasmExternType, )* Rets: WasmTypeList, Trap: TrapEarly<Rets>, FN: Fn( &mut Ctx $( ,$x )* ) -> Trap> ExternalFunction<($( $x ),*), Rets> for FN {
             #[allow(non_snake_case)]
             fn to_raw(&self) -> NonNull<vm::Func> {
-                assert_eq!(mem::size_of::<Self>(), 0, "you cannot use a closure that captures state for `Func`.");
-
                 /// This is required for the llvm backend to be able to unwind through this function.
                 #[cfg_attr(nightly, unwind(allowed))]
-                extern fn wrap<$( $x: WasmExternType, )* Rets: WasmTypeList, Trap: TrapEarly<Rets>, FN: Fn( &mut Ctx $( ,$x )* ) -> Trap>( ctx: &mut Ctx $( ,$x: <$x as WasmExternType>::Native )* ) -> Rets::CStruct {
-                    let f: FN = unsafe { mem::transmute_copy(&()) };
+                extern fn wrap<$( $x: WasmExternType, )* Rets: WasmTypeList, Trap: TrapEarly<Rets>, FN: Fn( &mut Ctx $( ,$x )* ) -> Trap>( vmctx: *mut Ctx $( ,$x: <$x as WasmExternType>::Native )* ) -> Rets::CStruct {
+                    // A closure that captures state is moved into a `vm::FuncEnvRef` by
+                    // `Func::new`, and is called with a `vm::FuncCtx` that points to it.
+                    let (ctx, f): (&mut Ctx, &FN) = unsafe {
+                        if mem::size_of::<FN>() == 0 {
+                            (&mut *vmctx, &*NonNull::<FN>::dangling().as_ptr())
+                        } else {
+                            let func_ctx = &*(vmctx as *const vm::FuncCtx);
+                            (&mut *func_ctx.vmctx, &*(func_ctx.func_env.as_ptr() as *const FN))
+                        }
+                    };
 
                     let err = match panic::catch_unwind(panic::AssertUnwindSafe(|| {
                         f( ctx $( ,WasmExternType::from_native($x) )* ).report()
@@ -442,6 +493,7 @@ where
             func,
             ctx,
             signature,
+            func_env: self.func_env.clone(),
         }
     }
 }
@@ -472,4 +524,30 @@ mod tests {
             },
         };
     }
+
+    #[test]
+    fn test_closure_env() {
+        use crate::{func, imports};
+        use std::rc::Rc;
+
+        let state = Rc::new(7);
+        let captured = Rc::clone(&state);
+        let import_object = imports! {
+            "env" => {
+                "foo" => func!(move |_ctx: &mut Ctx, a: i32| -> i32 { a + *captured }),
+            },
+        };
+
+        match import_object
+            .get_namespace("env")
+            .unwrap()
+            .get_export("foo")
+        {
+            Some(Export::Function { func_env, .. }) => assert!(func_env.is_some()),
+            _ => panic!("expected a function export"),
+        }
+        assert_eq!(Rc::strong_count(&state), 2);
+        drop(import_object);
+        assert_eq!(Rc::strong_count(&state), 1);
+    }
 }
@@ -1,4 +1,10 @@
-use crate::{memory::MemoryType, module::ModuleInfo, structures::TypedIndex, units::Pages};
+use crate::{
+    memory::MemoryType,
+    module::ModuleInfo,
+    structures::TypedIndex,
+    table::{AnyRef, FuncRef},
+    units::Pages,
+};
 use std::borrow::Cow;
 
 /// Represents a WebAssembly type.
@@ -12,6 +18,12 @@ pub enum Type {
     F32,
     /// The `f64` type.
     F64,
+    /// The `anyref` type, references to host objects or functions.
+    AnyRef,
+    /// The `funcref` type, references to functions.
+    FuncRef,
+    /// The `v128` type, a vector of 128 bits.
+    V128,
 }
 
 impl std::fmt::Display for Type {
@@ -34,6 +46,12 @@ pub enum Value {
     F32(f32),
     /// The `f64` type.
     F64(f64),
+    /// The `anyref` type.
+    AnyRef(AnyRef),
+    /// The `funcref` type.
+    FuncRef(FuncRef),
+    /// The `v128` type, with the first lane in the least significant bits.
+    V128(u128),
 }
 
 impl Value {
@@ -43,6 +61,9 @@ impl Value {
             Value::I64(_) => Type::I64,
             Value::F32(_) => Type::F32,
             Value::F64(_) => Type::F64,
+            Value::AnyRef(_) => Type::AnyRef,
+            Value::FuncRef(_) => Type::FuncRef,
+            Value::V128(_) => Type::V128,
         }
     }
 }
@@ -71,6 +92,12 @@ impl From<f64> for Value {
     }
 }
 
+impl From<u128> for Value {
+    fn from(v: u128) -> Self {
+        Value::V128(v)
+    }
+}
+
 pub unsafe trait NativeWasmType: Copy + Into<Value>
 where
     Self: Sized,
@@ -257,9 +284,11 @@ convert_value_impl!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
 pub enum ElementType {
     /// Any wasm function.
     Anyfunc,
+    /// References to host objects or functions.
+    AnyRef,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TableDescriptor {
     /// Type of data stored in this table.
     pub element: ElementType,
@@ -392,6 +421,15 @@ impl std::fmt::Display for FuncSig {
     }
 }
 
+/// The type of an import or an export of a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternDescriptor {
+    Function(FuncSig),
+    Memory(MemoryDescriptor),
+    Table(TableDescriptor),
+    Global(GlobalDescriptor),
+}
+
 pub trait LocalImport {
     type Local: TypedIndex;
     type Import: TypedIndex;
@@ -490,6 +528,11 @@ impl TypedIndex for SigIndex {
     }
 }
 
+// The index of a segment in the data section, as used by `memory.init` and `data.drop`.
+define_map_index!(DataIndex);
+// The index of a segment in the element section, as used by `table.init` and `elem.drop`.
+define_map_index!(ElemIndex);
+
 pub enum LocalOrImport<T>
 where
     T: LocalImport,
@@ -5,7 +5,14 @@ use crate::{
     structures::TypedIndex,
     types::{LocalOrImport, MemoryIndex},
 };
-use std::{ffi::c_void, mem, ptr};
+use std::{
+    any::Any,
+    ffi::c_void,
+    fmt, mem,
+    ptr::{self, NonNull},
+    rc::Rc,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use hashbrown::HashMap;
 
@@ -20,8 +27,8 @@ pub struct Ctx {
 
     pub(crate) local_functions: *const *const Func,
 
-    local_backing: *mut LocalBacking,
-    import_backing: *mut ImportBacking,
+    pub(crate) local_backing: *mut LocalBacking,
+    pub(crate) import_backing: *mut ImportBacking,
     pub(crate) module: *const ModuleInner,
 
     pub data: *mut c_void,
@@ -61,6 +68,16 @@ pub struct InternalCtx {
     /// signature id. This is used to allow call-indirect to other
     /// modules safely.
     pub dynamic_sigindices: *const SigId,
+
+    /// Set from another thread to stop the running code at the
+    /// next function entry or loop header.
+    pub interrupted: *const AtomicBool,
+
+    /// The amount of fuel consumed by metered code so far.
+    pub fuel_used: u64,
+
+    /// The amount of fuel metered code may consume before it stops.
+    pub fuel_limit: u64,
 }
 
 impl Ctx {
@@ -82,6 +99,11 @@ impl Ctx {
                 imported_funcs: import_backing.vm_functions.as_mut_ptr(),
 
                 dynamic_sigindices: local_backing.dynamic_sigindices.as_ptr(),
+
+                interrupted: &*local_backing.interrupted,
+
+                fuel_used: 0,
+                fuel_limit: u64::max_value(),
             },
             local_functions: local_backing.local_functions.as_ptr(),
 
@@ -114,6 +136,11 @@ impl Ctx {
                 imported_funcs: import_backing.vm_functions.as_mut_ptr(),
 
                 dynamic_sigindices: local_backing.dynamic_sigindices.as_ptr(),
+
+                interrupted: &*local_backing.interrupted,
+
+                fuel_used: 0,
+                fuel_limit: u64::max_value(),
             },
             local_functions: local_backing.local_functions.as_ptr(),
 
@@ -159,6 +186,14 @@ impl Ctx {
         }
     }
 
+    /// Returns `true` if the instance was interrupted with an `InterruptHandle`.
+    ///
+    /// Host functions that block, like sleeping or waiting for input,
+    /// should check this now and then and return early when it is set.
+    pub fn is_interrupted(&self) -> bool {
+        unsafe { (*self.internal.interrupted).load(Ordering::SeqCst) }
+    }
+
     /// Gives access to the emscripten symbol map, used for debugging
     pub unsafe fn borrow_symbol_map(&self) -> &Option<HashMap<u32, String>> {
         &(*self.module).info.em_symbol_map
@@ -200,9 +235,21 @@ impl Ctx {
         7 * (mem::size_of::<usize>() as u8)
     }
 
-    pub fn offset_local_functions() -> u8 {
+    pub fn offset_interrupted() -> u8 {
         8 * (mem::size_of::<usize>() as u8)
     }
+
+    pub fn offset_fuel_used() -> u8 {
+        9 * (mem::size_of::<usize>() as u8)
+    }
+
+    pub fn offset_fuel_limit() -> u8 {
+        9 * (mem::size_of::<usize>() as u8) + mem::size_of::<u64>() as u8
+    }
+
+    pub fn offset_local_functions() -> u8 {
+        9 * (mem::size_of::<usize>() as u8) + 2 * (mem::size_of::<u64>() as u8)
+    }
 }
 
 enum InnerFunc {}
@@ -212,7 +259,46 @@ enum InnerFunc {}
 #[repr(C)]
 pub struct Func(InnerFunc);
 
+enum InnerFuncEnv {}
+/// The environment of a host function, such as the state captured by a closure.
+/// Like `Func`, this cannot be dereferenced.
+#[repr(C)]
+pub struct FuncEnv(InnerFuncEnv);
+
+/// A counted reference to the environment of a host function, held by the
+/// `Func` it was created from and by every instance that imports it.
+#[derive(Clone)]
+pub struct FuncEnvRef(Rc<dyn Any>);
+
+impl FuncEnvRef {
+    pub(crate) fn new<T: 'static>(env: T) -> Self {
+        FuncEnvRef(Rc::new(env))
+    }
+
+    /// The environment, which stays valid as long as this reference does.
+    pub fn as_ptr(&self) -> NonNull<FuncEnv> {
+        NonNull::from(&*self.0).cast()
+    }
+}
+
+impl fmt::Debug for FuncEnvRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("FuncEnvRef").field(&self.as_ptr()).finish()
+    }
+}
+
+/// The context a host function with an environment is called with, in
+/// place of the vmctx of the instance that imports it.
+#[derive(Debug)]
+#[repr(C)]
+pub struct FuncCtx {
+    pub vmctx: *mut Ctx,
+    pub func_env: NonNull<FuncEnv>,
+}
+
 /// An imported function, which contains the vmctx that owns this function.
+///
+/// Host functions with an environment get a `FuncCtx` as their vmctx.
 #[derive(Debug, Clone)]
 #[repr(C)]
 pub struct ImportedFunc {
@@ -292,10 +378,12 @@ impl LocalMemory {
 }
 
 /// Definition of a global used by the VM.
+///
+/// The data is wide enough for a `v128`; smaller values are stored in its low bits.
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct LocalGlobal {
-    pub data: u64,
+    pub data: u128,
 }
 
 impl LocalGlobal {
@@ -356,6 +444,7 @@ impl Anyfunc {
 #[cfg(test)]
 mod vm_offset_tests {
     use super::{Anyfunc, Ctx, ImportedFunc, InternalCtx, LocalGlobal, LocalMemory, LocalTable};
+    use std::mem;
 
     #[test]
     fn vmctx() {
@@ -396,6 +485,21 @@ mod vm_offset_tests {
             offset_of!(InternalCtx => imported_funcs).get_byte_offset(),
         );
 
+        assert_eq!(
+            Ctx::offset_interrupted() as usize,
+            offset_of!(InternalCtx => interrupted).get_byte_offset(),
+        );
+
+        assert_eq!(
+            Ctx::offset_fuel_used() as usize,
+            offset_of!(InternalCtx => fuel_used).get_byte_offset(),
+        );
+
+        assert_eq!(
+            Ctx::offset_fuel_limit() as usize,
+            offset_of!(InternalCtx => fuel_limit).get_byte_offset(),
+        );
+
         assert_eq!(
             Ctx::offset_local_functions() as usize,
             offset_of!(Ctx => local_functions).get_byte_offset(),
@@ -447,6 +551,10 @@ mod vm_offset_tests {
             LocalGlobal::offset_data() as usize,
             offset_of!(LocalGlobal => data).get_byte_offset(),
         );
+
+        // The backends load values narrower than a `v128` from the start of `data`.
+        assert_eq!(LocalGlobal::offset_data(), 0);
+        assert_eq!(LocalGlobal::size() as usize, mem::size_of::<u128>());
     }
 
     #[test]
@@ -471,8 +579,9 @@ mod vm_offset_tests {
 #[cfg(test)]
 mod vm_ctx_tests {
     use super::{Ctx, ImportBacking, LocalBacking};
-    use crate::module::{ModuleInfo, ModuleInner, StringTable};
+    use crate::module::{ModuleInfo, ModuleInner, NameSection, StringTable};
     use crate::structures::Map;
+    use hashbrown::HashMap;
     use std::ffi::c_void;
 
     struct TestData {
@@ -508,6 +617,14 @@ mod vm_ctx_tests {
 
             dynamic_sigindices: Map::new().into_boxed_map(),
             local_functions: Map::new().into_boxed_map(),
+
+            passive_data: HashMap::new(),
+            passive_elements: HashMap::new(),
+
+            anyref_roots: Default::default(),
+            func_refs: Default::default(),
+
+            interrupted: Default::default(),
         };
         let mut import_backing = ImportBacking {
             memories: Map::new().into_boxed_map(),
@@ -518,6 +635,9 @@ mod vm_ctx_tests {
             vm_memories: Map::new().into_boxed_map(),
             vm_tables: Map::new().into_boxed_map(),
             vm_globals: Map::new().into_boxed_map(),
+
+            func_ctxs: Vec::new(),
+            func_envs: Vec::new(),
         };
         let module = generate_module();
         let data = &mut data as *mut _ as *mut c_void;
@@ -548,7 +668,6 @@ mod vm_ctx_tests {
         use crate::cache::Error as CacheError;
         use crate::typed_func::Wasm;
         use crate::types::{LocalFuncIndex, SigIndex};
-        use hashbrown::HashMap;
         use std::any::Any;
         use std::ptr::NonNull;
         struct Placeholder;
@@ -593,6 +712,9 @@ mod vm_ctx_tests {
                 data_initializers: Vec::new(),
                 elem_initializers: Vec::new(),
 
+                passive_data: HashMap::new(),
+                passive_elements: HashMap::new(),
+
                 start_func: None,
 
                 func_assoc: Map::new(),
@@ -605,6 +727,8 @@ mod vm_ctx_tests {
                 em_symbol_map: None,
 
                 custom_sections: HashMap::new(),
+
+                name_section: NameSection::default(),
             },
         }
     }
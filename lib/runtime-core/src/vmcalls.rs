@@ -1,12 +1,20 @@
 #![allow(clippy::cast_ptr_alignment)]
 
 use crate::{
-    memory::{DynamicMemory, StaticMemory},
+    instance::Interrupted,
+    memory::{wait, Atomic, DynamicMemory, SharedStaticMemory, StaticMemory},
+    metering::FuelExhausted,
     structures::TypedIndex,
-    types::{ImportedMemoryIndex, LocalMemoryIndex, LocalTableIndex},
+    table::Table,
+    typed_func::WasmTrapInfo,
+    types::{
+        DataIndex, ElemIndex, FuncIndex, ImportedMemoryIndex, LocalMemoryIndex, LocalOrImport,
+        MemoryIndex, TableIndex,
+    },
     units::Pages,
     vm,
 };
+use std::{mem, ptr, slice, sync::atomic::Ordering, time::Duration};
 
 // +*****************************+
 // |       LOCAL MEMORIES        |
@@ -20,7 +28,9 @@ pub unsafe extern "C" fn local_static_memory_grow(
     let local_memory = *ctx.internal.memories.add(memory_index.index());
     let memory = (*local_memory).memory as *mut StaticMemory;
 
-    match (*memory).grow(delta, &mut *local_memory) {
+    match (*ctx.local_backing).memories[memory_index]
+        .limit_grow(delta, || (*memory).grow(delta, &mut *local_memory))
+    {
         Ok(old) => old.0 as i32,
         Err(_) => -1,
     }
@@ -44,7 +54,9 @@ pub unsafe extern "C" fn local_dynamic_memory_grow(
     let local_memory = *ctx.internal.memories.add(memory_index.index());
     let memory = (*local_memory).memory as *mut DynamicMemory;
 
-    match (*memory).grow(delta, &mut *local_memory) {
+    match (*ctx.local_backing).memories[memory_index]
+        .limit_grow(delta, || (*memory).grow(delta, &mut *local_memory))
+    {
         Ok(old) => old.0 as i32,
         Err(_) => -1,
     }
@@ -60,6 +72,32 @@ pub unsafe extern "C" fn local_dynamic_memory_size(
     (*memory).size()
 }
 
+pub unsafe extern "C" fn local_shared_static_memory_grow(
+    ctx: &mut vm::Ctx,
+    memory_index: LocalMemoryIndex,
+    delta: Pages,
+) -> i32 {
+    let local_memory = *ctx.internal.memories.add(memory_index.index());
+    let memory = (*local_memory).memory as *mut SharedStaticMemory;
+
+    match (*ctx.local_backing).memories[memory_index]
+        .limit_grow(delta, || (*memory).grow(delta, &mut *local_memory))
+    {
+        Ok(old) => old.0 as i32,
+        Err(_) => -1,
+    }
+}
+
+pub unsafe extern "C" fn local_shared_static_memory_size(
+    ctx: &vm::Ctx,
+    memory_index: LocalMemoryIndex,
+) -> Pages {
+    let local_memory = *ctx.internal.memories.add(memory_index.index());
+    let memory = (*local_memory).memory as *mut SharedStaticMemory;
+
+    (*memory).size()
+}
+
 // +*****************************+
 // |      IMPORTED MEMORIES      |
 // +****************************+
@@ -75,7 +113,9 @@ pub unsafe extern "C" fn imported_static_memory_grow(
         .add(import_memory_index.index());
     let memory = (*local_memory).memory as *mut StaticMemory;
 
-    match (*memory).grow(delta, &mut *local_memory) {
+    match (*ctx.import_backing).memories[import_memory_index]
+        .limit_grow(delta, || (*memory).grow(delta, &mut *local_memory))
+    {
         Ok(old) => old.0 as i32,
         Err(_) => -1,
     }
@@ -102,7 +142,9 @@ pub unsafe extern "C" fn imported_dynamic_memory_grow(
     let local_memory = *ctx.internal.imported_memories.add(memory_index.index());
     let memory = (*local_memory).memory as *mut DynamicMemory;
 
-    match (*memory).grow(delta, &mut *local_memory) {
+    match (*ctx.import_backing).memories[memory_index]
+        .limit_grow(delta, || (*memory).grow(delta, &mut *local_memory))
+    {
         Ok(old) => old.0 as i32,
         Err(_) => -1,
     }
@@ -118,23 +160,398 @@ pub unsafe extern "C" fn imported_dynamic_memory_size(
     (*memory).size()
 }
 
+pub unsafe extern "C" fn imported_shared_static_memory_grow(
+    ctx: &mut vm::Ctx,
+    memory_index: ImportedMemoryIndex,
+    delta: Pages,
+) -> i32 {
+    let local_memory = *ctx.internal.imported_memories.add(memory_index.index());
+    let memory = (*local_memory).memory as *mut SharedStaticMemory;
+
+    match (*ctx.import_backing).memories[memory_index]
+        .limit_grow(delta, || (*memory).grow(delta, &mut *local_memory))
+    {
+        Ok(old) => old.0 as i32,
+        Err(_) => -1,
+    }
+}
+
+pub unsafe extern "C" fn imported_shared_static_memory_size(
+    ctx: &vm::Ctx,
+    memory_index: ImportedMemoryIndex,
+) -> Pages {
+    let local_memory = *ctx.internal.imported_memories.add(memory_index.index());
+    let memory = (*local_memory).memory as *mut SharedStaticMemory;
+
+    (*memory).size()
+}
+
 // +*****************************+
-// |        LOCAL TABLES         |
+// |           TABLES            |
 // +****************************+
 
-pub unsafe extern "C" fn local_table_grow(
+/// Called by the generated code for `table.get`, returns the raw word of the reference.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn table_get(ctx: &mut vm::Ctx, table_index: TableIndex, index: u32) -> u64 {
+    match table(ctx, table_index).get_raw(index) {
+        Some(raw) => raw,
+        None => trap(ctx, WasmTrapInfo::TableOutOfBounds),
+    }
+}
+
+/// Called by the generated code for `table.set`.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn table_set(
+    ctx: &mut vm::Ctx,
+    table_index: TableIndex,
+    index: u32,
+    raw: u64,
+) {
+    if table(ctx, table_index).set_raw(index, raw).is_err() {
+        trap(ctx, WasmTrapInfo::TableOutOfBounds);
+    }
+}
+
+/// Called by the generated code for `table.grow`, returns the previous size or -1.
+pub unsafe extern "C" fn table_grow(ctx: &mut vm::Ctx, table_index: TableIndex, delta: u32) -> i32 {
+    match table(ctx, table_index).grow(delta) {
+        Ok(old_size) => old_size as i32,
+        Err(_) => -1,
+    }
+}
+
+/// Called by the generated code for `table.size`.
+pub unsafe extern "C" fn table_size(ctx: &mut vm::Ctx, table_index: TableIndex) -> u32 {
+    table(ctx, table_index).size()
+}
+
+unsafe fn table<'a>(ctx: &vm::Ctx, table_index: TableIndex) -> &'a Table {
+    match table_index.local_or_import(&(*ctx.module).info) {
+        LocalOrImport::Local(local_table_index) => &(*ctx.local_backing).tables[local_table_index],
+        LocalOrImport::Import(import_table_index) => {
+            &(*ctx.import_backing).tables[import_table_index]
+        }
+    }
+}
+
+// +*****************************+
+// |         BULK MEMORY         |
+// +*****************************+
+
+/// Called by the generated code for `memory.init`.
+///
+/// Segments that are active or were dropped behave as empty segments.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn memory_init(
+    ctx: &mut vm::Ctx,
+    memory_index: MemoryIndex,
+    segment: DataIndex,
+    dst: u32,
+    src: u32,
+    len: u32,
+) {
+    let memory = memory_bytes(ctx, memory_index);
+    let data = match (*ctx.local_backing).passive_data.get(&segment) {
+        Some(data) => data.as_slice(),
+        None => &[],
+    };
+    if !in_bounds(src, len, data.len()) || !in_bounds(dst, len, memory.len()) {
+        trap(ctx, WasmTrapInfo::MemoryOutOfBounds);
+    }
+
+    ptr::copy_nonoverlapping(
+        data.as_ptr().add(src as usize),
+        memory.as_mut_ptr().add(dst as usize),
+        len as usize,
+    );
+}
+
+/// Called by the generated code for `data.drop`.
+pub unsafe extern "C" fn data_drop(ctx: &mut vm::Ctx, segment: DataIndex) {
+    (*ctx.local_backing).passive_data.remove(&segment);
+}
+
+/// Called by the generated code for `memory.copy`. The ranges may overlap.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn memory_copy(
+    ctx: &mut vm::Ctx,
+    memory_index: MemoryIndex,
+    dst: u32,
+    src: u32,
+    len: u32,
+) {
+    let memory = memory_bytes(ctx, memory_index);
+    if !in_bounds(src, len, memory.len()) || !in_bounds(dst, len, memory.len()) {
+        trap(ctx, WasmTrapInfo::MemoryOutOfBounds);
+    }
+
+    ptr::copy(
+        memory.as_ptr().add(src as usize),
+        memory.as_mut_ptr().add(dst as usize),
+        len as usize,
+    );
+}
+
+/// Called by the generated code for `memory.fill`.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn memory_fill(
     ctx: &mut vm::Ctx,
-    table_index: LocalTableIndex,
-    delta: u32,
+    memory_index: MemoryIndex,
+    dst: u32,
+    value: u32,
+    len: u32,
+) {
+    let memory = memory_bytes(ctx, memory_index);
+    if !in_bounds(dst, len, memory.len()) {
+        trap(ctx, WasmTrapInfo::MemoryOutOfBounds);
+    }
+
+    ptr::write_bytes(
+        memory.as_mut_ptr().add(dst as usize),
+        value as u8,
+        len as usize,
+    );
+}
+
+/// Called by the generated code for `table.init`.
+///
+/// Segments that are active or were dropped behave as empty segments.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn table_init(
+    ctx: &mut vm::Ctx,
+    table_index: TableIndex,
+    segment: ElemIndex,
+    dst: u32,
+    src: u32,
+    len: u32,
+) {
+    let table = table(ctx, table_index);
+    let func_indices = match (*ctx.local_backing).passive_elements.get(&segment) {
+        Some(func_indices) => func_indices.as_slice(),
+        None => &[],
+    };
+    if !in_bounds(src, len, func_indices.len()) || !in_bounds(dst, len, table.size() as usize) {
+        trap(ctx, WasmTrapInfo::TableOutOfBounds);
+    }
+
+    for i in 0..len {
+        let anyfunc = anyfunc(ctx, func_indices[(src + i) as usize]);
+        let _ = table.set_anyfunc(dst + i, anyfunc, &mut (*ctx.local_backing).func_refs);
+    }
+}
+
+/// Called by the generated code for `elem.drop`.
+pub unsafe extern "C" fn elem_drop(ctx: &mut vm::Ctx, segment: ElemIndex) {
+    (*ctx.local_backing).passive_elements.remove(&segment);
+}
+
+/// Called by the generated code for `table.copy`. The ranges may overlap.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn table_copy(
+    ctx: &mut vm::Ctx,
+    table_index: TableIndex,
+    dst: u32,
+    src: u32,
+    len: u32,
+) {
+    if table(ctx, table_index).copy_within(dst, src, len).is_err() {
+        trap(ctx, WasmTrapInfo::TableOutOfBounds);
+    }
+}
+
+/// Whether `len` items starting at `offset` fit in `size` items.
+fn in_bounds(offset: u32, len: u32, size: usize) -> bool {
+    u64::from(offset) + u64::from(len) <= size as u64
+}
+
+unsafe fn trap(ctx: &vm::Ctx, info: WasmTrapInfo) -> ! {
+    (&*ctx.module).runnable_module.do_early_trap(Box::new(info))
+}
+
+unsafe fn memory_bytes<'a>(ctx: &vm::Ctx, memory_index: MemoryIndex) -> &'a mut [u8] {
+    let local_memory = match memory_index.local_or_import(&(*ctx.module).info) {
+        LocalOrImport::Local(local_memory_index) => {
+            *ctx.internal.memories.add(local_memory_index.index())
+        }
+        LocalOrImport::Import(import_memory_index) => *ctx
+            .internal
+            .imported_memories
+            .add(import_memory_index.index()),
+    };
+    slice::from_raw_parts_mut((*local_memory).base, (*local_memory).bound)
+}
+
+/// The table element of a function, as the element section of the module would set it.
+unsafe fn anyfunc(ctx: &mut vm::Ctx, func_index: FuncIndex) -> vm::Anyfunc {
+    let info = &(*ctx.module).info;
+    let sig_id = *ctx
+        .internal
+        .dynamic_sigindices
+        .add(info.func_assoc[func_index].index());
+
+    let (func, func_ctx) = match func_index.local_or_import(info) {
+        LocalOrImport::Local(local_func_index) => (
+            *ctx.local_functions.add(local_func_index.index()),
+            ctx as *mut vm::Ctx,
+        ),
+        LocalOrImport::Import(imported_func_index) => {
+            let imported_func = &*ctx.internal.imported_funcs.add(imported_func_index.index());
+            (imported_func.func, imported_func.vmctx)
+        }
+    };
+
+    vm::Anyfunc {
+        func,
+        ctx: func_ctx,
+        sig_id,
+    }
+}
+
+// +*****************************+
+// |           ATOMICS           |
+// +****************************+
+
+/// Called by the generated code for `memory.atomic.wait32`.
+///
+/// A negative `timeout` waits forever, otherwise it is in nanoseconds.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn memory_atomic_wait32(
+    ctx: &mut vm::Ctx,
+    memory_index: MemoryIndex,
+    offset: u32,
+    addr: u32,
+    expected: i32,
+    timeout: i64,
+) -> i32 {
+    let cell = atomic_cell::<i32>(ctx, memory_index, offset, addr);
+    if !memory_is_shared(ctx, memory_index) {
+        trap(ctx, WasmTrapInfo::UnsharedMemoryWait);
+    }
+
+    wait_on(
+        ctx,
+        cell as *const _ as usize,
+        || cell.load(Ordering::SeqCst) == expected,
+        timeout,
+    )
+}
+
+/// Called by the generated code for `memory.atomic.wait64`.
+///
+/// A negative `timeout` waits forever, otherwise it is in nanoseconds.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn memory_atomic_wait64(
+    ctx: &mut vm::Ctx,
+    memory_index: MemoryIndex,
+    offset: u32,
+    addr: u32,
+    expected: i64,
+    timeout: i64,
+) -> i32 {
+    let cell = atomic_cell::<i64>(ctx, memory_index, offset, addr);
+    if !memory_is_shared(ctx, memory_index) {
+        trap(ctx, WasmTrapInfo::UnsharedMemoryWait);
+    }
+
+    wait_on(
+        ctx,
+        cell as *const _ as usize,
+        || cell.load(Ordering::SeqCst) == expected,
+        timeout,
+    )
+}
+
+/// Called by the generated code for `memory.atomic.notify`.
+///
+/// Returns the number of threads that were woken, which is always 0 for an unshared memory.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn memory_atomic_notify(
+    ctx: &mut vm::Ctx,
+    memory_index: MemoryIndex,
+    offset: u32,
+    addr: u32,
+    count: u32,
+) -> u32 {
+    let cell = atomic_cell::<i32>(ctx, memory_index, offset, addr);
+    wait::notify(cell as *const _ as usize, count)
+}
+
+/// The `T` at `offset + addr` in a memory, which must be in bounds and naturally aligned.
+unsafe fn atomic_cell<'a, T>(
+    ctx: &vm::Ctx,
+    memory_index: MemoryIndex,
+    offset: u32,
+    addr: u32,
+) -> &'a Atomic<T> {
+    let memory = memory_bytes(ctx, memory_index);
+    let address = u64::from(offset) + u64::from(addr);
+    let size = mem::size_of::<T>() as u64;
+    if address + size > memory.len() as u64 {
+        trap(ctx, WasmTrapInfo::MemoryOutOfBounds);
+    }
+    if address % size != 0 {
+        trap(ctx, WasmTrapInfo::MisalignedAtomicAccess);
+    }
+
+    &*(memory.as_ptr().add(address as usize) as *const Atomic<T>)
+}
+
+unsafe fn memory_is_shared(ctx: &vm::Ctx, memory_index: MemoryIndex) -> bool {
+    let info = &(*ctx.module).info;
+    match memory_index.local_or_import(info) {
+        LocalOrImport::Local(local_memory_index) => info.memories[local_memory_index].shared,
+        LocalOrImport::Import(import_memory_index) => {
+            info.imported_memories[import_memory_index].1.shared
+        }
+    }
+}
+
+/// Waits on the cell at `address`, trapping if the instance is interrupted meanwhile.
+unsafe fn wait_on(
+    ctx: &vm::Ctx,
+    address: usize,
+    is_expected: impl FnOnce() -> bool,
+    timeout: i64,
 ) -> i32 {
-    let _ = table_index;
-    let _ = delta;
-    let _ = ctx;
-    unimplemented!()
+    let result = wait::wait(address, is_expected, wait_timeout(timeout), || {
+        ctx.is_interrupted()
+    });
+    if result == wait::WaitResult::Interrupted {
+        interrupted(ctx);
+    }
+    result as i32
+}
+
+fn wait_timeout(timeout: i64) -> Option<Duration> {
+    if timeout < 0 {
+        None
+    } else {
+        Some(Duration::from_nanos(timeout as u64))
+    }
 }
 
-pub unsafe extern "C" fn local_table_size(ctx: &vm::Ctx, table_index: LocalTableIndex) -> u32 {
-    let _ = table_index;
-    let _ = ctx;
-    unimplemented!()
+// +*****************************+
+// |           METERING          |
+// +****************************+
+
+/// Called by the generated code once the fuel consumed by an instance exceeds its limit.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn fuel_exhausted(ctx: &vm::Ctx) {
+    (&*ctx.module)
+        .runnable_module
+        .do_early_trap(Box::new(FuelExhausted))
+}
+
+// +*****************************+
+// |          INTERRUPTS         |
+// +****************************+
+
+/// Called by the generated code when it finds the interrupt flag of an instance set.
+#[cfg_attr(nightly, unwind(allowed))]
+pub unsafe extern "C" fn interrupted(ctx: &vm::Ctx) {
+    // The interrupt is consumed, so the instance can be called again afterwards.
+    (*ctx.internal.interrupted).store(false, Ordering::SeqCst);
+    (&*ctx.module)
+        .runnable_module
+        .do_early_trap(Box::new(Interrupted))
 }
@@ -6,8 +6,11 @@ use std::{
     path::PathBuf,
 };
 
-use wasmer_runtime_core::cache::Error as CacheError;
 pub use wasmer_runtime_core::cache::{Artifact, Cache, WasmHash, WASMER_VERSION_HASH};
+use wasmer_runtime_core::{
+    backend::{Backend, Compiler},
+    cache::Error as CacheError,
+};
 
 /// Representation of a directory that contains compiled wasm artifacts.
 ///
@@ -80,21 +83,53 @@ impl FileSystemCache {
             Ok(Self { path })
         }
     }
-}
-
-impl Cache for FileSystemCache {
-    type LoadError = CacheError;
-    type StoreError = CacheError;
 
-    fn load(&self, key: WasmHash) -> Result<Module, CacheError> {
+    /// Reads the artifact stored under `key`, which must have been compiled
+    /// with `backend` if one is given.
+    fn load_artifact(
+        &self,
+        key: WasmHash,
+        backend: Option<Backend>,
+    ) -> Result<Artifact, CacheError> {
         let filename = key.encode();
         let mut new_path_buf = self.path.clone();
         new_path_buf.push(filename);
         let file = File::open(new_path_buf)?;
         let mmap = unsafe { Mmap::map(&file)? };
 
-        let serialized_cache = Artifact::deserialize(&mmap[..])?;
-        unsafe { wasmer_runtime_core::load_cache_with(serialized_cache, super::default_compiler()) }
+        match backend {
+            Some(backend) => Artifact::deserialize_for(&mmap[..], backend),
+            None => Artifact::deserialize(&mmap[..]),
+        }
+    }
+
+    /// Load a module from the cache with the provided compiler.
+    ///
+    /// Unlike [`Cache::load`], this doesn't pick a compiler for the
+    /// backend recorded in the artifact; if it was compiled with a different
+    /// backend, `CacheError::BackendMismatch` is returned without deserializing it.
+    ///
+    /// [`Cache::load`]: trait.Cache.html#tymethod.load
+    pub fn load_with(&self, key: WasmHash, compiler: &dyn Compiler) -> Result<Module, CacheError> {
+        let serialized_cache = self.load_artifact(key, Some(compiler.backend()))?;
+        unsafe { wasmer_runtime_core::load_cache_with(serialized_cache, compiler) }
+    }
+}
+
+impl Cache for FileSystemCache {
+    type LoadError = CacheError;
+    type StoreError = CacheError;
+
+    fn load(&self, key: WasmHash) -> Result<Module, CacheError> {
+        let serialized_cache = self.load_artifact(key, None)?;
+        let compiler =
+            super::compiler_for_backend(serialized_cache.backend()).ok_or_else(|| {
+                CacheError::BackendMismatch {
+                    expected: super::default_compiler().backend(),
+                    found: serialized_cache.backend(),
+                }
+            })?;
+        unsafe { wasmer_runtime_core::load_cache_with(serialized_cache, compiler) }
     }
 
     fn store(&mut self, key: WasmHash, module: Module) -> Result<(), CacheError> {
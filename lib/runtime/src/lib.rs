@@ -77,7 +77,7 @@
 pub use wasmer_runtime_core::export::Export;
 pub use wasmer_runtime_core::global::Global;
 pub use wasmer_runtime_core::import::ImportObject;
-pub use wasmer_runtime_core::instance::{DynFunc, Instance};
+pub use wasmer_runtime_core::instance::{DynFunc, Instance, InterruptHandle};
 pub use wasmer_runtime_core::memory::Memory;
 pub use wasmer_runtime_core::module::Module;
 pub use wasmer_runtime_core::table::Table;
@@ -95,15 +95,17 @@ pub mod memory {
 pub mod wasm {
     //! Various types exposed by the Wasmer Runtime.
     pub use wasmer_runtime_core::global::Global;
-    pub use wasmer_runtime_core::table::Table;
+    pub use wasmer_runtime_core::table::{AnyRef, Element, FuncRef, Table};
     pub use wasmer_runtime_core::types::{
-        FuncSig, GlobalDescriptor, MemoryDescriptor, TableDescriptor, Type, Value,
+        ElementType, ExternDescriptor, FuncSig, GlobalDescriptor, MemoryDescriptor,
+        TableDescriptor, Type, Value,
     };
 }
 
 pub mod error {
     pub use wasmer_runtime_core::cache::Error as CacheError;
     pub use wasmer_runtime_core::error::*;
+    pub use wasmer_runtime_core::typed_func::WasmTrapInfo;
 }
 
 pub mod units {
@@ -113,6 +115,7 @@ pub mod units {
 
 pub mod cache;
 
+pub use wasmer_runtime_core::backend::Backend;
 use wasmer_runtime_core::backend::{Compiler, CompilerConfig};
 
 /// Compile WebAssembly binary code into a [`Module`].
@@ -194,5 +197,46 @@ pub fn default_compiler() -> &'static dyn Compiler {
     &*DEFAULT_COMPILER as &dyn Compiler
 }
 
+/// Get a single instance of the compiler for the given backend,
+/// if that backend is enabled in this build.
+pub fn compiler_for_backend(backend: Backend) -> Option<&'static dyn Compiler> {
+    use lazy_static::lazy_static;
+
+    match backend {
+        #[cfg(feature = "default-compiler")]
+        Backend::Cranelift => {
+            lazy_static! {
+                static ref COMPILER: wasmer_clif_backend::CraneliftCompiler =
+                    { wasmer_clif_backend::CraneliftCompiler::new() };
+            }
+
+            Some(&*COMPILER as &dyn Compiler)
+        }
+
+        #[cfg(feature = "llvm")]
+        Backend::LLVM => {
+            lazy_static! {
+                static ref COMPILER: wasmer_llvm_backend::LLVMCompiler =
+                    { wasmer_llvm_backend::LLVMCompiler::new() };
+            }
+
+            Some(&*COMPILER as &dyn Compiler)
+        }
+
+        #[cfg(feature = "singlepass")]
+        Backend::Singlepass => {
+            lazy_static! {
+                static ref COMPILER: wasmer_singlepass_backend::SinglePassCompiler =
+                    { wasmer_singlepass_backend::SinglePassCompiler::new() };
+            }
+
+            Some(&*COMPILER as &dyn Compiler)
+        }
+
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
 /// The current version of this crate
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
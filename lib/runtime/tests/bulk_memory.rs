@@ -0,0 +1,197 @@
+// wabt can't write the bulk memory operators yet, so the module is put
+// together by hand. Every count and size here is below 128, so each fits in a
+// single LEB128 byte.
+fn section(id: u8, entries: &[&[u8]]) -> Vec<u8> {
+    let mut contents = vec![entries.len() as u8];
+    for entry in entries {
+        contents.extend_from_slice(entry);
+    }
+    let mut section = vec![id, contents.len() as u8];
+    section.extend(contents);
+    section
+}
+
+fn body(code: &[u8]) -> Vec<u8> {
+    // No locals, then the code and its `end`.
+    let mut body = vec![code.len() as u8 + 2, 0x00];
+    body.extend_from_slice(code);
+    body.push(0x0b);
+    body
+}
+
+fn export(name: &str, func_index: u8) -> Vec<u8> {
+    let mut export = vec![name.len() as u8];
+    export.extend_from_slice(name.as_bytes());
+    export.extend_from_slice(&[0x00, func_index]);
+    export
+}
+
+/// The module below, with the passive data segment `"hello"` and the passive
+/// element segment `[$one $two]`:
+///
+/// ```text
+/// (type $i32 (func (result i32)))
+/// (func $one (type $i32) (i32.const 1))
+/// (func $two (type $i32) (i32.const 2))
+/// (func (export "memory_init") (param i32 i32 i32) (memory.init 0 ...))
+/// (func (export "data_drop") (data.drop 0))
+/// (func (export "memory_copy") (param i32 i32 i32) (memory.copy ...))
+/// (func (export "memory_fill") (param i32 i32 i32) (memory.fill ...))
+/// (func (export "load8") (param i32) (result i32) (i32.load8_u (get_local 0)))
+/// (func (export "table_init") (param i32 i32 i32) (table.init 0 ...))
+/// (func (export "elem_drop") (elem.drop 0))
+/// (func (export "table_copy") (param i32 i32 i32) (table.copy ...))
+/// (func (export "call") (param i32) (result i32) (call_indirect (type $i32) (get_local 0)))
+/// (table 4 anyfunc)
+/// (memory 1)
+/// ```
+fn bulk_memory_wasm() -> Vec<u8> {
+    const ARGS: &[u8] = &[0x20, 0x00, 0x20, 0x01, 0x20, 0x02];
+    let with_args = |op: &[u8]| body(&[ARGS, op].concat());
+
+    let mut wasm = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+    wasm.extend(section(
+        1,
+        &[
+            &[0x60, 0x00, 0x01, 0x7f],
+            &[0x60, 0x03, 0x7f, 0x7f, 0x7f, 0x00],
+            &[0x60, 0x00, 0x00],
+            &[0x60, 0x01, 0x7f, 0x01, 0x7f],
+        ],
+    ));
+    wasm.extend(section(
+        3,
+        &[
+            &[0],
+            &[0],
+            &[1],
+            &[2],
+            &[1],
+            &[1],
+            &[3],
+            &[1],
+            &[2],
+            &[1],
+            &[3],
+        ],
+    ));
+    wasm.extend(section(4, &[&[0x70, 0x00, 0x04]]));
+    wasm.extend(section(5, &[&[0x00, 0x01]]));
+    wasm.extend(section(
+        7,
+        &[
+            &export("memory_init", 2),
+            &export("data_drop", 3),
+            &export("memory_copy", 4),
+            &export("memory_fill", 5),
+            &export("load8", 6),
+            &export("table_init", 7),
+            &export("elem_drop", 8),
+            &export("table_copy", 9),
+            &export("call", 10),
+        ],
+    ));
+    // A passive segment of functions $one and $two.
+    wasm.extend(section(9, &[&[0x01, 0x70, 0x02, 0x00, 0x01]]));
+    wasm.extend(section(
+        10,
+        &[
+            &body(&[0x41, 0x01]),
+            &body(&[0x41, 0x02]),
+            &with_args(&[0xfc, 0x08, 0x00, 0x00]),
+            &body(&[0xfc, 0x09, 0x00]),
+            &with_args(&[0xfc, 0x0a, 0x00, 0x00]),
+            &with_args(&[0xfc, 0x0b, 0x00]),
+            &body(&[0x20, 0x00, 0x2d, 0x00, 0x00]),
+            &with_args(&[0xfc, 0x0c, 0x00, 0x00]),
+            &body(&[0xfc, 0x0d, 0x00]),
+            &with_args(&[0xfc, 0x0e, 0x00, 0x00]),
+            &body(&[0x20, 0x00, 0x11, 0x00, 0x00]),
+        ],
+    ));
+    // A passive segment of the bytes "hello".
+    wasm.extend(section(11, &[&[0x01, 0x05, b'h', b'e', b'l', b'l', b'o']]));
+    wasm
+}
+
+#[test]
+#[cfg(any(feature = "llvm", feature = "singlepass"))]
+fn passive_segments_are_only_written_by_bulk_operators() {
+    use wasmer_runtime::{
+        compile,
+        error::{RuntimeError, WasmTrapInfo},
+        imports, Func,
+    };
+
+    fn assert_trap<T>(result: Result<T, RuntimeError>, info: WasmTrapInfo) {
+        match result {
+            Err(RuntimeError::Trap { trap }) => assert_eq!(trap.info, info),
+            result => panic!("expected a trap, got {:?}", result.map(|_| ())),
+        }
+    }
+
+    let module = compile(&bulk_memory_wasm()).unwrap();
+    let instance = module.instantiate(&imports! {}).unwrap();
+
+    let memory_init: Func<(i32, i32, i32)> = instance.func("memory_init").unwrap();
+    let data_drop: Func<()> = instance.func("data_drop").unwrap();
+    let memory_copy: Func<(i32, i32, i32)> = instance.func("memory_copy").unwrap();
+    let memory_fill: Func<(i32, i32, i32)> = instance.func("memory_fill").unwrap();
+    let load8: Func<i32, i32> = instance.func("load8").unwrap();
+    let table_init: Func<(i32, i32, i32)> = instance.func("table_init").unwrap();
+    let elem_drop: Func<()> = instance.func("elem_drop").unwrap();
+    let table_copy: Func<(i32, i32, i32)> = instance.func("table_copy").unwrap();
+    let call: Func<i32, i32> = instance.func("call").unwrap();
+
+    // Instantiation skips passive segments.
+    assert_eq!(load8.call(0).unwrap(), 0);
+    assert!(call.call(0).is_err());
+
+    memory_init.call(10, 1, 3).unwrap();
+    assert_eq!(load8.call(10).unwrap(), i32::from(b'e'));
+    assert_eq!(load8.call(12).unwrap(), i32::from(b'l'));
+    assert_trap(
+        memory_init.call(65534, 0, 5),
+        WasmTrapInfo::MemoryOutOfBounds,
+    );
+
+    memory_copy.call(20, 10, 3).unwrap();
+    assert_eq!(load8.call(20).unwrap(), i32::from(b'e'));
+    memory_fill.call(30, 0xab, 2).unwrap();
+    assert_eq!(load8.call(31).unwrap(), 0xab);
+    assert_trap(
+        memory_fill.call(65535, 0, 2),
+        WasmTrapInfo::MemoryOutOfBounds,
+    );
+
+    // A dropped segment is empty.
+    data_drop.call().unwrap();
+    memory_init.call(0, 0, 0).unwrap();
+    assert_trap(memory_init.call(0, 0, 1), WasmTrapInfo::MemoryOutOfBounds);
+
+    table_init.call(1, 0, 2).unwrap();
+    assert_eq!(call.call(1).unwrap(), 1);
+    assert_eq!(call.call(2).unwrap(), 2);
+    assert_trap(table_init.call(3, 0, 2), WasmTrapInfo::TableOutOfBounds);
+
+    table_copy.call(0, 1, 2).unwrap();
+    assert_eq!(call.call(0).unwrap(), 1);
+    assert_eq!(call.call(1).unwrap(), 2);
+    assert_trap(table_copy.call(3, 0, 2), WasmTrapInfo::TableOutOfBounds);
+
+    elem_drop.call().unwrap();
+    assert_trap(table_init.call(0, 0, 1), WasmTrapInfo::TableOutOfBounds);
+}
+
+#[test]
+#[cfg(feature = "default-compiler")]
+fn cranelift_rejects_bulk_memory() {
+    use wasmer_runtime::{compile_with, compiler_for_backend, error::CompileError, Backend};
+
+    let compiler = compiler_for_backend(Backend::Cranelift).unwrap();
+    match compile_with(&bulk_memory_wasm(), compiler) {
+        Err(CompileError::ValidationError { msg }) => assert!(msg.contains("bulk memory")),
+        Err(err) => panic!("unexpected compile error: {:?}", err),
+        Ok(_) => panic!("the module compiled"),
+    }
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "default-compiler")]
+
+use std::fs;
+use wabt::wat2wasm;
+use wasmer_runtime::{
+    cache::{Cache, FileSystemCache, WasmHash, WASMER_VERSION_HASH},
+    compile_with, compiler_for_backend,
+    error::CacheError,
+    Backend,
+};
+
+// The header starts with the magic, the version and the length of the body,
+// 8 bytes each, followed by the backend id and the target fingerprint.
+const BACKEND_OFFSET: usize = 24;
+const TARGET_OFFSET: usize = 32;
+
+/// Stores a module compiled with Cranelift, rewrites its artifact with
+/// `tamper`, and loads it back with Cranelift.
+fn load_tampered(tamper: impl FnOnce(&mut [u8])) -> Result<(), CacheError> {
+    let wasm = wat2wasm("(module (func (export \"f\")))").unwrap();
+    let compiler = compiler_for_backend(Backend::Cranelift).unwrap();
+    let module = compile_with(&wasm, compiler).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = unsafe { FileSystemCache::new(dir.path()).unwrap() };
+    let key = WasmHash::generate(&wasm);
+    cache.store(key, module).unwrap();
+
+    let path = dir.path().join(WASMER_VERSION_HASH).join(key.encode());
+    let mut artifact = fs::read(&path).unwrap();
+    tamper(&mut artifact);
+    fs::write(&path, artifact).unwrap();
+
+    cache.load_with(key, compiler).map(|_| ())
+}
+
+#[test]
+fn an_untouched_artifact_loads() {
+    load_tampered(|_| {}).unwrap();
+}
+
+#[test]
+fn an_artifact_of_another_backend_is_not_deserialized() {
+    match load_tampered(|artifact| {
+        // The id of the singlepass backend, in native byte order.
+        artifact[BACKEND_OFFSET..TARGET_OFFSET].copy_from_slice(&1u64.to_ne_bytes())
+    }) {
+        Err(CacheError::BackendMismatch { expected, found }) => {
+            assert_eq!(expected, Backend::Cranelift);
+            assert_eq!(found, Backend::Singlepass);
+        }
+        result => panic!("expected a backend mismatch, got {:?}", result),
+    }
+}
+
+#[test]
+fn an_artifact_of_another_target_is_not_deserialized() {
+    match load_tampered(|artifact| artifact[TARGET_OFFSET] ^= 0xff) {
+        Err(CacheError::TargetMismatch) => {}
+        result => panic!("expected a target mismatch, got {:?}", result),
+    }
+}
@@ -0,0 +1,84 @@
+#[test]
+fn closures_of_the_same_type_keep_their_own_state() {
+    use wabt::wat2wasm;
+    use wasmer_runtime::{compile, func, imports, Ctx, Func};
+
+    static WAT: &'static str = r#"
+        (module
+        (type $t (func (param i32) (result i32)))
+        (import "env" "add_one" (func $add_one (type $t)))
+        (import "env" "add_ten" (func $add_ten (type $t)))
+        (table anyfunc (elem $add_one $add_ten))
+        (func $call_both (param i32) (result i32)
+            (call $add_ten (call $add_one (get_local 0))))
+        (func $call_indirect (param i32) (param i32) (result i32)
+            (call_indirect (type $t) (get_local 1) (get_local 0)))
+        (export "call_both" (func $call_both))
+        (export "call_indirect" (func $call_indirect))
+        )
+    "#;
+
+    // Both closures have the same type, and so share the code of their `Func`.
+    fn adder(n: i32) -> impl Fn(&mut Ctx, i32) -> i32 {
+        move |_ctx: &mut Ctx, x: i32| x + n
+    }
+
+    let wasm = wat2wasm(WAT).unwrap();
+    let module = compile(&wasm).unwrap();
+    // The state of the closures lives as long as the import object.
+    let import_object = imports! {
+        "env" => {
+            "add_one" => func!(adder(1)),
+            "add_ten" => func!(adder(10)),
+        },
+    };
+    let instance = module.instantiate(&import_object).unwrap();
+
+    let call_both: Func<i32, i32> = instance.func("call_both").unwrap();
+    assert_eq!(call_both.call(100).unwrap(), 111);
+
+    let call_indirect: Func<(i32, i32), i32> = instance.func("call_indirect").unwrap();
+    assert_eq!(call_indirect.call(0, 100).unwrap(), 101);
+    assert_eq!(call_indirect.call(1, 100).unwrap(), 110);
+}
+
+#[test]
+fn closure_state_outlives_the_import_object() {
+    use std::{cell::Cell, rc::Rc};
+    use wabt::wat2wasm;
+    use wasmer_runtime::{compile, func, imports, Ctx, Func};
+
+    static WAT: &'static str = r#"
+        (module
+        (import "env" "bump" (func $bump (result i32)))
+        (func (export "call_bump") (result i32)
+            (call $bump))
+        )
+    "#;
+
+    let wasm = wat2wasm(WAT).unwrap();
+    let module = compile(&wasm).unwrap();
+    let calls = Rc::new(Cell::new(0));
+    let captured = Rc::clone(&calls);
+    // The import object is a temporary, dropped before the closure is called.
+    let instance = module
+        .instantiate(&imports! {
+            "env" => {
+                "bump" => func!(move |_ctx: &mut Ctx| -> i32 {
+                    captured.set(captured.get() + 1);
+                    captured.get()
+                }),
+            },
+        })
+        .unwrap();
+
+    let call_bump: Func<(), i32> = instance.func("call_bump").unwrap();
+    assert_eq!(call_bump.call().unwrap(), 1);
+    assert_eq!(call_bump.call().unwrap(), 2);
+    assert_eq!(calls.get(), 2);
+
+    // The instance was the last owner of the closure.
+    drop(call_bump);
+    drop(instance);
+    assert_eq!(Rc::strong_count(&calls), 1);
+}
@@ -0,0 +1,58 @@
+use std::{thread, time::Duration};
+use wabt::wat2wasm;
+use wasmer_runtime::{
+    compile_with, compiler_for_backend,
+    error::{CallError, RuntimeError},
+    imports, Backend, Value,
+};
+
+static WAT: &'static str = r#"
+    (module
+    (func (export "spin")
+        (loop $forever
+            (br $forever)))
+    (func (export "answer") (result i32)
+        (i32.const 42))
+    )
+"#;
+
+/// Interrupts a loop that never ends from another thread, then calls the
+/// instance again, as the interrupt only stops the call it was noticed in.
+fn check_interrupt(backend: Backend) {
+    let wasm = wat2wasm(WAT).unwrap();
+    let compiler = compiler_for_backend(backend).unwrap();
+    let module = compile_with(&wasm, compiler).unwrap();
+    let instance = module.instantiate(&imports! {}).unwrap();
+
+    let handle = instance.interrupt_handle();
+    let interrupter = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.interrupt();
+    });
+
+    match instance.call("spin", &[]) {
+        Err(CallError::Runtime(RuntimeError::Interrupted)) => {}
+        result => panic!("expected an interrupt, got {:?}", result),
+    }
+    interrupter.join().unwrap();
+
+    assert_eq!(instance.call("answer", &[]).unwrap(), vec![Value::I32(42)]);
+}
+
+#[test]
+#[cfg(feature = "default-compiler")]
+fn cranelift_loops_can_be_interrupted() {
+    check_interrupt(Backend::Cranelift);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn llvm_loops_can_be_interrupted() {
+    check_interrupt(Backend::LLVM);
+}
+
+#[test]
+#[cfg(feature = "singlepass")]
+fn singlepass_loops_can_be_interrupted() {
+    check_interrupt(Backend::Singlepass);
+}
@@ -0,0 +1,88 @@
+#[test]
+fn module_imports_and_exports() {
+    use wabt::wat2wasm;
+    use wasmer_runtime::{
+        compile, func, imports,
+        units::Pages,
+        wasm::{ExternDescriptor, FuncSig, GlobalDescriptor, MemoryDescriptor, Type},
+        Ctx,
+    };
+
+    static WAT: &'static str = r#"
+        (module
+        (import "env" "add" (func $add (param i32 i32) (result i32)))
+        (import "env" "memory" (memory 1 2))
+        (global $counter (mut i64) (i64.const 0))
+        (func $double (param i32) (result i32)
+            get_local 0
+            get_local 0
+            call $add
+        )
+        (export "double" (func $double))
+        (export "counter" (global $counter))
+        (export "memory" (memory 0))
+        )
+    "#;
+
+    let wasm = wat2wasm(WAT).unwrap();
+    let module = compile(&wasm).unwrap();
+
+    let imports: Vec<_> = module.imports().collect();
+    assert_eq!(
+        imports,
+        vec![
+            (
+                "env",
+                "add",
+                ExternDescriptor::Function(FuncSig::new(
+                    vec![Type::I32, Type::I32],
+                    vec![Type::I32]
+                ))
+            ),
+            (
+                "env",
+                "memory",
+                ExternDescriptor::Memory(MemoryDescriptor {
+                    minimum: Pages(1),
+                    maximum: Some(Pages(2)),
+                    shared: false,
+                })
+            ),
+        ]
+    );
+
+    let mut exports: Vec<_> = module.exports().collect();
+    exports.sort_by_key(|(name, _)| *name);
+    assert_eq!(
+        exports,
+        vec![
+            (
+                "counter",
+                ExternDescriptor::Global(GlobalDescriptor {
+                    mutable: true,
+                    ty: Type::I64,
+                })
+            ),
+            (
+                "double",
+                ExternDescriptor::Function(FuncSig::new(vec![Type::I32], vec![Type::I32]))
+            ),
+            ("memory", imports[1].2.clone()),
+        ]
+    );
+
+    fn add(_ctx: &mut Ctx, a: i32, b: i32) -> i32 {
+        a + b
+    }
+    let import_object = imports! {
+        "env" => {
+            "add" => func!(add),
+        },
+    };
+    let add = import_object
+        .get_namespace("env")
+        .unwrap()
+        .get_export("add")
+        .unwrap();
+    assert_eq!(add.descriptor(), imports[0].2);
+}
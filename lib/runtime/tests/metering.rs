@@ -0,0 +1,75 @@
+use wabt::wat2wasm;
+use wasmer_runtime::{
+    compile_with_config_with, compiler_for_backend,
+    error::{CallError, RuntimeError},
+    imports, Backend, Func,
+};
+use wasmer_runtime_core::{
+    backend::CompilerConfig,
+    metering::{CostTable, Operator},
+};
+
+static WAT: &'static str = r#"
+    (module
+    (func (export "count") (param $n i32) (result i32)
+        (local $i i32)
+        (block $done
+            (loop $loop
+                (br_if $done (i32.eq (get_local $i) (get_local $n)))
+                (set_local $i (i32.add (get_local $i) (i32.const 1)))
+                (br $loop)))
+        (get_local $i))
+    )
+"#;
+
+/// Runs a loop with fuel on `backend`, where only `i32.add` costs anything,
+/// so each iteration of the loop consumes exactly `ADD_COST`.
+fn check_metering(backend: Backend) {
+    const ADD_COST: u64 = 3;
+
+    let wasm = wat2wasm(WAT).unwrap();
+    let config = CompilerConfig {
+        metering: Some(CostTable::new(|op| match *op {
+            Operator::I32Add => ADD_COST,
+            _ => 0,
+        })),
+        ..Default::default()
+    };
+    let compiler = compiler_for_backend(backend).unwrap();
+    let module = compile_with_config_with(&wasm, config, compiler).unwrap();
+    let mut instance = module.instantiate(&imports! {}).unwrap();
+
+    instance.set_fuel(10 * ADD_COST);
+    {
+        let count: Func<i32, i32> = instance.func("count").unwrap();
+        assert_eq!(count.call(10).unwrap(), 10);
+    }
+    assert_eq!(instance.fuel_consumed(), 10 * ADD_COST);
+    assert_eq!(instance.fuel_remaining(), 0);
+
+    // The block that goes over the limit is charged before the call stops.
+    instance.set_fuel(5 * ADD_COST);
+    match instance.call("count", &[10.into()]) {
+        Err(CallError::Runtime(RuntimeError::FuelExhausted)) => {}
+        result => panic!("expected the fuel to run out, got {:?}", result),
+    }
+    assert_eq!(instance.fuel_consumed(), 6 * ADD_COST);
+}
+
+#[test]
+#[cfg(feature = "default-compiler")]
+fn cranelift_charges_fuel_per_block() {
+    check_metering(Backend::Cranelift);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn llvm_charges_fuel_per_block() {
+    check_metering(Backend::LLVM);
+}
+
+#[test]
+#[cfg(feature = "singlepass")]
+fn singlepass_charges_fuel_per_block() {
+    check_metering(Backend::Singlepass);
+}
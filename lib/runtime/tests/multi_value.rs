@@ -0,0 +1,85 @@
+use wabt::Wat2Wasm;
+use wasmer_runtime::{compile_with, compiler_for_backend, error::CompileError, Backend};
+
+// wabt only accepts several results when validation is turned off.
+fn multi_value_wat2wasm(wat: &str) -> Vec<u8> {
+    Wat2Wasm::new()
+        .validate(false)
+        .convert(wat)
+        .unwrap()
+        .as_ref()
+        .to_vec()
+}
+
+static LOCAL_WAT: &'static str = r#"
+    (module
+    (type $pair (func (result i32 i64)))
+    (func $swap (param i64 i32) (result i32 i64)
+        (get_local 1)
+        (get_local 0))
+    (func $pair (type $pair)
+        (call $swap (i64.const 7) (i32.const 3)))
+    (table anyfunc (elem $pair))
+    (func (export "direct") (type $pair)
+        (call $pair))
+    (func (export "indirect") (type $pair)
+        (call_indirect (type $pair) (i32.const 0)))
+    )
+"#;
+
+static IMPORT_WAT: &'static str = r#"
+    (module
+    (import "env" "pair" (func $pair (result i32 i64)))
+    )
+"#;
+
+#[test]
+#[cfg(feature = "llvm")]
+fn llvm_returns_several_values_from_local_functions() {
+    use wasmer_runtime::{imports, Func};
+
+    let wasm = multi_value_wat2wasm(LOCAL_WAT);
+    let compiler = compiler_for_backend(Backend::LLVM).unwrap();
+    let module = compile_with(&wasm, compiler).unwrap();
+    let instance = module.instantiate(&imports! {}).unwrap();
+
+    let direct: Func<(), (i32, i64)> = instance.func("direct").unwrap();
+    assert_eq!(direct.call().unwrap(), (3, 7));
+
+    let indirect: Func<(), (i32, i64)> = instance.func("indirect").unwrap();
+    assert_eq!(indirect.call().unwrap(), (3, 7));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn llvm_rejects_imported_functions_with_several_results() {
+    let wasm = multi_value_wat2wasm(IMPORT_WAT);
+    let compiler = compiler_for_backend(Backend::LLVM).unwrap();
+    match compile_with(&wasm, compiler) {
+        Err(CompileError::ValidationError { msg }) => assert!(msg.contains("multiple results")),
+        Err(err) => panic!("unexpected compile error: {:?}", err),
+        Ok(_) => panic!("the module compiled"),
+    }
+}
+
+#[test]
+#[cfg(feature = "default-compiler")]
+fn cranelift_rejects_several_results() {
+    let compiler = compiler_for_backend(Backend::Cranelift).unwrap();
+    for wat in &[LOCAL_WAT, IMPORT_WAT] {
+        let wasm = multi_value_wat2wasm(wat);
+        match compile_with(&wasm, compiler) {
+            Err(CompileError::ValidationError { msg }) => assert!(msg.contains("multi-value")),
+            Err(err) => panic!("unexpected compile error: {:?}", err),
+            Ok(_) => panic!("the module compiled"),
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "singlepass")]
+fn singlepass_rejects_several_results() {
+    let wasm = multi_value_wat2wasm(LOCAL_WAT);
+    let compiler = compiler_for_backend(Backend::Singlepass).unwrap();
+    assert!(compile_with(&wasm, compiler).is_err());
+}
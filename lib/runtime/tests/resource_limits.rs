@@ -0,0 +1,31 @@
+use wabt::wat2wasm;
+use wasmer_runtime::{compile, imports, units::Pages, Func};
+use wasmer_runtime_core::limits::MemoryLimit;
+
+static WAT: &'static str = r#"
+    (module
+    (memory 1)
+    (func (export "grow") (param $delta i32) (result i32)
+        (memory.grow (get_local $delta)))
+    (func (export "size") (result i32)
+        (memory.size))
+    )
+"#;
+
+#[test]
+fn memory_grow_fails_once_the_limit_is_hit() {
+    let wasm = wat2wasm(WAT).unwrap();
+    let module = compile(&wasm).unwrap();
+    let mut import_object = imports! {};
+    import_object.set_resource_limiter(|| MemoryLimit::new(Pages(3)));
+    let instance = module.instantiate(&import_object).unwrap();
+
+    let grow: Func<i32, i32> = instance.func("grow").unwrap();
+    let size: Func<(), i32> = instance.func("size").unwrap();
+
+    assert_eq!(grow.call(2).unwrap(), 1);
+    assert_eq!(grow.call(1).unwrap(), -1);
+    assert_eq!(size.call().unwrap(), 3);
+    // Growing by nothing still works at the limit.
+    assert_eq!(grow.call(0).unwrap(), 3);
+}
@@ -0,0 +1,106 @@
+use wasmer_runtime::{compile_with, compiler_for_backend, Backend};
+
+/// The module `(func (export "id") (param v128) (result v128) (get_local 0))`,
+/// written by hand as wabt can't write `v128` yet.
+fn simd_wasm() -> Vec<u8> {
+    vec![
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic and version
+        0x01, 0x06, 0x01, 0x60, 0x01, 0x7b, 0x01, 0x7b, // type section
+        0x03, 0x02, 0x01, 0x00, // function section
+        0x07, 0x06, 0x01, 0x02, b'i', b'd', 0x00, 0x00, // export section
+        0x0a, 0x06, 0x01, 0x04, 0x00, 0x20, 0x00, 0x0b, // code section
+    ]
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn llvm_passes_v128_values_across_the_host_boundary() {
+    use wasmer_runtime::{imports, Value};
+
+    let compiler = compiler_for_backend(Backend::LLVM).unwrap();
+    let module = compile_with(&simd_wasm(), compiler).unwrap();
+    let instance = module.instantiate(&imports! {}).unwrap();
+
+    let value = Value::V128(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    let id = instance.dyn_func("id").unwrap();
+    assert_eq!(id.call(&[value.clone()]).unwrap(), vec![value]);
+}
+
+/// A module exporting `(func "f" (param v128 v128) (result v128))`, which applies
+/// the binary SIMD operator `opcode` to its parameters.
+#[cfg(feature = "llvm")]
+fn simd_binary_op_wasm(opcode: u8) -> Vec<u8> {
+    // The opcode follows the SIMD prefix as a LEB128 number.
+    let opcode = if opcode < 0x80 {
+        vec![opcode]
+    } else {
+        vec![opcode, 0x01]
+    };
+    let mut body = vec![0x00, 0x20, 0x00, 0x20, 0x01, 0xfd];
+    body.extend(opcode);
+    body.push(0x0b);
+
+    let mut wasm = vec![
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic and version
+        0x01, 0x07, 0x01, 0x60, 0x02, 0x7b, 0x7b, 0x01, 0x7b, // type section
+        0x03, 0x02, 0x01, 0x00, // function section
+        0x07, 0x05, 0x01, 0x01, b'f', 0x00, 0x00, // export section
+    ];
+    let body_len = body.len() as u8;
+    wasm.extend(&[0x0a, body_len + 2, 0x01, body_len]); // code section
+    wasm.extend(body);
+    wasm
+}
+
+/// Packs four `f32`s into the lanes of a `v128`.
+#[cfg(feature = "llvm")]
+fn f32x4(lanes: [f32; 4]) -> u128 {
+    lanes.iter().enumerate().fold(0, |v128, (i, lane)| {
+        v128 | (lane.to_bits() as u128) << (32 * i)
+    })
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn llvm_f32x4_min_max_propagate_nans_and_order_zeros() {
+    use std::f32::NAN;
+    use wasmer_runtime::{imports, Value};
+
+    const F32X4_MIN: u8 = 0x9e;
+    const F32X4_MAX: u8 = 0x9f;
+
+    let a = f32x4([NAN, 1.0, -0.0, 0.0]);
+    let b = f32x4([1.0, NAN, 0.0, -0.0]);
+    let call = |opcode| {
+        let compiler = compiler_for_backend(Backend::LLVM).unwrap();
+        let module = compile_with(&simd_binary_op_wasm(opcode), compiler).unwrap();
+        let instance = module.instantiate(&imports! {}).unwrap();
+        let f = instance.dyn_func("f").unwrap();
+        match f.call(&[Value::V128(a), Value::V128(b)]).unwrap()[..] {
+            [Value::V128(res)] => (0..4).map(|i| (res >> (32 * i)) as u32).collect::<Vec<_>>(),
+            ref res => panic!("unexpected results: {:?}", res),
+        }
+    };
+
+    let min = call(F32X4_MIN);
+    let max = call(F32X4_MAX);
+    for lanes in &[&min, &max] {
+        assert!(f32::from_bits(lanes[0]).is_nan());
+        assert!(f32::from_bits(lanes[1]).is_nan());
+    }
+    assert_eq!(min[2..], [(-0.0f32).to_bits(); 2]);
+    assert_eq!(max[2..], [0.0f32.to_bits(); 2]);
+}
+
+#[test]
+#[cfg(feature = "default-compiler")]
+fn cranelift_rejects_simd() {
+    use wasmer_runtime::error::CompileError;
+
+    let compiler = compiler_for_backend(Backend::Cranelift).unwrap();
+    match compile_with(&simd_wasm(), compiler) {
+        Err(CompileError::ValidationError { msg }) => assert!(msg.contains("SIMD")),
+        Err(err) => panic!("unexpected compile error: {:?}", err),
+        Ok(_) => panic!("the module compiled"),
+    }
+}
@@ -0,0 +1,45 @@
+#[test]
+#[cfg(all(unix, feature = "singlepass"))]
+fn singlepass_module_survives_a_cache_round_trip() {
+    use wabt::Wat2Wasm;
+    use wasmer_runtime::{
+        cache::{Cache, FileSystemCache, WasmHash},
+        compile_with, compiler_for_backend, imports, Backend, Func,
+    };
+
+    // `memory.grow` and `memory.size` are vmcalls and `br_table` jumps through
+    // a table of code addresses, so both need relocating when loaded.
+    static WAT: &'static str = r#"
+        (module
+        (memory 1)
+        (func $grow_and_pick (param i32) (result i32)
+            (drop (memory.grow (i32.const 2)))
+            (block $default
+            (block $one
+                (block $zero
+                (br_table $zero $one $default (get_local 0)))
+                (return (i32.const 100)))
+            (return (memory.size)))
+            (i32.const -1)
+        )
+        (export "grow_and_pick" (func $grow_and_pick))
+        )
+    "#;
+
+    let wasm = Wat2Wasm::new().convert(WAT).unwrap();
+    let compiler = compiler_for_backend(Backend::Singlepass).unwrap();
+    let module = compile_with(wasm.as_ref(), compiler).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut cache = unsafe { FileSystemCache::new(dir.path()).unwrap() };
+    let key = WasmHash::generate(wasm.as_ref());
+    cache.store(key, module).unwrap();
+    let module = cache.load_with(key, compiler).unwrap();
+
+    let instance = module.instantiate(&imports! {}).unwrap();
+    let grow_and_pick: Func<i32, i32> = instance.func("grow_and_pick").unwrap();
+
+    assert_eq!(grow_and_pick.call(0).unwrap(), 100);
+    assert_eq!(grow_and_pick.call(1).unwrap(), 5);
+    assert_eq!(grow_and_pick.call(2).unwrap(), -1);
+}
@@ -0,0 +1,45 @@
+#[test]
+#[cfg(unix)]
+fn trap_backtrace_names_the_wasm_frames() {
+    use wabt::Wat2Wasm;
+    use wasmer_runtime::{
+        compile,
+        error::{RuntimeError, WasmTrapInfo},
+        imports, Func,
+    };
+
+    static WAT: &'static str = r#"
+        (module
+        (func $inner
+            unreachable
+        )
+        (func $outer
+            call $inner
+        )
+        (export "outer" (func $outer))
+        )
+    "#;
+
+    let wasm = Wat2Wasm::new()
+        .write_debug_names(true)
+        .convert(WAT)
+        .unwrap();
+    let module = compile(wasm.as_ref()).unwrap();
+    let instance = module.instantiate(&imports! {}).unwrap();
+    let outer: Func<(), ()> = instance.func("outer").unwrap();
+
+    let trap = match outer.call() {
+        Err(RuntimeError::Trap { trap }) => trap,
+        result => panic!("expected a trap, got {:?}", result.map(|_| ())),
+    };
+    assert_eq!(trap.info, WasmTrapInfo::Unreachable);
+    assert_eq!(trap.func_index(), Some(trap.backtrace[0].func_index));
+
+    let names: Vec<_> = trap
+        .backtrace
+        .iter()
+        .map(|frame| frame.func_name.as_ref().map(String::as_str))
+        .collect();
+    assert_eq!(names, [Some("inner"), Some("outer")]);
+    assert!(trap.to_string().contains("in function `inner`"));
+}
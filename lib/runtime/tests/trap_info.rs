@@ -0,0 +1,55 @@
+#[test]
+#[cfg(unix)]
+fn traps_report_their_kind() {
+    use wabt::wat2wasm;
+    use wasmer_runtime::{
+        compile,
+        error::{RuntimeError, WasmTrapInfo},
+        imports, Func,
+    };
+
+    static WAT: &'static str = r#"
+        (module
+        (type $t (func))
+        (memory 1)
+        (table anyfunc (elem $unreachable $i32_result))
+        (func $unreachable (export "unreachable")
+            unreachable)
+        (func $i32_result (result i32)
+            i32.const 0)
+        (func (export "call_indirect_oob")
+            (call_indirect (type $t) (i32.const 2)))
+        (func (export "call_indirect_signature")
+            (call_indirect (type $t) (i32.const 1)))
+        (func (export "memory_oob")
+            (drop (i32.load (i32.const 65536))))
+        (func (export "div_by_zero")
+            (drop (i32.div_u (i32.const 1) (i32.const 0))))
+        (func (export "trunc_overflow")
+            (drop (i32.trunc_u/f32 (f32.const -2))))
+        )
+    "#;
+
+    let wasm = wat2wasm(WAT).unwrap();
+    let module = compile(&wasm).unwrap();
+    let instance = module.instantiate(&imports! {}).unwrap();
+
+    let expected = [
+        ("unreachable", WasmTrapInfo::Unreachable),
+        ("call_indirect_oob", WasmTrapInfo::CallIndirectOOB),
+        (
+            "call_indirect_signature",
+            WasmTrapInfo::IncorrectCallIndirectSignature,
+        ),
+        ("memory_oob", WasmTrapInfo::MemoryOutOfBounds),
+        ("div_by_zero", WasmTrapInfo::IllegalArithmetic),
+        ("trunc_overflow", WasmTrapInfo::IllegalArithmetic),
+    ];
+    for (name, info) in expected.iter() {
+        let func: Func<(), ()> = instance.func(name).unwrap();
+        match func.call() {
+            Err(RuntimeError::Trap { trap }) => assert_eq!(trap.info, *info, "{}", name),
+            result => panic!("expected `{}` to trap, got {:?}", name, result.map(|_| ())),
+        }
+    }
+}
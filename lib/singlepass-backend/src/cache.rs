@@ -0,0 +1,200 @@
+use dynasmrt::ExecutableBuffer;
+use std::{collections::BTreeMap, sync::Arc};
+use wasmer_runtime_core::{
+    backend::{
+        sys::{Memory, Protect},
+        CacheGen,
+    },
+    cache::{Artifact, Error},
+    module::ModuleInfo,
+    typed_func::WasmTrapInfo,
+    vmcalls,
+};
+
+use serde_bench::{deserialize, serialize};
+
+/// A runtime function that the generated code calls through an absolute address.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub enum VmCall {
+    LocalStaticMemoryGrow,
+    LocalStaticMemorySize,
+    LocalDynamicMemoryGrow,
+    LocalDynamicMemorySize,
+    LocalSharedStaticMemoryGrow,
+    LocalSharedStaticMemorySize,
+    ImportedStaticMemoryGrow,
+    ImportedStaticMemorySize,
+    ImportedDynamicMemoryGrow,
+    ImportedDynamicMemorySize,
+    ImportedSharedStaticMemoryGrow,
+    ImportedSharedStaticMemorySize,
+    FuelExhausted,
+    Interrupted,
+    MemoryInit,
+    DataDrop,
+    MemoryCopy,
+    MemoryFill,
+    TableInit,
+    ElemDrop,
+    TableCopy,
+    TableGet,
+    TableSet,
+    TableGrow,
+    TableSize,
+    MemoryAtomicWait32,
+    MemoryAtomicWait64,
+    MemoryAtomicNotify,
+}
+
+impl VmCall {
+    /// The address of this function in the current process.
+    pub fn address(self) -> usize {
+        match self {
+            VmCall::LocalStaticMemoryGrow => vmcalls::local_static_memory_grow as usize,
+            VmCall::LocalStaticMemorySize => vmcalls::local_static_memory_size as usize,
+            VmCall::LocalDynamicMemoryGrow => vmcalls::local_dynamic_memory_grow as usize,
+            VmCall::LocalDynamicMemorySize => vmcalls::local_dynamic_memory_size as usize,
+            VmCall::LocalSharedStaticMemoryGrow => {
+                vmcalls::local_shared_static_memory_grow as usize
+            }
+            VmCall::LocalSharedStaticMemorySize => {
+                vmcalls::local_shared_static_memory_size as usize
+            }
+            VmCall::ImportedStaticMemoryGrow => vmcalls::imported_static_memory_grow as usize,
+            VmCall::ImportedStaticMemorySize => vmcalls::imported_static_memory_size as usize,
+            VmCall::ImportedDynamicMemoryGrow => vmcalls::imported_dynamic_memory_grow as usize,
+            VmCall::ImportedDynamicMemorySize => vmcalls::imported_dynamic_memory_size as usize,
+            VmCall::ImportedSharedStaticMemoryGrow => {
+                vmcalls::imported_shared_static_memory_grow as usize
+            }
+            VmCall::ImportedSharedStaticMemorySize => {
+                vmcalls::imported_shared_static_memory_size as usize
+            }
+            VmCall::FuelExhausted => vmcalls::fuel_exhausted as usize,
+            VmCall::Interrupted => vmcalls::interrupted as usize,
+            VmCall::MemoryInit => vmcalls::memory_init as usize,
+            VmCall::DataDrop => vmcalls::data_drop as usize,
+            VmCall::MemoryCopy => vmcalls::memory_copy as usize,
+            VmCall::MemoryFill => vmcalls::memory_fill as usize,
+            VmCall::TableInit => vmcalls::table_init as usize,
+            VmCall::ElemDrop => vmcalls::elem_drop as usize,
+            VmCall::TableCopy => vmcalls::table_copy as usize,
+            VmCall::TableGet => vmcalls::table_get as usize,
+            VmCall::TableSet => vmcalls::table_set as usize,
+            VmCall::TableGrow => vmcalls::table_grow as usize,
+            VmCall::TableSize => vmcalls::table_size as usize,
+            VmCall::MemoryAtomicWait32 => vmcalls::memory_atomic_wait32 as usize,
+            VmCall::MemoryAtomicWait64 => vmcalls::memory_atomic_wait64 as usize,
+            VmCall::MemoryAtomicNotify => vmcalls::memory_atomic_notify as usize,
+        }
+    }
+}
+
+/// What a relocation points to.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub enum RelocationTarget {
+    VmCall(VmCall),
+    /// The jump table of the n-th `br_table` in the module.
+    BrTable(usize),
+}
+
+/// A 64-bit absolute address embedded in the generated code.
+///
+/// These addresses are only valid in the process that compiled the module,
+/// so they are patched again when the code is loaded from a cache.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct Relocation {
+    /// Offset of the 8-byte immediate in the code buffer.
+    pub offset: usize,
+    pub target: RelocationTarget,
+}
+
+/// The kind of trap raised by a `ud2` in the generated code.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub enum TrapCode {
+    Unreachable,
+    IncorrectCallIndirectSignature,
+    MemoryOutOfBounds,
+    CallIndirectOOB,
+    IllegalArithmetic,
+    MisalignedAtomicAccess,
+}
+
+impl TrapCode {
+    pub fn trap_info(self) -> WasmTrapInfo {
+        match self {
+            TrapCode::Unreachable => WasmTrapInfo::Unreachable,
+            TrapCode::IncorrectCallIndirectSignature => {
+                WasmTrapInfo::IncorrectCallIndirectSignature
+            }
+            TrapCode::MemoryOutOfBounds => WasmTrapInfo::MemoryOutOfBounds,
+            TrapCode::CallIndirectOOB => WasmTrapInfo::CallIndirectOOB,
+            TrapCode::IllegalArithmetic => WasmTrapInfo::IllegalArithmetic,
+            TrapCode::MisalignedAtomicAccess => WasmTrapInfo::MisalignedAtomicAccess,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BackendCache {
+    pub code_size: usize,
+    /// Offsets of every function, including the import trampolines.
+    pub function_offsets: Vec<usize>,
+    /// Code offsets of the entries of every `br_table` jump table.
+    pub br_tables: Vec<Vec<usize>>,
+    pub relocations: Vec<Relocation>,
+    /// Code offsets of every `ud2`, with the kind of trap it raises.
+    pub trap_codes: BTreeMap<usize, TrapCode>,
+    pub func_import_count: usize,
+}
+
+impl BackendCache {
+    pub fn from_cache(cache: Artifact) -> Result<(ModuleInfo, Memory, Self), Error> {
+        let (info, backend_data, compiled_code) = cache.consume();
+
+        let backend_cache =
+            deserialize(&backend_data).map_err(|e| Error::DeserializeError(e.to_string()))?;
+
+        Ok((info, compiled_code, backend_cache))
+    }
+
+    pub fn into_backend_data(&self) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::new();
+
+        serialize(&mut buffer, self).map_err(|e| Error::SerializeError(e.to_string()))?;
+
+        Ok(buffer)
+    }
+}
+
+pub struct CacheGenerator {
+    backend_cache: Arc<BackendCache>,
+    code: Arc<ExecutableBuffer>,
+}
+
+impl CacheGenerator {
+    pub fn new(backend_cache: Arc<BackendCache>, code: Arc<ExecutableBuffer>) -> Self {
+        Self {
+            backend_cache,
+            code,
+        }
+    }
+}
+
+impl CacheGen for CacheGenerator {
+    fn generate_cache(&self) -> Result<(Box<[u8]>, Memory), Error> {
+        let code: &[u8] = &self.code;
+
+        let mut memory = Memory::with_size_protect(code.len(), Protect::ReadWrite)
+            .map_err(Error::SerializeError)?;
+
+        unsafe {
+            memory.as_slice_mut()[..code.len()].copy_from_slice(code);
+        }
+
+        Ok((
+            self.backend_cache.into_backend_data()?.into_boxed_slice(),
+            memory,
+        ))
+    }
+}
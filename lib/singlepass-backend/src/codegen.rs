@@ -1,5 +1,6 @@
 use wasmer_runtime_core::{
     backend::RunnableModule,
+    metering::OperatorCost,
     module::ModuleInfo,
     structures::Map,
     types::{FuncIndex, FuncSig, SigIndex},
@@ -22,6 +23,10 @@ pub trait ModuleCodeGenerator<FCG: FunctionCodeGenerator, RM: RunnableModule> {
 
     /// Adds an import function.
     fn feed_import_function(&mut self) -> Result<(), CodegenError>;
+
+    /// Sets the cost of each operator of each local function, before any
+    /// function is generated.
+    fn feed_fuel_costs(&mut self, costs: Vec<Vec<OperatorCost>>) -> Result<(), CodegenError>;
 }
 
 /// The function-scope code generator trait.
@@ -1,6 +1,7 @@
 #![allow(clippy::forget_copy)] // Used by dynasm.
 
 use super::codegen::*;
+use crate::cache::{BackendCache, CacheGenerator, Relocation, RelocationTarget, TrapCode, VmCall};
 use crate::emitter_x64::*;
 use crate::machine::*;
 use crate::protect_unix;
@@ -9,10 +10,16 @@ use dynasmrt::{
 };
 use smallvec::SmallVec;
 use std::ptr::NonNull;
-use std::{any::Any, collections::HashMap, sync::Arc};
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 use wasmer_runtime_core::{
     backend::RunnableModule,
+    cache::Error as CacheError,
     memory::MemoryType,
+    metering::OperatorCost,
     module::ModuleInfo,
     structures::{Map, TypedIndex},
     typed_func::Wasm,
@@ -21,9 +28,8 @@ use wasmer_runtime_core::{
         TableIndex, Type,
     },
     vm::{self, LocalGlobal, LocalMemory, LocalTable},
-    vmcalls,
 };
-use wasmparser::{Operator, Type as WpType};
+use wasmparser::{MemoryImmediate, Operator, Type as WpType};
 
 lazy_static! {
     /// Performs a System V call to `target` with [stack_top..stack_base] as the argument list, from right to left.
@@ -120,6 +126,8 @@ pub struct X64ModuleCodeGenerator {
     function_labels: Option<HashMap<usize, (DynamicLabel, Option<AssemblyOffset>)>>,
     assembler: Option<Assembler>,
     func_import_count: usize,
+    /// The cost of each operator of each local function, when metering.
+    fuel_costs: Option<Vec<Vec<OperatorCost>>>,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -135,6 +143,8 @@ pub struct X64FunctionCode {
     assembler: Option<Assembler>,
     function_labels: Option<HashMap<usize, (DynamicLabel, Option<AssemblyOffset>)>>,
     br_table_data: Option<Vec<Vec<usize>>>,
+    relocations: Option<Vec<Relocation>>,
+    trap_codes: Option<BTreeMap<usize, TrapCode>>,
     returns: SmallVec<[WpType; 1]>,
     locals: Vec<Location>,
     num_params: usize,
@@ -143,6 +153,11 @@ pub struct X64FunctionCode {
     control_stack: Vec<ControlFrame>,
     machine: Machine,
     unreachable_depth: usize,
+    fuel_costs: Option<Vec<OperatorCost>>,
+    /// The number of operators fed so far.
+    op_count: usize,
+    /// Fuel consumed by the operators since the last basic-block boundary.
+    fuel_pending: u64,
 }
 
 enum FuncPtrInner {}
@@ -153,14 +168,39 @@ unsafe impl Send for FuncPtr {}
 unsafe impl Sync for FuncPtr {}
 
 pub struct X64ExecutionContext {
-    #[allow(dead_code)]
-    code: ExecutableBuffer,
+    code: Arc<ExecutableBuffer>,
     #[allow(dead_code)]
     functions: Vec<X64FunctionCode>,
     function_pointers: Vec<FuncPtr>,
-    signatures: Arc<Map<SigIndex, FuncSig>>,
     _br_table_data: Vec<Vec<usize>>,
     func_import_count: usize,
+    backend_cache: Arc<BackendCache>,
+    /// The environment of `invoke` for each signature.
+    invoke_envs: Vec<InvokeEnv>,
+}
+
+/// What `invoke` needs to call a function and report its traps.
+struct InvokeEnv {
+    num_params: usize,
+    code: Arc<ExecutableBuffer>,
+    backend_cache: Arc<BackendCache>,
+}
+
+impl InvokeEnv {
+    fn for_signatures(
+        signatures: &Map<SigIndex, FuncSig>,
+        code: &Arc<ExecutableBuffer>,
+        backend_cache: &Arc<BackendCache>,
+    ) -> Vec<InvokeEnv> {
+        signatures
+            .iter()
+            .map(|(_, sig)| InvokeEnv {
+                num_params: sig.params().len(),
+                code: code.clone(),
+                backend_cache: backend_cache.clone(),
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -179,6 +219,93 @@ pub enum IfElseState {
     Else,
 }
 
+/// How an atomic read-modify-write combines the value in memory with its operand.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum AtomicRmwOp {
+    Add,
+    Sub,
+    And,
+    Or,
+    Xor,
+    Xchg,
+}
+
+impl X64ExecutionContext {
+    pub fn cache_generator(&self) -> CacheGenerator {
+        CacheGenerator::new(self.backend_cache.clone(), self.code.clone())
+    }
+
+    /// Rebuilds an execution context from code loaded from a cache,
+    /// patching the absolute addresses it contains for the current process.
+    pub fn from_cache(
+        backend_cache: BackendCache,
+        code: &[u8],
+        info: &ModuleInfo,
+    ) -> Result<X64ExecutionContext, CacheError> {
+        let mut code = code
+            .get(..backend_cache.code_size)
+            .ok_or_else(|| CacheError::DeserializeError("code section too short".to_string()))?
+            .to_vec();
+
+        // The jump tables are filled in once the final code address is known,
+        // but their own addresses are already needed to patch the code.
+        let mut br_table_data: Vec<Vec<usize>> = backend_cache
+            .br_tables
+            .iter()
+            .map(|table| vec![0usize; table.len()])
+            .collect();
+
+        for reloc in &backend_cache.relocations {
+            let address = match reloc.target {
+                RelocationTarget::VmCall(vmcall) => vmcall.address(),
+                RelocationTarget::BrTable(index) => br_table_data
+                    .get(index)
+                    .ok_or_else(|| {
+                        CacheError::DeserializeError("invalid br_table relocation".to_string())
+                    })?
+                    .as_ptr() as usize,
+            };
+            code.get_mut(reloc.offset..reloc.offset + 8)
+                .ok_or_else(|| {
+                    CacheError::DeserializeError("relocation out of bounds".to_string())
+                })?
+                .copy_from_slice(&(address as u64).to_le_bytes());
+        }
+
+        let mut assembler = Assembler::new().map_err(|e| CacheError::Unknown(e.to_string()))?;
+        assembler.extend(code);
+        let output = assembler.finalize().map_err(|_| {
+            CacheError::Unknown("unable to create an executable buffer".to_string())
+        })?;
+
+        for (table, offsets) in br_table_data.iter_mut().zip(backend_cache.br_tables.iter()) {
+            for (entry, offset) in table.iter_mut().zip(offsets.iter()) {
+                *entry = output.ptr(AssemblyOffset(*offset)) as usize;
+            }
+        }
+
+        let function_pointers = backend_cache
+            .function_offsets
+            .iter()
+            .map(|offset| FuncPtr(output.ptr(AssemblyOffset(*offset)) as _))
+            .collect();
+
+        let code = Arc::new(output);
+        let func_import_count = backend_cache.func_import_count;
+        let backend_cache = Arc::new(backend_cache);
+
+        Ok(X64ExecutionContext {
+            invoke_envs: InvokeEnv::for_signatures(&info.signatures, &code, &backend_cache),
+            code,
+            functions: vec![],
+            function_pointers,
+            _br_table_data: br_table_data,
+            func_import_count,
+            backend_cache,
+        })
+    }
+}
+
 impl RunnableModule for X64ExecutionContext {
     fn get_func(
         &self,
@@ -207,14 +334,16 @@ impl RunnableModule for X64ExecutionContext {
             rets: *mut u64,
             trap_info: *mut WasmTrapInfo,
             user_error: *mut Option<Box<dyn Any>>,
-            num_params_plus_one: Option<NonNull<c_void>>,
+            invoke_env: Option<NonNull<c_void>>,
         ) -> bool {
-            let args = ::std::slice::from_raw_parts(
-                args,
-                num_params_plus_one.unwrap().as_ptr() as usize - 1,
-            );
+            let invoke_env: &InvokeEnv = &*invoke_env.unwrap().cast().as_ptr();
+            let args = ::std::slice::from_raw_parts(args, invoke_env.num_params);
             let args_reverse: SmallVec<[u64; 8]> = args.iter().cloned().rev().collect();
-            match protect_unix::call_protected(|| {
+            let trap_table = protect_unix::TrapTable {
+                code_start: invoke_env.code.ptr(AssemblyOffset(0)) as usize,
+                trap_codes: &invoke_env.backend_cache.trap_codes,
+            };
+            match protect_unix::call_protected(trap_table, || {
                 CONSTRUCT_STACK_AND_CALL_WASM(
                     args_reverse.as_ptr(),
                     args_reverse.as_ptr().offset(args_reverse.len() as isize),
@@ -251,7 +380,7 @@ impl RunnableModule for X64ExecutionContext {
             Wasm::from_raw_parts(
                 dummy_trampoline,
                 invoke,
-                NonNull::new((self.signatures.get(sig_index).unwrap().params().len() + 1) as _), // +1 to keep it non-zero
+                Some(NonNull::from(&self.invoke_envs[sig_index.index()]).cast()),
             )
         })
     }
@@ -260,6 +389,19 @@ impl RunnableModule for X64ExecutionContext {
         protect_unix::TRAP_EARLY_DATA.with(|x| x.set(Some(data)));
         protect_unix::trigger_trap();
     }
+
+    fn get_local_func_index_at(&self, _: &ModuleInfo, address: usize) -> Option<LocalFuncIndex> {
+        let start = self.code.as_ptr() as usize;
+        if address < start || address >= start + self.code.len() {
+            return None;
+        }
+        self.function_pointers[self.func_import_count..]
+            .iter()
+            .enumerate()
+            .filter(|(_, ptr)| ptr.0 as usize <= address)
+            .max_by_key(|(_, ptr)| ptr.0 as usize)
+            .map(|(index, _)| LocalFuncIndex::new(index))
+    }
 }
 
 impl X64ModuleCodeGenerator {
@@ -271,6 +413,7 @@ impl X64ModuleCodeGenerator {
             function_labels: Some(HashMap::new()),
             assembler: Some(Assembler::new().unwrap()),
             func_import_count: 0,
+            fuel_costs: None,
         }
     }
 }
@@ -281,18 +424,24 @@ impl ModuleCodeGenerator<X64FunctionCode, X64ExecutionContext> for X64ModuleCode
     }
 
     fn next_function(&mut self) -> Result<&mut X64FunctionCode, CodegenError> {
-        let (mut assembler, mut function_labels, br_table_data) = match self.functions.last_mut() {
-            Some(x) => (
-                x.assembler.take().unwrap(),
-                x.function_labels.take().unwrap(),
-                x.br_table_data.take().unwrap(),
-            ),
-            None => (
-                self.assembler.take().unwrap(),
-                self.function_labels.take().unwrap(),
-                vec![],
-            ),
-        };
+        let (mut assembler, mut function_labels, br_table_data, relocations, trap_codes) =
+            match self.functions.last_mut() {
+                Some(x) => (
+                    x.assembler.take().unwrap(),
+                    x.function_labels.take().unwrap(),
+                    x.br_table_data.take().unwrap(),
+                    x.relocations.take().unwrap(),
+                    x.trap_codes.take().unwrap(),
+                ),
+                None => (
+                    self.assembler.take().unwrap(),
+                    self.function_labels.take().unwrap(),
+                    vec![],
+                    vec![],
+                    BTreeMap::new(),
+                ),
+            };
+        let local_func_index = self.functions.len();
         let begin_offset = assembler.offset();
         let begin_label_info = function_labels
             .entry(self.functions.len() + self.func_import_count)
@@ -313,6 +462,8 @@ impl ModuleCodeGenerator<X64FunctionCode, X64ExecutionContext> for X64ModuleCode
             assembler: Some(assembler),
             function_labels: Some(function_labels),
             br_table_data: Some(br_table_data),
+            relocations: Some(relocations),
+            trap_codes: Some(trap_codes),
             returns: smallvec![],
             locals: vec![],
             num_params: 0,
@@ -321,21 +472,34 @@ impl ModuleCodeGenerator<X64FunctionCode, X64ExecutionContext> for X64ModuleCode
             control_stack: vec![],
             machine: Machine::new(),
             unreachable_depth: 0,
+            fuel_costs: self
+                .fuel_costs
+                .as_mut()
+                .map(|fuel_costs| ::std::mem::replace(&mut fuel_costs[local_func_index], vec![])),
+            op_count: 0,
+            fuel_pending: 0,
         };
         self.functions.push(code);
         Ok(self.functions.last_mut().unwrap())
     }
 
     fn finalize(mut self, _: &ModuleInfo) -> Result<X64ExecutionContext, CodegenError> {
-        let (assembler, mut br_table_data) = match self.functions.last_mut() {
-            Some(x) => (x.assembler.take().unwrap(), x.br_table_data.take().unwrap()),
-            None => {
-                return Err(CodegenError {
-                    message: "no function",
-                });
-            }
-        };
+        let (assembler, mut br_table_data, relocations, trap_codes) =
+            match self.functions.last_mut() {
+                Some(x) => (
+                    x.assembler.take().unwrap(),
+                    x.br_table_data.take().unwrap(),
+                    x.relocations.take().unwrap(),
+                    x.trap_codes.take().unwrap(),
+                ),
+                None => {
+                    return Err(CodegenError {
+                        message: "no function",
+                    });
+                }
+            };
         let output = assembler.finalize().unwrap();
+        let br_tables = br_table_data.clone();
 
         for table in &mut br_table_data {
             for entry in table {
@@ -349,6 +513,7 @@ impl ModuleCodeGenerator<X64FunctionCode, X64ExecutionContext> for X64ModuleCode
             self.function_labels.as_ref().unwrap()
         };
         let mut out_labels: Vec<FuncPtr> = vec![];
+        let mut function_offsets: Vec<usize> = vec![];
 
         for i in 0..function_labels.len() {
             let (_, offset) = match function_labels.get(&i) {
@@ -368,19 +533,43 @@ impl ModuleCodeGenerator<X64FunctionCode, X64ExecutionContext> for X64ModuleCode
                 }
             };
             out_labels.push(FuncPtr(output.ptr(*offset) as _));
+            function_offsets.push(offset.0);
         }
 
+        let backend_cache = BackendCache {
+            code_size: output.len(),
+            function_offsets,
+            br_tables,
+            relocations,
+            trap_codes,
+            func_import_count: self.func_import_count,
+        };
+
+        let code = Arc::new(output);
+        let backend_cache = Arc::new(backend_cache);
+
         Ok(X64ExecutionContext {
-            code: output,
+            invoke_envs: InvokeEnv::for_signatures(
+                self.signatures.as_ref().unwrap(),
+                &code,
+                &backend_cache,
+            ),
+            code,
             functions: self.functions,
-            signatures: self.signatures.as_ref().unwrap().clone(),
             _br_table_data: br_table_data,
             func_import_count: self.func_import_count,
             function_pointers: out_labels,
+            backend_cache,
         })
     }
 
     fn feed_signatures(&mut self, signatures: Map<SigIndex, FuncSig>) -> Result<(), CodegenError> {
+        // Functions return their result in `rax` or `xmm0`, which leaves no room for more.
+        if signatures.iter().any(|(_, sig)| sig.returns().len() > 1) {
+            return Err(CodegenError {
+                message: "multi-value returns are not supported by the singlepass backend",
+            });
+        }
         self.signatures = Some(Arc::new(signatures));
         Ok(())
     }
@@ -404,13 +593,22 @@ impl ModuleCodeGenerator<X64FunctionCode, X64ExecutionContext> for X64ModuleCode
         labels.insert(id, (label, Some(offset)));
 
         // Emits a tail call trampoline that loads the address of the target import function
-        // from Ctx and jumps to it.
+        // and the context it is called with from Ctx, and jumps to it.
 
         a.emit_mov(
             Size::S64,
             Location::Memory(GPR::RDI, vm::Ctx::offset_imported_funcs() as i32),
             Location::GPR(GPR::RAX),
         );
+        a.emit_mov(
+            Size::S64,
+            Location::Memory(
+                GPR::RAX,
+                (vm::ImportedFunc::size() as usize * id + vm::ImportedFunc::offset_vmctx() as usize)
+                    as i32,
+            ),
+            Location::GPR(GPR::RDI),
+        );
         a.emit_mov(
             Size::S64,
             Location::Memory(
@@ -426,6 +624,11 @@ impl ModuleCodeGenerator<X64FunctionCode, X64ExecutionContext> for X64ModuleCode
 
         Ok(())
     }
+
+    fn feed_fuel_costs(&mut self, costs: Vec<Vec<OperatorCost>>) -> Result<(), CodegenError> {
+        self.fuel_costs = Some(costs);
+        Ok(())
+    }
 }
 
 impl X64FunctionCode {
@@ -968,6 +1171,144 @@ impl X64FunctionCode {
         Self::emit_relaxed_avx(a, m, f, loc, loc, ret);
     }
 
+    /// Adds `cost` to the fuel consumed by the instance, and calls into the runtime
+    /// if that exceeds its fuel limit.
+    fn emit_fuel_check(
+        a: &mut Assembler,
+        m: &mut Machine,
+        relocations: &mut Vec<Relocation>,
+        cost: u64,
+    ) {
+        let tmp = m.acquire_temp_gpr().unwrap();
+        a.emit_mov(Size::S64, Location::Imm64(cost), Location::GPR(tmp));
+        a.emit_add(
+            Size::S64,
+            Location::Memory(Machine::get_vmctx_reg(), vm::Ctx::offset_fuel_used() as i32),
+            Location::GPR(tmp),
+        );
+        a.emit_mov(
+            Size::S64,
+            Location::GPR(tmp),
+            Location::Memory(Machine::get_vmctx_reg(), vm::Ctx::offset_fuel_used() as i32),
+        );
+        a.emit_cmp(
+            Size::S64,
+            Location::Memory(
+                Machine::get_vmctx_reg(),
+                vm::Ctx::offset_fuel_limit() as i32,
+            ),
+            Location::GPR(tmp),
+        );
+        m.release_temp_gpr(tmp);
+
+        let enough_fuel = a.get_label();
+        a.emit_jmp(Condition::BelowEqual, enough_fuel);
+        Self::emit_exit_call(a, m, relocations, VmCall::FuelExhausted);
+        a.emit_label(enough_fuel);
+    }
+
+    /// Stops the function if the interrupt flag of the instance is set.
+    fn emit_interrupt_check(a: &mut Assembler, m: &mut Machine, relocations: &mut Vec<Relocation>) {
+        let tmp = m.acquire_temp_gpr().unwrap();
+        a.emit_mov(
+            Size::S64,
+            Location::Memory(
+                Machine::get_vmctx_reg(),
+                vm::Ctx::offset_interrupted() as i32,
+            ),
+            Location::GPR(tmp),
+        );
+        a.emit_movzx(
+            Size::S8,
+            Location::Memory(tmp, 0),
+            Size::S32,
+            Location::GPR(tmp),
+        );
+        a.emit_cmp(Size::S32, Location::Imm32(0), Location::GPR(tmp));
+        m.release_temp_gpr(tmp);
+
+        let not_interrupted = a.get_label();
+        a.emit_jmp(Condition::Equal, not_interrupted);
+        Self::emit_exit_call(a, m, relocations, VmCall::Interrupted);
+        a.emit_label(not_interrupted);
+    }
+
+    /// Calls `vmcall`, which unwinds and never returns.
+    fn emit_exit_call(
+        a: &mut Assembler,
+        m: &mut Machine,
+        relocations: &mut Vec<Relocation>,
+        vmcall: VmCall,
+    ) {
+        Self::emit_vmcall(a, m, relocations, vmcall, ::std::iter::empty());
+    }
+
+    /// Calls `vmcall` with the vmctx and `params` as arguments.
+    fn emit_vmcall<I: Iterator<Item = Location>>(
+        a: &mut Assembler,
+        m: &mut Machine,
+        relocations: &mut Vec<Relocation>,
+        vmcall: VmCall,
+        params: I,
+    ) {
+        let mut reloc_offset = 0;
+        Self::emit_call_sysv(
+            a,
+            m,
+            |a| {
+                a.emit_mov(
+                    Size::S64,
+                    Location::Imm64(vmcall.address() as u64),
+                    Location::GPR(GPR::RAX),
+                );
+                reloc_offset = a.offset().0 - 8;
+                a.emit_call_location(Location::GPR(GPR::RAX));
+            },
+            params,
+        );
+        relocations.push(Relocation {
+            offset: reloc_offset,
+            target: RelocationTarget::VmCall(vmcall),
+        });
+    }
+
+    /// Calls the runtime function of a bulk memory or table operator, with the immediates
+    /// of the operator followed by its `operand_count` operands as arguments.
+    ///
+    /// The result of the function, if any, is left in RAX.
+    fn emit_bulk_memory_call(
+        a: &mut Assembler,
+        m: &mut Machine,
+        relocations: &mut Vec<Relocation>,
+        value_stack: &mut Vec<(Location, LocalOrTemp)>,
+        vmcall: VmCall,
+        immediates: &[u32],
+        operand_count: usize,
+    ) {
+        let operands: SmallVec<[_; 3]> = value_stack
+            .drain(value_stack.len() - operand_count..)
+            .collect();
+        let released: SmallVec<[Location; 3]> = operands
+            .iter()
+            .filter(|&&(_, lot)| lot == LocalOrTemp::Temp)
+            .map(|&(x, _)| x)
+            .collect();
+        m.release_locations_only_regs(&released);
+
+        Self::emit_vmcall(
+            a,
+            m,
+            relocations,
+            vmcall,
+            immediates
+                .iter()
+                .map(|&imm| Location::Imm32(imm))
+                .chain(operands.iter().map(|&(x, _)| x)),
+        );
+
+        m.release_locations_only_stack(a, &released);
+    }
+
     /// Emits a System V call sequence.
     ///
     /// This function must not use RAX before `cb` is called.
@@ -1136,11 +1477,34 @@ impl X64FunctionCode {
         Self::emit_call_sysv(a, m, |a| a.emit_call_label(label), params)
     }
 
+    /// Emits a `ud2`, recording the kind of trap it raises.
+    fn emit_trap(a: &mut Assembler, trap_codes: &mut BTreeMap<usize, TrapCode>, code: TrapCode) {
+        trap_codes.insert(a.get_offset().0, code);
+        a.emit_ud2();
+    }
+
+    /// Emits a trap that is raised when `condition` holds.
+    fn emit_conditional_trap(
+        a: &mut Assembler,
+        trap_codes: &mut BTreeMap<usize, TrapCode>,
+        condition: Condition,
+        code: TrapCode,
+    ) {
+        let trap = a.get_label();
+        let after = a.get_label();
+        a.emit_jmp(condition, trap);
+        a.emit_jmp(Condition::None, after);
+        a.emit_label(trap);
+        Self::emit_trap(a, trap_codes, code);
+        a.emit_label(after);
+    }
+
     /// Emits a memory operation.
     fn emit_memory_op<F: FnOnce(&mut Assembler, &mut Machine, GPR)>(
         module_info: &ModuleInfo,
         a: &mut Assembler,
         m: &mut Machine,
+        trap_codes: &mut BTreeMap<usize, TrapCode>,
         addr: Location,
         offset: usize,
         value_size: usize,
@@ -1218,7 +1582,12 @@ impl X64FunctionCode {
             // Trap if the end address of the requested area is above that of the linear memory.
             a.emit_add(Size::S64, Location::GPR(tmp_base), Location::GPR(tmp_addr));
             a.emit_cmp(Size::S64, Location::GPR(tmp_bound), Location::GPR(tmp_addr));
-            a.emit_conditional_trap(Condition::Above);
+            Self::emit_conditional_trap(
+                a,
+                trap_codes,
+                Condition::Above,
+                TrapCode::MemoryOutOfBounds,
+            );
         }
 
         m.release_temp_gpr(tmp_bound);
@@ -1238,10 +1607,291 @@ impl X64FunctionCode {
         m.release_temp_gpr(tmp_addr);
     }
 
+    /// Emits an atomic memory operation, which traps unless the address is aligned to `value_size`.
+    fn emit_atomic_memory_op<F: FnOnce(&mut Assembler, &mut Machine, GPR)>(
+        module_info: &ModuleInfo,
+        a: &mut Assembler,
+        m: &mut Machine,
+        trap_codes: &mut BTreeMap<usize, TrapCode>,
+        addr: Location,
+        offset: usize,
+        value_size: usize,
+        cb: F,
+    ) {
+        // `emit_memory_op` holds on to `trap_codes`, so the alignment trap is added afterwards.
+        let mut alignment_traps = BTreeMap::new();
+        Self::emit_memory_op(
+            module_info,
+            a,
+            m,
+            trap_codes,
+            addr,
+            offset,
+            value_size,
+            |a, m, addr| {
+                // Linear memories are page aligned, so the host address is aligned
+                // exactly when the wasm address is.
+                if value_size > 1 {
+                    let tmp = m.acquire_temp_gpr().unwrap();
+                    a.emit_mov(Size::S64, Location::GPR(addr), Location::GPR(tmp));
+                    a.emit_and(
+                        Size::S64,
+                        Location::Imm32(value_size as u32 - 1),
+                        Location::GPR(tmp),
+                    );
+                    Self::emit_conditional_trap(
+                        a,
+                        &mut alignment_traps,
+                        Condition::NotEqual,
+                        TrapCode::MisalignedAtomicAccess,
+                    );
+                    m.release_temp_gpr(tmp);
+                }
+                cb(a, m, addr);
+            },
+        );
+        trap_codes.extend(alignment_traps);
+    }
+
+    /// Runs `cb` with two registers other than RAX, which `cmpxchg` compares with
+    /// and loads into: the first holds the address in `addr`, and the second is free.
+    fn with_cmpxchg_gprs<F: FnOnce(&mut Assembler, GPR, GPR)>(
+        a: &mut Assembler,
+        m: &mut Machine,
+        addr: GPR,
+        cb: F,
+    ) {
+        // These are the two temporary registers left besides `addr`, so whichever
+        // of the three is RAX, the other two are not.
+        let tmp1 = m.acquire_temp_gpr().unwrap();
+        let tmp2 = m.acquire_temp_gpr().unwrap();
+        let mut gprs = [addr, tmp1, tmp2]
+            .iter()
+            .cloned()
+            .filter(|&gpr| gpr != GPR::RAX);
+        let (real_addr, value) = (gprs.next().unwrap(), gprs.next().unwrap());
+        if real_addr != addr {
+            a.emit_mov(Size::S64, Location::GPR(addr), Location::GPR(real_addr));
+        }
+        cb(a, real_addr, value);
+        m.release_temp_gpr(tmp2);
+        m.release_temp_gpr(tmp1);
+    }
+
+    /// Moves the `narrow_sz` integer in the low bits of `reg` to `ret`, zero-extended to `sz`.
+    fn emit_zero_extend_to(a: &mut Assembler, narrow_sz: Size, sz: Size, reg: GPR, ret: Location) {
+        match narrow_sz {
+            Size::S8 | Size::S16 => {
+                a.emit_movzx(narrow_sz, Location::GPR(reg), Size::S32, Location::GPR(reg))
+            }
+            // Writing a 32-bit register clears its upper half.
+            Size::S32 => a.emit_mov(Size::S32, Location::GPR(reg), Location::GPR(reg)),
+            Size::S64 => {}
+        }
+        a.emit_mov(sz, Location::GPR(reg), ret);
+    }
+
+    /// Emits an atomic load of a `narrow_sz` integer, zero-extended to `sz`.
+    ///
+    /// Aligned loads are atomic on x86, and atomic stores are locked, so a plain
+    /// `mov` is sequentially consistent.
+    fn emit_atomic_load(
+        module_info: &ModuleInfo,
+        a: &mut Assembler,
+        m: &mut Machine,
+        trap_codes: &mut BTreeMap<usize, TrapCode>,
+        value_stack: &mut Vec<(Location, LocalOrTemp)>,
+        op: &Operator,
+        memarg: &MemoryImmediate,
+    ) {
+        let (sz, narrow_sz) = atomic_access_sizes(op);
+        let target = get_location_released(a, m, value_stack.pop().unwrap());
+        let ret = m.acquire_locations(a, &[size_to_wp_type(sz)], false)[0];
+        value_stack.push((ret, LocalOrTemp::Temp));
+
+        Self::emit_atomic_memory_op(
+            module_info,
+            a,
+            m,
+            trap_codes,
+            target,
+            memarg.offset as usize,
+            size_in_bytes(narrow_sz),
+            |a, m, addr| {
+                let value = m.acquire_temp_gpr().unwrap();
+                a.emit_mov(narrow_sz, Location::Memory(addr, 0), Location::GPR(value));
+                Self::emit_zero_extend_to(a, narrow_sz, sz, value, ret);
+                m.release_temp_gpr(value);
+            },
+        );
+    }
+
+    /// Emits an atomic store of the low `narrow_sz` bits of a `sz` value.
+    fn emit_atomic_store(
+        module_info: &ModuleInfo,
+        a: &mut Assembler,
+        m: &mut Machine,
+        trap_codes: &mut BTreeMap<usize, TrapCode>,
+        value_stack: &mut Vec<(Location, LocalOrTemp)>,
+        op: &Operator,
+        memarg: &MemoryImmediate,
+    ) {
+        let (sz, narrow_sz) = atomic_access_sizes(op);
+        let target_value = get_location_released(a, m, value_stack.pop().unwrap());
+        let target_addr = get_location_released(a, m, value_stack.pop().unwrap());
+
+        Self::emit_atomic_memory_op(
+            module_info,
+            a,
+            m,
+            trap_codes,
+            target_addr,
+            memarg.offset as usize,
+            size_in_bytes(narrow_sz),
+            |a, m, addr| {
+                // `xchg` with a memory operand is always locked.
+                let value = m.acquire_temp_gpr().unwrap();
+                a.emit_mov(sz, target_value, Location::GPR(value));
+                a.emit_xchg(narrow_sz, Location::GPR(value), Location::Memory(addr, 0));
+                m.release_temp_gpr(value);
+            },
+        );
+    }
+
+    /// Emits an atomic read-modify-write of a `narrow_sz` integer, which returns
+    /// the old value zero-extended to `sz`.
+    fn emit_atomic_rmw(
+        module_info: &ModuleInfo,
+        a: &mut Assembler,
+        m: &mut Machine,
+        trap_codes: &mut BTreeMap<usize, TrapCode>,
+        value_stack: &mut Vec<(Location, LocalOrTemp)>,
+        op: &Operator,
+        memarg: &MemoryImmediate,
+    ) {
+        let (sz, narrow_sz) = atomic_access_sizes(op);
+        let rmw_op = atomic_rmw_op(op);
+        let target_value = get_location_released(a, m, value_stack.pop().unwrap());
+        let target_addr = get_location_released(a, m, value_stack.pop().unwrap());
+        let ret = m.acquire_locations(a, &[size_to_wp_type(sz)], false)[0];
+        value_stack.push((ret, LocalOrTemp::Temp));
+
+        Self::emit_atomic_memory_op(
+            module_info,
+            a,
+            m,
+            trap_codes,
+            target_addr,
+            memarg.offset as usize,
+            size_in_bytes(narrow_sz),
+            |a, m, addr| match rmw_op {
+                AtomicRmwOp::Add | AtomicRmwOp::Sub | AtomicRmwOp::Xchg => {
+                    let value = m.acquire_temp_gpr().unwrap();
+                    a.emit_mov(sz, target_value, Location::GPR(value));
+                    match rmw_op {
+                        AtomicRmwOp::Add => a.emit_lock_xadd(
+                            narrow_sz,
+                            Location::GPR(value),
+                            Location::Memory(addr, 0),
+                        ),
+                        AtomicRmwOp::Sub => {
+                            a.emit_neg(sz, Location::GPR(value));
+                            a.emit_lock_xadd(
+                                narrow_sz,
+                                Location::GPR(value),
+                                Location::Memory(addr, 0),
+                            );
+                        }
+                        _ => {
+                            a.emit_xchg(narrow_sz, Location::GPR(value), Location::Memory(addr, 0))
+                        }
+                    }
+                    Self::emit_zero_extend_to(a, narrow_sz, sz, value, ret);
+                    m.release_temp_gpr(value);
+                }
+                AtomicRmwOp::And | AtomicRmwOp::Or | AtomicRmwOp::Xor => {
+                    // There is no fetching form of these, so they retry a `cmpxchg` until
+                    // no other thread wrote in between.
+                    Self::with_cmpxchg_gprs(a, m, addr, |a, real_addr, value| {
+                        a.emit_mov(
+                            narrow_sz,
+                            Location::Memory(real_addr, 0),
+                            Location::GPR(GPR::RAX),
+                        );
+
+                        let retry = a.get_label();
+                        a.emit_label(retry);
+                        a.emit_mov(sz, target_value, Location::GPR(value));
+                        match rmw_op {
+                            AtomicRmwOp::And => {
+                                a.emit_and(sz, Location::GPR(GPR::RAX), Location::GPR(value))
+                            }
+                            AtomicRmwOp::Or => {
+                                a.emit_or(sz, Location::GPR(GPR::RAX), Location::GPR(value))
+                            }
+                            _ => a.emit_xor(sz, Location::GPR(GPR::RAX), Location::GPR(value)),
+                        }
+                        a.emit_lock_cmpxchg(
+                            narrow_sz,
+                            Location::GPR(value),
+                            Location::Memory(real_addr, 0),
+                        );
+                        a.emit_jmp(Condition::NotEqual, retry);
+
+                        Self::emit_zero_extend_to(a, narrow_sz, sz, GPR::RAX, ret);
+                    });
+                }
+            },
+        );
+    }
+
+    /// Emits an atomic compare-exchange of a `narrow_sz` integer, which returns
+    /// the old value zero-extended to `sz`.
+    fn emit_atomic_cmpxchg(
+        module_info: &ModuleInfo,
+        a: &mut Assembler,
+        m: &mut Machine,
+        trap_codes: &mut BTreeMap<usize, TrapCode>,
+        value_stack: &mut Vec<(Location, LocalOrTemp)>,
+        op: &Operator,
+        memarg: &MemoryImmediate,
+    ) {
+        let (sz, narrow_sz) = atomic_access_sizes(op);
+        let replacement = get_location_released(a, m, value_stack.pop().unwrap());
+        let expected = get_location_released(a, m, value_stack.pop().unwrap());
+        let target_addr = get_location_released(a, m, value_stack.pop().unwrap());
+        let ret = m.acquire_locations(a, &[size_to_wp_type(sz)], false)[0];
+        value_stack.push((ret, LocalOrTemp::Temp));
+
+        Self::emit_atomic_memory_op(
+            module_info,
+            a,
+            m,
+            trap_codes,
+            target_addr,
+            memarg.offset as usize,
+            size_in_bytes(narrow_sz),
+            |a, m, addr| {
+                Self::with_cmpxchg_gprs(a, m, addr, |a, real_addr, value| {
+                    a.emit_mov(sz, replacement, Location::GPR(value));
+                    a.emit_mov(sz, expected, Location::GPR(GPR::RAX));
+                    a.emit_lock_cmpxchg(
+                        narrow_sz,
+                        Location::GPR(value),
+                        Location::Memory(real_addr, 0),
+                    );
+
+                    Self::emit_zero_extend_to(a, narrow_sz, sz, GPR::RAX, ret);
+                });
+            },
+        );
+    }
+
     // Checks for underflow/overflow/nan before IxxTrunc{U/S}F32.
     fn emit_f32_int_conv_check(
         a: &mut Assembler,
         m: &mut Machine,
+        trap_codes: &mut BTreeMap<usize, TrapCode>,
         reg: XMM,
         lower_bound: f32,
         upper_bound: f32,
@@ -1279,7 +1929,7 @@ impl X64FunctionCode {
 
         a.emit_jmp(Condition::None, end);
         a.emit_label(trap);
-        a.emit_ud2();
+        Self::emit_trap(a, trap_codes, TrapCode::IllegalArithmetic);
         a.emit_label(end);
 
         m.release_temp_xmm(tmp_x);
@@ -1290,6 +1940,7 @@ impl X64FunctionCode {
     fn emit_f64_int_conv_check(
         a: &mut Assembler,
         m: &mut Machine,
+        trap_codes: &mut BTreeMap<usize, TrapCode>,
         reg: XMM,
         lower_bound: f64,
         upper_bound: f64,
@@ -1327,7 +1978,7 @@ impl X64FunctionCode {
 
         a.emit_jmp(Condition::None, end);
         a.emit_label(trap);
-        a.emit_ud2();
+        Self::emit_trap(a, trap_codes, TrapCode::IllegalArithmetic);
         a.emit_label(end);
 
         m.release_temp_xmm(tmp_x);
@@ -1361,6 +2012,8 @@ impl FunctionCodeGenerator for X64FunctionCode {
             .machine
             .init_locals(a, self.num_locals, self.num_params);
 
+        Self::emit_interrupt_check(a, &mut self.machine, self.relocations.as_mut().unwrap());
+
         self.control_stack.push(ControlFrame {
             label: a.get_label(),
             loop_like: false,
@@ -1373,12 +2026,19 @@ impl FunctionCodeGenerator for X64FunctionCode {
 
     fn finalize(&mut self) -> Result<(), CodegenError> {
         let a = self.assembler.as_mut().unwrap();
-        a.emit_ud2();
+        Self::emit_trap(a, self.trap_codes.as_mut().unwrap(), TrapCode::Unreachable);
         Ok(())
     }
 
     fn feed_opcode(&mut self, op: &Operator, module_info: &ModuleInfo) -> Result<(), CodegenError> {
         //println!("{:?} {}", op, self.value_stack.len());
+        let op_cost = self.fuel_costs.as_ref().map(|fuel_costs| {
+            fuel_costs
+                .get(self.op_count)
+                .map_or(0, |op_cost| op_cost.cost)
+        });
+        self.op_count += 1;
+
         let was_unreachable;
 
         if self.unreachable_depth > 0 {
@@ -1409,6 +2069,19 @@ impl FunctionCodeGenerator for X64FunctionCode {
             was_unreachable = false;
         }
 
+        if let Some(op_cost) = op_cost {
+            self.fuel_pending += op_cost;
+            if is_block_boundary(op) && self.fuel_pending > 0 {
+                Self::emit_fuel_check(
+                    self.assembler.as_mut().unwrap(),
+                    &mut self.machine,
+                    self.relocations.as_mut().unwrap(),
+                    self.fuel_pending,
+                );
+                self.fuel_pending = 0;
+            }
+        }
+
         let a = self.assembler.as_mut().unwrap();
         match *op {
             Operator::GetGlobal { global_index } => {
@@ -2536,7 +3209,14 @@ impl FunctionCodeGenerator for X64FunctionCode {
                 let tmp_in = self.machine.acquire_temp_xmm().unwrap();
 
                 a.emit_mov(Size::S32, loc, Location::XMM(tmp_in));
-                Self::emit_f32_int_conv_check(a, &mut self.machine, tmp_in, -1.0, 4294967296.0);
+                Self::emit_f32_int_conv_check(
+                    a,
+                    &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
+                    tmp_in,
+                    -1.0,
+                    4294967296.0,
+                );
 
                 a.emit_cvttss2si_64(XMMOrMemory::XMM(tmp_in), tmp_out);
                 a.emit_mov(Size::S32, Location::GPR(tmp_out), ret);
@@ -2557,6 +3237,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                 Self::emit_f32_int_conv_check(
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     tmp_in,
                     -2147483904.0,
                     2147483648.0,
@@ -2581,6 +3262,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                 Self::emit_f32_int_conv_check(
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     tmp_in,
                     -9223373136366403584.0,
                     9223372036854775808.0,
@@ -2619,6 +3301,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                 Self::emit_f32_int_conv_check(
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     tmp_in,
                     -1.0,
                     18446744073709551616.0,
@@ -2664,7 +3347,14 @@ impl FunctionCodeGenerator for X64FunctionCode {
                 let tmp_in = self.machine.acquire_temp_xmm().unwrap();
 
                 a.emit_mov(Size::S64, loc, Location::XMM(tmp_in));
-                Self::emit_f64_int_conv_check(a, &mut self.machine, tmp_in, -1.0, 4294967296.0);
+                Self::emit_f64_int_conv_check(
+                    a,
+                    &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
+                    tmp_in,
+                    -1.0,
+                    4294967296.0,
+                );
 
                 a.emit_cvttsd2si_64(XMMOrMemory::XMM(tmp_in), tmp_out);
                 a.emit_mov(Size::S32, Location::GPR(tmp_out), ret);
@@ -2697,6 +3387,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                 Self::emit_f64_int_conv_check(
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     real_in,
                     -2147483649.0,
                     2147483648.0,
@@ -2721,6 +3412,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                 Self::emit_f64_int_conv_check(
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     tmp_in,
                     -9223372036854777856.0,
                     9223372036854775808.0,
@@ -2745,6 +3437,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                 Self::emit_f64_int_conv_check(
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     tmp_in,
                     -1.0,
                     18446744073709551616.0,
@@ -2981,7 +3674,6 @@ impl FunctionCodeGenerator for X64FunctionCode {
                 }
             }
             Operator::CallIndirect { index, table_index } => {
-                assert_eq!(table_index, 0);
                 let sig = self.signatures.get(SigIndex::new(index as usize)).unwrap();
                 let param_types: SmallVec<[WpType; 8]> =
                     sig.params().iter().cloned().map(type_to_wp_type).collect();
@@ -3006,20 +3698,24 @@ impl FunctionCodeGenerator for X64FunctionCode {
                 let table_count = self.machine.acquire_temp_gpr().unwrap();
                 let sigidx = self.machine.acquire_temp_gpr().unwrap();
 
+                let (tables_offset, table_slot) =
+                    match TableIndex::new(table_index as usize).local_or_import(module_info) {
+                        LocalOrImport::Local(local_table_index) => {
+                            (vm::Ctx::offset_tables(), local_table_index.index())
+                        }
+                        LocalOrImport::Import(import_table_index) => (
+                            vm::Ctx::offset_imported_tables(),
+                            import_table_index.index(),
+                        ),
+                    };
                 a.emit_mov(
                     Size::S64,
-                    Location::Memory(
-                        Machine::get_vmctx_reg(),
-                        match TableIndex::new(0).local_or_import(module_info) {
-                            LocalOrImport::Local(_) => vm::Ctx::offset_tables(),
-                            LocalOrImport::Import(_) => vm::Ctx::offset_imported_tables(),
-                        } as i32,
-                    ),
+                    Location::Memory(Machine::get_vmctx_reg(), tables_offset as i32),
                     Location::GPR(table_base),
                 );
                 a.emit_mov(
                     Size::S64,
-                    Location::Memory(table_base, 0),
+                    Location::Memory(table_base, (table_slot * 8) as i32),
                     Location::GPR(table_base),
                 );
                 a.emit_mov(
@@ -3033,7 +3729,12 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     Location::GPR(table_base),
                 );
                 a.emit_cmp(Size::S32, func_index, Location::GPR(table_count));
-                a.emit_conditional_trap(Condition::BelowEqual);
+                Self::emit_conditional_trap(
+                    a,
+                    self.trap_codes.as_mut().unwrap(),
+                    Condition::BelowEqual,
+                    TrapCode::CallIndirectOOB,
+                );
                 a.emit_mov(Size::S64, func_index, Location::GPR(table_count));
                 a.emit_imul_imm32_gpr64(vm::Anyfunc::size() as u32, table_count);
                 a.emit_add(
@@ -3059,7 +3760,12 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     Location::GPR(sigidx),
                     Location::Memory(table_count, (vm::Anyfunc::offset_sig_id() as usize) as i32),
                 );
-                a.emit_conditional_trap(Condition::NotEqual);
+                Self::emit_conditional_trap(
+                    a,
+                    self.trap_codes.as_mut().unwrap(),
+                    Condition::NotEqual,
+                    TrapCode::IncorrectCallIndirectSignature,
+                );
 
                 self.machine.release_temp_gpr(sigidx);
                 self.machine.release_temp_gpr(table_count);
@@ -3223,28 +3929,35 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     value_stack_depth: self.value_stack.len(),
                 });
                 a.emit_label(label);
+                Self::emit_interrupt_check(
+                    a,
+                    &mut self.machine,
+                    self.relocations.as_mut().unwrap(),
+                );
             }
             Operator::Nop => {}
             Operator::MemorySize { reserved } => {
                 let memory_index = MemoryIndex::new(reserved as usize);
-                let target: usize = match memory_index.local_or_import(module_info) {
+                let vmcall = match memory_index.local_or_import(module_info) {
                     LocalOrImport::Local(local_mem_index) => {
                         let mem_desc = &module_info.memories[local_mem_index];
                         match mem_desc.memory_type() {
-                            MemoryType::Dynamic => vmcalls::local_dynamic_memory_size as usize,
-                            MemoryType::Static => vmcalls::local_static_memory_size as usize,
-                            MemoryType::SharedStatic => unimplemented!(),
+                            MemoryType::Dynamic => VmCall::LocalDynamicMemorySize,
+                            MemoryType::Static => VmCall::LocalStaticMemorySize,
+                            MemoryType::SharedStatic => VmCall::LocalSharedStaticMemorySize,
                         }
                     }
                     LocalOrImport::Import(import_mem_index) => {
                         let mem_desc = &module_info.imported_memories[import_mem_index].1;
                         match mem_desc.memory_type() {
-                            MemoryType::Dynamic => vmcalls::imported_dynamic_memory_size as usize,
-                            MemoryType::Static => vmcalls::imported_static_memory_size as usize,
-                            MemoryType::SharedStatic => unimplemented!(),
+                            MemoryType::Dynamic => VmCall::ImportedDynamicMemorySize,
+                            MemoryType::Static => VmCall::ImportedStaticMemorySize,
+                            MemoryType::SharedStatic => VmCall::ImportedSharedStaticMemorySize,
                         }
                     }
                 };
+                let target = vmcall.address();
+                let mut reloc_offset = 0;
                 Self::emit_call_sysv(
                     a,
                     &mut self.machine,
@@ -3254,34 +3967,41 @@ impl FunctionCodeGenerator for X64FunctionCode {
                             Location::Imm64(target as u64),
                             Location::GPR(GPR::RAX),
                         );
+                        reloc_offset = a.offset().0 - 8;
                         a.emit_call_location(Location::GPR(GPR::RAX));
                     },
                     ::std::iter::once(Location::Imm32(memory_index.index() as u32)),
                 );
+                self.relocations.as_mut().unwrap().push(Relocation {
+                    offset: reloc_offset,
+                    target: RelocationTarget::VmCall(vmcall),
+                });
                 let ret = self.machine.acquire_locations(a, &[WpType::I64], false)[0];
                 self.value_stack.push((ret, LocalOrTemp::Temp));
                 a.emit_mov(Size::S64, Location::GPR(GPR::RAX), ret);
             }
             Operator::MemoryGrow { reserved } => {
                 let memory_index = MemoryIndex::new(reserved as usize);
-                let target: usize = match memory_index.local_or_import(module_info) {
+                let vmcall = match memory_index.local_or_import(module_info) {
                     LocalOrImport::Local(local_mem_index) => {
                         let mem_desc = &module_info.memories[local_mem_index];
                         match mem_desc.memory_type() {
-                            MemoryType::Dynamic => vmcalls::local_dynamic_memory_grow as usize,
-                            MemoryType::Static => vmcalls::local_static_memory_grow as usize,
-                            MemoryType::SharedStatic => unimplemented!(),
+                            MemoryType::Dynamic => VmCall::LocalDynamicMemoryGrow,
+                            MemoryType::Static => VmCall::LocalStaticMemoryGrow,
+                            MemoryType::SharedStatic => VmCall::LocalSharedStaticMemoryGrow,
                         }
                     }
                     LocalOrImport::Import(import_mem_index) => {
                         let mem_desc = &module_info.imported_memories[import_mem_index].1;
                         match mem_desc.memory_type() {
-                            MemoryType::Dynamic => vmcalls::imported_dynamic_memory_grow as usize,
-                            MemoryType::Static => vmcalls::imported_static_memory_grow as usize,
-                            MemoryType::SharedStatic => unimplemented!(),
+                            MemoryType::Dynamic => VmCall::ImportedDynamicMemoryGrow,
+                            MemoryType::Static => VmCall::ImportedStaticMemoryGrow,
+                            MemoryType::SharedStatic => VmCall::ImportedSharedStaticMemoryGrow,
                         }
                     }
                 };
+                let target = vmcall.address();
+                let mut reloc_offset = 0;
 
                 let (param_pages, param_pages_lot) = self.value_stack.pop().unwrap();
 
@@ -3298,11 +4018,16 @@ impl FunctionCodeGenerator for X64FunctionCode {
                             Location::Imm64(target as u64),
                             Location::GPR(GPR::RAX),
                         );
+                        reloc_offset = a.offset().0 - 8;
                         a.emit_call_location(Location::GPR(GPR::RAX));
                     },
                     ::std::iter::once(Location::Imm32(memory_index.index() as u32))
                         .chain(::std::iter::once(param_pages)),
                 );
+                self.relocations.as_mut().unwrap().push(Relocation {
+                    offset: reloc_offset,
+                    target: RelocationTarget::VmCall(vmcall),
+                });
 
                 if param_pages_lot == LocalOrTemp::Temp {
                     self.machine.release_locations_only_stack(a, &[param_pages]);
@@ -3312,6 +4037,133 @@ impl FunctionCodeGenerator for X64FunctionCode {
                 self.value_stack.push((ret, LocalOrTemp::Temp));
                 a.emit_mov(Size::S64, Location::GPR(GPR::RAX), ret);
             }
+            // The bulk memory operators only apply to the first memory and table.
+            Operator::MemoryInit { segment } => Self::emit_bulk_memory_call(
+                a,
+                &mut self.machine,
+                self.relocations.as_mut().unwrap(),
+                &mut self.value_stack,
+                VmCall::MemoryInit,
+                &[0, segment],
+                3,
+            ),
+            Operator::DataDrop { segment } => Self::emit_bulk_memory_call(
+                a,
+                &mut self.machine,
+                self.relocations.as_mut().unwrap(),
+                &mut self.value_stack,
+                VmCall::DataDrop,
+                &[segment],
+                0,
+            ),
+            Operator::MemoryCopy => Self::emit_bulk_memory_call(
+                a,
+                &mut self.machine,
+                self.relocations.as_mut().unwrap(),
+                &mut self.value_stack,
+                VmCall::MemoryCopy,
+                &[0],
+                3,
+            ),
+            Operator::MemoryFill => Self::emit_bulk_memory_call(
+                a,
+                &mut self.machine,
+                self.relocations.as_mut().unwrap(),
+                &mut self.value_stack,
+                VmCall::MemoryFill,
+                &[0],
+                3,
+            ),
+            Operator::TableInit { segment } => Self::emit_bulk_memory_call(
+                a,
+                &mut self.machine,
+                self.relocations.as_mut().unwrap(),
+                &mut self.value_stack,
+                VmCall::TableInit,
+                &[0, segment],
+                3,
+            ),
+            Operator::ElemDrop { segment } => Self::emit_bulk_memory_call(
+                a,
+                &mut self.machine,
+                self.relocations.as_mut().unwrap(),
+                &mut self.value_stack,
+                VmCall::ElemDrop,
+                &[segment],
+                0,
+            ),
+            Operator::TableCopy => Self::emit_bulk_memory_call(
+                a,
+                &mut self.machine,
+                self.relocations.as_mut().unwrap(),
+                &mut self.value_stack,
+                VmCall::TableCopy,
+                &[0],
+                3,
+            ),
+            Operator::RefNull => {
+                // References are the raw words of `wasmer_runtime_core::table::AnyRef`, 0 is null.
+                self.value_stack
+                    .push((Location::Imm64(0), LocalOrTemp::Temp));
+            }
+            Operator::RefIsNull => Self::emit_cmpop_i64_dynamic_b(
+                a,
+                &mut self.machine,
+                &mut self.value_stack,
+                Condition::Equal,
+                Location::Imm64(0),
+            ),
+            Operator::TableGet { table } => {
+                Self::emit_bulk_memory_call(
+                    a,
+                    &mut self.machine,
+                    self.relocations.as_mut().unwrap(),
+                    &mut self.value_stack,
+                    VmCall::TableGet,
+                    &[table],
+                    1,
+                );
+                let ret = self.machine.acquire_locations(a, &[WpType::AnyRef], false)[0];
+                self.value_stack.push((ret, LocalOrTemp::Temp));
+                a.emit_mov(Size::S64, Location::GPR(GPR::RAX), ret);
+            }
+            Operator::TableSet { table } => Self::emit_bulk_memory_call(
+                a,
+                &mut self.machine,
+                self.relocations.as_mut().unwrap(),
+                &mut self.value_stack,
+                VmCall::TableSet,
+                &[table],
+                2,
+            ),
+            Operator::TableGrow { table } => {
+                Self::emit_bulk_memory_call(
+                    a,
+                    &mut self.machine,
+                    self.relocations.as_mut().unwrap(),
+                    &mut self.value_stack,
+                    VmCall::TableGrow,
+                    &[table],
+                    1,
+                );
+                let ret = self.machine.acquire_locations(a, &[WpType::I32], false)[0];
+                self.value_stack.push((ret, LocalOrTemp::Temp));
+                a.emit_mov(Size::S32, Location::GPR(GPR::RAX), ret);
+            }
+            Operator::TableSize { table } => {
+                Self::emit_bulk_memory_call(
+                    a,
+                    &mut self.machine,
+                    self.relocations.as_mut().unwrap(),
+                    &mut self.value_stack,
+                    VmCall::TableSize,
+                    &[table],
+                    0,
+                );
+                let ret = self.machine.acquire_locations(a, &[WpType::I32], false)[0];
+                self.value_stack.push((ret, LocalOrTemp::Temp));
+                a.emit_mov(Size::S32, Location::GPR(GPR::RAX), ret);
+            }
             Operator::I32Load { ref memarg } => {
                 let target =
                     get_location_released(a, &mut self.machine, self.value_stack.pop().unwrap());
@@ -3322,6 +4174,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     4,
@@ -3347,6 +4200,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     4,
@@ -3372,6 +4226,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     1,
@@ -3398,6 +4253,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     1,
@@ -3424,6 +4280,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     2,
@@ -3450,6 +4307,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     2,
@@ -3476,6 +4334,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target_addr,
                     memarg.offset as usize,
                     4,
@@ -3501,6 +4360,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target_addr,
                     memarg.offset as usize,
                     4,
@@ -3526,6 +4386,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target_addr,
                     memarg.offset as usize,
                     1,
@@ -3551,6 +4412,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target_addr,
                     memarg.offset as usize,
                     2,
@@ -3576,6 +4438,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     8,
@@ -3601,6 +4464,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     8,
@@ -3626,6 +4490,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     1,
@@ -3652,6 +4517,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     1,
@@ -3678,6 +4544,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     2,
@@ -3704,6 +4571,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     2,
@@ -3730,6 +4598,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     4,
@@ -3761,6 +4630,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target,
                     memarg.offset as usize,
                     4,
@@ -3787,6 +4657,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target_addr,
                     memarg.offset as usize,
                     8,
@@ -3812,6 +4683,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target_addr,
                     memarg.offset as usize,
                     8,
@@ -3837,6 +4709,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target_addr,
                     memarg.offset as usize,
                     1,
@@ -3862,6 +4735,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target_addr,
                     memarg.offset as usize,
                     2,
@@ -3887,6 +4761,7 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     module_info,
                     a,
                     &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
                     target_addr,
                     memarg.offset as usize,
                     4,
@@ -3902,8 +4777,143 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     },
                 );
             }
+            Operator::I32AtomicLoad { ref memarg }
+            | Operator::I64AtomicLoad { ref memarg }
+            | Operator::I32AtomicLoad8U { ref memarg }
+            | Operator::I32AtomicLoad16U { ref memarg }
+            | Operator::I64AtomicLoad8U { ref memarg }
+            | Operator::I64AtomicLoad16U { ref memarg }
+            | Operator::I64AtomicLoad32U { ref memarg } => {
+                Self::emit_atomic_load(
+                    module_info,
+                    a,
+                    &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
+                    &mut self.value_stack,
+                    op,
+                    memarg,
+                );
+            }
+            Operator::I32AtomicStore { ref memarg }
+            | Operator::I64AtomicStore { ref memarg }
+            | Operator::I32AtomicStore8 { ref memarg }
+            | Operator::I32AtomicStore16 { ref memarg }
+            | Operator::I64AtomicStore8 { ref memarg }
+            | Operator::I64AtomicStore16 { ref memarg }
+            | Operator::I64AtomicStore32 { ref memarg } => {
+                Self::emit_atomic_store(
+                    module_info,
+                    a,
+                    &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
+                    &mut self.value_stack,
+                    op,
+                    memarg,
+                );
+            }
+            Operator::I32AtomicRmwAdd { ref memarg }
+            | Operator::I64AtomicRmwAdd { ref memarg }
+            | Operator::I32AtomicRmw8UAdd { ref memarg }
+            | Operator::I32AtomicRmw16UAdd { ref memarg }
+            | Operator::I64AtomicRmw8UAdd { ref memarg }
+            | Operator::I64AtomicRmw16UAdd { ref memarg }
+            | Operator::I64AtomicRmw32UAdd { ref memarg }
+            | Operator::I32AtomicRmwSub { ref memarg }
+            | Operator::I64AtomicRmwSub { ref memarg }
+            | Operator::I32AtomicRmw8USub { ref memarg }
+            | Operator::I32AtomicRmw16USub { ref memarg }
+            | Operator::I64AtomicRmw8USub { ref memarg }
+            | Operator::I64AtomicRmw16USub { ref memarg }
+            | Operator::I64AtomicRmw32USub { ref memarg }
+            | Operator::I32AtomicRmwAnd { ref memarg }
+            | Operator::I64AtomicRmwAnd { ref memarg }
+            | Operator::I32AtomicRmw8UAnd { ref memarg }
+            | Operator::I32AtomicRmw16UAnd { ref memarg }
+            | Operator::I64AtomicRmw8UAnd { ref memarg }
+            | Operator::I64AtomicRmw16UAnd { ref memarg }
+            | Operator::I64AtomicRmw32UAnd { ref memarg }
+            | Operator::I32AtomicRmwOr { ref memarg }
+            | Operator::I64AtomicRmwOr { ref memarg }
+            | Operator::I32AtomicRmw8UOr { ref memarg }
+            | Operator::I32AtomicRmw16UOr { ref memarg }
+            | Operator::I64AtomicRmw8UOr { ref memarg }
+            | Operator::I64AtomicRmw16UOr { ref memarg }
+            | Operator::I64AtomicRmw32UOr { ref memarg }
+            | Operator::I32AtomicRmwXor { ref memarg }
+            | Operator::I64AtomicRmwXor { ref memarg }
+            | Operator::I32AtomicRmw8UXor { ref memarg }
+            | Operator::I32AtomicRmw16UXor { ref memarg }
+            | Operator::I64AtomicRmw8UXor { ref memarg }
+            | Operator::I64AtomicRmw16UXor { ref memarg }
+            | Operator::I64AtomicRmw32UXor { ref memarg }
+            | Operator::I32AtomicRmwXchg { ref memarg }
+            | Operator::I64AtomicRmwXchg { ref memarg }
+            | Operator::I32AtomicRmw8UXchg { ref memarg }
+            | Operator::I32AtomicRmw16UXchg { ref memarg }
+            | Operator::I64AtomicRmw8UXchg { ref memarg }
+            | Operator::I64AtomicRmw16UXchg { ref memarg }
+            | Operator::I64AtomicRmw32UXchg { ref memarg } => {
+                Self::emit_atomic_rmw(
+                    module_info,
+                    a,
+                    &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
+                    &mut self.value_stack,
+                    op,
+                    memarg,
+                );
+            }
+            Operator::I32AtomicRmwCmpxchg { ref memarg }
+            | Operator::I64AtomicRmwCmpxchg { ref memarg }
+            | Operator::I32AtomicRmw8UCmpxchg { ref memarg }
+            | Operator::I32AtomicRmw16UCmpxchg { ref memarg }
+            | Operator::I64AtomicRmw8UCmpxchg { ref memarg }
+            | Operator::I64AtomicRmw16UCmpxchg { ref memarg }
+            | Operator::I64AtomicRmw32UCmpxchg { ref memarg } => {
+                Self::emit_atomic_cmpxchg(
+                    module_info,
+                    a,
+                    &mut self.machine,
+                    self.trap_codes.as_mut().unwrap(),
+                    &mut self.value_stack,
+                    op,
+                    memarg,
+                );
+            }
+            Operator::I32Wait { ref memarg } | Operator::I64Wait { ref memarg } => {
+                let vmcall = match *op {
+                    Operator::I32Wait { .. } => VmCall::MemoryAtomicWait32,
+                    _ => VmCall::MemoryAtomicWait64,
+                };
+                Self::emit_bulk_memory_call(
+                    a,
+                    &mut self.machine,
+                    self.relocations.as_mut().unwrap(),
+                    &mut self.value_stack,
+                    vmcall,
+                    &[0, memarg.offset],
+                    3,
+                );
+                let ret = self.machine.acquire_locations(a, &[WpType::I32], false)[0];
+                self.value_stack.push((ret, LocalOrTemp::Temp));
+                a.emit_mov(Size::S32, Location::GPR(GPR::RAX), ret);
+            }
+            Operator::Wake { ref memarg } => {
+                Self::emit_bulk_memory_call(
+                    a,
+                    &mut self.machine,
+                    self.relocations.as_mut().unwrap(),
+                    &mut self.value_stack,
+                    VmCall::MemoryAtomicNotify,
+                    &[0, memarg.offset],
+                    2,
+                );
+                let ret = self.machine.acquire_locations(a, &[WpType::I32], false)[0];
+                self.value_stack.push((ret, LocalOrTemp::Temp));
+                a.emit_mov(Size::S32, Location::GPR(GPR::RAX), ret);
+            }
             Operator::Unreachable => {
-                a.emit_ud2();
+                Self::emit_trap(a, self.trap_codes.as_mut().unwrap(), TrapCode::Unreachable);
                 self.unreachable_depth = 1;
             }
             Operator::Return => {
@@ -3998,6 +5008,10 @@ impl FunctionCodeGenerator for X64FunctionCode {
                     Location::Imm64(table.as_ptr() as usize as u64),
                     Location::GPR(GPR::RCX),
                 );
+                self.relocations.as_mut().unwrap().push(Relocation {
+                    offset: a.offset().0 - 8,
+                    target: RelocationTarget::BrTable(self.br_table_data.as_ref().unwrap().len()),
+                });
                 a.emit_mov(Size::S32, cond, Location::GPR(GPR::RDX));
                 a.emit_shl(Size::S32, Location::Imm8(3), Location::GPR(GPR::RDX));
                 a.emit_add(Size::S64, Location::GPR(GPR::RCX), Location::GPR(GPR::RDX));
@@ -4107,6 +5121,147 @@ fn type_to_wp_type(ty: Type) -> WpType {
         Type::I64 => WpType::I64,
         Type::F32 => WpType::F32,
         Type::F64 => WpType::F64,
+        Type::AnyRef => WpType::AnyRef,
+        Type::FuncRef => WpType::AnyFunc,
+        Type::V128 => WpType::V128,
+    }
+}
+
+/// The size of the result of an atomic operator, and the size it accesses memory with.
+fn atomic_access_sizes(op: &Operator) -> (Size, Size) {
+    match *op {
+        Operator::I32AtomicLoad { .. }
+        | Operator::I32AtomicStore { .. }
+        | Operator::I32AtomicRmwAdd { .. }
+        | Operator::I32AtomicRmwSub { .. }
+        | Operator::I32AtomicRmwAnd { .. }
+        | Operator::I32AtomicRmwOr { .. }
+        | Operator::I32AtomicRmwXor { .. }
+        | Operator::I32AtomicRmwXchg { .. }
+        | Operator::I32AtomicRmwCmpxchg { .. } => (Size::S32, Size::S32),
+        Operator::I32AtomicLoad8U { .. }
+        | Operator::I32AtomicStore8 { .. }
+        | Operator::I32AtomicRmw8UAdd { .. }
+        | Operator::I32AtomicRmw8USub { .. }
+        | Operator::I32AtomicRmw8UAnd { .. }
+        | Operator::I32AtomicRmw8UOr { .. }
+        | Operator::I32AtomicRmw8UXor { .. }
+        | Operator::I32AtomicRmw8UXchg { .. }
+        | Operator::I32AtomicRmw8UCmpxchg { .. } => (Size::S32, Size::S8),
+        Operator::I32AtomicLoad16U { .. }
+        | Operator::I32AtomicStore16 { .. }
+        | Operator::I32AtomicRmw16UAdd { .. }
+        | Operator::I32AtomicRmw16USub { .. }
+        | Operator::I32AtomicRmw16UAnd { .. }
+        | Operator::I32AtomicRmw16UOr { .. }
+        | Operator::I32AtomicRmw16UXor { .. }
+        | Operator::I32AtomicRmw16UXchg { .. }
+        | Operator::I32AtomicRmw16UCmpxchg { .. } => (Size::S32, Size::S16),
+        Operator::I64AtomicLoad8U { .. }
+        | Operator::I64AtomicStore8 { .. }
+        | Operator::I64AtomicRmw8UAdd { .. }
+        | Operator::I64AtomicRmw8USub { .. }
+        | Operator::I64AtomicRmw8UAnd { .. }
+        | Operator::I64AtomicRmw8UOr { .. }
+        | Operator::I64AtomicRmw8UXor { .. }
+        | Operator::I64AtomicRmw8UXchg { .. }
+        | Operator::I64AtomicRmw8UCmpxchg { .. } => (Size::S64, Size::S8),
+        Operator::I64AtomicLoad16U { .. }
+        | Operator::I64AtomicStore16 { .. }
+        | Operator::I64AtomicRmw16UAdd { .. }
+        | Operator::I64AtomicRmw16USub { .. }
+        | Operator::I64AtomicRmw16UAnd { .. }
+        | Operator::I64AtomicRmw16UOr { .. }
+        | Operator::I64AtomicRmw16UXor { .. }
+        | Operator::I64AtomicRmw16UXchg { .. }
+        | Operator::I64AtomicRmw16UCmpxchg { .. } => (Size::S64, Size::S16),
+        Operator::I64AtomicLoad32U { .. }
+        | Operator::I64AtomicStore32 { .. }
+        | Operator::I64AtomicRmw32UAdd { .. }
+        | Operator::I64AtomicRmw32USub { .. }
+        | Operator::I64AtomicRmw32UAnd { .. }
+        | Operator::I64AtomicRmw32UOr { .. }
+        | Operator::I64AtomicRmw32UXor { .. }
+        | Operator::I64AtomicRmw32UXchg { .. }
+        | Operator::I64AtomicRmw32UCmpxchg { .. } => (Size::S64, Size::S32),
+        _ => (Size::S64, Size::S64),
+    }
+}
+
+fn atomic_rmw_op(op: &Operator) -> AtomicRmwOp {
+    match *op {
+        Operator::I32AtomicRmwAdd { .. }
+        | Operator::I64AtomicRmwAdd { .. }
+        | Operator::I32AtomicRmw8UAdd { .. }
+        | Operator::I32AtomicRmw16UAdd { .. }
+        | Operator::I64AtomicRmw8UAdd { .. }
+        | Operator::I64AtomicRmw16UAdd { .. }
+        | Operator::I64AtomicRmw32UAdd { .. } => AtomicRmwOp::Add,
+        Operator::I32AtomicRmwSub { .. }
+        | Operator::I64AtomicRmwSub { .. }
+        | Operator::I32AtomicRmw8USub { .. }
+        | Operator::I32AtomicRmw16USub { .. }
+        | Operator::I64AtomicRmw8USub { .. }
+        | Operator::I64AtomicRmw16USub { .. }
+        | Operator::I64AtomicRmw32USub { .. } => AtomicRmwOp::Sub,
+        Operator::I32AtomicRmwAnd { .. }
+        | Operator::I64AtomicRmwAnd { .. }
+        | Operator::I32AtomicRmw8UAnd { .. }
+        | Operator::I32AtomicRmw16UAnd { .. }
+        | Operator::I64AtomicRmw8UAnd { .. }
+        | Operator::I64AtomicRmw16UAnd { .. }
+        | Operator::I64AtomicRmw32UAnd { .. } => AtomicRmwOp::And,
+        Operator::I32AtomicRmwOr { .. }
+        | Operator::I64AtomicRmwOr { .. }
+        | Operator::I32AtomicRmw8UOr { .. }
+        | Operator::I32AtomicRmw16UOr { .. }
+        | Operator::I64AtomicRmw8UOr { .. }
+        | Operator::I64AtomicRmw16UOr { .. }
+        | Operator::I64AtomicRmw32UOr { .. } => AtomicRmwOp::Or,
+        Operator::I32AtomicRmwXor { .. }
+        | Operator::I64AtomicRmwXor { .. }
+        | Operator::I32AtomicRmw8UXor { .. }
+        | Operator::I32AtomicRmw16UXor { .. }
+        | Operator::I64AtomicRmw8UXor { .. }
+        | Operator::I64AtomicRmw16UXor { .. }
+        | Operator::I64AtomicRmw32UXor { .. } => AtomicRmwOp::Xor,
+        _ => AtomicRmwOp::Xchg,
+    }
+}
+
+fn size_to_wp_type(sz: Size) -> WpType {
+    match sz {
+        Size::S64 => WpType::I64,
+        _ => WpType::I32,
+    }
+}
+
+fn size_in_bytes(sz: Size) -> usize {
+    match sz {
+        Size::S8 => 1,
+        Size::S16 => 2,
+        Size::S32 => 4,
+        Size::S64 => 8,
+    }
+}
+
+/// Fuel is charged right before the operators that end a basic block,
+/// or that may hand control over to other code.
+fn is_block_boundary(op: &Operator) -> bool {
+    match *op {
+        Operator::Unreachable
+        | Operator::Block { .. }
+        | Operator::Loop { .. }
+        | Operator::If { .. }
+        | Operator::Else
+        | Operator::End
+        | Operator::Br { .. }
+        | Operator::BrIf { .. }
+        | Operator::BrTable { .. }
+        | Operator::Return
+        | Operator::Call { .. }
+        | Operator::CallIndirect { .. } => true,
+        _ => false,
     }
 }
 
@@ -120,6 +120,7 @@ pub trait Emitter {
     fn emit_lzcnt(&mut self, sz: Size, src: Location, dst: Location);
     fn emit_tzcnt(&mut self, sz: Size, src: Location, dst: Location);
     fn emit_popcnt(&mut self, sz: Size, src: Location, dst: Location);
+    fn emit_neg(&mut self, sz: Size, value: Location);
     fn emit_movzx(&mut self, sz_src: Size, src: Location, sz_dst: Size, dst: Location);
     fn emit_movsx(&mut self, sz_src: Size, src: Location, sz_dst: Size, dst: Location);
 
@@ -129,6 +130,10 @@ pub trait Emitter {
     fn emit_cmovae_gpr_32(&mut self, src: GPR, dst: GPR);
     fn emit_cmovae_gpr_64(&mut self, src: GPR, dst: GPR);
 
+    fn emit_xchg(&mut self, sz: Size, src: Location, dst: Location);
+    fn emit_lock_xadd(&mut self, sz: Size, src: Location, dst: Location);
+    fn emit_lock_cmpxchg(&mut self, sz: Size, src: Location, dst: Location);
+
     fn emit_vaddss(&mut self, src1: XMM, src2: XMMOrMemory, dst: XMM);
     fn emit_vaddsd(&mut self, src1: XMM, src2: XMMOrMemory, dst: XMM);
     fn emit_vsubss(&mut self, src1: XMM, src2: XMMOrMemory, dst: XMM);
@@ -735,6 +740,9 @@ impl Emitter for Assembler {
             binop_mem_gpr!(popcnt, self, sz, src, dst, { unreachable!() })
         });
     }
+    fn emit_neg(&mut self, sz: Size, value: Location) {
+        unop_gpr_or_mem!(neg, self, sz, value, { unreachable!() });
+    }
     fn emit_movzx(&mut self, sz_src: Size, src: Location, sz_dst: Size, dst: Location) {
         match (sz_src, src, sz_dst, dst) {
             (Size::S8, Location::GPR(src), Size::S32, Location::GPR(dst)) => {
@@ -816,6 +824,58 @@ impl Emitter for Assembler {
         dynasm!(self ; cmovae Rq(dst as u8), Rq(src as u8));
     }
 
+    fn emit_xchg(&mut self, sz: Size, src: Location, dst: Location) {
+        match (sz, src, dst) {
+            (Size::S8, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; xchg [Rq(dst as u8) + disp], Rb(src as u8));
+            }
+            (Size::S16, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; xchg [Rq(dst as u8) + disp], Rw(src as u8));
+            }
+            (Size::S32, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; xchg [Rq(dst as u8) + disp], Rd(src as u8));
+            }
+            (Size::S64, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; xchg [Rq(dst as u8) + disp], Rq(src as u8));
+            }
+            _ => unreachable!(),
+        }
+    }
+    fn emit_lock_xadd(&mut self, sz: Size, src: Location, dst: Location) {
+        match (sz, src, dst) {
+            (Size::S8, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; lock xadd [Rq(dst as u8) + disp], Rb(src as u8));
+            }
+            (Size::S16, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; lock xadd [Rq(dst as u8) + disp], Rw(src as u8));
+            }
+            (Size::S32, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; lock xadd [Rq(dst as u8) + disp], Rd(src as u8));
+            }
+            (Size::S64, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; lock xadd [Rq(dst as u8) + disp], Rq(src as u8));
+            }
+            _ => unreachable!(),
+        }
+    }
+    fn emit_lock_cmpxchg(&mut self, sz: Size, src: Location, dst: Location) {
+        match (sz, src, dst) {
+            (Size::S8, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; lock cmpxchg [Rq(dst as u8) + disp], Rb(src as u8));
+            }
+            (Size::S16, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; lock cmpxchg [Rq(dst as u8) + disp], Rw(src as u8));
+            }
+            (Size::S32, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; lock cmpxchg [Rq(dst as u8) + disp], Rd(src as u8));
+            }
+            (Size::S64, Location::GPR(src), Location::Memory(dst, disp)) => {
+                dynasm!(self ; lock cmpxchg [Rq(dst as u8) + disp], Rq(src as u8));
+            }
+            _ => unreachable!(),
+        }
+    }
+
     avx_fn!(vaddss, emit_vaddss);
     avx_fn!(vaddsd, emit_vaddsd);
 
@@ -18,6 +18,12 @@ extern crate byteorder;
 #[macro_use]
 extern crate smallvec;
 
+#[macro_use]
+extern crate serde_derive;
+
+extern crate serde;
+
+mod cache;
 mod codegen;
 mod codegen_x64;
 mod emitter_x64;
@@ -25,24 +31,16 @@ mod machine;
 mod parse;
 mod protect_unix;
 
+use crate::cache::BackendCache;
 use crate::codegen::{CodegenError, ModuleCodeGenerator};
 use crate::parse::LoadError;
 use wasmer_runtime_core::{
-    backend::{sys::Memory, Backend, CacheGen, Compiler, CompilerConfig, Token},
+    backend::{Backend, Compiler, CompilerConfig, Token},
     cache::{Artifact, Error as CacheError},
     error::{CompileError, CompileResult},
-    module::{ModuleInfo, ModuleInner},
+    module::ModuleInner,
 };
 
-struct Placeholder;
-impl CacheGen for Placeholder {
-    fn generate_cache(&self) -> Result<(Box<[u8]>, Memory), CacheError> {
-        Err(CacheError::Unknown(
-            "the singlepass backend doesn't support caching yet".to_string(),
-        ))
-    }
-}
-
 pub struct SinglePassCompiler {}
 impl SinglePassCompiler {
     pub fn new() -> Self {
@@ -58,19 +56,36 @@ impl Compiler for SinglePassCompiler {
         _: Token,
     ) -> CompileResult<ModuleInner> {
         let mut mcg = codegen_x64::X64ModuleCodeGenerator::new();
+        if let Some(ref costs) = compiler_config.metering {
+            mcg.feed_fuel_costs(costs.module_costs(wasm)?)?;
+        }
         let info = parse::read_module(wasm, Backend::Singlepass, &mut mcg, &compiler_config)?;
         let exec_context = mcg.finalize(&info)?;
         Ok(ModuleInner {
-            cache_gen: Box::new(Placeholder),
+            cache_gen: Box::new(exec_context.cache_generator()),
             runnable_module: Box::new(exec_context),
             info: info,
         })
     }
 
-    unsafe fn from_cache(&self, _artifact: Artifact, _: Token) -> Result<ModuleInner, CacheError> {
-        Err(CacheError::Unknown(
-            "the singlepass backend doesn't support caching yet".to_string(),
-        ))
+    unsafe fn from_cache(&self, artifact: Artifact, _: Token) -> Result<ModuleInner, CacheError> {
+        let (info, compiled_code, backend_cache) = BackendCache::from_cache(artifact)?;
+
+        let exec_context = codegen_x64::X64ExecutionContext::from_cache(
+            backend_cache,
+            compiled_code.as_slice(),
+            &info,
+        )?;
+
+        Ok(ModuleInner {
+            cache_gen: Box::new(exec_context.cache_generator()),
+            runnable_module: Box::new(exec_context),
+            info,
+        })
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Singlepass
     }
 }
 
@@ -138,7 +138,9 @@ impl Machine {
         for ty in tys {
             let loc = match *ty {
                 WpType::F32 | WpType::F64 => self.pick_xmm().map(Location::XMM),
-                WpType::I32 | WpType::I64 => self.pick_gpr().map(Location::GPR),
+                WpType::I32 | WpType::I64 | WpType::AnyRef | WpType::AnyFunc => {
+                    self.pick_gpr().map(Location::GPR)
+                }
                 _ => unreachable!(),
             };
 
@@ -3,14 +3,15 @@ use hashbrown::HashMap;
 use wasmer_runtime_core::{
     backend::{Backend, CompilerConfig, RunnableModule},
     module::{
-        DataInitializer, ExportIndex, ImportName, ModuleInfo, StringTable, StringTableBuilder,
-        TableInitializer,
+        DataInitializer, ExportIndex, ImportName, ModuleInfo, NameSection, StringTable,
+        StringTableBuilder, TableInitializer,
     },
     structures::{Map, TypedIndex},
+    table::{AnyRef, FuncRef},
     types::{
-        ElementType, FuncIndex, FuncSig, GlobalDescriptor, GlobalIndex, GlobalInit,
-        ImportedGlobalIndex, Initializer, MemoryDescriptor, MemoryIndex, SigIndex, TableDescriptor,
-        TableIndex, Type, Value,
+        DataIndex, ElemIndex, ElementType, FuncIndex, FuncSig, GlobalDescriptor, GlobalIndex,
+        GlobalInit, ImportedGlobalIndex, Initializer, MemoryDescriptor, MemoryIndex, SigIndex,
+        TableDescriptor, TableIndex, Type, Value,
     },
     units::Pages,
 };
@@ -63,6 +64,9 @@ pub fn read_module<
         data_initializers: Vec::new(),
         elem_initializers: Vec::new(),
 
+        passive_data: HashMap::new(),
+        passive_elements: HashMap::new(),
+
         start_func: None,
 
         func_assoc: Map::new(),
@@ -75,16 +79,18 @@ pub fn read_module<
         em_symbol_map: compiler_config.symbol_map.clone(),
 
         custom_sections: HashMap::new(),
+
+        name_section: NameSection::default(),
     };
 
     let mut parser = wasmparser::ValidatingParser::new(
         wasm,
         Some(wasmparser::ValidatingParserConfig {
             operator_config: wasmparser::OperatorValidatorConfig {
-                enable_threads: false,
-                enable_reference_types: false,
+                enable_threads: true,
+                enable_reference_types: true,
                 enable_simd: false,
-                enable_bulk_memory: false,
+                enable_bulk_memory: true,
             },
             mutable_global_imports: false,
         }),
@@ -93,6 +99,9 @@ pub fn read_module<
     let mut namespace_builder = Some(StringTableBuilder::new());
     let mut name_builder = Some(StringTableBuilder::new());
     let mut func_count: usize = ::std::usize::MAX;
+    // Passive segments are identified by their index among all the segments of their section.
+    let mut elem_count: usize = 0;
+    let mut data_count: usize = 0;
 
     loop {
         use wasmparser::ParserState;
@@ -119,9 +128,8 @@ pub fn read_module<
                         mcg.feed_import_function()?;
                     }
                     ImportSectionEntryType::Table(table_ty) => {
-                        assert_eq!(table_ty.element_type, WpType::AnyFunc);
                         let table_desc = TableDescriptor {
-                            element: ElementType::Anyfunc,
+                            element: wp_type_to_element_type(table_ty.element_type)?,
                             minimum: table_ty.limits.initial,
                             maximum: table_ty.limits.maximum,
                         };
@@ -151,7 +159,7 @@ pub fn read_module<
             }
             ParserState::TableSectionEntry(table_ty) => {
                 let table_desc = TableDescriptor {
-                    element: ElementType::Anyfunc,
+                    element: wp_type_to_element_type(table_ty.element_type)?,
                     minimum: table_ty.limits.initial,
                     maximum: table_ty.limits.maximum,
                 };
@@ -232,8 +240,14 @@ pub fn read_module<
                 }
                 fcg.finalize()?;
             }
-            ParserState::BeginActiveElementSectionEntry(table_index) => {
-                let table_index = TableIndex::new(table_index as usize);
+            ParserState::BeginActiveElementSectionEntry(_)
+            | ParserState::BeginPassiveElementSectionEntry(_) => {
+                let table_index = match *state {
+                    ParserState::BeginActiveElementSectionEntry(table_index) => {
+                        Some(TableIndex::new(table_index as usize))
+                    }
+                    _ => None,
+                };
                 let mut elements: Option<Vec<FuncIndex>> = None;
                 let mut base: Option<Initializer> = None;
 
@@ -260,16 +274,31 @@ pub fn read_module<
                     }
                 }
 
-                let table_init = TableInitializer {
-                    table_index,
-                    base: base.unwrap(),
-                    elements: elements.unwrap(),
-                };
+                match table_index {
+                    Some(table_index) => {
+                        let table_init = TableInitializer {
+                            table_index,
+                            base: base.unwrap(),
+                            elements: elements.unwrap(),
+                        };
 
-                info.elem_initializers.push(table_init);
+                        info.elem_initializers.push(table_init);
+                    }
+                    None => {
+                        info.passive_elements
+                            .insert(ElemIndex::new(elem_count), elements.unwrap());
+                    }
+                }
+                elem_count += 1;
             }
-            ParserState::BeginActiveDataSectionEntry(memory_index) => {
-                let memory_index = MemoryIndex::new(memory_index as usize);
+            ParserState::BeginActiveDataSectionEntry(_)
+            | ParserState::BeginPassiveDataSectionEntry => {
+                let memory_index = match *state {
+                    ParserState::BeginActiveDataSectionEntry(memory_index) => {
+                        Some(MemoryIndex::new(memory_index as usize))
+                    }
+                    _ => None,
+                };
                 let mut base: Option<Initializer> = None;
                 let mut data: Vec<u8> = vec![];
 
@@ -292,12 +321,20 @@ pub fn read_module<
                     }
                 }
 
-                let data_init = DataInitializer {
-                    memory_index,
-                    base: base.unwrap(),
-                    data,
-                };
-                info.data_initializers.push(data_init);
+                match memory_index {
+                    Some(memory_index) => {
+                        let data_init = DataInitializer {
+                            memory_index,
+                            base: base.unwrap(),
+                            data,
+                        };
+                        info.data_initializers.push(data_init);
+                    }
+                    None => {
+                        info.passive_data.insert(DataIndex::new(data_count), data);
+                    }
+                }
+                data_count += 1;
             }
             ParserState::BeginGlobalSectionEntry(ty) => {
                 let init = loop {
@@ -315,6 +352,13 @@ pub fn read_module<
                     mutable: ty.mutable,
                     ty: wp_type_to_type(ty.content_type)?,
                 };
+                let init = match init {
+                    // `ref.null` takes the type of the global it initializes.
+                    Initializer::Const(Value::AnyRef(_)) if desc.ty == Type::FuncRef => {
+                        Initializer::Const(Value::FuncRef(FuncRef::null()))
+                    }
+                    init => init,
+                };
 
                 let global_init = GlobalInit { desc, init };
 
@@ -332,6 +376,8 @@ pub fn wp_type_to_type(ty: WpType) -> Result<Type, BinaryReaderError> {
         WpType::I64 => Type::I64,
         WpType::F32 => Type::F32,
         WpType::F64 => Type::F64,
+        WpType::AnyRef => Type::AnyRef,
+        WpType::AnyFunc => Type::FuncRef,
         WpType::V128 => {
             return Err(BinaryReaderError {
                 message: "the wasmer llvm backend does not yet support the simd extension",
@@ -348,6 +394,20 @@ pub fn type_to_wp_type(ty: Type) -> WpType {
         Type::I64 => WpType::I64,
         Type::F32 => WpType::F32,
         Type::F64 => WpType::F64,
+        Type::AnyRef => WpType::AnyRef,
+        Type::FuncRef => WpType::AnyFunc,
+        Type::V128 => WpType::V128,
+    }
+}
+
+fn wp_type_to_element_type(ty: WpType) -> Result<ElementType, BinaryReaderError> {
+    match ty {
+        WpType::AnyFunc => Ok(ElementType::Anyfunc),
+        WpType::AnyRef => Ok(ElementType::AnyRef),
+        _ => Err(BinaryReaderError {
+            message: "that type is not supported as a table element type",
+            offset: -1isize as usize,
+        }),
     }
 }
 
@@ -383,6 +443,7 @@ fn eval_init_expr(op: &Operator) -> Result<Initializer, BinaryReaderError> {
         Operator::F64Const { value } => {
             Initializer::Const(Value::F64(f64::from_bits(value.bits())))
         }
+        Operator::RefNull => Initializer::Const(Value::AnyRef(AnyRef::null())),
         _ => {
             return Err(BinaryReaderError {
                 message: "init expr evaluation failed: unsupported opcode",
@@ -31,7 +31,8 @@ mod tests {
 
         match result {
             Err(err) => match err {
-                CallError::Runtime(RuntimeError::Trap { msg }) => {
+                CallError::Runtime(RuntimeError::Trap { trap }) => {
+                    let msg = trap.to_string();
                     assert!(!msg.contains("segmentation violation"));
                     assert!(!msg.contains("bus error"));
                 }
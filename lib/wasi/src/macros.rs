@@ -3,11 +3,11 @@ macro_rules! wasi_try {
         let res: Result<_, crate::syscalls::types::__wasi_errno_t> = $expr;
         match res {
             Ok(val) => {
-                debug!("wasi::wasi_try::val: {:?}", val);
+                debug!(target: "wasi::wasi_try", "val: {:?}", val);
                 val
             }
             Err(err) => {
-                debug!("wasi::wasi_try::err: {:?}", err);
+                debug!(target: "wasi::wasi_try", "err: {:?}", err);
                 return err;
             }
         }
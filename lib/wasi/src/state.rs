@@ -2,19 +2,22 @@
 //     vfs::Vfs,
 //     file_like::{FileLike, Metadata};
 // };
-use crate::syscalls::types::*;
+use crate::syscalls::{platform_file_id, platform_open_file, types::*};
 use generational_arena::{Arena, Index as Inode};
 use hashbrown::hash_map::{Entry, HashMap};
 use std::{
     cell::Cell,
+    collections::VecDeque,
     fs,
     io::{self, Read, Seek, Write},
+    path::{Component, Path, PathBuf},
     time::SystemTime,
 };
-use wasmer_runtime_core::debug;
 use zbox::init_env as zbox_init_env;
 
 pub const MAX_SYMLINKS: usize = 100;
+/// The largest size, in bytes, that an in-memory file can be grown to.
+pub const MAX_BUFFER_SIZE: u64 = 64 * 1024 * 1024;
 
 #[derive(Debug)]
 pub enum WasiFile {
@@ -53,6 +56,26 @@ impl Write for WasiFile {
     }
 }
 
+impl WasiFile {
+    pub fn set_len(&mut self, len: u64) -> io::Result<()> {
+        match self {
+            WasiFile::ZboxFile(zbf) => zbf
+                .set_len(len as usize)
+                .map_err(|_| io::Error::from(io::ErrorKind::Other)),
+            WasiFile::HostFile(hf) => hf.set_len(len),
+        }
+    }
+
+    pub fn sync_all(&mut self) -> io::Result<()> {
+        match self {
+            WasiFile::ZboxFile(zbf) => zbf
+                .finish()
+                .map_err(|_| io::Error::from(io::ErrorKind::Other)),
+            WasiFile::HostFile(hf) => hf.sync_all(),
+        }
+    }
+}
+
 impl Read for WasiFile {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         match self {
@@ -110,7 +133,9 @@ impl InodeVal {
     ) -> Self {
         InodeVal {
             stat: __wasi_filestat_t {
-                st_filetype: if metadata.is_dir() {
+                st_filetype: if metadata.file_type().is_symlink() {
+                    __WASI_FILETYPE_SYMBOLIC_LINK
+                } else if metadata.is_dir() {
                     __WASI_FILETYPE_DIRECTORY
                 } else {
                     __WASI_FILETYPE_REGULAR_FILE
@@ -147,15 +172,23 @@ impl InodeVal {
 #[derive(Debug)]
 pub enum Kind {
     File {
-        handle: WasiFile,
+        /// The host file is opened the first time the file is opened with `path_open`.
+        handle: Option<WasiFile>,
+        path: PathBuf,
+        /// The device and inode numbers of the host file when it was looked up,
+        /// so that a file swapped in its place since then isn't opened.
+        host_id: Option<(u64, u64)>,
     },
     Dir {
-        handle: WasiFile,
+        /// `None` for pre-opened directories, which `..` can't leave.
+        parent: Option<Inode>,
+        path: PathBuf,
         /// The entries of a directory are lazily filled.
         entries: HashMap<String, Inode>,
     },
     Symlink {
-        forwarded: Inode,
+        /// The contents of the link, relative to the directory containing it.
+        target: PathBuf,
     },
     Buffer {
         buffer: Vec<u8>,
@@ -204,14 +237,11 @@ impl WasiFs {
             debug!("Attempting to preopen {}", &file);
             // TODO: think about this
             let default_rights = 0x1FFFFFFF; // all rights
-            let cur_file: fs::File = fs::OpenOptions::new()
-                .read(true)
-                .open(file)
-                .expect("Could not find file");
-            let cur_file_metadata = cur_file.metadata().unwrap();
+            let cur_file_metadata = fs::metadata(file).expect("Could not find file");
             let kind = if cur_file_metadata.is_dir() {
                 Kind::Dir {
-                    handle: WasiFile::HostFile(cur_file),
+                    parent: None,
+                    path: PathBuf::from(file),
                     entries: Default::default(),
                 }
             } else {
@@ -224,8 +254,7 @@ impl WasiFs {
             let inode_val =
                 InodeVal::from_file_metadata(&cur_file_metadata, file.clone(), true, kind);
 
-            let inode = wasi_fs.inodes.insert(inode_val);
-            wasi_fs.inodes[inode].stat.st_ino = wasi_fs.inode_counter.get();
+            let inode = wasi_fs.insert_inode(inode_val);
             wasi_fs
                 .create_fd(default_rights, default_rights, 0, inode)
                 .expect("Could not open fd");
@@ -294,49 +323,213 @@ impl WasiFs {
         })
     }
 
-    #[allow(dead_code)]
-    fn filestat_inode(
-        &self,
-        inode: Inode,
-        flags: __wasi_lookupflags_t,
-    ) -> Result<__wasi_filestat_t, __wasi_errno_t> {
-        let inode_val = &self.inodes[inode];
-        if let (true, Kind::Symlink { mut forwarded }) =
-            (flags & __WASI_LOOKUP_SYMLINK_FOLLOW != 0, &inode_val.kind)
-        {
-            // Time to follow the symlink.
-            let mut counter = 0;
-
-            while counter <= MAX_SYMLINKS {
-                let inode_val = &self.inodes[forwarded];
-                if let &Kind::Symlink {
-                    forwarded: new_forwarded,
-                } = &inode_val.kind
-                {
-                    counter += 1;
-                    forwarded = new_forwarded;
-                } else {
-                    return Ok(inode_val.stat);
-                }
-            }
+    /// Adds an inode to the filesystem, giving it the next inode number.
+    fn insert_inode(&mut self, mut inode_val: InodeVal) -> Inode {
+        let ino = self.inode_counter.get();
+        self.inode_counter.set(ino + 1);
+        inode_val.stat.st_ino = ino;
+        self.inodes.insert(inode_val)
+    }
 
-            Err(__WASI_EMLINK)
+    /// Records the host file at `path` as the entry `name` of the directory `parent`.
+    pub fn insert_entry(
+        &mut self,
+        parent: Inode,
+        name: &str,
+        path: PathBuf,
+    ) -> Result<Inode, __wasi_errno_t> {
+        let metadata = fs::symlink_metadata(&path).map_err(host_error_to_errno)?;
+        let kind = if metadata.file_type().is_symlink() {
+            Kind::Symlink {
+                target: fs::read_link(&path).map_err(host_error_to_errno)?,
+            }
+        } else if metadata.is_dir() {
+            Kind::Dir {
+                parent: Some(parent),
+                path,
+                entries: HashMap::new(),
+            }
         } else {
-            Ok(inode_val.stat)
+            Kind::File {
+                handle: None,
+                path,
+                host_id: platform_file_id(&metadata),
+            }
+        };
+        let inode_val = InodeVal::from_file_metadata(&metadata, name.to_string(), false, kind);
+        let inode = self.insert_inode(inode_val);
+        self.add_entry(parent, name, inode);
+        Ok(inode)
+    }
+
+    /// Records `inode` as the entry `name` of the directory `parent`.
+    pub fn add_entry(&mut self, parent: Inode, name: &str, inode: Inode) {
+        if let Kind::Dir { entries, .. } = &mut self.inodes[parent].kind {
+            entries.insert(name.to_string(), inode);
         }
     }
 
-    #[allow(dead_code)]
-    pub fn filestat_path(
+    /// Forgets the entry `name` of the directory `parent`, after it was removed on the host.
+    ///
+    /// The inode itself is kept, as file descriptors may still refer to it.
+    pub fn remove_entry(&mut self, parent: Inode, name: &str) -> Option<Inode> {
+        match &mut self.inodes[parent].kind {
+            Kind::Dir { entries, .. } => entries.remove(name),
+            _ => None,
+        }
+    }
+
+    /// The host path of the entry `name` of the directory `dir`.
+    pub fn host_path(&self, dir: Inode, name: &str) -> Result<PathBuf, __wasi_errno_t> {
+        match &self.inodes[dir].kind {
+            Kind::Dir { path, .. } => Ok(path.join(name)),
+            _ => Err(__WASI_ENOTDIR),
+        }
+    }
+
+    /// Finds the entry `name` of the directory `dir`, loading it from the host if needed.
+    pub fn get_child(&mut self, dir: Inode, name: &str) -> Result<Inode, __wasi_errno_t> {
+        if let Kind::Dir { entries, .. } = &self.inodes[dir].kind {
+            if let Some(child) = entries.get(name) {
+                return Ok(*child);
+            }
+        }
+        let path = self.host_path(dir, name)?;
+        self.insert_entry(dir, name, path)
+    }
+
+    fn resolve(
         &mut self,
-        preopened_fd: __wasi_fd_t,
-        flags: __wasi_lookupflags_t,
+        mut inode: Inode,
+        mut segments: VecDeque<String>,
+        follow_symlinks: bool,
+    ) -> Result<Inode, __wasi_errno_t> {
+        let mut symlinks = 0;
+        while let Some(segment) = segments.pop_front() {
+            if segment == ".." {
+                inode = match &self.inodes[inode].kind {
+                    Kind::Dir {
+                        parent: Some(parent),
+                        ..
+                    } => *parent,
+                    Kind::Dir { parent: None, .. } => return Err(__WASI_ENOTCAPABLE),
+                    _ => return Err(__WASI_ENOTDIR),
+                };
+                continue;
+            }
+            let child = self.get_child(inode, &segment)?;
+            if let Kind::Symlink { target } = &self.inodes[child].kind {
+                if follow_symlinks || !segments.is_empty() {
+                    symlinks += 1;
+                    if symlinks > MAX_SYMLINKS {
+                        return Err(__WASI_ELOOP);
+                    }
+                    // the link is resolved from the directory containing it
+                    for segment in path_segments(target)?.into_iter().rev() {
+                        segments.push_front(segment);
+                    }
+                    continue;
+                }
+            }
+            inode = child;
+        }
+        Ok(inode)
+    }
+
+    /// Finds the inode at `path`, relative to the directory opened as `base`.
+    ///
+    /// Neither `..` nor symlinks can lead out of the pre-opened directory that
+    /// `base` belongs to. A symlink at the end of `path` is only followed when
+    /// `follow_symlinks` is set.
+    pub fn get_inode_at_path(
+        &mut self,
+        base: __wasi_fd_t,
+        path: &str,
+        follow_symlinks: bool,
+    ) -> Result<Inode, __wasi_errno_t> {
+        let base_inode = self.fd_map.get(&base).ok_or(__WASI_EBADF)?.inode;
+        let segments = path_segments(Path::new(path))?;
+        self.resolve(base_inode, segments, follow_symlinks)
+    }
+
+    /// Finds the directory containing `path`, and returns it with the last name of `path`.
+    pub fn get_parent_inode_at_path(
+        &mut self,
+        base: __wasi_fd_t,
         path: &str,
-    ) -> Result<__wasi_filestat_t, __wasi_errno_t> {
-        warn!("Should use preopned_fd: {}", preopened_fd);
-        let inode = self.get_inode(path).ok_or(__WASI_EINVAL)?;
+    ) -> Result<(Inode, String), __wasi_errno_t> {
+        let base_inode = self.fd_map.get(&base).ok_or(__WASI_EBADF)?.inode;
+        let mut segments = path_segments(Path::new(path))?;
+        let name = match segments.pop_back() {
+            Some(name) => name,
+            None => return Err(__WASI_ENOENT),
+        };
+        if name == ".." {
+            return Err(__WASI_EINVAL);
+        }
+        let parent = self.resolve(base_inode, segments, true)?;
+        match self.inodes[parent].kind {
+            Kind::Dir { .. } => Ok((parent, name)),
+            _ => Err(__WASI_ENOTDIR),
+        }
+    }
 
-        self.filestat_inode(inode, flags)
+    /// Opens the host file of `inode`, unless it is already open.
+    ///
+    /// The file must still be the one that was looked up: a symlink that has
+    /// replaced it isn't followed, and any other file is `ENOTCAPABLE`.
+    pub fn open_file(&mut self, inode: Inode) -> Result<(), __wasi_errno_t> {
+        if let Kind::File {
+            handle,
+            path,
+            host_id,
+        } = &mut self.inodes[inode].kind
+        {
+            if handle.is_none() {
+                // fds opened read-only share the handle, so open it for writing when we can
+                let file = platform_open_file(&path, true)
+                    .or_else(|_| platform_open_file(&path, false))
+                    .map_err(host_error_to_errno)?;
+                let metadata = file.metadata().map_err(host_error_to_errno)?;
+                if platform_file_id(&metadata) != *host_id {
+                    return Err(__WASI_ENOTCAPABLE);
+                }
+                *handle = Some(WasiFile::HostFile(file));
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the entries of the directory `dir` by name, after `.` and `..`.
+    pub fn read_dir(
+        &mut self,
+        dir: Inode,
+    ) -> Result<Vec<(String, __wasi_filestat_t)>, __wasi_errno_t> {
+        let (path, parent) = match &self.inodes[dir].kind {
+            Kind::Dir { path, parent, .. } => (path.clone(), parent.unwrap_or(dir)),
+            _ => return Err(__WASI_ENOTDIR),
+        };
+        let mut names = fs::read_dir(&path)
+            .map_err(host_error_to_errno)?
+            .map(|entry| {
+                entry
+                    .map_err(host_error_to_errno)?
+                    .file_name()
+                    .into_string()
+                    .map_err(|_| __WASI_EILSEQ)
+            })
+            .collect::<Result<Vec<String>, __wasi_errno_t>>()?;
+        names.sort();
+
+        let mut entries = vec![
+            (".".to_string(), self.inodes[dir].stat),
+            ("..".to_string(), self.inodes[parent].stat),
+        ];
+        for name in names {
+            let child = self.get_child(dir, &name)?;
+            entries.push((name, self.inodes[child].stat));
+        }
+        Ok(entries)
     }
 
     pub fn filestat_fd(&self, fd: __wasi_fd_t) -> Result<__wasi_filestat_t, __wasi_errno_t> {
@@ -397,10 +590,14 @@ impl WasiFs {
                 let inode = &mut self.inodes[fd.inode];
 
                 match &mut inode.kind {
-                    Kind::File { handle } => handle.flush().map_err(|_| __WASI_EIO)?,
+                    Kind::File {
+                        handle: Some(handle),
+                        ..
+                    } => handle.flush().map_err(|_| __WASI_EIO)?,
+                    Kind::File { handle: None, .. } => (),
                     // TODO: verify this behavior
                     Kind::Dir { .. } => return Err(__WASI_EISDIR),
-                    Kind::Symlink { .. } => unimplemented!(),
+                    Kind::Symlink { .. } => return Err(__WASI_EINVAL),
                     Kind::Buffer { .. } => (),
                 }
             }
@@ -431,6 +628,57 @@ impl WasiFs {
     }
 }
 
+/// Converts an error from the host filesystem to the closest WASI errno.
+pub fn host_error_to_errno(err: io::Error) -> __wasi_errno_t {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        if let Some(code) = err.raw_os_error() {
+            match code {
+                libc::EPERM => return __WASI_EPERM,
+                libc::ENOENT => return __WASI_ENOENT,
+                libc::EACCES => return __WASI_EACCES,
+                libc::EBUSY => return __WASI_EBUSY,
+                libc::EEXIST => return __WASI_EEXIST,
+                libc::EXDEV => return __WASI_EXDEV,
+                libc::ENOTDIR => return __WASI_ENOTDIR,
+                libc::EISDIR => return __WASI_EISDIR,
+                libc::EINVAL => return __WASI_EINVAL,
+                libc::EFBIG => return __WASI_EFBIG,
+                libc::ENOSPC => return __WASI_ENOSPC,
+                libc::EROFS => return __WASI_EROFS,
+                libc::EMLINK => return __WASI_EMLINK,
+                libc::ENAMETOOLONG => return __WASI_ENAMETOOLONG,
+                libc::ENOTEMPTY => return __WASI_ENOTEMPTY,
+                libc::ELOOP => return __WASI_ELOOP,
+                _ => (),
+            }
+        }
+    }
+    match err.kind() {
+        io::ErrorKind::NotFound => __WASI_ENOENT,
+        io::ErrorKind::PermissionDenied => __WASI_EACCES,
+        io::ErrorKind::AlreadyExists => __WASI_EEXIST,
+        io::ErrorKind::InvalidInput => __WASI_EINVAL,
+        _ => __WASI_EIO,
+    }
+}
+
+/// Splits a guest path into the names it goes through, rejecting absolute paths.
+fn path_segments(path: &Path) -> Result<VecDeque<String>, __wasi_errno_t> {
+    let mut segments = VecDeque::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => {
+                segments.push_back(name.to_str().ok_or(__WASI_EILSEQ)?.to_string())
+            }
+            Component::ParentDir => segments.push_back("..".to_string()),
+            Component::CurDir => (),
+            Component::RootDir | Component::Prefix(_) => return Err(__WASI_ENOTCAPABLE),
+        }
+    }
+    Ok(segments)
+}
+
 #[derive(Debug)]
 pub struct WasiState<'a> {
     pub fs: WasiFs,
@@ -8,14 +8,17 @@ pub mod windows;
 use self::types::*;
 use crate::{
     ptr::{Array, WasmPtr},
-    state::{Fd, InodeVal, Kind, WasiFile, WasiState, MAX_SYMLINKS},
+    state::{host_error_to_errno, Fd, Kind, WasiFile, WasiFs, WasiState, MAX_BUFFER_SIZE},
     ExitCode,
 };
 use rand::{thread_rng, Rng};
 use std::cell::Cell;
 use std::convert::Infallible;
+use std::fs;
 use std::io::{self, Read, Seek, Write};
-use wasmer_runtime_core::{debug, memory::Memory, vm::Ctx};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use wasmer_runtime_core::{memory::Memory, vm::Ctx};
 
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 pub use unix::*;
@@ -51,6 +54,78 @@ fn write_bytes<T: Write>(
     Ok(bytes_written)
 }
 
+fn read_bytes<T: Read>(
+    mut reader: T,
+    memory: &Memory,
+    iovs_arr_cell: &[Cell<__wasi_iovec_t>],
+) -> Result<u32, __wasi_errno_t> {
+    let mut bytes_read = 0;
+
+    for iov in iovs_arr_cell {
+        let iov_inner = iov.get();
+        let bytes = iov_inner.buf.deref(memory, 0, iov_inner.buf_len)?;
+        let mut raw_bytes: &mut [u8] =
+            unsafe { &mut *(bytes as *const [_] as *mut [_] as *mut [u8]) };
+        bytes_read += reader.read(raw_bytes).map_err(|_| __WASI_EIO)? as u32;
+    }
+    Ok(bytes_read)
+}
+
+/// Reads a string, such as a path, that the guest passed as a pointer and a length
+fn read_string(
+    memory: &Memory,
+    ptr: WasmPtr<u8, Array>,
+    len: u32,
+) -> Result<String, __wasi_errno_t> {
+    let cells = ptr.deref(memory, 0, len)?;
+    String::from_utf8(cells.iter().map(Cell::get).collect()).map_err(|_| __WASI_EILSEQ)
+}
+
+/// Updates the timestamps of `stat` selected by `fst_flags`
+fn set_times(
+    stat: &mut __wasi_filestat_t,
+    st_atim: __wasi_timestamp_t,
+    st_mtim: __wasi_timestamp_t,
+    fst_flags: __wasi_fstflags_t,
+) -> Result<(), __wasi_errno_t> {
+    if (fst_flags & __WASI_FILESTAT_SET_ATIM != 0 && fst_flags & __WASI_FILESTAT_SET_ATIM_NOW != 0)
+        || (fst_flags & __WASI_FILESTAT_SET_MTIM != 0
+            && fst_flags & __WASI_FILESTAT_SET_MTIM_NOW != 0)
+    {
+        return Err(__WASI_EINVAL);
+    }
+
+    let now = || {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or(0)
+    };
+
+    if fst_flags & __WASI_FILESTAT_SET_ATIM != 0 {
+        stat.st_atim = st_atim;
+    } else if fst_flags & __WASI_FILESTAT_SET_ATIM_NOW != 0 {
+        stat.st_atim = now();
+    }
+
+    if fst_flags & __WASI_FILESTAT_SET_MTIM != 0 {
+        stat.st_mtim = st_mtim;
+    } else if fst_flags & __WASI_FILESTAT_SET_MTIM_NOW != 0 {
+        stat.st_mtim = now();
+    }
+
+    Ok(())
+}
+
+/// Resizes an in-memory file, refusing sizes that the host shouldn't allocate
+fn resize_buffer(buffer: &mut Vec<u8>, size: __wasi_filesize_t) -> Result<(), __wasi_errno_t> {
+    if size > MAX_BUFFER_SIZE {
+        return Err(__WASI_EFBIG);
+    }
+    buffer.resize(size as usize, 0);
+    Ok(())
+}
+
 /// checks that `rights_check_set` is a subset of `rights_set`
 fn has_rights(rights_set: __wasi_rights_t, rights_check_set: __wasi_rights_t) -> bool {
     rights_set | rights_check_set == rights_set
@@ -95,13 +170,14 @@ pub fn args_get(
     argv: WasmPtr<WasmPtr<u8, Array>, Array>,
     argv_buf: WasmPtr<u8, Array>,
 ) -> __wasi_errno_t {
-    debug!("wasi::args_get");
+    debug!(target: "wasi::args_get", "called");
     let state = get_wasi_state(ctx);
     let memory = ctx.memory(0);
 
     let result = write_buffer_array(memory, &*state.args, argv, argv_buf);
 
     debug!(
+        target: "wasi::args_get",
         "=> args:\n{}",
         state
             .args
@@ -131,7 +207,7 @@ pub fn args_sizes_get(
     argc: WasmPtr<u32>,
     argv_buf_size: WasmPtr<u32>,
 ) -> __wasi_errno_t {
-    debug!("wasi::args_sizes_get");
+    debug!(target: "wasi::args_sizes_get", "called");
     let memory = ctx.memory(0);
 
     let argc = wasi_try!(argc.deref(memory));
@@ -144,7 +220,7 @@ pub fn args_sizes_get(
     argc.set(argc_val);
     argv_buf_size.set(argv_buf_size_val);
 
-    debug!("=> argc={}, argv_buf_size={}", argc_val, argv_buf_size_val);
+    debug!(target: "wasi::args_sizes_get", "=> argc={}, argv_buf_size={}", argc_val, argv_buf_size_val);
 
     __WASI_ESUCCESS
 }
@@ -162,7 +238,7 @@ pub fn clock_res_get(
     clock_id: __wasi_clockid_t,
     resolution: WasmPtr<__wasi_timestamp_t>,
 ) -> __wasi_errno_t {
-    debug!("wasi::clock_res_get");
+    debug!(target: "wasi::clock_res_get", "called");
     let memory = ctx.memory(0);
 
     let out_addr = wasi_try!(resolution.deref(memory));
@@ -185,7 +261,7 @@ pub fn clock_time_get(
     precision: __wasi_timestamp_t,
     time: WasmPtr<__wasi_timestamp_t>,
 ) -> __wasi_errno_t {
-    debug!("wasi::clock_time_get");
+    debug!(target: "wasi::clock_time_get", "called");
     let memory = ctx.memory(0);
 
     let out_addr = wasi_try!(time.deref(memory));
@@ -205,7 +281,7 @@ pub fn environ_get(
     environ: WasmPtr<WasmPtr<u8, Array>, Array>,
     environ_buf: WasmPtr<u8, Array>,
 ) -> __wasi_errno_t {
-    debug!("wasi::environ_get");
+    debug!(target: "wasi::environ_get", "called");
     let state = get_wasi_state(ctx);
     let memory = ctx.memory(0);
 
@@ -224,7 +300,7 @@ pub fn environ_sizes_get(
     environ_count: WasmPtr<u32>,
     environ_buf_size: WasmPtr<u32>,
 ) -> __wasi_errno_t {
-    debug!("wasi::environ_sizes_get");
+    debug!(target: "wasi::environ_sizes_get", "called");
     let memory = ctx.memory(0);
 
     let environ_count = wasi_try!(environ_count.deref(memory));
@@ -256,7 +332,7 @@ pub fn fd_advise(
     len: __wasi_filesize_t,
     advice: __wasi_advice_t,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_advise: fd={}", fd);
+    debug!(target: "wasi::fd_advise", "fd={}", fd);
 
     // this is used for our own benefit, so just returning success is a valid
     // implementation for now
@@ -278,8 +354,43 @@ pub fn fd_allocate(
     offset: __wasi_filesize_t,
     len: __wasi_filesize_t,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_allocate");
-    unimplemented!()
+    debug!(target: "wasi::fd_allocate", "called");
+    let state = get_wasi_state(ctx);
+    let fd_entry = wasi_try!(state.fs.fd_map.get(&fd).ok_or(__WASI_EBADF));
+
+    if !has_rights(fd_entry.rights, __WASI_RIGHT_FD_ALLOCATE) {
+        return __WASI_EACCES;
+    }
+
+    let new_size = wasi_try!(offset.checked_add(len).ok_or(__WASI_EFBIG));
+    let inode = &mut state.fs.inodes[fd_entry.inode];
+
+    // allocating never shrinks the file
+    let size = match &mut inode.kind {
+        Kind::File {
+            handle: Some(handle),
+            ..
+        } => {
+            let size = wasi_try!(handle
+                .seek(io::SeekFrom::End(0))
+                .map_err(host_error_to_errno));
+            if new_size > size {
+                wasi_try!(handle.set_len(new_size).map_err(host_error_to_errno));
+            }
+            size.max(new_size)
+        }
+        Kind::Buffer { buffer } => {
+            if new_size > buffer.len() as u64 {
+                wasi_try!(resize_buffer(buffer, new_size));
+            }
+            buffer.len() as u64
+        }
+        Kind::Dir { .. } => return __WASI_EISDIR,
+        Kind::File { handle: None, .. } | Kind::Symlink { .. } => return __WASI_EBADF,
+    };
+    inode.stat.st_size = size;
+
+    __WASI_ESUCCESS
 }
 
 /// ### `fd_close()`
@@ -293,7 +404,7 @@ pub fn fd_allocate(
 /// - `__WASI_EBADF`
 ///     If `fd` is invalid or not open (TODO: consider __WASI_EINVAL)
 pub fn fd_close(ctx: &mut Ctx, fd: __wasi_fd_t) -> __wasi_errno_t {
-    debug!("wasi::fd_close");
+    debug!(target: "wasi::fd_close", "called");
     // FD is too large
     return __WASI_EMFILE;
     // FD is a directory (due to user input)
@@ -309,7 +420,7 @@ pub fn fd_close(ctx: &mut Ctx, fd: __wasi_fd_t) -> __wasi_errno_t {
 /// - `__wasi_fd_t fd`
 ///     The file descriptor to sync
 pub fn fd_datasync(ctx: &mut Ctx, fd: __wasi_fd_t) -> __wasi_errno_t {
-    debug!("wasi::fd_datasync");
+    debug!(target: "wasi::fd_datasync", "called");
     let state = get_wasi_state(ctx);
 
     if let Err(e) = state.fs.flush(fd) {
@@ -333,7 +444,8 @@ pub fn fd_fdstat_get(
     buf_ptr: WasmPtr<__wasi_fdstat_t>,
 ) -> __wasi_errno_t {
     debug!(
-        "wasi::fd_fdstat_get: fd={}, buf_ptr={}",
+        target: "wasi::fd_fdstat_get",
+        "fd={}, buf_ptr={}",
         fd,
         buf_ptr.offset()
     );
@@ -359,7 +471,7 @@ pub fn fd_fdstat_set_flags(
     fd: __wasi_fd_t,
     flags: __wasi_fdflags_t,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_fdstat_set_flags");
+    debug!(target: "wasi::fd_fdstat_set_flags", "called");
     let state = get_wasi_state(ctx);
     let fd_entry = wasi_try!(state.fs.fd_map.get_mut(&fd).ok_or(__WASI_EBADF));
 
@@ -386,7 +498,7 @@ pub fn fd_fdstat_set_rights(
     fs_rights_base: __wasi_rights_t,
     fs_rights_inheriting: __wasi_rights_t,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_fdstat_set_rights");
+    debug!(target: "wasi::fd_fdstat_set_rights", "called");
     let state = get_wasi_state(ctx);
     let fd_entry = wasi_try!(state.fs.fd_map.get_mut(&fd).ok_or(__WASI_EBADF));
 
@@ -416,7 +528,7 @@ pub fn fd_filestat_get(
     fd: __wasi_fd_t,
     buf: WasmPtr<__wasi_filestat_t>,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_filestat_get");
+    debug!(target: "wasi::fd_filestat_get", "called");
     let mut state = get_wasi_state(ctx);
     let memory = ctx.memory(0);
 
@@ -428,13 +540,40 @@ pub fn fd_filestat_get(
     __WASI_ESUCCESS
 }
 
+/// ### `fd_filestat_set_size()`
+/// Change the size of an open file, zeroing out any new bytes
+/// Inputs:
+/// - `__wasi_fd_t fd`
+///     File descriptor to adjust
+/// - `__wasi_filesize_t st_size`
+///     New size that `fd` will be set to
 pub fn fd_filestat_set_size(
     ctx: &mut Ctx,
     fd: __wasi_fd_t,
     st_size: __wasi_filesize_t,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_filestat_set_size");
-    unimplemented!()
+    debug!(target: "wasi::fd_filestat_set_size", "called");
+    let state = get_wasi_state(ctx);
+    let fd_entry = wasi_try!(state.fs.fd_map.get(&fd).ok_or(__WASI_EBADF));
+
+    if !has_rights(fd_entry.rights, __WASI_RIGHT_FD_FILESTAT_SET_SIZE) {
+        return __WASI_EACCES;
+    }
+
+    let inode = &mut state.fs.inodes[fd_entry.inode];
+
+    match &mut inode.kind {
+        Kind::File {
+            handle: Some(handle),
+            ..
+        } => wasi_try!(handle.set_len(st_size).map_err(host_error_to_errno)),
+        Kind::Buffer { buffer } => wasi_try!(resize_buffer(buffer, st_size)),
+        Kind::Dir { .. } => return __WASI_EISDIR,
+        Kind::File { handle: None, .. } | Kind::Symlink { .. } => return __WASI_EBADF,
+    }
+    inode.stat.st_size = st_size;
+
+    __WASI_ESUCCESS
 }
 
 /// ### `fd_filestat_set_times()`
@@ -453,7 +592,7 @@ pub fn fd_filestat_set_times(
     st_mtim: __wasi_timestamp_t,
     fst_flags: __wasi_fstflags_t,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_filestat_set_times");
+    debug!(target: "wasi::fd_filestat_set_times", "called");
     let state = get_wasi_state(ctx);
     let fd_entry = wasi_try!(state.fs.fd_map.get_mut(&fd).ok_or(__WASI_EBADF));
 
@@ -461,32 +600,26 @@ pub fn fd_filestat_set_times(
         return __WASI_EACCES;
     }
 
-    if (fst_flags & __WASI_FILESTAT_SET_ATIM != 0 && fst_flags & __WASI_FILESTAT_SET_ATIM_NOW != 0)
-        || (fst_flags & __WASI_FILESTAT_SET_MTIM != 0
-            && fst_flags & __WASI_FILESTAT_SET_MTIM_NOW != 0)
-    {
-        return __WASI_EINVAL;
-    }
-
     let inode = &mut state.fs.inodes[fd_entry.inode];
-
-    if fst_flags & __WASI_FILESTAT_SET_ATIM != 0 {
-        inode.stat.st_atim = st_atim;
-    } else if fst_flags & __WASI_FILESTAT_SET_ATIM_NOW != 0 {
-        // set to current real time
-        unimplemented!();
-    }
-
-    if fst_flags & __WASI_FILESTAT_SET_MTIM != 0 {
-        inode.stat.st_mtim = st_mtim;
-    } else if fst_flags & __WASI_FILESTAT_SET_MTIM_NOW != 0 {
-        // set to current real time
-        unimplemented!();
-    }
+    wasi_try!(set_times(&mut inode.stat, st_atim, st_mtim, fst_flags));
 
     __WASI_ESUCCESS
 }
 
+/// ### `fd_pread()`
+/// Read from a file at the given offset, without adjusting its offset
+/// Inputs:
+/// - `__wasi_fd_t fd`
+///     File descriptor (opened with reading) from which data will be read
+/// - `const __wasi_iovec_t *iovs`
+///     Vectors where data will be stored
+/// - `u32 iovs_len`
+///     Length of data in `iovs`
+/// - `__wasi_filesize_t offset`
+///     The offset to read from
+/// Output:
+/// - `u32 *nread`
+///     Number of bytes read
 pub fn fd_pread(
     ctx: &mut Ctx,
     fd: __wasi_fd_t,
@@ -495,13 +628,46 @@ pub fn fd_pread(
     offset: __wasi_filesize_t,
     nread: WasmPtr<u32>,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_pread");
+    debug!(target: "wasi::fd_pread", "fd={}, offset={}", fd, offset);
     let memory = ctx.memory(0);
 
     let iov_cells = wasi_try!(iovs.deref(memory, 0, iovs_len));
     let nread_cell = wasi_try!(nread.deref(memory));
 
-    unimplemented!();
+    let bytes_read = match fd {
+        // stdio can't be read at an offset
+        0 | 1 | 2 => return __WASI_ESPIPE,
+        _ => {
+            let state = get_wasi_state(ctx);
+            let fd_entry = wasi_try!(state.fs.fd_map.get(&fd).ok_or(__WASI_EBADF));
+
+            if !has_rights(fd_entry.rights, __WASI_RIGHT_FD_READ | __WASI_RIGHT_FD_SEEK) {
+                return __WASI_EACCES;
+            }
+
+            let inode = &mut state.fs.inodes[fd_entry.inode];
+
+            match &mut inode.kind {
+                Kind::File {
+                    handle: Some(handle),
+                    ..
+                } => {
+                    wasi_try!(handle
+                        .seek(io::SeekFrom::Start(offset))
+                        .map_err(host_error_to_errno));
+                    wasi_try!(read_bytes(handle, memory, iov_cells))
+                }
+                Kind::Dir { .. } => return __WASI_EISDIR,
+                Kind::File { handle: None, .. } | Kind::Symlink { .. } => return __WASI_EBADF,
+                Kind::Buffer { buffer } => {
+                    let rest = buffer.get(offset as usize..).unwrap_or(&[]);
+                    wasi_try!(read_bytes(rest, memory, iov_cells))
+                }
+            }
+        }
+    };
+
+    nread_cell.set(bytes_read);
 
     __WASI_ESUCCESS
 }
@@ -519,7 +685,7 @@ pub fn fd_prestat_get(
     fd: __wasi_fd_t,
     buf: WasmPtr<__wasi_prestat_t>,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_prestat_get: fd={}", fd);
+    debug!(target: "wasi::fd_prestat_get", "fd={}", fd);
     let memory = ctx.memory(0);
 
     let prestat_ptr = wasi_try!(buf.deref(memory));
@@ -537,7 +703,8 @@ pub fn fd_prestat_dir_name(
     path_len: u32,
 ) -> __wasi_errno_t {
     debug!(
-        "wasi::fd_prestat_dir_name: fd={}, path_len={}",
+        target: "wasi::fd_prestat_dir_name",
+        "fd={}, path_len={}",
         fd, path_len
     );
     let memory = ctx.memory(0);
@@ -560,6 +727,7 @@ pub fn fd_prestat_dir_name(
             path_chars[i].set(0);
 
             debug!(
+                target: "wasi::fd_prestat_dir_name",
                 "=> result: \"{}\"",
                 ::std::str::from_utf8(unsafe { &*(&path_chars[..] as *const [_] as *const [u8]) })
                     .unwrap()
@@ -596,7 +764,7 @@ pub fn fd_pwrite(
     offset: __wasi_filesize_t,
     nwritten: WasmPtr<u32>,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_pwrite");
+    debug!(target: "wasi::fd_pwrite", "called");
     // TODO: refactor, this is just copied from `fd_write`...
     let memory = ctx.memory(0);
     let iovs_arr_cell = wasi_try!(iovs.deref(memory, 0, iovs_len));
@@ -629,7 +797,10 @@ pub fn fd_pwrite(
             let inode = &mut state.fs.inodes[fd_entry.inode];
 
             let bytes_written = match &mut inode.kind {
-                Kind::File { handle } => {
+                Kind::File {
+                    handle: Some(handle),
+                    ..
+                } => {
                     handle.seek(::std::io::SeekFrom::Start(offset as u64));
                     wasi_try!(write_bytes(handle, memory, iovs_arr_cell))
                 }
@@ -637,9 +808,9 @@ pub fn fd_pwrite(
                     // TODO: verify
                     return __WASI_EISDIR;
                 }
-                Kind::Symlink { .. } => unimplemented!(),
+                Kind::File { handle: None, .. } | Kind::Symlink { .. } => return __WASI_EBADF,
                 Kind::Buffer { buffer } => wasi_try!(write_bytes(
-                    &mut buffer[(offset as usize)..],
+                    buffer.get_mut(offset as usize..).unwrap_or(&mut []),
                     memory,
                     iovs_arr_cell
                 )),
@@ -673,29 +844,12 @@ pub fn fd_read(
     iovs_len: u32,
     nread: WasmPtr<u32>,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_read: fd={}", fd);
+    debug!(target: "wasi::fd_read", "fd={}", fd);
     let memory = ctx.memory(0);
 
     let iovs_arr_cell = wasi_try!(iovs.deref(memory, 0, iovs_len));
     let nread_cell = wasi_try!(nread.deref(memory));
 
-    fn read_bytes<T: Read>(
-        mut reader: T,
-        memory: &Memory,
-        iovs_arr_cell: &[Cell<__wasi_iovec_t>],
-    ) -> Result<u32, __wasi_errno_t> {
-        let mut bytes_read = 0;
-
-        for iov in iovs_arr_cell {
-            let iov_inner = iov.get();
-            let bytes = iov_inner.buf.deref(memory, 0, iov_inner.buf_len)?;
-            let mut raw_bytes: &mut [u8] =
-                unsafe { &mut *(bytes as *const [_] as *mut [_] as *mut [u8]) };
-            bytes_read += reader.read(raw_bytes).map_err(|_| __WASI_EIO)? as u32;
-        }
-        Ok(bytes_read)
-    }
-
     let bytes_read = match fd {
         0 => {
             let stdin = io::stdin();
@@ -717,7 +871,10 @@ pub fn fd_read(
             let inode = &mut state.fs.inodes[fd_entry.inode];
 
             let bytes_read = match &mut inode.kind {
-                Kind::File { handle } => {
+                Kind::File {
+                    handle: Some(handle),
+                    ..
+                } => {
                     handle.seek(::std::io::SeekFrom::Start(offset as u64));
                     wasi_try!(read_bytes(handle, memory, iovs_arr_cell))
                 }
@@ -725,9 +882,10 @@ pub fn fd_read(
                     // TODO: verify
                     return __WASI_EISDIR;
                 }
-                Kind::Symlink { .. } => unimplemented!(),
+                Kind::File { handle: None, .. } | Kind::Symlink { .. } => return __WASI_EBADF,
                 Kind::Buffer { buffer } => {
-                    wasi_try!(read_bytes(&buffer[offset..], memory, iovs_arr_cell))
+                    let rest = buffer.get(offset..).unwrap_or(&[]);
+                    wasi_try!(read_bytes(rest, memory, iovs_arr_cell))
                 }
             };
 
@@ -765,16 +923,45 @@ pub fn fd_readdir(
     cookie: __wasi_dircookie_t,
     bufused: WasmPtr<u32>,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_readdir");
+    debug!(target: "wasi::fd_readdir", "fd={}, cookie={}", fd, cookie);
     let memory = ctx.memory(0);
+    let state = get_wasi_state(ctx);
 
-    if let (Ok(buf_arr_cell), Ok(bufused_cell)) =
-        (buf.deref(memory, 0, buf_len), bufused.deref(memory))
-    {
-        unimplemented!()
-    } else {
-        __WASI_EFAULT
+    let buf_arr_cell = wasi_try!(buf.deref(memory, 0, buf_len));
+    let bufused_cell = wasi_try!(bufused.deref(memory));
+    let fd_entry = wasi_try!(state.fs.fd_map.get(&fd).ok_or(__WASI_EBADF));
+
+    if !has_rights(fd_entry.rights, __WASI_RIGHT_FD_READDIR) {
+        return __WASI_EACCES;
     }
+
+    let dir = fd_entry.inode;
+    let entries = wasi_try!(state.fs.read_dir(dir));
+
+    // the cookie of an entry is its index, so reading resumes after it
+    let mut dirents = Vec::new();
+    for (index, (name, stat)) in entries.iter().enumerate().skip(cookie as usize) {
+        if dirents.len() >= buf_len as usize {
+            break;
+        }
+        let dirent = __wasi_dirent_t {
+            d_next: index as u64 + 1,
+            d_ino: stat.st_ino,
+            d_namlen: name.len() as u32,
+            d_type: stat.st_filetype,
+        };
+        dirents.extend_from_slice(&dirent.to_bytes());
+        dirents.extend_from_slice(name.as_bytes());
+    }
+
+    // the last entry is cut off when the buffer is full, as the guest expects
+    let used = dirents.len().min(buf_len as usize);
+    for (cell, &byte) in buf_arr_cell.iter().zip(&dirents[..used]) {
+        cell.set(byte);
+    }
+    bufused_cell.set(used as u32);
+
+    __WASI_ESUCCESS
 }
 
 /// ### `fd_renumber()`
@@ -785,7 +972,7 @@ pub fn fd_readdir(
 /// - `__wasi_fd_t to`
 ///     Location to copy file descriptor to
 pub fn fd_renumber(ctx: &mut Ctx, from: __wasi_fd_t, to: __wasi_fd_t) -> __wasi_errno_t {
-    debug!("wasi::fd_renumber: from={}, to={}", from, to);
+    debug!(target: "wasi::fd_renumber", "from={}, to={}", from, to);
     let state = get_wasi_state(ctx);
     let fd_entry = wasi_try!(state.fs.fd_map.get(&from).ok_or(__WASI_EBADF));
 
@@ -819,7 +1006,7 @@ pub fn fd_seek(
     whence: __wasi_whence_t,
     newoffset: WasmPtr<__wasi_filesize_t>,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_seek: fd={}, offset={}", fd, offset);
+    debug!(target: "wasi::fd_seek", "fd={}, offset={}", fd, offset);
     let memory = ctx.memory(0);
     let state = get_wasi_state(ctx);
     let new_offset_cell = wasi_try!(newoffset.deref(memory));
@@ -831,12 +1018,30 @@ pub fn fd_seek(
     }
 
     // TODO: handle case if fd is a dir?
-    match whence {
-        __WASI_WHENCE_CUR => fd_entry.offset = (fd_entry.offset as i64 + offset) as u64,
-        __WASI_WHENCE_END => unimplemented!(),
-        __WASI_WHENCE_SET => fd_entry.offset = offset as u64,
+    let new_offset = match whence {
+        __WASI_WHENCE_CUR => fd_entry.offset as i64 + offset,
+        __WASI_WHENCE_END => {
+            let end = match &mut state.fs.inodes[fd_entry.inode].kind {
+                Kind::File {
+                    handle: Some(handle),
+                    ..
+                } => wasi_try!(handle
+                    .seek(io::SeekFrom::End(0))
+                    .map_err(host_error_to_errno)),
+                Kind::Buffer { buffer } => buffer.len() as u64,
+                Kind::Dir { .. } => return __WASI_EISDIR,
+                Kind::File { handle: None, .. } | Kind::Symlink { .. } => return __WASI_EBADF,
+            };
+            end as i64 + offset
+        }
+        __WASI_WHENCE_SET => offset,
         _ => return __WASI_EINVAL,
+    };
+
+    if new_offset < 0 {
+        return __WASI_EINVAL;
     }
+    fd_entry.offset = new_offset as u64;
 
     new_offset_cell.set(fd_entry.offset);
 
@@ -853,9 +1058,32 @@ pub fn fd_seek(
 /// - `__WASI_EPERM`
 /// - `__WASI_ENOTCAPABLE`
 pub fn fd_sync(ctx: &mut Ctx, fd: __wasi_fd_t) -> __wasi_errno_t {
-    debug!("wasi::fd_sync");
-    // TODO: check __WASI_RIGHT_FD_SYNC
-    unimplemented!()
+    debug!(target: "wasi::fd_sync", "called");
+    let state = get_wasi_state(ctx);
+
+    match fd {
+        0 => (),
+        1 => wasi_try!(io::stdout().flush().map_err(|_| __WASI_EIO)),
+        2 => wasi_try!(io::stderr().flush().map_err(|_| __WASI_EIO)),
+        _ => {
+            let fd_entry = wasi_try!(state.fs.fd_map.get(&fd).ok_or(__WASI_EBADF));
+
+            if !has_rights(fd_entry.rights, __WASI_RIGHT_FD_SYNC) {
+                return __WASI_EACCES;
+            }
+
+            match &mut state.fs.inodes[fd_entry.inode].kind {
+                Kind::File {
+                    handle: Some(handle),
+                    ..
+                } => wasi_try!(handle.sync_all().map_err(host_error_to_errno)),
+                Kind::Dir { .. } | Kind::Buffer { .. } => (),
+                Kind::File { handle: None, .. } | Kind::Symlink { .. } => return __WASI_EBADF,
+            }
+        }
+    }
+
+    __WASI_ESUCCESS
 }
 
 /// ### `fd_tell()`
@@ -871,7 +1099,7 @@ pub fn fd_tell(
     fd: __wasi_fd_t,
     offset: WasmPtr<__wasi_filesize_t>,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_tell");
+    debug!(target: "wasi::fd_tell", "called");
     let memory = ctx.memory(0);
     let state = get_wasi_state(ctx);
     let offset_cell = wasi_try!(offset.deref(memory));
@@ -908,7 +1136,7 @@ pub fn fd_write(
     iovs_len: u32,
     nwritten: WasmPtr<u32>,
 ) -> __wasi_errno_t {
-    debug!("wasi::fd_write: fd={}", fd);
+    debug!(target: "wasi::fd_write", "fd={}", fd);
     let memory = ctx.memory(0);
     let iovs_arr_cell = wasi_try!(iovs.deref(memory, 0, iovs_len));
     let nwritten_cell = wasi_try!(nwritten.deref(memory));
@@ -941,7 +1169,10 @@ pub fn fd_write(
             let inode = &mut state.fs.inodes[fd_entry.inode];
 
             let bytes_written = match &mut inode.kind {
-                Kind::File { handle } => {
+                Kind::File {
+                    handle: Some(handle),
+                    ..
+                } => {
                     handle.seek(::std::io::SeekFrom::Start(offset as u64));
 
                     wasi_try!(write_bytes(handle, memory, iovs_arr_cell))
@@ -950,7 +1181,7 @@ pub fn fd_write(
                     // TODO: verify
                     return __WASI_EISDIR;
                 }
-                Kind::Symlink { .. } => unimplemented!(),
+                Kind::File { handle: None, .. } | Kind::Symlink { .. } => return __WASI_EBADF,
                 Kind::Buffer { buffer } => {
                     wasi_try!(write_bytes(&mut buffer[offset..], memory, iovs_arr_cell))
                 }
@@ -986,9 +1217,25 @@ pub fn path_create_directory(
     path: WasmPtr<u8, Array>,
     path_len: u32,
 ) -> __wasi_errno_t {
-    debug!("wasi::path_create_directory");
-    // check __WASI_RIGHT_PATH_CREATE_DIRECTORY
-    unimplemented!()
+    debug!(target: "wasi::path_create_directory", "called");
+    let memory = ctx.memory(0);
+    let state = get_wasi_state(ctx);
+
+    let working_dir = wasi_try!(state.fs.fd_map.get(&fd).ok_or(__WASI_EBADF));
+
+    if !has_rights(working_dir.rights, __WASI_RIGHT_PATH_CREATE_DIRECTORY) {
+        return __WASI_EACCES;
+    }
+
+    let path_string = wasi_try!(read_string(memory, path, path_len));
+    debug!(target: "wasi::path_create_directory", "=> path: {}", &path_string);
+
+    let (parent, name) = wasi_try!(state.fs.get_parent_inode_at_path(fd, &path_string));
+    let host_path = wasi_try!(state.fs.host_path(parent, &name));
+    wasi_try!(fs::create_dir(&host_path).map_err(host_error_to_errno));
+    wasi_try!(state.fs.insert_entry(parent, &name, host_path));
+
+    __WASI_ESUCCESS
 }
 
 /// ### `path_filestat_get()`
@@ -1013,7 +1260,7 @@ pub fn path_filestat_get(
     path_len: u32,
     buf: WasmPtr<__wasi_filestat_t>,
 ) -> __wasi_errno_t {
-    debug!("wasi::path_filestat_get");
+    debug!(target: "wasi::path_filestat_get", "called");
     let state = get_wasi_state(ctx);
     let memory = ctx.memory(0);
 
@@ -1023,45 +1270,13 @@ pub fn path_filestat_get(
         return __WASI_EACCES;
     }
 
-    let path_vec = wasi_try!(::std::str::from_utf8(unsafe {
-        &*(wasi_try!(path.deref(memory, 0, path_len)) as *const [_] as *const [u8])
-    })
-    .map_err(|_| __WASI_EINVAL))
-    .split('/')
-    .map(|str| str.to_string())
-    .collect::<Vec<String>>();
+    let path_string = wasi_try!(read_string(memory, path, path_len));
     let buf_cell = wasi_try!(buf.deref(memory));
 
-    // find the inode by traversing the path
-    let mut inode = root_dir.inode;
-    'outer: for segment in path_vec {
-        // loop to traverse symlinks
-        // TODO: proper cycle detection
-        let mut sym_count = 0;
-        loop {
-            match &state.fs.inodes[inode].kind {
-                Kind::Dir { entries, .. } => {
-                    if let Some(entry) = entries.get(&segment) {
-                        inode = entry.clone();
-                        continue 'outer;
-                    } else {
-                        return __WASI_ENOENT;
-                    }
-                }
-                Kind::Symlink { forwarded } => {
-                    sym_count += 1;
-                    inode = forwarded.clone();
-                    if sym_count > MAX_SYMLINKS {
-                        return __WASI_ELOOP;
-                    }
-                }
-                _ => {
-                    return __WASI_ENOTDIR;
-                }
-            }
-        }
-    }
-
+    let follow_symlinks = flags & __WASI_LOOKUP_SYMLINK_FOLLOW != 0;
+    let inode = wasi_try!(state
+        .fs
+        .get_inode_at_path(fd, &path_string, follow_symlinks));
     let stat = state.fs.inodes[inode].stat;
 
     buf_cell.set(stat);
@@ -1096,8 +1311,30 @@ pub fn path_filestat_set_times(
     st_mtim: __wasi_timestamp_t,
     fst_flags: __wasi_fstflags_t,
 ) -> __wasi_errno_t {
-    debug!("wasi::path_filestat_set_times");
-    unimplemented!()
+    debug!(target: "wasi::path_filestat_set_times", "called");
+    let memory = ctx.memory(0);
+    let state = get_wasi_state(ctx);
+
+    let root_dir = wasi_try!(state.fs.fd_map.get(&fd).ok_or(__WASI_EBADF));
+
+    if !has_rights(root_dir.rights, __WASI_RIGHT_PATH_FILESTAT_SET_TIMES) {
+        return __WASI_EACCES;
+    }
+
+    let path_string = wasi_try!(read_string(memory, path, path_len));
+
+    let follow_symlinks = flags & __WASI_LOOKUP_SYMLINK_FOLLOW != 0;
+    let inode = wasi_try!(state
+        .fs
+        .get_inode_at_path(fd, &path_string, follow_symlinks));
+    wasi_try!(set_times(
+        &mut state.fs.inodes[inode].stat,
+        st_atim,
+        st_mtim,
+        fst_flags
+    ));
+
+    __WASI_ESUCCESS
 }
 
 /// ### `path_link()`
@@ -1127,8 +1364,43 @@ pub fn path_link(
     new_path: WasmPtr<u8, Array>,
     new_path_len: u32,
 ) -> __wasi_errno_t {
-    debug!("wasi::path_link");
-    unimplemented!()
+    debug!(target: "wasi::path_link", "called");
+    let memory = ctx.memory(0);
+    let state = get_wasi_state(ctx);
+
+    let source_dir = wasi_try!(state.fs.fd_map.get(&old_fd).ok_or(__WASI_EBADF));
+    if !has_rights(source_dir.rights, __WASI_RIGHT_PATH_LINK_SOURCE) {
+        return __WASI_EACCES;
+    }
+    let target_dir = wasi_try!(state.fs.fd_map.get(&new_fd).ok_or(__WASI_EBADF));
+    if !has_rights(target_dir.rights, __WASI_RIGHT_PATH_LINK_TARGET) {
+        return __WASI_EACCES;
+    }
+
+    let old_path_string = wasi_try!(read_string(memory, old_path, old_path_len));
+    let new_path_string = wasi_try!(read_string(memory, new_path, new_path_len));
+    debug!(target: "wasi::path_link", "=> {} -> {}", &old_path_string, &new_path_string);
+
+    let follow_symlinks = old_flags & __WASI_LOOKUP_SYMLINK_FOLLOW != 0;
+    let inode = wasi_try!(state
+        .fs
+        .get_inode_at_path(old_fd, &old_path_string, follow_symlinks));
+    let old_host_path = match &state.fs.inodes[inode].kind {
+        Kind::File { path, .. } => path.clone(),
+        Kind::Dir { .. } => return __WASI_EPERM,
+        // TODO: support linking to symlinks themselves
+        Kind::Symlink { .. } | Kind::Buffer { .. } => return __WASI_ENOTSUP,
+    };
+
+    let (new_parent, new_name) =
+        wasi_try!(state.fs.get_parent_inode_at_path(new_fd, &new_path_string));
+    let new_host_path = wasi_try!(state.fs.host_path(new_parent, &new_name));
+    wasi_try!(fs::hard_link(&old_host_path, &new_host_path).map_err(host_error_to_errno));
+
+    // both names refer to the same inode
+    state.fs.add_entry(new_parent, &new_name, inode);
+
+    __WASI_ESUCCESS
 }
 
 /// ### `path_open()`
@@ -1167,7 +1439,7 @@ pub fn path_open(
     fs_flags: __wasi_fdflags_t,
     fd: WasmPtr<__wasi_fd_t>,
 ) -> __wasi_errno_t {
-    debug!("wasi::path_open");
+    debug!(target: "wasi::path_open", "called");
     let memory = ctx.memory(0);
     /* TODO: find actual upper bound on name size (also this is a path, not a name :think-fish:) */
     if path_len > 1024 * 1024 {
@@ -1175,7 +1447,6 @@ pub fn path_open(
     }
 
     let fd_cell = wasi_try!(fd.deref(memory));
-    let path_cells = wasi_try!(path.deref(memory, 0, path_len));
     let state = get_wasi_state(ctx);
 
     // o_flags:
@@ -1190,206 +1461,197 @@ pub fn path_open(
     if !has_rights(working_dir.rights, __WASI_RIGHT_PATH_OPEN) {
         return __WASI_EACCES;
     }
-
-    let path_string =
-        wasi_try!(
-            std::str::from_utf8(unsafe { &*(path_cells as *const [_] as *const [u8]) })
-                .map_err(|_| __WASI_EINVAL)
-        );
-    let path = std::path::PathBuf::from(path_string);
-    let path_vec = wasi_try!(path
-        .components()
-        .map(|comp| {
-            comp.as_os_str()
-                .to_str()
-                .map(|inner_str| inner_str.to_string())
-                .ok_or(__WASI_EINVAL)
-        })
-        .collect::<Result<Vec<String>, __wasi_errno_t>>());
-    debug!("Path vec: {:#?}", path_vec);
-
-    if path_vec.is_empty() {
-        return __WASI_EINVAL;
+    if o_flags & __WASI_O_CREAT != 0
+        && !has_rights(working_dir.rights, __WASI_RIGHT_PATH_CREATE_FILE)
+    {
+        return __WASI_EACCES;
     }
-
-    let mut cur_dir_inode = working_dir.inode;
-    let mut cumulative_path = std::path::PathBuf::from(".");
-
-    // traverse path
-    if path_vec.len() > 1 {
-        for path_segment in &path_vec[..(path_vec.len() - 1)] {
-            match &state.fs.inodes[cur_dir_inode].kind {
-                Kind::Dir { entries, .. } => {
-                    if let Some(child) = entries.get(path_segment) {
-                        let inode_val = *child;
-                        cur_dir_inode = inode_val;
-                    } else {
-                        // attempt to lazily load or create
-                        if path_segment == ".." {
-                            unimplemented!(
-                                "\"..\" in paths in `path_open` has not been implemented yet"
-                            );
-                        }
-                        // lazily load
-                        cumulative_path.push(path_segment);
-                        let mut open_options = std::fs::OpenOptions::new();
-                        let open_options = open_options.read(true);
-                        // ASSUMPTION: __WASI_O_CREAT applies recursively
-                        let open_options = if o_flags & __WASI_O_CREAT != 0 {
-                            open_options.create(true)
-                        } else {
-                            open_options
-                        };
-                        // TODO: handle __WASI_O_TRUNC on directories
-
-                        let cur_dir = wasi_try!(open_options
-                            .open(&cumulative_path)
-                            .map_err(|_| __WASI_EINVAL));
-
-                        // TODO: refactor and reuse
-                        let cur_file_metadata = cur_dir.metadata().unwrap();
-                        let kind = if cur_file_metadata.is_dir() {
-                            Kind::Dir {
-                                handle: WasiFile::HostFile(cur_dir),
-                                entries: Default::default(),
-                            }
-                        } else {
-                            return __WASI_ENOTDIR;
-                        };
-                        let inode_val = InodeVal::from_file_metadata(
-                            &cur_file_metadata,
-                            path_segment.clone(),
-                            false,
-                            kind,
-                        );
-
-                        let new_inode = state.fs.inodes.insert(inode_val);
-                        let inode_idx = state.fs.inode_counter.get();
-                        state.fs.inode_counter.replace(inode_idx + 1);
-                        // reborrow to insert entry
-                        if let Kind::Dir { entries, .. } = &mut state.fs.inodes[cur_dir_inode].kind
-                        {
-                            assert!(entries.insert(path_segment.clone(), new_inode).is_none());
-                            state.fs.inodes[new_inode].stat.st_ino = state.fs.inode_counter.get();
-                            cur_dir_inode = new_inode;
-                        }
-                    }
-                }
-                Kind::Symlink { .. } => unimplemented!("Symlinks not yet supported in `path_open`"),
-                _ => return __WASI_ENOTDIR,
-            }
-        }
+    if o_flags & __WASI_O_TRUNC != 0
+        && !has_rights(working_dir.rights, __WASI_RIGHT_PATH_FILESTAT_SET_SIZE)
+    {
+        return __WASI_EACCES;
     }
 
-    let file_name = path_vec.last().unwrap();
+    // the new fd only gets the rights that its directory passes on
+    let fs_rights_base = fs_rights_base & working_dir.rights_inheriting;
+    let fs_rights_inheriting = fs_rights_inheriting & working_dir.rights_inheriting;
 
-    debug!(
-        "Looking for file {} in directory {:#?}",
-        file_name, cumulative_path
-    );
-    cumulative_path.push(file_name);
-    let file_path = cumulative_path;
+    let path_string = wasi_try!(read_string(memory, path, path_len));
+    debug!(target: "wasi::path_open", "=> path: {}", &path_string);
 
-    let out_fd = if let Kind::Dir { entries, .. } = &mut state.fs.inodes[cur_dir_inode].kind {
-        if let Some(child) = entries.get(file_name).cloned() {
-            let child_inode_val = &state.fs.inodes[child];
+    let follow_symlinks = dirflags & __WASI_LOOKUP_SYMLINK_FOLLOW != 0;
+    let inode = match state
+        .fs
+        .get_inode_at_path(dirfd, &path_string, follow_symlinks)
+    {
+        Ok(inode) => {
             // early return based on flags
             if o_flags & __WASI_O_EXCL != 0 {
                 return __WASI_EEXIST;
             }
-            if o_flags & __WASI_O_DIRECTORY != 0 {
-                match &child_inode_val.kind {
-                    Kind::Dir { .. } => (),
-                    Kind::Symlink { .. } => {
-                        unimplemented!("Symlinks not yet supported in path_open")
-                    }
-                    _ => return __WASI_ENOTDIR,
-                }
+            match &state.fs.inodes[inode].kind {
+                Kind::Dir { .. } if o_flags & __WASI_O_TRUNC != 0 => return __WASI_EISDIR,
+                Kind::Dir { .. } => (),
+                // the symlink wasn't followed
+                Kind::Symlink { .. } => return __WASI_ELOOP,
+                _ if o_flags & __WASI_O_DIRECTORY != 0 => return __WASI_ENOTDIR,
+                _ => (),
             }
-            // do logic on child
-            wasi_try!(state
-                .fs
-                .create_fd(fs_rights_base, fs_rights_inheriting, fs_flags, child))
-        } else {
-            // if entry does not exist in parent directory, try to lazily
-            // load it; possibly creating or truncating it if flags set
-            let real_opened_file = {
-                let mut open_options = std::fs::OpenOptions::new();
-                let open_options = open_options.read(true).write(true);
-                let open_options = if o_flags & __WASI_O_CREAT != 0 {
-                    debug!(
-                        "File {} may be created when opened if it does not exist",
-                        &path_string
-                    );
-                    open_options.create(true)
-                } else {
-                    open_options
-                };
-                let open_options = if o_flags & __WASI_O_TRUNC != 0 {
-                    debug!("File {} will be truncated when opened", &path_string);
-                    open_options.truncate(true)
-                } else {
-                    open_options
-                };
-                let real_open_file =
-                    wasi_try!(open_options.open(&file_path).map_err(|_| __WASI_EIO));
-                debug!("Opening host file {}", &path_string);
 
-                real_open_file
-            };
-            // record lazily loaded or newly created fd
-            let new_inode = state.fs.inodes.insert(InodeVal {
-                stat: __wasi_filestat_t::default(),
-                is_preopened: false,
-                name: file_name.clone(),
-                kind: Kind::File {
-                    handle: WasiFile::HostFile(real_opened_file),
-                },
-            });
-            // reborrow to insert entry
-            if let Kind::Dir { entries, .. } = &mut state.fs.inodes[working_dir.inode].kind {
-                entries.insert(file_name.clone(), new_inode);
+            wasi_try!(state.fs.open_file(inode));
+            if o_flags & __WASI_O_TRUNC != 0 {
+                debug!(target: "wasi::path_open", "File {} will be truncated when opened", &path_string);
+                let inode_val = &mut state.fs.inodes[inode];
+                if let Kind::File {
+                    handle: Some(handle),
+                    ..
+                } = &mut inode_val.kind
+                {
+                    wasi_try!(handle.set_len(0).map_err(host_error_to_errno));
+                }
+                inode_val.stat.st_size = 0;
             }
-            let new_fd = wasi_try!(state.fs.create_fd(
-                fs_rights_base,
-                fs_rights_inheriting,
-                fs_flags,
-                new_inode,
-            ));
-
-            new_fd
+            inode
         }
-    } else {
-        // working_dir did not match on Kind::Dir
-        return __WASI_ENOTDIR;
+        Err(__WASI_ENOENT) if o_flags & __WASI_O_CREAT != 0 => {
+            debug!(target: "wasi::path_open", "File {} will be created", &path_string);
+            let (parent, name) = wasi_try!(state.fs.get_parent_inode_at_path(dirfd, &path_string));
+            let host_path = wasi_try!(state.fs.host_path(parent, &name));
+            let file = wasi_try!(fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&host_path)
+                .map_err(host_error_to_errno));
+
+            let inode = wasi_try!(state.fs.insert_entry(parent, &name, host_path));
+            if let Kind::File { handle, .. } = &mut state.fs.inodes[inode].kind {
+                *handle = Some(WasiFile::HostFile(file));
+            }
+            inode
+        }
+        Err(e) => return e,
     };
 
+    let out_fd =
+        wasi_try!(state
+            .fs
+            .create_fd(fs_rights_base, fs_rights_inheriting, fs_flags, inode));
+
     fd_cell.set(out_fd);
 
     __WASI_ESUCCESS
 }
 
+/// ### `path_readlink()`
+/// Read the value of a symlink
+/// Inputs:
+/// - `__wasi_fd_t fd`
+///     The directory that `path` is relative to
+/// - `const char *path`
+///     String containing the path of the symlink
+/// - `u32 path_len`
+///     The length of the `path` string
+/// - `char *buf`
+///     Buffer where the value of the symlink is written, without a null terminator
+/// - `u32 buf_len`
+///     Length of `buf`; longer values are truncated
+/// Output:
+/// - `u32 *bufused`
+///     The number of bytes written to `buf`
 pub fn path_readlink(
     ctx: &mut Ctx,
     fd: __wasi_fd_t,
     path: WasmPtr<u8, Array>,
     path_len: u32,
-    buf: WasmPtr<u8>,
+    buf: WasmPtr<u8, Array>,
     buf_len: u32,
     bufused: WasmPtr<u32>,
 ) -> __wasi_errno_t {
-    debug!("wasi::path_readlink");
-    unimplemented!()
+    debug!(target: "wasi::path_readlink", "called");
+    let memory = ctx.memory(0);
+    let state = get_wasi_state(ctx);
+
+    let base_dir = wasi_try!(state.fs.fd_map.get(&fd).ok_or(__WASI_EBADF));
+
+    if !has_rights(base_dir.rights, __WASI_RIGHT_PATH_READLINK) {
+        return __WASI_EACCES;
+    }
+
+    let path_string = wasi_try!(read_string(memory, path, path_len));
+    let bufused_cell = wasi_try!(bufused.deref(memory));
+
+    let inode = wasi_try!(state.fs.get_inode_at_path(fd, &path_string, false));
+    let target = match &state.fs.inodes[inode].kind {
+        Kind::Symlink { target } => wasi_try!(target.to_str().ok_or(__WASI_EILSEQ)),
+        _ => return __WASI_EINVAL,
+    };
+
+    let bytes_used = target.len().min(buf_len as usize);
+    let buf_arr_cell = wasi_try!(buf.deref(memory, 0, bytes_used as u32));
+    for (cell, &byte) in buf_arr_cell.iter().zip(target.as_bytes()) {
+        cell.set(byte);
+    }
+    bufused_cell.set(bytes_used as u32);
+
+    __WASI_ESUCCESS
 }
+/// ### `path_remove_directory()`
+/// Remove an empty directory
+/// Inputs:
+/// - `__wasi_fd_t fd`
+///     The directory that `path` is relative to
+/// - `const char *path`
+///     String containing the path of the directory to remove
+/// - `u32 path_len`
+///     The length of the `path` string
 pub fn path_remove_directory(
     ctx: &mut Ctx,
     fd: __wasi_fd_t,
     path: WasmPtr<u8, Array>,
     path_len: u32,
 ) -> __wasi_errno_t {
-    debug!("wasi::path_remove_directory");
-    unimplemented!()
+    debug!(target: "wasi::path_remove_directory", "called");
+    let memory = ctx.memory(0);
+    let state = get_wasi_state(ctx);
+
+    let base_dir = wasi_try!(state.fs.fd_map.get(&fd).ok_or(__WASI_EBADF));
+
+    if !has_rights(base_dir.rights, __WASI_RIGHT_PATH_REMOVE_DIRECTORY) {
+        return __WASI_EACCES;
+    }
+
+    let path_string = wasi_try!(read_string(memory, path, path_len));
+    debug!(target: "wasi::path_remove_directory", "=> path: {}", &path_string);
+
+    let (parent, name) = wasi_try!(state.fs.get_parent_inode_at_path(fd, &path_string));
+    let inode = wasi_try!(state.fs.get_child(parent, &name));
+    match state.fs.inodes[inode].kind {
+        Kind::Dir { .. } => (),
+        _ => return __WASI_ENOTDIR,
+    }
+
+    let host_path = wasi_try!(state.fs.host_path(parent, &name));
+    wasi_try!(fs::remove_dir(&host_path).map_err(host_error_to_errno));
+    state.fs.remove_entry(parent, &name);
+
+    __WASI_ESUCCESS
 }
+/// ### `path_rename()`
+/// Rename a file or directory
+/// Inputs:
+/// - `__wasi_fd_t old_fd`
+///     The directory that `old_path` is relative to
+/// - `const char *old_path`
+///     String containing the path to rename
+/// - `u32 old_path_len`
+///     The length of the `old_path` string
+/// - `__wasi_fd_t new_fd`
+///     The directory that `new_path` is relative to
+/// - `const char *new_path`
+///     String containing the new path; an existing file at that path is replaced
+/// - `u32 new_path_len`
+///     The length of the `new_path` string
 pub fn path_rename(
     ctx: &mut Ctx,
     old_fd: __wasi_fd_t,
@@ -1399,9 +1661,67 @@ pub fn path_rename(
     new_path: WasmPtr<u8, Array>,
     new_path_len: u32,
 ) -> __wasi_errno_t {
-    debug!("wasi::path_rename");
-    unimplemented!()
+    debug!(target: "wasi::path_rename", "called");
+    let memory = ctx.memory(0);
+    let state = get_wasi_state(ctx);
+
+    let source_dir = wasi_try!(state.fs.fd_map.get(&old_fd).ok_or(__WASI_EBADF));
+    if !has_rights(source_dir.rights, __WASI_RIGHT_PATH_RENAME_SOURCE) {
+        return __WASI_EACCES;
+    }
+    let target_dir = wasi_try!(state.fs.fd_map.get(&new_fd).ok_or(__WASI_EBADF));
+    if !has_rights(target_dir.rights, __WASI_RIGHT_PATH_RENAME_TARGET) {
+        return __WASI_EACCES;
+    }
+
+    let old_path_string = wasi_try!(read_string(memory, old_path, old_path_len));
+    let new_path_string = wasi_try!(read_string(memory, new_path, new_path_len));
+    debug!(target: "wasi::path_rename", "=> {} -> {}", &old_path_string, &new_path_string);
+
+    let (old_parent, old_name) =
+        wasi_try!(state.fs.get_parent_inode_at_path(old_fd, &old_path_string));
+    let (new_parent, new_name) =
+        wasi_try!(state.fs.get_parent_inode_at_path(new_fd, &new_path_string));
+    let inode = wasi_try!(state.fs.get_child(old_parent, &old_name));
+
+    let old_host_path = wasi_try!(state.fs.host_path(old_parent, &old_name));
+    let new_host_path = wasi_try!(state.fs.host_path(new_parent, &new_name));
+    wasi_try!(fs::rename(&old_host_path, &new_host_path).map_err(host_error_to_errno));
+
+    state.fs.remove_entry(old_parent, &old_name);
+    let inode_val = &mut state.fs.inodes[inode];
+    inode_val.name = new_name.clone();
+    match &mut inode_val.kind {
+        Kind::File { path, .. } => *path = new_host_path,
+        Kind::Dir {
+            parent,
+            path,
+            entries,
+        } => {
+            *parent = Some(new_parent);
+            *path = new_host_path;
+            // the loaded entries have host paths under the old path
+            entries.clear();
+        }
+        Kind::Symlink { .. } | Kind::Buffer { .. } => (),
+    }
+    state.fs.add_entry(new_parent, &new_name, inode);
+
+    __WASI_ESUCCESS
 }
+/// ### `path_symlink()`
+/// Create a symlink
+/// Inputs:
+/// - `const char *old_path`
+///     The value of the symlink, which is resolved relative to the directory containing it
+/// - `u32 old_path_len`
+///     The length of the `old_path` string
+/// - `__wasi_fd_t fd`
+///     The directory that `new_path` is relative to
+/// - `const char *new_path`
+///     String containing the path of the symlink to create
+/// - `u32 new_path_len`
+///     The length of the `new_path` string
 pub fn path_symlink(
     ctx: &mut Ctx,
     old_path: WasmPtr<u8, Array>,
@@ -1410,18 +1730,165 @@ pub fn path_symlink(
     new_path: WasmPtr<u8, Array>,
     new_path_len: u32,
 ) -> __wasi_errno_t {
-    debug!("wasi::path_symlink");
-    unimplemented!()
+    debug!(target: "wasi::path_symlink", "called");
+    let memory = ctx.memory(0);
+    let state = get_wasi_state(ctx);
+
+    let base_dir = wasi_try!(state.fs.fd_map.get(&fd).ok_or(__WASI_EBADF));
+
+    if !has_rights(base_dir.rights, __WASI_RIGHT_PATH_SYMLINK) {
+        return __WASI_EACCES;
+    }
+
+    let old_path_string = wasi_try!(read_string(memory, old_path, old_path_len));
+    let new_path_string = wasi_try!(read_string(memory, new_path, new_path_len));
+    debug!(target: "wasi::path_symlink", "=> {} -> {}", &new_path_string, &old_path_string);
+
+    let (parent, name) = wasi_try!(state.fs.get_parent_inode_at_path(fd, &new_path_string));
+    let host_path = wasi_try!(state.fs.host_path(parent, &name));
+    // the value is stored as is; lookups refuse to follow it out of the sandbox
+    wasi_try!(platform_symlink(&old_path_string, &host_path));
+    wasi_try!(state.fs.insert_entry(parent, &name, host_path));
+
+    __WASI_ESUCCESS
 }
+/// ### `path_unlink_file()`
+/// Remove a file or a symlink
+/// Inputs:
+/// - `__wasi_fd_t fd`
+///     The directory that `path` is relative to
+/// - `const char *path`
+///     String containing the path of the file to remove
+/// - `u32 path_len`
+///     The length of the `path` string
 pub fn path_unlink_file(
     ctx: &mut Ctx,
     fd: __wasi_fd_t,
     path: WasmPtr<u8, Array>,
     path_len: u32,
 ) -> __wasi_errno_t {
-    debug!("wasi::path_unlink_file");
-    unimplemented!()
+    debug!(target: "wasi::path_unlink_file", "called");
+    let memory = ctx.memory(0);
+    let state = get_wasi_state(ctx);
+
+    let base_dir = wasi_try!(state.fs.fd_map.get(&fd).ok_or(__WASI_EBADF));
+
+    if !has_rights(base_dir.rights, __WASI_RIGHT_PATH_UNLINK_FILE) {
+        return __WASI_EACCES;
+    }
+
+    let path_string = wasi_try!(read_string(memory, path, path_len));
+    debug!(target: "wasi::path_unlink_file", "=> path: {}", &path_string);
+
+    let (parent, name) = wasi_try!(state.fs.get_parent_inode_at_path(fd, &path_string));
+    let inode = wasi_try!(state.fs.get_child(parent, &name));
+    if let Kind::Dir { .. } = state.fs.inodes[inode].kind {
+        return __WASI_EISDIR;
+    }
+
+    let host_path = wasi_try!(state.fs.host_path(parent, &name));
+    wasi_try!(fs::remove_file(&host_path).map_err(host_error_to_errno));
+    state.fs.remove_entry(parent, &name);
+
+    __WASI_ESUCCESS
+}
+/// How long `poll_oneoff` blocks at a time before checking whether the instance was interrupted
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The time left until `clock` is due, in nanoseconds
+fn clock_timeout(clock: __wasi_subscription_clock_t) -> Result<u64, __wasi_errno_t> {
+    match clock.clock_id {
+        __WASI_CLOCK_MONOTONIC
+        | __WASI_CLOCK_PROCESS_CPUTIME_ID
+        | __WASI_CLOCK_REALTIME
+        | __WASI_CLOCK_THREAD_CPUTIME_ID => (),
+        _ => return Err(__WASI_EINVAL),
+    }
+    if clock.flags & __WASI_SUBSCRIPTION_CLOCK_ABSTIME == 0 {
+        return Ok(clock.timeout);
+    }
+
+    let now = Cell::new(0);
+    match platform_clock_time_get(clock.clock_id, clock.precision, &now) {
+        __WASI_ESUCCESS => Ok(clock.timeout.saturating_sub(now.get())),
+        errno => Err(errno),
+    }
+}
+
+/// Checks whether `fd` is ready to be read from or written to; open files always are
+fn fd_readiness(
+    fs: &mut WasiFs,
+    type_: __wasi_eventtype_t,
+    fd: __wasi_fd_t,
+) -> Result<__wasi_event_fd_readwrite_t, __wasi_errno_t> {
+    let nbytes = match fd {
+        __WASI_STDOUT_FILENO | __WASI_STDERR_FILENO if type_ == __WASI_EVENTTYPE_FD_WRITE => 0,
+        // reading stdin is handled by the caller, as it may block
+        __WASI_STDIN_FILENO | __WASI_STDOUT_FILENO | __WASI_STDERR_FILENO => {
+            return Err(__WASI_EINVAL)
+        }
+        _ => {
+            let fd_entry = fs.fd_map.get(&fd).ok_or(__WASI_EBADF)?;
+
+            if !has_rights(fd_entry.rights, __WASI_RIGHT_POLL_FD_READWRITE) {
+                return Err(__WASI_EACCES);
+            }
+
+            let offset = fd_entry.offset;
+            match &mut fs.inodes[fd_entry.inode].kind {
+                Kind::File {
+                    handle: Some(handle),
+                    ..
+                } if type_ == __WASI_EVENTTYPE_FD_READ => {
+                    let size = handle
+                        .seek(io::SeekFrom::End(0))
+                        .map_err(host_error_to_errno)?;
+                    size.saturating_sub(offset)
+                }
+                Kind::Buffer { buffer } if type_ == __WASI_EVENTTYPE_FD_READ => {
+                    (buffer.len() as u64).saturating_sub(offset)
+                }
+                Kind::File {
+                    handle: Some(_), ..
+                }
+                | Kind::Buffer { .. } => 0,
+                Kind::Dir { .. } => return Err(__WASI_EISDIR),
+                Kind::File { handle: None, .. } | Kind::Symlink { .. } => return Err(__WASI_EBADF),
+            }
+        }
+    };
+
+    Ok(__wasi_event_fd_readwrite_t { nbytes, flags: 0 })
 }
+
+fn poll_event(
+    subscription: &__wasi_subscription_t,
+    readiness: Result<__wasi_event_fd_readwrite_t, __wasi_errno_t>,
+) -> __wasi_event_t {
+    let (error, nbytes, flags) = match readiness {
+        Ok(readwrite) => (__WASI_ESUCCESS, readwrite.nbytes, readwrite.flags),
+        Err(errno) => (errno, 0, 0),
+    };
+    __wasi_event_t {
+        userdata: subscription.userdata,
+        error,
+        type_: subscription.type_,
+        u: EventEnum::FdReadWrite { nbytes, flags }.untagged(),
+    }
+}
+
+/// ### `poll_oneoff()`
+/// Wait until at least one of the subscriptions produces an event
+/// Inputs:
+/// - `const __wasi_subscription_t *in`
+///     The clocks and file descriptors to wait for
+/// - `__wasi_event_t *out`
+///     Where the events are written; it has room for `nsubscriptions` events
+/// - `u32 nsubscriptions`
+///     The number of subscriptions in `in`
+/// Output:
+/// - `u32 *nevents`
+///     The number of events written to `out`
 pub fn poll_oneoff(
     ctx: &mut Ctx,
     in_: WasmPtr<__wasi_subscription_t, Array>,
@@ -1429,15 +1896,104 @@ pub fn poll_oneoff(
     nsubscriptions: u32,
     nevents: WasmPtr<u32>,
 ) -> __wasi_errno_t {
-    debug!("wasi::poll_oneoff");
-    unimplemented!()
+    debug!(target: "wasi::poll_oneoff", "nsubscriptions={}", nsubscriptions);
+    let memory = ctx.memory(0);
+    let state = get_wasi_state(ctx);
+
+    if nsubscriptions == 0 {
+        return __WASI_EINVAL;
+    }
+
+    let subscription_cells = wasi_try!(in_.deref(memory, 0, nsubscriptions));
+    let event_cells = wasi_try!(out_.deref(memory, 0, nsubscriptions));
+    let nevents_cell = wasi_try!(nevents.deref(memory));
+
+    let mut events = Vec::new();
+    // clock subscriptions with the nanoseconds they are due in
+    let mut clocks = Vec::new();
+    let mut stdin_subscriptions = Vec::new();
+
+    for subscription in subscription_cells.iter().map(Cell::get) {
+        match subscription.tagged() {
+            Some(SubscriptionEnum::Clock(clock)) => match clock_timeout(clock) {
+                Ok(timeout) => clocks.push((subscription, timeout)),
+                Err(errno) => events.push(poll_event(&subscription, Err(errno))),
+            },
+            Some(SubscriptionEnum::FdReadWrite(fd_readwrite)) => {
+                if fd_readwrite.fd == __WASI_STDIN_FILENO
+                    && subscription.type_ == __WASI_EVENTTYPE_FD_READ
+                {
+                    stdin_subscriptions.push(subscription);
+                } else {
+                    let readiness =
+                        fd_readiness(&mut state.fs, subscription.type_, fd_readwrite.fd);
+                    events.push(poll_event(&subscription, readiness));
+                }
+            }
+            None => return __WASI_EINVAL,
+        }
+    }
+
+    let start = Instant::now();
+    loop {
+        let elapsed = start.elapsed();
+        let elapsed = elapsed.as_secs() * 1_000_000_000 + u64::from(elapsed.subsec_nanos());
+        // only block when nothing is ready yet
+        let wait = if events.is_empty() {
+            clocks
+                .iter()
+                .map(|&(_, timeout)| timeout.saturating_sub(elapsed))
+                .min()
+        } else {
+            Some(0)
+        };
+
+        // block in short slices, so an interrupted instance isn't kept waiting
+        let slice = wait.map_or(POLL_INTERVAL, |wait| {
+            Duration::from_nanos(wait).min(POLL_INTERVAL)
+        });
+
+        if stdin_subscriptions.is_empty() {
+            // without stdin, at least one clock is pending when nothing is ready
+            thread::sleep(slice);
+        } else if let Some(readwrite) = wasi_try!(platform_poll_stdin(Some(slice))) {
+            for subscription in &stdin_subscriptions {
+                events.push(poll_event(subscription, Ok(readwrite)));
+            }
+        }
+
+        let elapsed = start.elapsed();
+        let elapsed = elapsed.as_secs() * 1_000_000_000 + u64::from(elapsed.subsec_nanos());
+        for (subscription, _) in clocks.iter().filter(|&&(_, timeout)| timeout <= elapsed) {
+            let no_readwrite = __wasi_event_fd_readwrite_t {
+                nbytes: 0,
+                flags: 0,
+            };
+            events.push(poll_event(subscription, Ok(no_readwrite)));
+        }
+
+        // an interrupted wait may return before anything is ready
+        if !events.is_empty() {
+            break;
+        }
+        if ctx.is_interrupted() {
+            return __WASI_EINTR;
+        }
+    }
+
+    for (cell, event) in event_cells.iter().zip(&events) {
+        cell.set(*event);
+    }
+    nevents_cell.set(events.len() as u32);
+
+    __WASI_ESUCCESS
 }
 pub fn proc_exit(ctx: &mut Ctx, code: __wasi_exitcode_t) -> Result<Infallible, ExitCode> {
-    debug!("wasi::proc_exit, {}", code);
+    debug!(target: "wasi::proc_exit", "wasi::proc_exit, {}", code);
     Err(ExitCode { code })
 }
 pub fn proc_raise(ctx: &mut Ctx, sig: __wasi_signal_t) -> __wasi_errno_t {
-    debug!("wasi::proc_raise");
+    debug!(target: "wasi::proc_raise", "called");
     unimplemented!()
 }
 
@@ -1449,7 +2005,7 @@ pub fn proc_raise(ctx: &mut Ctx, sig: __wasi_signal_t) -> __wasi_errno_t {
 /// - `size_t buf_len`
 ///     The number of bytes that will be written
 pub fn random_get(ctx: &mut Ctx, buf: WasmPtr<u8, Array>, buf_len: u32) -> __wasi_errno_t {
-    debug!("wasi::random_get");
+    debug!(target: "wasi::random_get", "called");
     let mut rng = thread_rng();
     let memory = ctx.memory(0);
 
@@ -1466,7 +2022,7 @@ pub fn random_get(ctx: &mut Ctx, buf: WasmPtr<u8, Array>, buf_len: u32) -> __was
 /// ### `sched_yield()`
 /// Yields execution of the thread
 pub fn sched_yield(ctx: &mut Ctx) -> __wasi_errno_t {
-    debug!("wasi::sched_yield");
+    debug!(target: "wasi::sched_yield", "called");
     ::std::thread::yield_now();
     __WASI_ESUCCESS
 }
@@ -1480,7 +2036,7 @@ pub fn sock_recv(
     ro_datalen: WasmPtr<u32>,
     ro_flags: WasmPtr<__wasi_roflags_t>,
 ) -> __wasi_errno_t {
-    debug!("wasi::sock_recv");
+    debug!(target: "wasi::sock_recv", "called");
     unimplemented!()
 }
 pub fn sock_send(
@@ -1491,10 +2047,10 @@ pub fn sock_send(
     si_flags: __wasi_siflags_t,
     so_datalen: WasmPtr<u32>,
 ) -> __wasi_errno_t {
-    debug!("wasi::sock_send");
+    debug!(target: "wasi::sock_send", "called");
     unimplemented!()
 }
 pub fn sock_shutdown(ctx: &mut Ctx, sock: __wasi_fd_t, how: __wasi_sdflags_t) -> __wasi_errno_t {
-    debug!("wasi::sock_shutdown");
+    debug!(target: "wasi::sock_shutdown", "called");
     unimplemented!()
 }
@@ -1,7 +1,7 @@
 #![allow(non_camel_case_types)]
 
 use crate::ptr::{Array, WasmPtr};
-use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt, LE};
 use std::fmt;
 use std::mem;
 use wasmer_runtime_core::types::ValueType;
@@ -43,6 +43,18 @@ pub struct __wasi_dirent_t {
     pub d_type: __wasi_filetype_t,
 }
 
+impl __wasi_dirent_t {
+    /// Lays the dirent out the way `fd_readdir` returns it, before the name it is followed by.
+    pub fn to_bytes(&self) -> [u8; 24] {
+        let mut bytes = [0; 24];
+        LE::write_u64(&mut bytes[0..8], self.d_next);
+        LE::write_u64(&mut bytes[8..16], self.d_ino);
+        LE::write_u32(&mut bytes[16..20], self.d_namlen);
+        bytes[20] = self.d_type;
+        bytes
+    }
+}
+
 pub type __wasi_errno_t = u16;
 pub const __WASI_ESUCCESS: u16 = 0;
 pub const __WASI_E2BIG: u16 = 1;
@@ -174,6 +186,8 @@ impl __wasi_event_t {
     }
 }
 
+unsafe impl ValueType for __wasi_event_t {}
+
 pub type __wasi_eventrwflags_t = u16;
 pub const __WASI_EVENT_FD_READWRITE_HANGUP: u16 = 1 << 0;
 
@@ -449,6 +463,8 @@ impl __wasi_subscription_t {
     }
 }
 
+unsafe impl ValueType for __wasi_subscription_t {}
+
 pub type __wasi_timestamp_t = u64;
 
 pub type __wasi_userdata_t = u64;
@@ -1,10 +1,21 @@
+use crate::state::host_error_to_errno;
 use crate::syscalls::types::*;
 use libc::{
-    clock_getres, clock_gettime, timespec, CLOCK_MONOTONIC, CLOCK_PROCESS_CPUTIME_ID,
-    CLOCK_REALTIME, CLOCK_THREAD_CPUTIME_ID,
+    c_int, clock_getres, clock_gettime, ioctl, poll, pollfd, timespec, CLOCK_MONOTONIC,
+    CLOCK_PROCESS_CPUTIME_ID, CLOCK_REALTIME, CLOCK_THREAD_CPUTIME_ID, FIONREAD, POLLHUP, POLLIN,
+    STDIN_FILENO,
 };
 use std::cell::Cell;
+use std::fs;
+use std::io;
 use std::mem;
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::path::Path;
+use std::time::Duration;
+
+fn timespec_to_nanos(timespec: &timespec) -> __wasi_timestamp_t {
+    timespec.tv_sec as __wasi_timestamp_t * 1_000_000_000 + timespec.tv_nsec as __wasi_timestamp_t
+}
 
 pub fn platform_clock_res_get(
     clock_id: __wasi_clockid_t,
@@ -23,7 +34,7 @@ pub fn platform_clock_res_get(
         (clock_getres(unix_clock_id, &mut timespec_out), timespec_out)
     };
 
-    resolution.set(timespec_out.tv_nsec as __wasi_timestamp_t);
+    resolution.set(timespec_to_nanos(&timespec_out));
 
     // TODO: map output of clock_getres to __wasi_errno_t
     __WASI_ESUCCESS
@@ -52,8 +63,70 @@ pub fn platform_clock_time_get(
 
     // TODO: adjust output by precision...
 
-    time.set(timespec_out.tv_nsec as __wasi_timestamp_t);
+    time.set(timespec_to_nanos(&timespec_out));
 
     // TODO: map output of clock_gettime to __wasi_errno_t
     __WASI_ESUCCESS
 }
+
+pub fn platform_symlink(target: &str, link: &Path) -> Result<(), __wasi_errno_t> {
+    std::os::unix::fs::symlink(target, link).map_err(host_error_to_errno)
+}
+
+/// Opens the file at `path`, failing with `ELOOP` if it was replaced by a symlink.
+pub fn platform_open_file(path: &Path, write: bool) -> io::Result<fs::File> {
+    fs::OpenOptions::new()
+        .read(true)
+        .write(write)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(path)
+}
+
+/// The device and inode numbers that identify a host file.
+pub fn platform_file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// Waits up to `timeout`, or forever, for stdin to be readable.
+pub fn platform_poll_stdin(
+    timeout: Option<Duration>,
+) -> Result<Option<__wasi_event_fd_readwrite_t>, __wasi_errno_t> {
+    let timeout_ms = match timeout {
+        // rounded up, so that the clock that set the timeout is due once it expires
+        Some(timeout) => (timeout.as_secs() * 1000
+            + u64::from((timeout.subsec_nanos() + 999_999) / 1_000_000))
+        .min(c_int::max_value() as u64) as c_int,
+        None => -1,
+    };
+    let mut stdin_pollfd = pollfd {
+        fd: STDIN_FILENO,
+        events: POLLIN,
+        revents: 0,
+    };
+
+    match unsafe { poll(&mut stdin_pollfd, 1, timeout_ms) } {
+        -1 => {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                Ok(None)
+            } else {
+                Err(host_error_to_errno(err))
+            }
+        }
+        0 => Ok(None),
+        _ => {
+            let mut nbytes: c_int = 0;
+            if unsafe { ioctl(STDIN_FILENO, FIONREAD, &mut nbytes as *mut c_int) } == -1 {
+                nbytes = 0;
+            }
+            Ok(Some(__wasi_event_fd_readwrite_t {
+                nbytes: nbytes.max(0) as __wasi_filesize_t,
+                flags: if stdin_pollfd.revents & POLLHUP != 0 {
+                    __WASI_EVENT_FD_READWRITE_HANGUP
+                } else {
+                    0
+                },
+            }))
+        }
+    }
+}
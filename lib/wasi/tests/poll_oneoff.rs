@@ -0,0 +1,133 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use wabt::wat2wasm;
+use wasmer_runtime::{instantiate, Func, Instance};
+use wasmer_wasi::generate_import_object;
+
+const ESUCCESS: i32 = 0;
+const EBADF: i32 = 8;
+const EINTR: i32 = 27;
+
+const EVENTTYPE_CLOCK: i32 = 0;
+const EVENTTYPE_FD_WRITE: i32 = 2;
+
+// Subscriptions are written from 0x100, 56 bytes each, and events are read
+// back from 0x400, 32 bytes each.
+static WAT: &'static str = r#"
+    (module
+    (import "wasi_unstable" "poll_oneoff"
+        (func $poll_oneoff (param i32 i32 i32 i32) (result i32)))
+    (memory 1)
+    (func $subscription (param $index i32) (result i32)
+        (i32.add (i32.const 0x100) (i32.mul (get_local $index) (i32.const 56))))
+    (func $event (param $index i32) (result i32)
+        (i32.add (i32.const 0x400) (i32.mul (get_local $index) (i32.const 32))))
+    (func (export "subscribe_clock") (param $index i32) (param $timeout i64)
+        (local $sub i32)
+        (set_local $sub (call $subscription (get_local $index)))
+        (i64.store (get_local $sub) (i64.extend_u/i32 (get_local $index)))
+        (i32.store8 offset=8 (get_local $sub) (i32.const 0))
+        ;; a relative timeout on the monotonic clock
+        (i32.store offset=24 (get_local $sub) (i32.const 1))
+        (i64.store offset=32 (get_local $sub) (get_local $timeout))
+        (i64.store offset=40 (get_local $sub) (i64.const 0))
+        (i32.store16 offset=48 (get_local $sub) (i32.const 0)))
+    (func (export "subscribe_fd") (param $index i32) (param $type i32) (param $fd i32)
+        (local $sub i32)
+        (set_local $sub (call $subscription (get_local $index)))
+        (i64.store (get_local $sub) (i64.extend_u/i32 (get_local $index)))
+        (i32.store8 offset=8 (get_local $sub) (get_local $type))
+        (i32.store offset=16 (get_local $sub) (get_local $fd)))
+    (func (export "poll") (param $nsubscriptions i32) (result i32)
+        (call $poll_oneoff (i32.const 0x100) (i32.const 0x400) (get_local $nsubscriptions)
+            (i32.const 0)))
+    (func (export "nevents") (result i32)
+        (i32.load (i32.const 0)))
+    (func (export "event_userdata") (param $index i32) (result i64)
+        (i64.load (call $event (get_local $index))))
+    (func (export "event_error") (param $index i32) (result i32)
+        (i32.load16_u offset=8 (call $event (get_local $index))))
+    (func (export "event_type") (param $index i32) (result i32)
+        (i32.load8_u offset=10 (call $event (get_local $index))))
+    )
+"#;
+
+fn poll_instance() -> Instance {
+    let wasm = wat2wasm(WAT).unwrap();
+    instantiate(&wasm, &generate_import_object(vec![], vec![], vec![])).unwrap()
+}
+
+#[test]
+fn clock_subscriptions_wait_for_the_earliest_clock() {
+    let instance = poll_instance();
+    let subscribe_clock: Func<(i32, i64)> = instance.func("subscribe_clock").unwrap();
+    let poll: Func<i32, i32> = instance.func("poll").unwrap();
+    let nevents: Func<(), i32> = instance.func("nevents").unwrap();
+    let event_userdata: Func<i32, i64> = instance.func("event_userdata").unwrap();
+    let event_type: Func<i32, i32> = instance.func("event_type").unwrap();
+
+    subscribe_clock.call(0, 60_000_000_000).unwrap();
+    subscribe_clock.call(1, 50_000_000).unwrap();
+
+    let start = Instant::now();
+    assert_eq!(poll.call(2).unwrap(), ESUCCESS);
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(50));
+    assert!(elapsed < Duration::from_secs(30));
+
+    assert_eq!(nevents.call().unwrap(), 1);
+    assert_eq!(event_userdata.call(0).unwrap(), 1);
+    assert_eq!(event_type.call(0).unwrap(), EVENTTYPE_CLOCK);
+}
+
+#[test]
+fn ready_fd_subscriptions_do_not_wait_for_clocks() {
+    let instance = poll_instance();
+    let subscribe_clock: Func<(i32, i64)> = instance.func("subscribe_clock").unwrap();
+    let subscribe_fd: Func<(i32, i32, i32)> = instance.func("subscribe_fd").unwrap();
+    let poll: Func<i32, i32> = instance.func("poll").unwrap();
+    let nevents: Func<(), i32> = instance.func("nevents").unwrap();
+    let event_userdata: Func<i32, i64> = instance.func("event_userdata").unwrap();
+    let event_error: Func<i32, i32> = instance.func("event_error").unwrap();
+    let event_type: Func<i32, i32> = instance.func("event_type").unwrap();
+
+    subscribe_clock.call(0, 60_000_000_000).unwrap();
+    // stdout can always be written to, and fd 42 isn't open
+    subscribe_fd.call(1, EVENTTYPE_FD_WRITE, 1).unwrap();
+    subscribe_fd.call(2, EVENTTYPE_FD_WRITE, 42).unwrap();
+
+    let start = Instant::now();
+    assert_eq!(poll.call(3).unwrap(), ESUCCESS);
+    assert!(start.elapsed() < Duration::from_secs(30));
+
+    assert_eq!(nevents.call().unwrap(), 2);
+    assert_eq!(event_userdata.call(0).unwrap(), 1);
+    assert_eq!(event_error.call(0).unwrap(), ESUCCESS);
+    assert_eq!(event_type.call(0).unwrap(), EVENTTYPE_FD_WRITE);
+    assert_eq!(event_userdata.call(1).unwrap(), 2);
+    assert_eq!(event_error.call(1).unwrap(), EBADF);
+}
+
+#[test]
+fn interrupting_the_instance_stops_a_clock_wait() {
+    let instance = poll_instance();
+    let subscribe_clock: Func<(i32, i64)> = instance.func("subscribe_clock").unwrap();
+    let poll: Func<i32, i32> = instance.func("poll").unwrap();
+
+    subscribe_clock.call(0, 60_000_000_000).unwrap();
+
+    let handle = instance.interrupt_handle();
+    let interrupter = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.interrupt();
+    });
+
+    let start = Instant::now();
+    // the interrupt may also be noticed by the compiled code once the call returns
+    let result = poll.call(1);
+    assert!(start.elapsed() < Duration::from_secs(30));
+    assert!(result.is_err() || result.unwrap() == EINTR);
+    interrupter.join().unwrap();
+}
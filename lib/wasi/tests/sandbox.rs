@@ -0,0 +1,128 @@
+use std::fs;
+use tempfile::TempDir;
+use wabt::wat2wasm;
+use wasmer_runtime::{instantiate, Func, Instance};
+use wasmer_wasi::generate_import_object;
+
+const ESUCCESS: i32 = 0;
+const EACCES: i32 = 2;
+#[cfg(unix)]
+const ELOOP: i32 = 32;
+const ENOTCAPABLE: i32 = 76;
+
+const ALL_RIGHTS: i64 = 0x1FFF_FFFF;
+const RIGHT_FD_READ: i64 = 1 << 1;
+const RIGHT_PATH_OPEN: i64 = 1 << 13;
+#[cfg(unix)]
+const LOOKUP_SYMLINK_FOLLOW: i32 = 1;
+
+// Exposes the syscalls under test, with the paths they are given stored in memory.
+static WAT: &'static str = r#"
+    (module
+    (import "wasi_unstable" "path_open"
+        (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+    (import "wasi_unstable" "fd_fdstat_set_rights"
+        (func $fd_fdstat_set_rights (param i32 i64 i64) (result i32)))
+    (import "wasi_unstable" "fd_allocate"
+        (func $fd_allocate (param i32 i64 i64) (result i32)))
+    (import "wasi_unstable" "path_filestat_get"
+        (func $path_filestat_get (param i32 i32 i32 i32 i32) (result i32)))
+    (memory 1)
+    (data (i32.const 16) "../secret")
+    (data (i32.const 32) "sub/../../secret")
+    (data (i32.const 64) "link")
+    (data (i32.const 80) "/etc")
+    (data (i32.const 96) "file")
+    (func (export "open") (param $path i32) (param $len i32) (param $dirflags i32)
+        (param $rights i64) (result i32)
+        (call $path_open (i32.const 3) (get_local $dirflags) (get_local $path) (get_local $len)
+            (i32.const 0) (get_local $rights) (get_local $rights) (i32.const 0) (i32.const 0)))
+    (func (export "opened_fd") (result i32)
+        (i32.load (i32.const 0)))
+    (func (export "set_rights") (param $fd i32) (param $rights i64) (result i32)
+        (call $fd_fdstat_set_rights (get_local $fd) (get_local $rights) (get_local $rights)))
+    (func (export "allocate") (param $fd i32) (result i32)
+        (call $fd_allocate (get_local $fd) (i64.const 0) (i64.const 16)))
+    (func (export "stat") (param $path i32) (param $len i32) (result i32)
+        (call $path_filestat_get (i32.const 3) (i32.const 0) (get_local $path) (get_local $len)
+            (i32.const 128)))
+    )
+"#;
+
+/// Lays out `secret` next to the `sandbox` directory, and instantiates the
+/// module with only `sandbox` pre-opened.
+fn sandboxed_instance() -> (TempDir, Instance) {
+    let dir = tempfile::tempdir().unwrap();
+    let sandbox = dir.path().join("sandbox");
+    fs::write(dir.path().join("secret"), b"secret").unwrap();
+    fs::create_dir_all(sandbox.join("sub")).unwrap();
+    fs::write(sandbox.join("file"), b"file").unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("../secret", sandbox.join("link")).unwrap();
+
+    let wasm = wat2wasm(WAT).unwrap();
+    let import_object =
+        generate_import_object(vec![], vec![], vec![sandbox.to_str().unwrap().to_string()]);
+    let instance = instantiate(&wasm, &import_object).unwrap();
+    (dir, instance)
+}
+
+#[test]
+fn paths_cannot_escape_a_preopened_directory() {
+    let (_dir, instance) = sandboxed_instance();
+    let open: Func<(i32, i32, i32, i64), i32> = instance.func("open").unwrap();
+
+    assert_eq!(open.call(16, 9, 0, ALL_RIGHTS).unwrap(), ENOTCAPABLE);
+    assert_eq!(open.call(32, 16, 0, ALL_RIGHTS).unwrap(), ENOTCAPABLE);
+    assert_eq!(open.call(80, 4, 0, ALL_RIGHTS).unwrap(), ENOTCAPABLE);
+    assert_eq!(open.call(96, 4, 0, ALL_RIGHTS).unwrap(), ESUCCESS);
+
+    #[cfg(unix)]
+    {
+        assert_eq!(open.call(64, 4, 0, ALL_RIGHTS).unwrap(), ELOOP);
+        assert_eq!(
+            open.call(64, 4, LOOKUP_SYMLINK_FOLLOW, ALL_RIGHTS).unwrap(),
+            ENOTCAPABLE
+        );
+    }
+}
+
+#[test]
+fn missing_rights_are_denied() {
+    let (_dir, instance) = sandboxed_instance();
+    let open: Func<(i32, i32, i32, i64), i32> = instance.func("open").unwrap();
+    let opened_fd: Func<(), i32> = instance.func("opened_fd").unwrap();
+    let set_rights: Func<(i32, i64), i32> = instance.func("set_rights").unwrap();
+    let allocate: Func<i32, i32> = instance.func("allocate").unwrap();
+
+    // a read-only file descriptor can't be grown
+    assert_eq!(open.call(96, 4, 0, RIGHT_FD_READ).unwrap(), ESUCCESS);
+    let fd = opened_fd.call().unwrap();
+    assert_eq!(allocate.call(fd).unwrap(), EACCES);
+
+    // rights that were dropped can't be taken back
+    assert_eq!(set_rights.call(3, RIGHT_FD_READ).unwrap(), ESUCCESS);
+    assert_eq!(open.call(96, 4, 0, RIGHT_FD_READ).unwrap(), EACCES);
+    assert_eq!(set_rights.call(3, RIGHT_PATH_OPEN).unwrap(), ENOTCAPABLE);
+}
+
+#[test]
+#[cfg(unix)]
+fn files_swapped_on_the_host_after_lookup_are_not_opened() {
+    let (dir, instance) = sandboxed_instance();
+    let open: Func<(i32, i32, i32, i64), i32> = instance.func("open").unwrap();
+    let stat: Func<(i32, i32), i32> = instance.func("stat").unwrap();
+    let file = dir.path().join("sandbox").join("file");
+    let secret = dir.path().join("secret");
+
+    // looks `file` up without opening it
+    assert_eq!(stat.call(96, 4).unwrap(), ESUCCESS);
+
+    fs::remove_file(&file).unwrap();
+    std::os::unix::fs::symlink(&secret, &file).unwrap();
+    assert_eq!(open.call(96, 4, 0, ALL_RIGHTS).unwrap(), ELOOP);
+
+    fs::remove_file(&file).unwrap();
+    fs::hard_link(&secret, &file).unwrap();
+    assert_eq!(open.call(96, 4, 0, ALL_RIGHTS).unwrap(), ENOTCAPABLE);
+}
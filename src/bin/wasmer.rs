@@ -7,22 +7,29 @@ use std::io::Read;
 use std::path::PathBuf;
 use std::process::exit;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 
 use hashbrown::HashMap;
-use structopt::StructOpt;
+use structopt::{clap::ErrorKind, StructOpt};
 
+use wasmer::inspect::{Abi, ModuleSummary};
 use wasmer::*;
 use wasmer_clif_backend::CraneliftCompiler;
 #[cfg(feature = "backend:llvm")]
 use wasmer_llvm_backend::LLVMCompiler;
 use wasmer_runtime::{
     cache::{Cache as BaseCache, FileSystemCache, WasmHash, WASMER_VERSION_HASH},
-    error::RuntimeError,
-    Func, Value,
+    error::{CallError, Error, RuntimeError, Trap, WasmTrapInfo},
+    Func, Instance, InterruptHandle, Module,
 };
 use wasmer_runtime_core::{
     self,
     backend::{Compiler, CompilerConfig},
+    import::ImportObject,
+    limits::MemoryLimit,
+    units::{Bytes, Pages},
 };
 #[cfg(feature = "backend:singlepass")]
 use wasmer_singlepass_backend::SinglePassCompiler;
@@ -34,6 +41,10 @@ use wasmer_wasi;
 mod wasmer_wasi {
     use wasmer_runtime_core::{import::ImportObject, module::Module};
 
+    pub struct ExitCode {
+        pub code: u32,
+    }
+
     pub fn is_wasi_module(_module: &Module) -> bool {
         false
     }
@@ -59,12 +70,21 @@ enum CLIOptions {
     #[structopt(name = "validate")]
     Validate(Validate),
 
+    /// Run a .wast spec script and report the assertions that fail
+    #[structopt(name = "wast")]
+    Wast(Wast),
+
+    /// Show the imports, exports and sections of a WebAssembly module
+    #[structopt(name = "inspect")]
+    Inspect(Inspect),
+
     /// Update wasmer to the latest version
     #[structopt(name = "self-update")]
     SelfUpdate,
 }
 
 #[derive(Debug, StructOpt)]
+#[structopt(raw(after_help = "EXIT_STATUS_HELP"))]
 struct Run {
     // Disable the cache
     #[structopt(long = "disable-cache")]
@@ -86,6 +106,14 @@ struct Run {
     #[structopt(long = "em-symbol-map", parse(from_os_str), group = "emscripten")]
     em_symbol_map: Option<PathBuf>,
 
+    /// Emscripten filesystem image (a .tar or .tar.zst archive) to use instead of the host's
+    #[structopt(long = "em-vfs-image", parse(from_os_str))]
+    em_vfs_image: Option<PathBuf>,
+
+    /// Copy a host directory into the Emscripten filesystem (GUEST_DIR:HOST_DIR)
+    #[structopt(long = "em-mapdir", multiple = true)]
+    em_mapped_dirs: Vec<String>,
+
     /// WASI pre-opened directory
     #[structopt(long = "dir", multiple = true, group = "wasi")]
     pre_opened_directories: Vec<String>,
@@ -93,6 +121,23 @@ struct Run {
     #[structopt(long = "command-name", hidden = true)]
     command_name: Option<String>,
 
+    /// Stop the program if it runs for longer than the given duration (e.g. 500ms, 30s, 2m)
+    #[structopt(long = "timeout", parse(try_from_str = "utils::parse_duration"))]
+    timeout: Option<Duration>,
+
+    /// Limit the memory the program may allocate for its memories and tables (e.g. 64MiB, 1GiB)
+    #[structopt(long = "max-memory", parse(try_from_str = "utils::parse_memory_size"))]
+    max_memory: Option<usize>,
+
+    /// The exported function to call, with the application arguments as its parameters,
+    /// in modules that are neither Emscripten nor WASI modules
+    #[structopt(long = "invoke", default_value = "main")]
+    invoke: String,
+
+    /// Log runtime events to stderr, with a filter like RUST_LOG's (e.g. debug, wasi=debug, emscripten::___syscall5=debug)
+    #[structopt(long = "log")]
+    log: Option<String>,
+
     /// Application arguments
     #[structopt(name = "--", raw(multiple = "true"))]
     args: Vec<String>,
@@ -152,6 +197,48 @@ struct Validate {
     path: PathBuf,
 }
 
+#[derive(Debug, StructOpt)]
+struct Wast {
+    /// Input file
+    #[structopt(parse(from_os_str))]
+    path: PathBuf,
+
+    /// The backend to compile the modules of the script with
+    #[structopt(
+        long = "backend",
+        default_value = "cranelift",
+        raw(possible_values = "Backend::variants()", case_insensitive = "true")
+    )]
+    backend: Backend,
+}
+
+#[derive(Debug, StructOpt)]
+struct Inspect {
+    /// Input file. Formats accepted: wasm, wast
+    #[structopt(parse(from_os_str))]
+    path: PathBuf,
+
+    /// Print the summary as JSON
+    #[structopt(long = "json")]
+    json: bool,
+}
+
+/// Get the compiler of the given backend
+fn get_compiler(backend: &Backend) -> Result<Box<dyn Compiler>, String> {
+    let compiler: Box<dyn Compiler> = match backend {
+        #[cfg(feature = "backend:singlepass")]
+        Backend::Singlepass => Box::new(SinglePassCompiler::new()),
+        #[cfg(not(feature = "backend:singlepass"))]
+        Backend::Singlepass => return Err("The singlepass backend is not enabled".to_string()),
+        Backend::Cranelift => Box::new(CraneliftCompiler::new()),
+        #[cfg(feature = "backend:llvm")]
+        Backend::LLVM => Box::new(LLVMCompiler::new()),
+        #[cfg(not(feature = "backend:llvm"))]
+        Backend::LLVM => return Err("the llvm backend is not enabled".to_string()),
+    };
+    Ok(compiler)
+}
+
 /// Read the contents of a file
 fn read_file_contents(path: &PathBuf) -> Result<Vec<u8>, io::Error> {
     let mut buffer: Vec<u8> = Vec::new();
@@ -175,8 +262,252 @@ fn get_cache_dir() -> PathBuf {
     }
 }
 
+/// The exit code of `wasmer run` when the program is stopped by `--timeout`.
+const TIMEOUT_EXIT_CODE: i32 = 124;
+/// How long the program gets to stop after being interrupted by `--timeout`,
+/// e.g. while it's blocked in a host call, before `wasmer run` exits anyway.
+const TIMEOUT_GRACE_PERIOD: Duration = Duration::from_secs(1);
+// The errors of `wasmer run` itself use the codes of `sysexits.h`, as shells
+// give 126 and 127 their own meaning, and `timeout` does the same for 125.
+/// The exit code of `wasmer` when its arguments or options are invalid (`EX_USAGE`).
+const USAGE_EXIT_CODE: i32 = 64;
+/// The exit code of `wasmer run` when it fails before running the program (`EX_NOINPUT`).
+const ERROR_EXIT_CODE: i32 = 66;
+/// The exit code of `wasmer run` when the module can't be compiled (`EX_DATAERR`).
+const COMPILE_ERROR_EXIT_CODE: i32 = 65;
+/// The exit code of `wasmer run` when the module can't be instantiated (`EX_UNAVAILABLE`).
+const LINK_ERROR_EXIT_CODE: i32 = 69;
+
+/// The signals a native program would be killed by where a wasm program traps.
+/// `wasmer run` exits with 128 plus the signal, as shells report signals.
+const SIGILL: i32 = 4;
+const SIGABRT: i32 = 6;
+const SIGFPE: i32 = 8;
+const SIGSEGV: i32 = 11;
+
+const EXIT_STATUS_HELP: &str = "EXIT STATUS:
+    The exit code of the program, when it calls WASI's proc_exit or Emscripten's exit.
+    64      The arguments or options are invalid, e.g. --invoke names a missing function.
+    65      The module couldn't be compiled.
+    66      The program couldn't be run, e.g. the file couldn't be read.
+    69      The module couldn't be instantiated, e.g. an import is missing.
+    124     The program was stopped by --timeout.
+    132     The program trapped on unreachable code or a mismatching call_indirect (SIGILL).
+    134     A host function failed, or the program trapped for another reason (SIGABRT).
+    136     The program trapped on an integer division by zero or overflow (SIGFPE).
+    139     The program trapped on an out of bounds or misaligned memory access, or an out of bounds
+            table index (SIGSEGV).";
+
+/// Why `wasmer run` couldn't run a program, which decides its exit code.
+enum RunError {
+    Usage(String),
+    Error(String),
+    Compile(String),
+    Link(String),
+}
+
+impl RunError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            RunError::Usage(_) => USAGE_EXIT_CODE,
+            RunError::Error(_) => ERROR_EXIT_CODE,
+            RunError::Compile(_) => COMPILE_ERROR_EXIT_CODE,
+            RunError::Link(_) => LINK_ERROR_EXIT_CODE,
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            RunError::Usage(message)
+            | RunError::Error(message)
+            | RunError::Compile(message)
+            | RunError::Link(message) => message,
+        }
+    }
+}
+
+impl From<String> for RunError {
+    fn from(message: String) -> RunError {
+        RunError::Error(message)
+    }
+}
+
+/// Stops the program once `--timeout` has elapsed, if a timeout was given.
+///
+/// It starts before the module is instantiated, as that runs its start function.
+/// The instance it watches is interrupted first, and if the program still hasn't
+/// stopped after `TIMEOUT_GRACE_PERIOD`, or is still being instantiated, the whole
+/// process exits with the timeout status.
+struct Watchdog {
+    instance: Option<Arc<Mutex<Option<InterruptHandle>>>>,
+}
+
+impl Watchdog {
+    fn start(timeout: Option<Duration>) -> Watchdog {
+        let instance = timeout.map(|timeout| {
+            let instance = Arc::new(Mutex::new(None::<InterruptHandle>));
+            let watched = Arc::clone(&instance);
+            thread::spawn(move || {
+                thread::sleep(timeout);
+                let handle = watched.lock().unwrap().take();
+                if let Some(handle) = handle {
+                    handle.interrupt();
+                    thread::sleep(TIMEOUT_GRACE_PERIOD);
+                }
+                exit_timed_out(timeout);
+            });
+            instance
+        });
+        Watchdog { instance }
+    }
+
+    /// Interrupts `instance` instead of exiting right away once the timeout elapses.
+    fn watch(&self, instance: &Instance) {
+        if let Some(watched) = &self.instance {
+            *watched.lock().unwrap() = Some(instance.interrupt_handle());
+        }
+    }
+}
+
+/// Applies the `--max-memory` limit to the instances created with `import_object`.
+fn limit_memory(import_object: &mut ImportObject, max_memory: Option<usize>) {
+    if let Some(max_memory) = max_memory {
+        let max_pages: Pages = Bytes(max_memory).into();
+        import_object.set_resource_limiter(move || MemoryLimit::new(max_pages));
+    }
+}
+
+/// Builds the filesystem requested with `--em-vfs-image` and `--em-mapdir`, if any.
+#[cfg(feature = "vfs")]
+fn emscripten_vfs(options: &Run) -> Result<Option<wasmer_emscripten::EmscriptenVfs>, RunError> {
+    use wasmer_runtime_abi::vfs::vfs::Vfs;
+
+    if options.em_vfs_image.is_none() && options.em_mapped_dirs.is_empty() {
+        return Ok(None);
+    }
+
+    let mut vfs = match &options.em_vfs_image {
+        Some(image_path) => {
+            let image = File::open(image_path)
+                .map_err(|err| format!("Can't open the image {}: {}", image_path.display(), err))?;
+            let vfs = if image_path.extension().map_or(false, |ext| ext == "zst") {
+                Vfs::from_tar_zstd_bytes(image)
+            } else {
+                Vfs::from_tar_bytes(image)
+            };
+            vfs.map_err(|err| format!("Can't load the image {}: {}", image_path.display(), err))?
+        }
+        None => {
+            Vfs::new()
+                .map_err(|err| format!("Can't create the filesystem: {}", err))?
+                .0
+        }
+    };
+
+    for mapped_dir in &options.em_mapped_dirs {
+        let mut parts = mapped_dir.splitn(2, ':');
+        let (guest_dir, host_dir) = match (parts.next(), parts.next()) {
+            (Some(guest_dir), Some(host_dir)) => (guest_dir, host_dir),
+            _ => {
+                return Err(RunError::Usage(format!(
+                    "Invalid --em-mapdir \"{}\", expected GUEST_DIR:HOST_DIR",
+                    mapped_dir
+                )))
+            }
+        };
+        vfs.map_host_dir(host_dir, guest_dir)
+            .map_err(|err| format!("Can't map {} to {}: {}", host_dir, guest_dir, err))?;
+    }
+
+    Ok(Some(wasmer_emscripten::EmscriptenVfs::new(vfs)))
+}
+
+#[cfg(not(feature = "vfs"))]
+fn emscripten_vfs(options: &Run) -> Result<Option<()>, RunError> {
+    if options.em_vfs_image.is_some() || !options.em_mapped_dirs.is_empty() {
+        return Err(RunError::Usage(
+            "--em-vfs-image and --em-mapdir need wasmer to be built with the `vfs` feature"
+                .to_string(),
+        ));
+    }
+    Ok(None)
+}
+
+fn exit_timed_out(timeout: Duration) -> ! {
+    eprintln!(
+        "Error: the program didn't finish within {:?} and was stopped",
+        timeout
+    );
+    exit(TIMEOUT_EXIT_CODE);
+}
+
+/// Prints the trap and its wasm backtrace, like a panic would.
+fn exit_trapped(trap: &Trap) -> ! {
+    eprintln!("wasm trap occured: {}", trap);
+    eprint!("{}", trap.format_backtrace());
+    let signal = match trap.info {
+        WasmTrapInfo::Unreachable | WasmTrapInfo::IncorrectCallIndirectSignature => SIGILL,
+        WasmTrapInfo::MemoryOutOfBounds
+        | WasmTrapInfo::CallIndirectOOB
+        | WasmTrapInfo::MisalignedAtomicAccess
+        | WasmTrapInfo::TableOutOfBounds => SIGSEGV,
+        WasmTrapInfo::IllegalArithmetic => SIGFPE,
+        WasmTrapInfo::UnsharedMemoryWait | WasmTrapInfo::Unknown => SIGABRT,
+    };
+    exit(128 + signal);
+}
+
+/// Exits the way the program stopped: with its exit code if it exited, or
+/// with the status of the trap or failure that stopped it.
+fn exit_stopped(options: &Run, err: RuntimeError) -> ! {
+    let data = match err {
+        RuntimeError::Trap { trap } => exit_trapped(&trap),
+        RuntimeError::Interrupted => exit_timed_out(options.timeout.unwrap_or_default()),
+        RuntimeError::FuelExhausted => {
+            eprintln!("Error: the program ran out of fuel");
+            exit(128 + SIGABRT);
+        }
+        RuntimeError::Error { data } => data,
+    };
+
+    if let Some(exit_code) = data.downcast_ref::<wasmer_wasi::ExitCode>() {
+        exit(exit_code.code as i32);
+    }
+    if let Some(exit_code) = data.downcast_ref::<wasmer_emscripten::ExitCode>() {
+        exit(exit_code.code);
+    }
+
+    let message = if let Some(message) = data.downcast_ref::<String>() {
+        message.as_str()
+    } else if let Some(message) = data.downcast_ref::<&str>() {
+        *message
+    } else {
+        "unknown error"
+    };
+    eprintln!("Error: a host function failed: {}", message);
+    exit(128 + SIGABRT);
+}
+
+/// Instantiates the module, and starts the `--timeout` watchdog. A start
+/// function that traps or exits stops the program like its main function would.
+fn instantiate(
+    options: &Run,
+    module: &Module,
+    import_object: &ImportObject,
+) -> Result<Instance, RunError> {
+    let watchdog = Watchdog::start(options.timeout);
+    let instance = module.instantiate(import_object).map_err(|err| match err {
+        Error::RuntimeError(err) | Error::CallError(CallError::Runtime(err)) => {
+            exit_stopped(options, err)
+        }
+        err => RunError::Link(format!("Can't instantiate module: {}", err)),
+    })?;
+    watchdog.watch(&instance);
+    Ok(instance)
+}
+
 /// Execute a wasm/wat file
-fn execute_wasm(options: &Run) -> Result<(), String> {
+fn execute_wasm(options: &Run) -> Result<(), RunError> {
     // force disable caching on windows
     #[cfg(target_os = "windows")]
     let disable_cache = true;
@@ -240,17 +571,7 @@ fn execute_wasm(options: &Run) -> Result<(), String> {
             .map_err(|e| format!("Can't convert from wast to wasm: {:?}", e))?;
     }
 
-    let compiler: Box<dyn Compiler> = match options.backend {
-        #[cfg(feature = "backend:singlepass")]
-        Backend::Singlepass => Box::new(SinglePassCompiler::new()),
-        #[cfg(not(feature = "backend:singlepass"))]
-        Backend::Singlepass => return Err("The singlepass backend is not enabled".to_string()),
-        Backend::Cranelift => Box::new(CraneliftCompiler::new()),
-        #[cfg(feature = "backend:llvm")]
-        Backend::LLVM => Box::new(LLVMCompiler::new()),
-        #[cfg(not(feature = "backend:llvm"))]
-        Backend::LLVM => return Err("the llvm backend is not enabled".to_string()),
-    };
+    let compiler = get_compiler(&options.backend).map_err(RunError::Usage)?;
 
     let module = if !disable_cache {
         // If we have cache enabled
@@ -268,10 +589,11 @@ fn execute_wasm(options: &Run) -> Result<(), String> {
             FileSystemCache::new(wasmer_cache_dir).map_err(|e| format!("Cache error: {:?}", e))?
         };
 
-        // cache.load will return the Module if it's able to deserialize it properly, and an error if:
+        // cache.load_with will return the Module if it's able to deserialize it properly, and an error if:
         // * The file is not found
         // * The file exists, but it's corrupted or can't be converted to a module
-        let module = match cache.load(hash) {
+        // * The file was compiled with a different backend or for a different target
+        let module = match cache.load_with(hash, &*compiler) {
             Ok(module) => {
                 // We are able to load the module from cache
                 module
@@ -281,10 +603,11 @@ fn execute_wasm(options: &Run) -> Result<(), String> {
                     &wasm_binary[..],
                     CompilerConfig {
                         symbol_map: em_symbol_map,
+                        ..Default::default()
                     },
                     &*compiler,
                 )
-                .map_err(|e| format!("Can't compile module: {:?}", e))?;
+                .map_err(|e| RunError::Compile(format!("Can't compile module: {}", e)))?;
                 // We try to save the module into a cache file
                 cache.store(hash, module.clone()).unwrap_or_default();
 
@@ -297,34 +620,49 @@ fn execute_wasm(options: &Run) -> Result<(), String> {
             &wasm_binary[..],
             CompilerConfig {
                 symbol_map: em_symbol_map,
+                ..Default::default()
             },
             &*compiler,
         )
-        .map_err(|e| format!("Can't compile module: {:?}", e))?
+        .map_err(|e| RunError::Compile(format!("Can't compile module: {}", e)))?
     };
 
     // TODO: refactor this
     if wasmer_emscripten::is_emscripten_module(&module) {
         let mut emscripten_globals = wasmer_emscripten::EmscriptenGlobals::new(&module);
-        let import_object = wasmer_emscripten::generate_emscripten_env(&mut emscripten_globals);
-        let mut instance = module
-            .instantiate(&import_object)
-            .map_err(|e| format!("Can't instantiate module: {:?}", e))?;
-
-        wasmer_emscripten::run_emscripten_instance(
-            &module,
-            &mut instance,
-            if let Some(cn) = &options.command_name {
-                cn
-            } else {
-                options.path.to_str().unwrap()
-            },
-            options.args.iter().map(|arg| arg.as_str()).collect(),
-        )
-        .map_err(|e| format!("{:?}", e))?;
+        let mut import_object = wasmer_emscripten::generate_emscripten_env(&mut emscripten_globals);
+        limit_memory(&mut import_object, options.max_memory);
+        let mut instance = instantiate(options, &module, &import_object)?;
+
+        let program_name = if let Some(cn) = &options.command_name {
+            cn
+        } else {
+            options.path.to_str().unwrap()
+        };
+        let args = options.args.iter().map(|arg| arg.as_str()).collect();
+        let result = match emscripten_vfs(options)? {
+            #[cfg(feature = "vfs")]
+            Some(vfs) => wasmer_emscripten::run_emscripten_instance_with_vfs(
+                &module,
+                &mut instance,
+                program_name,
+                args,
+                vfs,
+            ),
+            _ => wasmer_emscripten::run_emscripten_instance(
+                &module,
+                &mut instance,
+                program_name,
+                args,
+            ),
+        };
+        match result {
+            Err(CallError::Runtime(err)) => exit_stopped(options, err),
+            result => result.map_err(|e| e.to_string())?,
+        }
     } else {
         if cfg!(feature = "wasi") && wasmer_wasi::is_wasi_module(&module) {
-            let import_object = wasmer_wasi::generate_import_object(
+            let mut import_object = wasmer_wasi::generate_import_object(
                 if let Some(cn) = &options.command_name {
                     [cn.clone()]
                 } else {
@@ -340,43 +678,36 @@ fn execute_wasm(options: &Run) -> Result<(), String> {
                     .collect(),
                 options.pre_opened_directories.clone(),
             );
+            limit_memory(&mut import_object, options.max_memory);
 
-            let instance = module
-                .instantiate(&import_object)
-                .map_err(|e| format!("Can't instantiate module: {:?}", e))?;
-
-            let start: Func<(), ()> = instance.func("_start").map_err(|e| format!("{:?}", e))?;
+            let instance = instantiate(options, &module, &import_object)?;
 
-            let result = start.call();
+            let start: Func<(), ()> = instance.func("_start").map_err(|e| e.to_string())?;
 
-            if let Err(ref err) = result {
-                match err {
-                    RuntimeError::Trap { msg } => panic!("wasm trap occured: {}", msg),
-                    RuntimeError::Error { data } => {
-                        if let Some(error_code) = data.downcast_ref::<wasmer_wasi::ExitCode>() {
-                            std::process::exit(error_code.code as i32)
-                        }
-                    }
-                }
-                panic!("error: {:?}", err)
+            if let Err(err) = start.call() {
+                exit_stopped(options, err);
             }
         } else {
-            let import_object = wasmer_runtime_core::import::ImportObject::new();
-            let instance = module
-                .instantiate(&import_object)
-                .map_err(|e| format!("Can't instantiate module: {:?}", e))?;
-
-            let args: Vec<Value> = options
-                .args
-                .iter()
-                .map(|arg| arg.as_str())
-                .map(|x| Value::I32(x.parse().unwrap()))
-                .collect();
-            instance
-                .dyn_func("main")
-                .map_err(|e| format!("{:?}", e))?
-                .call(&args)
-                .map_err(|e| format!("{:?}", e))?;
+            let mut import_object = ImportObject::new();
+            limit_memory(&mut import_object, options.max_memory);
+            let instance = instantiate(options, &module, &import_object)?;
+
+            let func = instance.dyn_func(&options.invoke).map_err(|e| {
+                RunError::Usage(format!(
+                    "Can't find the function `{}`: {:?}",
+                    options.invoke, e
+                ))
+            })?;
+            let args = utils::parse_args(func.signature(), &options.args)
+                .map_err(|e| RunError::Usage(format!("Can't call `{}`: {}", options.invoke, e)))?;
+            let result = func.call(&args);
+            let values = match result {
+                Err(CallError::Runtime(err)) => exit_stopped(options, err),
+                result => result.map_err(|e| e.to_string())?,
+            };
+            for value in &values {
+                println!("{}", utils::format_value(value));
+            }
         }
     }
 
@@ -384,12 +715,9 @@ fn execute_wasm(options: &Run) -> Result<(), String> {
 }
 
 fn run(options: Run) {
-    match execute_wasm(&options) {
-        Ok(()) => {}
-        Err(message) => {
-            eprintln!("{:?}", message);
-            exit(1);
-        }
+    if let Err(err) = execute_wasm(&options) {
+        eprintln!("Error: {}", err.message());
+        exit(err.exit_code());
     }
 }
 
@@ -429,8 +757,126 @@ fn validate(validate: Validate) {
     }
 }
 
+fn run_wast_script(options: Wast) -> Result<bool, String> {
+    let wast_path = options.path;
+
+    let source: Vec<u8> = read_file_contents(&wast_path).map_err(|err| {
+        format!(
+            "Can't read the file {}: {}",
+            wast_path.as_os_str().to_string_lossy(),
+            err
+        )
+    })?;
+    let filename = wast_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let compiler = get_compiler(&options.backend)?;
+    let report = wast::run_wast(&source, &filename, &*compiler)?;
+
+    for failure in &report.failures {
+        println!("{}: {}", filename, failure);
+    }
+    println!(
+        "{}: {} passed, {} failed",
+        filename,
+        report.passed,
+        report.failures.len()
+    );
+
+    Ok(report.failures.is_empty())
+}
+
+/// Runs logic for the `wast` subcommand
+fn wast(options: Wast) {
+    match run_wast_script(options) {
+        Ok(true) => {}
+        Ok(false) => exit(1),
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            exit(-1);
+        }
+    }
+}
+
+fn inspect_wasm(options: Inspect) -> Result<(), String> {
+    let wasm_path = options.path;
+
+    let mut wasm_binary: Vec<u8> = read_file_contents(&wasm_path).map_err(|err| {
+        format!(
+            "Can't read the file {}: {}",
+            wasm_path.as_os_str().to_string_lossy(),
+            err
+        )
+    })?;
+
+    if !utils::is_wasm_binary(&wasm_binary) {
+        wasm_binary = wabt::wat2wasm(wasm_binary)
+            .map_err(|e| format!("Can't convert from wast to wasm: {:?}", e))?;
+    }
+
+    let module =
+        webassembly::compile(&wasm_binary).map_err(|e| format!("Can't compile module: {:?}", e))?;
+
+    let abi = if wasmer_emscripten::is_emscripten_module(&module) {
+        Abi::Emscripten
+    } else if wasmer_wasi::is_wasi_module(&module) {
+        Abi::Wasi
+    } else {
+        Abi::None
+    };
+    let summary = ModuleSummary::new(&module, abi);
+
+    if options.json {
+        let json = serde_json::to_string_pretty(&summary)
+            .map_err(|e| format!("Can't serialize the summary: {}", e))?;
+        println!("{}", json);
+    } else {
+        print!("{}", summary);
+    }
+
+    Ok(())
+}
+
+/// Runs logic for the `inspect` subcommand
+fn inspect(options: Inspect) {
+    if let Err(message) = inspect_wasm(options) {
+        eprintln!("Error: {}", message);
+        exit(-1);
+    }
+}
+
+/// Sends the log events of the runtime to stderr, filtered with `RUST_LOG`
+/// or with the filter given to `--log`.
+fn init_logger(filter: Option<&str>) {
+    let mut builder = env_logger::Builder::from_default_env();
+    if let Some(filter) = filter {
+        builder.parse(filter);
+    }
+    builder.init();
+}
+
+/// Parses the command line, exiting with `USAGE_EXIT_CODE` when it is invalid.
+fn parse_options() -> CLIOptions {
+    match CLIOptions::clap().get_matches_safe() {
+        Ok(matches) => CLIOptions::from_clap(&matches),
+        Err(err) => match err.kind {
+            ErrorKind::HelpDisplayed | ErrorKind::VersionDisplayed => err.exit(),
+            _ => {
+                eprintln!("{}", err.message);
+                exit(USAGE_EXIT_CODE);
+            }
+        },
+    }
+}
+
 fn main() {
-    let options = CLIOptions::from_args();
+    let options = parse_options();
+    init_logger(match options {
+        CLIOptions::Run(ref run) => run.log.as_ref().map(String::as_str),
+        _ => None,
+    });
     match options {
         CLIOptions::Run(options) => run(options),
         #[cfg(not(target_os = "windows"))]
@@ -456,6 +902,12 @@ fn main() {
         CLIOptions::Validate(validate_options) => {
             validate(validate_options);
         }
+        CLIOptions::Wast(wast_options) => {
+            wast(wast_options);
+        }
+        CLIOptions::Inspect(inspect_options) => {
+            inspect(inspect_options);
+        }
         #[cfg(target_os = "windows")]
         CLIOptions::Cache(_) => {
             println!("Caching is disabled for Windows.");
@@ -0,0 +1,356 @@
+//! A summary of the contents of a module, as printed by `wasmer inspect`.
+
+use serde_derive::Serialize;
+use std::fmt;
+use wasmer_runtime_core::{
+    module::{ImportName, Module, ModuleInfo},
+    structures::TypedIndex,
+    types::{ExternDescriptor, MemoryDescriptor, TableDescriptor},
+};
+
+/// The ABI a module expects its host to provide.
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Abi {
+    Emscripten,
+    Wasi,
+    None,
+}
+
+impl fmt::Display for Abi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Abi::Emscripten => "emscripten",
+            Abi::Wasi => "wasi",
+            Abi::None => "none",
+        })
+    }
+}
+
+/// The metadata of a module: what it imports and exports, the entities it
+/// defines and the sections it carries.
+#[derive(Serialize, Debug)]
+pub struct ModuleSummary {
+    pub abi: Abi,
+    pub imports: Vec<Import>,
+    pub exports: Vec<Export>,
+    pub memories: Vec<Memory>,
+    pub tables: Vec<Table>,
+    pub globals: Vec<Global>,
+    pub custom_sections: Vec<CustomSection>,
+    pub data_segments: Vec<Segment>,
+    pub elem_segments: Vec<Segment>,
+    pub start_function: Option<Function>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Import {
+    pub namespace: String,
+    pub name: String,
+    pub kind: &'static str,
+    pub description: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Export {
+    pub name: String,
+    pub kind: &'static str,
+    pub description: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Memory {
+    pub index: usize,
+    /// The `namespace.name` the memory is imported from, if it is imported.
+    pub import: Option<String>,
+    /// The limits, in pages of 64KiB.
+    pub minimum: u32,
+    pub maximum: Option<u32>,
+    pub shared: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Table {
+    pub index: usize,
+    pub import: Option<String>,
+    /// The limits, in elements.
+    pub minimum: u32,
+    pub maximum: Option<u32>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Global {
+    pub index: usize,
+    pub import: Option<String>,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub mutable: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CustomSection {
+    pub name: String,
+    pub size: usize,
+}
+
+/// A data segment, initializing memory `index` with `size` bytes, or an
+/// element segment, initializing table `index` with `size` elements.
+#[derive(Serialize, Debug)]
+pub struct Segment {
+    pub index: usize,
+    pub size: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Function {
+    pub index: usize,
+    pub name: Option<String>,
+}
+
+impl ModuleSummary {
+    pub fn new(module: &Module, abi: Abi) -> ModuleSummary {
+        let info = module.info();
+
+        let imports = module
+            .imports()
+            .map(|(namespace, name, descriptor)| Import {
+                namespace: namespace.to_string(),
+                name: name.to_string(),
+                kind: kind(&descriptor),
+                description: describe(&descriptor),
+            })
+            .collect();
+
+        let mut exports: Vec<Export> = module
+            .exports()
+            .map(|(name, descriptor)| Export {
+                name: name.to_string(),
+                kind: kind(&descriptor),
+                description: describe(&descriptor),
+            })
+            .collect();
+        exports.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let memories = info
+            .imported_memories
+            .iter()
+            .map(|(_, (name, desc))| (Some(import_name(info, name)), desc))
+            .chain(info.memories.iter().map(|(_, desc)| (None, desc)))
+            .enumerate()
+            .map(|(index, (import, desc))| Memory {
+                index,
+                import,
+                minimum: desc.minimum.0,
+                maximum: desc.maximum.map(|pages| pages.0),
+                shared: desc.shared,
+            })
+            .collect();
+
+        let tables = info
+            .imported_tables
+            .iter()
+            .map(|(_, (name, desc))| (Some(import_name(info, name)), desc))
+            .chain(info.tables.iter().map(|(_, desc)| (None, desc)))
+            .enumerate()
+            .map(|(index, (import, desc))| Table {
+                index,
+                import,
+                minimum: desc.minimum,
+                maximum: desc.maximum,
+            })
+            .collect();
+
+        let globals = info
+            .imported_globals
+            .iter()
+            .map(|(_, (name, desc))| (Some(import_name(info, name)), desc))
+            .chain(info.globals.iter().map(|(_, init)| (None, &init.desc)))
+            .enumerate()
+            .map(|(index, (import, desc))| Global {
+                index,
+                import,
+                ty: desc.ty.to_string(),
+                mutable: desc.mutable,
+            })
+            .collect();
+
+        let mut custom_sections: Vec<CustomSection> = info
+            .custom_sections
+            .iter()
+            .map(|(name, data)| CustomSection {
+                name: name.clone(),
+                size: data.len(),
+            })
+            .collect();
+        custom_sections.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let data_segments = info
+            .data_initializers
+            .iter()
+            .map(|init| Segment {
+                index: init.memory_index.index(),
+                size: init.data.len(),
+            })
+            .collect();
+
+        let elem_segments = info
+            .elem_initializers
+            .iter()
+            .map(|init| Segment {
+                index: init.table_index.index(),
+                size: init.elements.len(),
+            })
+            .collect();
+
+        let start_function = info.start_func.map(|func_index| Function {
+            index: func_index.index(),
+            name: info.func_name(func_index).map(str::to_string),
+        });
+
+        ModuleSummary {
+            abi,
+            imports,
+            exports,
+            memories,
+            tables,
+            globals,
+            custom_sections,
+            data_segments,
+            elem_segments,
+            start_function,
+        }
+    }
+}
+
+fn import_name(info: &ModuleInfo, name: &ImportName) -> String {
+    format!(
+        "{}.{}",
+        info.namespace_table.get(name.namespace_index),
+        info.name_table.get(name.name_index)
+    )
+}
+
+fn kind(descriptor: &ExternDescriptor) -> &'static str {
+    match descriptor {
+        ExternDescriptor::Function(_) => "function",
+        ExternDescriptor::Memory(_) => "memory",
+        ExternDescriptor::Table(_) => "table",
+        ExternDescriptor::Global(_) => "global",
+    }
+}
+
+fn describe(descriptor: &ExternDescriptor) -> String {
+    match descriptor {
+        ExternDescriptor::Function(signature) => signature.to_string(),
+        ExternDescriptor::Memory(desc) => describe_memory(desc),
+        ExternDescriptor::Table(desc) => describe_table(desc),
+        ExternDescriptor::Global(desc) => {
+            let mutability = if desc.mutable { "mut " } else { "" };
+            format!("{}{}", mutability, desc.ty)
+        }
+    }
+}
+
+fn describe_memory(desc: &MemoryDescriptor) -> String {
+    let limits = describe_limits(desc.minimum.0, desc.maximum.map(|pages| pages.0), "pages");
+    if desc.shared {
+        format!("{}, shared", limits)
+    } else {
+        limits
+    }
+}
+
+fn describe_table(desc: &TableDescriptor) -> String {
+    describe_limits(desc.minimum, desc.maximum, "elements")
+}
+
+fn describe_limits(minimum: u32, maximum: Option<u32>, unit: &str) -> String {
+    match maximum {
+        Some(maximum) => format!("{}..{} {}", minimum, maximum, unit),
+        None => format!("{}.. {}", minimum, unit),
+    }
+}
+
+fn write_import(f: &mut fmt::Formatter, import: &Option<String>) -> fmt::Result {
+    match import {
+        Some(name) => write!(f, " (imported from {})", name),
+        None => Ok(()),
+    }
+}
+
+impl fmt::Display for ModuleSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "ABI: {}", self.abi)?;
+
+        writeln!(f, "Imports:")?;
+        for import in &self.imports {
+            writeln!(
+                f,
+                "  {}.{}: {} {}",
+                import.namespace, import.name, import.kind, import.description
+            )?;
+        }
+
+        writeln!(f, "Exports:")?;
+        for export in &self.exports {
+            writeln!(
+                f,
+                "  {}: {} {}",
+                export.name, export.kind, export.description
+            )?;
+        }
+
+        writeln!(f, "Memories:")?;
+        for memory in &self.memories {
+            let limits = describe_limits(memory.minimum, memory.maximum, "pages");
+            write!(f, "  {}: {}", memory.index, limits)?;
+            if memory.shared {
+                write!(f, ", shared")?;
+            }
+            write_import(f, &memory.import)?;
+            writeln!(f)?;
+        }
+
+        writeln!(f, "Tables:")?;
+        for table in &self.tables {
+            let limits = describe_limits(table.minimum, table.maximum, "elements");
+            write!(f, "  {}: {}", table.index, limits)?;
+            write_import(f, &table.import)?;
+            writeln!(f)?;
+        }
+
+        writeln!(f, "Globals:")?;
+        for global in &self.globals {
+            let mutability = if global.mutable { "mut " } else { "" };
+            write!(f, "  {}: {}{}", global.index, mutability, global.ty)?;
+            write_import(f, &global.import)?;
+            writeln!(f)?;
+        }
+
+        writeln!(f, "Custom sections:")?;
+        for section in &self.custom_sections {
+            writeln!(f, "  {}: {} bytes", section.name, section.size)?;
+        }
+
+        writeln!(f, "Data segments:")?;
+        for segment in &self.data_segments {
+            writeln!(f, "  memory {}: {} bytes", segment.index, segment.size)?;
+        }
+
+        writeln!(f, "Element segments:")?;
+        for segment in &self.elem_segments {
+            writeln!(f, "  table {}: {} elements", segment.index, segment.size)?;
+        }
+
+        match &self.start_function {
+            Some(Function {
+                index,
+                name: Some(name),
+            }) => writeln!(f, "Start function: {} (function #{})", name, index),
+            Some(Function { index, name: None }) => {
+                writeln!(f, "Start function: function #{}", index)
+            }
+            None => writeln!(f, "Start function: none"),
+        }
+    }
+}
@@ -1,8 +1,12 @@
 #[macro_use]
 extern crate wasmer_runtime_core;
+#[macro_use]
+extern crate log;
 // extern crate wasmer_emscripten;
 
+pub mod inspect;
 #[macro_use]
 pub mod update;
 pub mod utils;
+pub mod wast;
 pub mod webassembly;
@@ -1,6 +1,138 @@
 //! Utility functions for the WebAssembly module
 
+use std::time::Duration;
+use wasmer_runtime_core::{
+    table::{AnyRef, FuncRef},
+    types::{FuncSig, Type, Value},
+};
+
 /// Detect if a provided binary is a Wasm file
 pub fn is_wasm_binary(binary: &[u8]) -> bool {
     binary.starts_with(&[b'\0', b'a', b's', b'm'])
 }
+
+/// Parses a duration such as `500ms`, `30s`, `2m` or `1h`.
+/// A number without a unit is a number of seconds.
+pub fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let duration = duration.trim();
+    let unit_start = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or_else(|| duration.len());
+    let (value, unit) = duration.split_at(unit_start);
+
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("Invalid duration `{}`", duration))?;
+    let seconds = |multiplier: u64| {
+        value
+            .checked_mul(multiplier)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("The duration `{}` is too long", duration))
+    };
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(value)),
+        "" | "s" => seconds(1),
+        "m" => seconds(60),
+        "h" => seconds(60 * 60),
+        _ => Err(format!(
+            "Invalid duration unit `{}` (expected `ms`, `s`, `m` or `h`)",
+            unit
+        )),
+    }
+}
+
+/// Parses an amount of memory such as `65536`, `512KiB`, `64MiB` or `1GiB`.
+/// A number without a unit is a number of bytes.
+pub fn parse_memory_size(size: &str) -> Result<usize, String> {
+    let size = size.trim();
+    let unit_start = size
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or_else(|| size.len());
+    let (value, unit) = size.split_at(unit_start);
+
+    let value: usize = value
+        .parse()
+        .map_err(|_| format!("Invalid memory size `{}`", size))?;
+    let multiplier = match unit {
+        "" | "B" => 1,
+        "K" | "KiB" => 1 << 10,
+        "M" | "MiB" => 1 << 20,
+        "G" | "GiB" => 1 << 30,
+        _ => {
+            return Err(format!(
+                "Invalid memory size unit `{}` (expected `B`, `KiB`, `MiB` or `GiB`)",
+                unit
+            ))
+        }
+    };
+
+    value
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("The memory size `{}` is too large", size))
+}
+
+/// Parses the arguments of a call to a function with the given signature,
+/// each according to the type of its parameter.
+pub fn parse_args(signature: &FuncSig, args: &[String]) -> Result<Vec<Value>, String> {
+    if args.len() != signature.params().len() {
+        return Err(format!(
+            "The function takes {} arguments ({}), but {} were given",
+            signature.params().len(),
+            signature,
+            args.len()
+        ));
+    }
+
+    signature
+        .params()
+        .iter()
+        .zip(args)
+        .map(|(&ty, arg)| parse_value(ty, arg))
+        .collect()
+}
+
+/// Parses a value of the given type. Integers may also be given as unsigned
+/// numbers, which are reinterpreted as signed ones, references can only be `null`
+/// and `v128`s are 128-bit integers, in hexadecimal if prefixed with `0x`.
+pub fn parse_value(ty: Type, value: &str) -> Result<Value, String> {
+    let parsed = match ty {
+        Type::I32 => value
+            .parse::<i32>()
+            .or_else(|_| value.parse::<u32>().map(|i| i as i32))
+            .map(Value::I32)
+            .ok(),
+        Type::I64 => value
+            .parse::<i64>()
+            .or_else(|_| value.parse::<u64>().map(|i| i as i64))
+            .map(Value::I64)
+            .ok(),
+        Type::F32 => value.parse().map(Value::F32).ok(),
+        Type::F64 => value.parse().map(Value::F64).ok(),
+        Type::AnyRef if value == "null" => Some(Value::AnyRef(AnyRef::null())),
+        Type::FuncRef if value == "null" => Some(Value::FuncRef(FuncRef::null())),
+        Type::AnyRef | Type::FuncRef => None,
+        Type::V128 => match value.get(..2) {
+            Some("0x") => u128::from_str_radix(&value[2..], 16),
+            _ => value.parse(),
+        }
+        .map(Value::V128)
+        .ok(),
+    };
+    parsed.ok_or_else(|| format!("Can't parse `{}` as a value of type {}", value, ty))
+}
+
+/// Formats a value the way `parse_value` reads it.
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::I32(i) => i.to_string(),
+        Value::I64(i) => i.to_string(),
+        Value::F32(f) => f.to_string(),
+        Value::F64(f) => f.to_string(),
+        Value::AnyRef(r) if r.is_null() => "null".to_string(),
+        Value::FuncRef(r) if r.is_null() => "null".to_string(),
+        Value::AnyRef(r) => format!("{:?}", r),
+        Value::FuncRef(r) => format!("{:?}", r),
+        Value::V128(v) => format!("0x{:032x}", v),
+    }
+}
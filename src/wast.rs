@@ -0,0 +1,358 @@
+//! An interpreter for `.wast` spec scripts, as run by `wasmer wast`.
+//!
+//! Unlike the `spectests` crate, which turns scripts into Rust tests when it
+//! is built, the commands of a script are run one after the other, against
+//! instances that live until the end of the script.
+
+use hashbrown::HashMap;
+use std::fmt;
+use wabt::script::{self, Action, Command, CommandKind, ScriptParser};
+use wasmer_runtime_core::{
+    backend::Compiler,
+    error::{CallError, CallResult, RuntimeError},
+    export::Export,
+    import::{ImportObject, LikeNamespace, Namespace},
+    typed_func::WasmTrapInfo,
+    types::Value,
+    Instance,
+};
+
+/// The `spectest` module that spec scripts import from.
+static SPECTEST_MODULE: &str = r#"
+(module
+  (func (export "print"))
+  (func (export "print_i32") (param i32))
+  (func (export "print_i64") (param i64))
+  (func (export "print_f32") (param f32))
+  (func (export "print_f64") (param f64))
+  (func (export "print_i32_f32") (param i32 f32))
+  (func (export "print_f64_f64") (param f64 f64))
+  (table (export "table") 10 20 anyfunc)
+  (memory (export "memory") 1 2)
+  (global (export "global_i32") i32 (i32.const 666))
+  (global (export "global_i64") i64 (i64.const 666))
+  (global (export "global_f32") f32 (f32.const 666))
+  (global (export "global_f64") f64 (f64.const 666)))
+"#;
+
+/// The outcome of the assertions and actions of a script.
+#[derive(Debug, Default)]
+pub struct WastReport {
+    pub passed: usize,
+    pub failures: Vec<WastFailure>,
+}
+
+/// A command of a script that did not behave as expected.
+#[derive(Debug)]
+pub struct WastFailure {
+    pub line: u64,
+    pub command: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for WastFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}: {}", self.line, self.command, self.message)
+    }
+}
+
+/// Runs the script in `source`, compiling its modules with `compiler`.
+///
+/// An error is returned if the script cannot be parsed; the commands that
+/// fail are listed in the report.
+pub fn run_wast(
+    source: &[u8],
+    filename: &str,
+    compiler: &dyn Compiler,
+) -> Result<WastReport, String> {
+    let mut parser: ScriptParser = ScriptParser::from_source_and_name(source, filename)
+        .map_err(|e| format!("Can't parse the script: {:?}", e))?;
+    let mut runner = WastRunner::new(compiler)?;
+    let mut report = WastReport::default();
+
+    while let Some(Command { line, kind }) = parser
+        .next()
+        .map_err(|e| format!("Can't parse the script: {:?}", e))?
+    {
+        let command = command_name(&kind);
+        match runner.run_command(kind) {
+            Ok(()) => report.passed += 1,
+            Err(message) => report.failures.push(WastFailure {
+                line,
+                command,
+                message,
+            }),
+        }
+    }
+
+    Ok(report)
+}
+
+struct WastRunner<'a> {
+    compiler: &'a dyn Compiler,
+    import_object: ImportObject,
+    // Instances are kept until the end of the script, as the namespaces
+    // they were registered as refer to them.
+    instances: Vec<Instance>,
+    named_instances: HashMap<String, usize>,
+    current_instance: Option<usize>,
+}
+
+impl<'a> WastRunner<'a> {
+    fn new(compiler: &'a dyn Compiler) -> Result<Self, String> {
+        let wasm = wabt::wat2wasm(SPECTEST_MODULE).expect("the spectest module is valid");
+        let spectest = wasmer_runtime_core::compile_with(&wasm, compiler)
+            .map_err(|e| format!("Can't compile the spectest module: {:?}", e))?
+            .instantiate(&ImportObject::new())
+            .map_err(|e| format!("Can't instantiate the spectest module: {:?}", e))?;
+
+        let mut import_object = ImportObject::new();
+        import_object.register("spectest", spectest);
+
+        Ok(WastRunner {
+            compiler,
+            import_object,
+            instances: vec![],
+            named_instances: HashMap::new(),
+            current_instance: None,
+        })
+    }
+
+    fn run_command(&mut self, kind: CommandKind) -> Result<(), String> {
+        match kind {
+            CommandKind::Module { module, name } => {
+                // Later commands must not run against the previous module if this one fails.
+                self.current_instance = None;
+                let instance = self.instantiate(&module.into_vec())?;
+                self.instances.push(instance);
+                let index = self.instances.len() - 1;
+                self.current_instance = Some(index);
+                if let Some(name) = name {
+                    self.named_instances.insert(name, index);
+                }
+                Ok(())
+            }
+            CommandKind::Register { name, as_name } => {
+                let instance = self.instance(&name)?;
+                let mut namespace = Namespace::new();
+                for (export_name, export) in instance.exports() {
+                    namespace.insert(export_name, export);
+                }
+                self.import_object.register(as_name, namespace);
+                Ok(())
+            }
+            CommandKind::PerformAction(action) => self
+                .perform(&action)?
+                .map(|_| ())
+                .map_err(|e| format!("unexpected error: {:?}", e)),
+            CommandKind::AssertReturn { action, expected } => {
+                let results = self
+                    .perform(&action)?
+                    .map_err(|e| format!("unexpected error: {:?}", e))?;
+                let expected: Vec<Value> = expected.into_iter().map(convert_value).collect();
+                if results.len() == expected.len()
+                    && results.iter().zip(&expected).all(|(a, b)| same_value(a, b))
+                {
+                    Ok(())
+                } else {
+                    Err(format!("expected {:?}, got {:?}", expected, results))
+                }
+            }
+            CommandKind::AssertReturnCanonicalNan { action } => {
+                self.assert_nan(&action, "canonical", is_canonical_nan)
+            }
+            CommandKind::AssertReturnArithmeticNan { action } => {
+                self.assert_nan(&action, "arithmetic", is_arithmetic_nan)
+            }
+            CommandKind::AssertTrap { action, message } => self.assert_trap(&action, &message),
+            // Running out of stack is a fault like any other, so it traps with no particular kind.
+            CommandKind::AssertExhaustion { action } => match self.perform(&action)? {
+                Err(CallError::Runtime(RuntimeError::Trap { .. })) => Ok(()),
+                Err(e) => Err(format!(
+                    "expected the call stack to be exhausted, got {:?}",
+                    e
+                )),
+                Ok(results) => Err(format!(
+                    "expected the call stack to be exhausted, got {:?}",
+                    results
+                )),
+            },
+            CommandKind::AssertInvalid { module, message }
+            | CommandKind::AssertMalformed { module, message } => {
+                self.assert_rejected(&module.into_vec(), &message)
+            }
+            CommandKind::AssertUninstantiable { module, message }
+            | CommandKind::AssertUnlinkable { module, message } => {
+                let module = wasmer_runtime_core::compile_with(&module.into_vec(), self.compiler)
+                    .map_err(|e| format!("can't compile the module: {:?}", e))?;
+                match module.instantiate(&self.import_object) {
+                    Ok(_) => Err(format!("expected an instantiation error \"{}\"", message)),
+                    Err(_) => Ok(()),
+                }
+            }
+        }
+    }
+
+    fn instantiate(&self, wasm: &[u8]) -> Result<Instance, String> {
+        wasmer_runtime_core::compile_with(wasm, self.compiler)
+            .map_err(|e| format!("can't compile the module: {:?}", e))?
+            .instantiate(&self.import_object)
+            .map_err(|e| format!("can't instantiate the module: {:?}", e))
+    }
+
+    /// The instance with the given name, or the last one.
+    fn instance(&self, name: &Option<String>) -> Result<&Instance, String> {
+        let index = match name {
+            Some(name) => self.named_instances.get(name).cloned(),
+            None => self.current_instance,
+        };
+        match (index, name) {
+            (Some(index), _) => Ok(&self.instances[index]),
+            (None, Some(name)) => Err(format!("no module named {}", name)),
+            (None, None) => Err("no module to run against".to_string()),
+        }
+    }
+
+    /// Performs an action. The outer error is for actions that cannot be
+    /// attempted, the inner one for the calls that fail.
+    fn perform(&self, action: &Action) -> Result<CallResult<Vec<Value>>, String> {
+        match action {
+            Action::Invoke {
+                module,
+                field,
+                args,
+            } => {
+                let func = self
+                    .instance(module)?
+                    .dyn_func(field)
+                    .map_err(|e| format!("can't find the function {}: {:?}", field, e))?;
+                let args: Vec<Value> = args.iter().cloned().map(convert_value).collect();
+                Ok(func.call(&args))
+            }
+            Action::Get { module, field } => match self.instance(module)?.get_export(field) {
+                Some(Export::Global(global)) => Ok(Ok(vec![global.get()])),
+                _ => Err(format!("can't find the global {}", field)),
+            },
+        }
+    }
+
+    fn assert_nan(
+        &self,
+        action: &Action,
+        kind: &str,
+        check: fn(&Value) -> bool,
+    ) -> Result<(), String> {
+        let results = self
+            .perform(action)?
+            .map_err(|e| format!("unexpected error: {:?}", e))?;
+        match results.as_slice() {
+            [result] if check(result) => Ok(()),
+            _ => Err(format!("expected an {} NaN, got {:?}", kind, results)),
+        }
+    }
+
+    /// Checks that the action traps, with the kind of trap `message` describes.
+    fn assert_trap(&self, action: &Action, message: &str) -> Result<(), String> {
+        match self.perform(action)? {
+            Err(CallError::Runtime(RuntimeError::Trap { trap })) => {
+                let matches = match trap_info_for(message) {
+                    Some(info) => trap.info == info,
+                    None => trap.info.to_string().contains(message),
+                };
+                if matches {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "expected \"{}\", got the trap \"{}\"",
+                        message, trap.info
+                    ))
+                }
+            }
+            Err(e) => Err(format!("expected \"{}\", got {:?}", message, e)),
+            Ok(results) => Err(format!("expected \"{}\", got {:?}", message, results)),
+        }
+    }
+
+    /// Checks that the module fails validation, and that the compiler rejects it too.
+    fn assert_rejected(&self, wasm: &[u8], message: &str) -> Result<(), String> {
+        if wasmer_runtime_core::validate(wasm) {
+            return Err(format!("expected \"{}\", but the module is valid", message));
+        }
+        match wasmer_runtime_core::compile_with(wasm, self.compiler) {
+            Ok(_) => Err(format!("expected \"{}\", but the module compiled", message)),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+fn command_name(kind: &CommandKind) -> &'static str {
+    match kind {
+        CommandKind::Module { .. } => "module",
+        CommandKind::Register { .. } => "register",
+        CommandKind::PerformAction(_) => "action",
+        CommandKind::AssertReturn { .. } => "assert_return",
+        CommandKind::AssertReturnCanonicalNan { .. } => "assert_return_canonical_nan",
+        CommandKind::AssertReturnArithmeticNan { .. } => "assert_return_arithmetic_nan",
+        CommandKind::AssertTrap { .. } => "assert_trap",
+        CommandKind::AssertExhaustion { .. } => "assert_exhaustion",
+        CommandKind::AssertInvalid { .. } => "assert_invalid",
+        CommandKind::AssertMalformed { .. } => "assert_malformed",
+        CommandKind::AssertUninstantiable { .. } => "assert_uninstantiable",
+        CommandKind::AssertUnlinkable { .. } => "assert_unlinkable",
+    }
+}
+
+/// The kind of trap that spec scripts describe with `message`, if it is one wasmer tells apart.
+fn trap_info_for(message: &str) -> Option<WasmTrapInfo> {
+    let info = match message {
+        "unreachable" | "unreachable executed" => WasmTrapInfo::Unreachable,
+        "indirect call type mismatch" | "indirect call signature mismatch" => {
+            WasmTrapInfo::IncorrectCallIndirectSignature
+        }
+        "out of bounds memory access" => WasmTrapInfo::MemoryOutOfBounds,
+        "out of bounds table access" => WasmTrapInfo::TableOutOfBounds,
+        "undefined element" | "uninitialized element" => WasmTrapInfo::CallIndirectOOB,
+        "integer divide by zero" | "integer overflow" | "invalid conversion to integer" => {
+            WasmTrapInfo::IllegalArithmetic
+        }
+        "unaligned atomic" => WasmTrapInfo::MisalignedAtomicAccess,
+        _ => return None,
+    };
+    Some(info)
+}
+
+fn convert_value(value: script::Value) -> Value {
+    match value {
+        script::Value::I32(i) => Value::I32(i),
+        script::Value::I64(i) => Value::I64(i),
+        script::Value::F32(f) => Value::F32(f),
+        script::Value::F64(f) => Value::F64(f),
+    }
+}
+
+/// Floats are compared bit for bit, except for NaNs, whose payload may vary.
+fn same_value(result: &Value, expected: &Value) -> bool {
+    match (result, expected) {
+        (Value::F32(a), Value::F32(b)) => a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan()),
+        (Value::F64(a), Value::F64(b)) => a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan()),
+        (a, b) => a == b,
+    }
+}
+
+/// A canonical NaN only has the most significant bit of its mantissa set.
+fn is_canonical_nan(value: &Value) -> bool {
+    match value {
+        Value::F32(f) => f.to_bits() & 0x7fff_ffff == 0x7fc0_0000,
+        Value::F64(f) => f.to_bits() & 0x7fff_ffff_ffff_ffff == 0x7ff8_0000_0000_0000,
+        _ => false,
+    }
+}
+
+/// An arithmetic NaN has the most significant bit of its mantissa set.
+fn is_arithmetic_nan(value: &Value) -> bool {
+    match value {
+        Value::F32(f) => f.to_bits() & 0x7fc0_0000 == 0x7fc0_0000,
+        Value::F64(f) => f.to_bits() & 0x7ff8_0000_0000_0000 == 0x7ff8_0000_0000_0000,
+        _ => false,
+    }
+}
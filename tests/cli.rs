@@ -0,0 +1,86 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{self, Command};
+use std::time::{Duration, Instant};
+
+static ANSWER: &'static str = r#"
+    (module
+    (func (export "answer") (param i32) (result i32)
+        (get_local 0))
+    )
+"#;
+
+static LOOPING_START: &'static str = r#"
+    (module
+    (func $spin
+        (loop $forever
+            (br $forever)))
+    (start $spin)
+    )
+"#;
+
+/// The `wasmer` binary built next to this test, in `target/<profile>`.
+fn wasmer_binary() -> PathBuf {
+    let mut path = env::current_exe().unwrap();
+    path.pop();
+    if path.ends_with("deps") {
+        path.pop();
+    }
+    path.join(format!("wasmer{}", env::consts::EXE_SUFFIX))
+}
+
+/// Writes `wat` to a file of its own, named after `name`.
+fn write_wat(name: &str, wat: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("wasmer-{}-{}.wat", name, process::id()));
+    fs::write(&path, wat).unwrap();
+    path
+}
+
+#[test]
+fn timeout_stops_a_looping_start_function() {
+    let wat = write_wat("looping-start", LOOPING_START);
+
+    let started = Instant::now();
+    let status = Command::new(wasmer_binary())
+        .args(&["run", "--timeout", "500ms"])
+        .arg(&wat)
+        .status();
+    fs::remove_file(&wat).unwrap();
+
+    assert_eq!(status.unwrap().code(), Some(124));
+    assert!(started.elapsed() < Duration::from_secs(30));
+}
+
+#[test]
+fn invalid_arguments_exit_with_ex_usage() {
+    let wat = write_wat("answer", ANSWER);
+    let run = |options: &[&str], args: &[&str]| {
+        Command::new(wasmer_binary())
+            .arg("run")
+            .args(options)
+            .arg(&wat)
+            .args(args)
+            .status()
+            .unwrap()
+            .code()
+    };
+
+    let missing_function = run(&["--invoke", "question"], &["42"]);
+    let bad_argument = run(&["--invoke", "answer"], &["forty-two"]);
+    let bad_option = run(&["--timeout", "soon"], &[]);
+    fs::remove_file(&wat).unwrap();
+
+    assert_eq!(missing_function, Some(64));
+    assert_eq!(bad_argument, Some(64));
+    assert_eq!(bad_option, Some(64));
+}
+
+#[test]
+fn a_missing_file_exits_with_ex_noinput() {
+    let status = Command::new(wasmer_binary())
+        .args(&["run", "does-not-exist.wasm"])
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(66));
+}
@@ -0,0 +1,43 @@
+use wasmer::wast::run_wast;
+use wasmer_clif_backend::CraneliftCompiler;
+
+#[test]
+fn assertions_that_hold_pass() {
+    let source = include_bytes!("wast/assertions.wast");
+    let report = run_wast(source, "assertions.wast", &CraneliftCompiler::new()).unwrap();
+
+    let failures: Vec<_> = report.failures.iter().map(ToString::to_string).collect();
+    assert_eq!(failures, Vec::<String>::new());
+    assert_eq!(report.passed, 8);
+}
+
+#[test]
+fn assertions_that_do_not_hold_fail() {
+    static SCRIPT: &'static str = r#"
+        (module
+          (func (export "unreachable")
+            unreachable)
+          (func (export "nop")))
+
+        (assert_trap (invoke "unreachable") "integer divide by zero")
+        (assert_trap (invoke "nop") "unreachable")
+        (assert_invalid (module (func)) "type mismatch")
+    "#;
+
+    let report = run_wast(SCRIPT.as_bytes(), "failing.wast", &CraneliftCompiler::new()).unwrap();
+
+    let commands: Vec<_> = report
+        .failures
+        .iter()
+        .map(|failure| (failure.line, failure.command))
+        .collect();
+    assert_eq!(
+        commands,
+        [
+            (7, "assert_trap"),
+            (8, "assert_trap"),
+            (9, "assert_invalid")
+        ]
+    );
+    assert_eq!(report.passed, 1);
+}
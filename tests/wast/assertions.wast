@@ -0,0 +1,23 @@
+(module
+  (memory 1)
+  (func (export "add") (param i32 i32) (result i32)
+    (i32.add (get_local 0) (get_local 1)))
+  (func (export "unreachable")
+    unreachable)
+  (func (export "div_s") (param i32 i32) (result i32)
+    (i32.div_s (get_local 0) (get_local 1)))
+  (func (export "load") (param i32) (result i32)
+    (i32.load (get_local 0))))
+
+(assert_return (invoke "add" (i32.const 1) (i32.const 2)) (i32.const 3))
+(assert_trap (invoke "unreachable") "unreachable")
+(assert_trap (invoke "div_s" (i32.const 1) (i32.const 0)) "integer divide by zero")
+(assert_trap (invoke "div_s" (i32.const 0x80000000) (i32.const -1)) "integer overflow")
+(assert_trap (invoke "load" (i32.const 65536)) "out of bounds memory access")
+
+(assert_invalid
+  (module (func (result i32) (i64.const 0)))
+  "type mismatch")
+(assert_malformed
+  (module binary "\00asm\02\00\00\00")
+  "unknown binary version")